@@ -79,7 +79,9 @@ mkdir -p "$COMMON/Modules"
 cp "$WORKING_DIR/module.modulemap" "$COMMON/Modules/"
 
 mkdir -p "$COMMON/Headers"
-cbindgen "$REPO_ROOT/pinenut-ffi" -l C -o "$COMMON/Headers/$FRAMEWORK_NAME.h"
+# The checked-in header must match the FFI crate.
+"$REPO_ROOT/pinenut-ffi/generate-header.sh" --verify
+cp "$REPO_ROOT/pinenut-ffi/include/pinenut.h" "$COMMON/Headers/$FRAMEWORK_NAME.h"
 
 # iOS hardware
 mkdir -p "$XCFRAMEWORK_ROOT/ios-arm64"
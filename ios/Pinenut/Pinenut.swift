@@ -135,6 +135,24 @@ public enum Level: UInt8 {
     case verbose
 }
 
+/// Represents a change of the application lifecycle, see `Logger.hint(_:)`.
+public enum Hint: UInt8 {
+    /// The application is about to enter the background.
+    ///
+    /// The buffered logs are written to the log file before returning.
+    case enteringBackground = 1
+
+    /// The application has received a memory warning.
+    ///
+    /// The buffered logs are flushed asynchronously.
+    case lowMemory
+
+    /// The application is about to terminate.
+    ///
+    /// The buffered logs are written to the log file before returning.
+    case terminating
+}
+
 /// Represents a location in the code where a `Pinenut` log was generated.
 public struct Location {
     /// The code file where the log was generated. `nil` if not available.
@@ -244,6 +262,17 @@ public final class Logger {
         }
     }
 
+    /// Performs the behavior appropriate for the change of the application lifecycle,
+    /// so that the lifecycle notifications can be mapped to the logger in one call.
+    ///
+    /// It may block the current thread until the buffered logs have been written.
+    @inlinable
+    public func hint(_ hint: Hint) throws {
+        try call {
+            pinenut_logger_hint(pointer, hint.rawValue, $0)
+        }
+    }
+
     /// Deletes the expired log files with lifetime (seconds).
     ///
     /// The low-level IO operations are performed asynchronously.
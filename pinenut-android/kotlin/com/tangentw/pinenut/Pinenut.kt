@@ -0,0 +1,220 @@
+package com.tangentw.pinenut
+
+import java.io.Closeable
+import java.util.concurrent.locks.ReentrantReadWriteLock
+import kotlin.concurrent.read
+import kotlin.concurrent.write
+
+/** The exception thrown when an operation of Pinenut fails. */
+class PinenutException(message: String) : RuntimeException(message)
+
+/** Represents a logging domain, see `Domain` of `pinenut-log`. */
+data class Domain(val identifier: String, val directory: String)
+
+/** The log level, in descending order of severity. */
+enum class Level(internal val value: Int) {
+    ERROR(1),
+    WARN(2),
+    INFO(3),
+    DEBUG(4),
+    VERBOSE(5);
+
+    internal companion object {
+        fun of(value: Int): Level = values().firstOrNull { it.value == value } ?: VERBOSE
+    }
+}
+
+/** The time dimension of the log file rotation. */
+enum class TimeDimension(internal val value: Int) {
+    DAY(1),
+    HOUR(2),
+    MINUTE(3),
+}
+
+/** The configuration of the logger, see `Config` of `pinenut-log`. */
+data class Config(
+    val useMmap: Boolean = true,
+    val bufferLen: Long = 320 * 1024,
+    val rotation: TimeDimension = TimeDimension.MINUTE,
+    /** The public key (in base64) used to encrypt the logs, no encryption if null. */
+    val keyStr: String? = null,
+    val compressionLevel: Int = 10,
+)
+
+/** The log record, the absent line and thread id are null. */
+data class Record(
+    val level: Level,
+    val datetimeMillis: Long,
+    val tag: String?,
+    val file: String?,
+    val func: String?,
+    val line: Int?,
+    val threadId: Long?,
+    val content: String,
+)
+
+/** Visits the records parsed from the log file, see [Pinenut.parse]. */
+fun interface RecordVisitor {
+    fun visit(record: Record)
+}
+
+/**
+ * The logger of a domain.
+ *
+ * The native logger is deallocated by [shutdown] (or [close]), and the logger can't
+ * be used afterwards. The operations racing with the shutdown are ignored instead of
+ * touching the deallocated logger.
+ */
+class Logger(domain: Domain, config: Config = Config()) : Closeable {
+    private val lock = ReentrantReadWriteLock()
+    private var ptr: Long = PinenutNative.loggerNew(
+        domain.identifier,
+        domain.directory,
+        config.useMmap,
+        config.bufferLen,
+        config.rotation.value,
+        config.keyStr,
+        config.compressionLevel,
+    )
+
+    fun log(
+        level: Level,
+        content: String,
+        tag: String? = null,
+        file: String? = null,
+        func: String? = null,
+        line: Int? = null,
+        threadId: Long? = null,
+        datetimeMillis: Long = System.currentTimeMillis(),
+    ) = withPtr {
+        PinenutNative.loggerLog(
+            it, level.value, datetimeMillis, tag, file, func, line ?: -1, threadId ?: -1, content,
+        )
+    }
+
+    fun error(content: String, tag: String? = null) = log(Level.ERROR, content, tag)
+
+    fun warn(content: String, tag: String? = null) = log(Level.WARN, content, tag)
+
+    fun info(content: String, tag: String? = null) = log(Level.INFO, content, tag)
+
+    fun debug(content: String, tag: String? = null) = log(Level.DEBUG, content, tag)
+
+    fun verbose(content: String, tag: String? = null) = log(Level.VERBOSE, content, tag)
+
+    /** Flushes the logs in the buffer to the log file. */
+    fun flush() = withPtr { PinenutNative.loggerFlush(it) }
+
+    /** Deletes the log files whose lifetime (in seconds) has expired. */
+    fun trim(lifetime: Long) = withPtr { PinenutNative.loggerTrim(it, lifetime) }
+
+    /** Shuts down the logger and deallocates the native logger. */
+    fun shutdown() = lock.write {
+        val ptr = ptr
+        this.ptr = 0
+        if (ptr != 0L) PinenutNative.loggerShutdown(ptr)
+    }
+
+    override fun close() = shutdown()
+
+    private inline fun withPtr(action: (Long) -> Unit) = lock.read {
+        if (ptr != 0L) action(ptr)
+    }
+}
+
+object Pinenut {
+    /** Extracts the logs of the domain in the time range to the destination file. */
+    fun extract(domain: Domain, startMillis: Long, endMillis: Long, destPath: String) =
+        PinenutNative.extract(domain.identifier, domain.directory, startMillis, endMillis, destPath)
+
+    /**
+     * Parses the log file, and calls the visitor with each record in order on the
+     * calling thread.
+     */
+    fun parse(path: String, secretKey: ByteArray? = null, visitor: RecordVisitor) =
+        PinenutNative.parse(path, secretKey, NativeRecordVisitor(visitor))
+
+    /** Parses the log file into the readable text file. */
+    fun parseToFile(path: String, destPath: String, secretKey: ByteArray? = null) =
+        PinenutNative.parseToFile(path, destPath, secretKey)
+}
+
+/** Adapts the flattened records passed by the native side. */
+internal class NativeRecordVisitor(private val visitor: RecordVisitor) {
+    @Suppress("unused") // Called by the native side.
+    fun visit(
+        level: Int,
+        datetimeMillis: Long,
+        tag: String?,
+        file: String?,
+        func: String?,
+        line: Int,
+        threadId: Long,
+        content: String,
+    ) = visitor.visit(
+        Record(
+            Level.of(level),
+            datetimeMillis,
+            tag,
+            file,
+            func,
+            line.takeIf { it >= 0 },
+            threadId.takeIf { it >= 0 },
+            content,
+        )
+    )
+}
+
+internal object PinenutNative {
+    init {
+        System.loadLibrary("pinenut_android")
+    }
+
+    @JvmStatic
+    external fun loggerNew(
+        identifier: String,
+        directory: String,
+        useMmap: Boolean,
+        bufferLen: Long,
+        rotation: Int,
+        keyStr: String?,
+        compressionLevel: Int,
+    ): Long
+
+    @JvmStatic
+    external fun loggerLog(
+        ptr: Long,
+        level: Int,
+        datetimeMillis: Long,
+        tag: String?,
+        file: String?,
+        func: String?,
+        line: Int,
+        threadId: Long,
+        content: String,
+    )
+
+    @JvmStatic
+    external fun loggerFlush(ptr: Long)
+
+    @JvmStatic
+    external fun loggerTrim(ptr: Long, lifetime: Long)
+
+    @JvmStatic
+    external fun loggerShutdown(ptr: Long)
+
+    @JvmStatic
+    external fun extract(
+        identifier: String,
+        directory: String,
+        startMillis: Long,
+        endMillis: Long,
+        destPath: String,
+    )
+
+    @JvmStatic
+    external fun parse(path: String, secretKey: ByteArray?, visitor: NativeRecordVisitor)
+
+    @JvmStatic
+    external fun parseToFile(path: String, destPath: String, secretKey: ByteArray?)
+}
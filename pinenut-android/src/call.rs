@@ -0,0 +1,46 @@
+use std::{any::Any, error::Error, panic};
+
+use jni::JNIEnv;
+
+/// The class of the exceptions thrown on the Kotlin side.
+const EXCEPTION_CLASS: &str = "com/tangentw/pinenut/PinenutException";
+
+pub(crate) type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Calls the function, the error or the panic of it is thrown as a
+/// `PinenutException` and the default value is returned.
+pub(crate) fn jni_call<'local, T, F>(env: &mut JNIEnv<'local>, call: F) -> T
+where
+    T: Default,
+    F: FnOnce(&mut JNIEnv<'local>) -> Result<T>,
+{
+    match panic::catch_unwind(panic::AssertUnwindSafe(|| call(env))) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => {
+            throw(env, error.to_string());
+            T::default()
+        }
+        Err(error) => {
+            throw(env, panic_desc(error));
+            T::default()
+        }
+    }
+}
+
+fn throw(env: &mut JNIEnv, desc: String) {
+    // The pending exception (e.g., thrown by the visitor of the records) takes
+    // precedence.
+    if !env.exception_check().unwrap_or_default() {
+        _ = env.throw_new(EXCEPTION_CLASS, desc);
+    }
+}
+
+fn panic_desc(error: Box<dyn Any + Send + 'static>) -> String {
+    if let Some(str) = error.downcast_ref::<&'static str>() {
+        str.to_string()
+    } else if let Some(str) = error.downcast_ref::<String>() {
+        str.clone()
+    } else {
+        "panic".to_string()
+    }
+}
@@ -0,0 +1,307 @@
+//! The JNI bindings of Pinenut for Android.
+//!
+//! The functions are the native methods of `com.tangentw.pinenut.PinenutNative`,
+//! which are wrapped by the Kotlin API under the `kotlin` directory.
+
+mod call;
+
+use jni::{
+    objects::{JByteArray, JObject, JString},
+    sys::{jint, jlong},
+    JNIEnv,
+};
+use pinenut_log::{DateTime, Domain, Level, SecretKey, TimeDimension};
+
+use crate::call::Result;
+
+/// Obtains the string of the Java string, `None` if it is null.
+#[inline]
+fn string(env: &mut JNIEnv, string: &JString) -> Result<Option<String>> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(env.get_string(string)?.into()))
+}
+
+/// Constructs a Java string, the null reference if the string is `None`.
+#[inline]
+fn new_string<'local>(env: &mut JNIEnv<'local>, string: Option<&str>) -> Result<JObject<'local>> {
+    Ok(match string {
+        Some(string) => env.new_string(string)?.into(),
+        None => JObject::null(),
+    })
+}
+
+#[inline]
+fn domain(env: &mut JNIEnv, identifier: &JString, directory: &JString) -> Result<Domain> {
+    let identifier = string(env, identifier)?.unwrap_or_default();
+    let directory = string(env, directory)?.unwrap_or_default();
+    Ok(Domain::new(identifier, directory.into()))
+}
+
+#[inline]
+fn secret_key(env: &mut JNIEnv, key: &JByteArray) -> Result<Option<SecretKey>> {
+    if key.is_null() {
+        return Ok(None);
+    }
+    Ok(env.convert_byte_array(key)?.try_into().ok())
+}
+
+#[inline]
+fn datetime(millis: jlong) -> DateTime {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+#[inline]
+fn level(level: jint) -> Level {
+    match level {
+        1 => Level::Error,
+        2 => Level::Warn,
+        3 => Level::Info,
+        4 => Level::Debug,
+        _ => Level::Verbose,
+    }
+}
+
+#[inline]
+fn level_value(level: Level) -> jint {
+    match level {
+        Level::Error => 1,
+        Level::Warn => 2,
+        Level::Info => 3,
+        Level::Debug => 4,
+        _ => 5,
+    }
+}
+
+#[inline]
+fn time_dimension(dimension: jint) -> TimeDimension {
+    match dimension {
+        1 => TimeDimension::Day,
+        3 => TimeDimension::Minute,
+        _ => TimeDimension::Hour,
+    }
+}
+
+pub mod logger {
+    use jni::{
+        objects::{JClass, JString},
+        sys::{jboolean, jint, jlong, JNI_FALSE},
+        JNIEnv,
+    };
+    use pinenut_log::{Config, Location, Logger, Meta, Record};
+
+    use crate::{call::jni_call, datetime, domain, level, string, time_dimension};
+
+    #[no_mangle]
+    #[allow(clippy::too_many_arguments)]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_loggerNew<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        identifier: JString<'local>,
+        directory: JString<'local>,
+        use_mmap: jboolean,
+        buffer_len: jlong,
+        rotation: jint,
+        key_str: JString<'local>,
+        compression_level: jint,
+    ) -> jlong {
+        jni_call(&mut env, |env| {
+            let config = Config::new()
+                .use_mmap(use_mmap != JNI_FALSE)
+                .buffer_len(buffer_len.try_into()?)
+                .rotation(time_dimension(rotation))
+                .key_str(string(env, &key_str)?)
+                .compression_level(compression_level);
+            let logger = Logger::new(domain(env, &identifier, &directory)?, config);
+            Ok(Box::into_raw(Box::new(logger)) as jlong)
+        })
+    }
+
+    /// The absent line and thread id are negative.
+    #[no_mangle]
+    #[allow(clippy::too_many_arguments)]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_loggerLog<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        ptr: jlong,
+        raw_level: jint,
+        datetime_millis: jlong,
+        tag: JString<'local>,
+        file: JString<'local>,
+        func: JString<'local>,
+        line: jint,
+        thread_id: jlong,
+        content: JString<'local>,
+    ) {
+        jni_call(&mut env, |env| {
+            let Some(logger) = (unsafe { (ptr as *const Logger).as_ref() }) else {
+                return Ok(());
+            };
+            let (tag, file, func) = (string(env, &tag)?, string(env, &file)?, string(env, &func)?);
+            let content = string(env, &content)?.unwrap_or_default();
+            let location = Location::new(file.as_deref(), func.as_deref(), line.try_into().ok());
+            let meta = Meta::new(
+                level(raw_level),
+                datetime(datetime_millis),
+                location,
+                tag.as_deref(),
+                thread_id.try_into().ok(),
+            );
+            logger.log(&Record::new(meta, &content));
+            Ok(())
+        })
+    }
+
+    #[no_mangle]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_loggerFlush<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        ptr: jlong,
+    ) {
+        jni_call(&mut env, |_| {
+            if let Some(logger) = unsafe { (ptr as *const Logger).as_ref() } {
+                logger.flush();
+            }
+            Ok(())
+        })
+    }
+
+    #[no_mangle]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_loggerTrim<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        ptr: jlong,
+        lifetime: jlong,
+    ) {
+        jni_call(&mut env, |_| {
+            if let Some(logger) = unsafe { (ptr as *const Logger).as_ref() } {
+                logger.trim(lifetime.try_into()?);
+            }
+            Ok(())
+        })
+    }
+
+    /// Shuts down the logger and deallocates it, the pointer must not be used
+    /// afterwards.
+    #[no_mangle]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_loggerShutdown<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        ptr: jlong,
+    ) {
+        jni_call(&mut env, |_| {
+            if ptr != 0 {
+                unsafe { Box::from_raw(ptr as *mut Logger) }.shutdown();
+            }
+            Ok(())
+        })
+    }
+}
+
+pub mod extract {
+    use jni::{
+        objects::{JClass, JString},
+        sys::jlong,
+        JNIEnv,
+    };
+    use pinenut_log::extract;
+
+    use crate::{call::jni_call, datetime, domain, string};
+
+    #[no_mangle]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_extract<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        identifier: JString<'local>,
+        directory: JString<'local>,
+        start_millis: jlong,
+        end_millis: jlong,
+        dest_path: JString<'local>,
+    ) {
+        jni_call(&mut env, |env| {
+            let domain = domain(env, &identifier, &directory)?;
+            let dest_path = string(env, &dest_path)?.unwrap_or_default();
+            Ok(extract(domain, datetime(start_millis)..=datetime(end_millis), dest_path)?)
+        })
+    }
+}
+
+pub mod parser {
+    use std::io;
+
+    use jni::{
+        objects::{JByteArray, JClass, JObject, JString, JValue},
+        JNIEnv,
+    };
+    use pinenut_log::{parse, parse_to_file, DefaultFormatter, Record};
+
+    use crate::{
+        call::{jni_call, Result},
+        level_value, new_string, secret_key, string,
+    };
+
+    /// The signature of `RecordVisitor.visit`.
+    const VISIT_SIGNATURE: &str = "(IJLjava/lang/String;Ljava/lang/String;Ljava/lang/String;\
+                                   IJLjava/lang/String;)V";
+
+    /// Parses the log file, and calls the `visit` method of the visitor with each
+    /// record in order on the calling thread.
+    ///
+    /// Parsing stops if the visitor throws, and the exception is rethrown.
+    #[no_mangle]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_parse<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        path: JString<'local>,
+        secret_key_bytes: JByteArray<'local>,
+        visitor: JObject<'local>,
+    ) {
+        jni_call(&mut env, |env| {
+            let path = string(env, &path)?.unwrap_or_default();
+            let secret_key = secret_key(env, &secret_key_bytes)?;
+            Ok(parse(path, secret_key, |record| {
+                visit(env, &visitor, record).map_err(|err| io::Error::other(err.to_string()))
+            })?)
+        })
+    }
+
+    fn visit(env: &mut JNIEnv, visitor: &JObject, record: &Record) -> Result<()> {
+        let (meta, location) = (record.meta(), record.meta().location());
+        // The local references are released after each record.
+        env.with_local_frame(4, |env| -> Result<()> {
+            let tag = new_string(env, meta.tag())?;
+            let file = new_string(env, location.file())?;
+            let func = new_string(env, location.func())?;
+            let content = new_string(env, Some(record.content()))?;
+            let args = [
+                JValue::Int(level_value(meta.level())),
+                JValue::Long(meta.datetime().timestamp_millis()),
+                JValue::Object(&tag),
+                JValue::Object(&file),
+                JValue::Object(&func),
+                JValue::Int(location.line().and_then(|l| l.try_into().ok()).unwrap_or(-1)),
+                JValue::Long(meta.thread_id().and_then(|id| id.try_into().ok()).unwrap_or(-1)),
+                JValue::Object(&content),
+            ];
+            env.call_method(visitor, "visit", VISIT_SIGNATURE, &args)?;
+            Ok(())
+        })
+    }
+
+    #[no_mangle]
+    pub extern "system" fn Java_com_tangentw_pinenut_PinenutNative_parseToFile<'local>(
+        mut env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        path: JString<'local>,
+        dest_path: JString<'local>,
+        secret_key_bytes: JByteArray<'local>,
+    ) {
+        jni_call(&mut env, |env| {
+            let path = string(env, &path)?.unwrap_or_default();
+            let dest_path = string(env, &dest_path)?.unwrap_or_default();
+            let secret_key = secret_key(env, &secret_key_bytes)?;
+            Ok(parse_to_file(path, dest_path, secret_key, DefaultFormatter)?)
+        })
+    }
+}
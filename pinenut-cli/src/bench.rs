@@ -0,0 +1,90 @@
+use std::{
+    env, fs, process,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+use pinenut_log::{
+    encrypt::gen_echd_key_pair, Config, DateTime, Domain, Level, LocationBuilder, MetaBuilder,
+    RecordBuilder,
+};
+
+#[derive(Args)]
+pub(crate) struct Bench {
+    /// The number of records to log.
+    #[arg(short = 'n', long, default_value_t = 1_000_000)]
+    records: usize,
+    /// The compression level.
+    #[arg(short, long, default_value_t = 10)]
+    compression_level: i32,
+    /// Whether to encrypt the records.
+    #[arg(short, long)]
+    encrypt: bool,
+    /// The buffer length in KB.
+    #[arg(short, long, default_value_t = 320)]
+    buffer_len: usize,
+    /// Whether to use the memory instead of mmap for the buffer.
+    #[arg(long)]
+    no_mmap: bool,
+}
+
+impl Bench {
+    pub(crate) fn exec(self) {
+        let dir = env::temp_dir().join(format!("pinenut-bench-{}", process::id()));
+        let domain = Domain::new("bench".to_string(), dir.clone());
+
+        let mut config = Config::new()
+            .compression_level(self.compression_level)
+            .buffer_len(self.buffer_len * 1024)
+            .use_mmap(!self.no_mmap)
+            .tracker(None);
+        if self.encrypt {
+            config = config.key(Some(gen_echd_key_pair().1));
+        }
+        let logger = domain.logger(config);
+
+        // A pool of the synthetic contents, so that they are not formatted while logging.
+        let contents: Vec<_> = (0..64)
+            .map(|i| format!("The request #{i} has been completed, status: 200, elapsed: {i}ms"))
+            .collect();
+        let location = LocationBuilder::new().file("main.rs").func("main").line(42).build();
+        let start_timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64);
+
+        println!("Logging {} records ...", self.records);
+        let start = Instant::now();
+        for index in 0..self.records {
+            // The records are 1 millisecond apart.
+            let timestamp = start_timestamp + index as i64;
+            let meta = MetaBuilder::new()
+                .level(Level::Info)
+                .datetime(DateTime::from_timestamp_millis(timestamp).unwrap_or_default())
+                .location(location.clone())
+                .tag("network")
+                .thread_id(1)
+                .build();
+            let content = &contents[index % contents.len()];
+            logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        }
+        let logged = start.elapsed();
+        logger.shutdown();
+        let total = start.elapsed();
+
+        let file_len: u64 = fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.path().extension().is_some_and(|ext| ext == "pine"))
+                    .filter_map(|e| e.metadata().ok())
+                    .map(|m| m.len())
+                    .sum()
+            })
+            .unwrap_or(0);
+        _ = fs::remove_dir_all(&dir);
+
+        let per_second = |elapsed: Duration| self.records as f64 / elapsed.as_secs_f64();
+        println!("Logged in {logged:.2?} ({:.0} records/s)", per_second(logged));
+        println!("Written in {total:.2?} ({:.0} records/s)", per_second(total));
+        println!("Log files: {file_len} bytes");
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use pinenut_log::{
+    Config, Domain, Level, LocationBuilder, LoggerError, MetaBuilder, RecordBuilder,
+};
+
+use crate::public_key;
+
+#[derive(Args)]
+pub(crate) struct GenLogs {
+    /// The directory where the log files are written.
+    #[arg(short, long)]
+    dir: PathBuf,
+    /// The identifier of the domain.
+    #[arg(short, long, default_value = "pinenut")]
+    identifier: String,
+    /// The number of records to log.
+    #[arg(short = 'n', long, default_value_t = 100_000)]
+    count: usize,
+    /// The rate of logging, e.g., `1000/s` or `600/m`. The records are logged as
+    /// fast as possible if it is not specified.
+    #[arg(short, long, value_parser = parse_rate)]
+    rate: Option<f64>,
+    /// The public key (in Base64 or a path to a PEM file) that the records are
+    /// encrypted for, they are not encrypted if it is not specified. The key is of
+    /// P-256 (33 bytes) or X25519 (32 bytes).
+    #[arg(short, long)]
+    public_key: Option<String>,
+}
+
+impl GenLogs {
+    /// Logs the synthetic records through the logger, whose log files serve as the
+    /// fixtures (e.g., to benchmark the parsing).
+    pub(crate) fn exec(self) {
+        let key = match self.public_key.as_deref().map(public_key) {
+            Some(Some(key)) => Some(key),
+            Some(None) => {
+                println!("Error: the public key must be 33 (P-256) or 32 (X25519) bytes");
+                process::exit(2);
+            }
+            None => None,
+        };
+
+        // The logger writes the plaintext records if the key is rejected, which is
+        // reported to the tracker.
+        let rejected = Arc::new(Mutex::new(None));
+        let tracker = {
+            let rejected = Arc::clone(&rejected);
+            move |err: LoggerError, _: &'static str, _: u32| {
+                if let LoggerError::Encrypt(err) = err {
+                    *rejected.lock().unwrap() = Some(err);
+                }
+            }
+        };
+        let config = Config::new().tracker(Some(Box::new(tracker)));
+        // The algorithm of the key is inferred from its length.
+        let config = match key.map(<[u8; 32]>::try_from) {
+            Some(Ok(key)) => config.x25519_key(Some(key)),
+            Some(Err(key)) => config.key(key.try_into().ok()),
+            None => config,
+        };
+        let domain = Domain::new(self.identifier, self.dir);
+        let logger = domain.logger(config);
+        if let Some(err) = rejected.lock().unwrap().take() {
+            logger.shutdown();
+            println!("Error: the public key is rejected: {err}");
+            process::exit(2);
+        }
+
+        println!("Logging {} records ...", self.count);
+        let start = Instant::now();
+        for index in 0..self.count {
+            if let Some(rate) = self.rate {
+                let due = Duration::from_secs_f64(index as f64 / rate);
+                if let Some(ahead) = due.checked_sub(start.elapsed()) {
+                    thread::sleep(ahead);
+                }
+            }
+            let (level, tag, func, content) = synthetic(index);
+            let location = LocationBuilder::new()
+                .file("main.rs")
+                .func(func)
+                .line(index as u32 % 500 + 1)
+                .build();
+            let meta = MetaBuilder::new()
+                .level(level)
+                .location(location)
+                .tag(tag)
+                .thread_id(index as u64 % 4 + 1)
+                .build();
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        }
+        logger.shutdown();
+        println!("Logged in {:.2?}", start.elapsed());
+    }
+}
+
+/// The level, tag, function and content of the synthetic record, which mimic the
+/// logs of an app: mostly the informational ones with a few warnings and errors.
+fn synthetic(index: usize) -> (Level, &'static str, &'static str, String) {
+    match index % 100 {
+        0 => (Level::Error, "network", "request", format!("The request #{index} failed: timeout")),
+        1..=4 => {
+            (Level::Warn, "storage", "save", format!("The cache is {}% full", index % 40 + 60))
+        }
+        5..=29 => (
+            Level::Debug,
+            "ui",
+            "render",
+            format!("Rendered the view #{} in {}ms", index % 16, index % 7 + 1),
+        ),
+        _ => (
+            Level::Info,
+            "network",
+            "request",
+            format!(
+                "The request #{index} has been completed, status: 200, elapsed: {}ms",
+                index % 300
+            ),
+        ),
+    }
+}
+
+/// Parses the rate in records per second from `N/s`, `N/m` or `N`.
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    let (count, per) = match rate.split_once('/') {
+        Some((count, "s")) => (count, 1.),
+        Some((count, "m")) => (count, 60.),
+        Some(_) => return Err("the unit must be `s` or `m`".to_string()),
+        None => (rate, 1.),
+    };
+    match count.parse::<f64>() {
+        Ok(count) if count > 0. => Ok(count / per),
+        _ => Err("the count must be a positive number".to_string()),
+    }
+}
@@ -0,0 +1,78 @@
+use base64::{prelude::BASE64_STANDARD, Engine};
+use clap::{Args, Subcommand};
+use pinenut_log::{encrypt::key_fingerprint, KeyAlgorithm};
+
+use crate::{public_key, secret_key};
+
+#[derive(Args)]
+pub(crate) struct Key {
+    #[command(subcommand)]
+    command: KeyCommand,
+}
+
+#[derive(Subcommand)]
+enum KeyCommand {
+    /// Derives the public keys of the secret key, and prints them with their
+    /// fingerprints.
+    Info(Info),
+    /// Prints the fingerprint of the public key, which is recorded in the chunks
+    /// encrypted for it.
+    Fingerprint(Fingerprint),
+}
+
+impl Key {
+    pub(crate) fn exec(self) {
+        match self.command {
+            KeyCommand::Info(info) => info.exec(),
+            KeyCommand::Fingerprint(fingerprint) => fingerprint.exec(),
+        }
+    }
+}
+
+#[derive(Args)]
+struct Info {
+    /// The secret key, in Base64 or a path to a PEM file.
+    #[arg(short, long)]
+    secret_key: String,
+}
+
+impl Info {
+    fn exec(self) {
+        let secret_key = match secret_key(&self.secret_key) {
+            Ok(secret_key) => secret_key,
+            Err(err) => return println!("Error: {err}"),
+        };
+        // The key pair of either algorithm may have been generated, the public key
+        // embedded in the app is one of them.
+        for (name, algorithm) in [("P-256", KeyAlgorithm::P256), ("X25519", KeyAlgorithm::X25519)] {
+            match algorithm.public_key(&secret_key) {
+                Ok(public_key) => {
+                    println!("{name} Public Key: {}", BASE64_STANDARD.encode(&public_key));
+                    println!("{name} Fingerprint: {}", key_fingerprint(&public_key));
+                }
+                Err(err) => println!("{name} Error: {err}"),
+            }
+        }
+    }
+}
+
+#[derive(Args)]
+struct Fingerprint {
+    /// The public key, in Base64 or a path to a PEM file.
+    #[arg(short, long)]
+    public_key: String,
+}
+
+impl Fingerprint {
+    fn exec(self) {
+        let Some(public_key) = public_key(&self.public_key) else {
+            return println!("Error: the public key must be 33 (P-256) or 32 (X25519) bytes");
+        };
+        let algorithm = match KeyAlgorithm::of_public_key(&public_key) {
+            Some(KeyAlgorithm::X25519) => "X25519",
+            _ => "P-256",
+        };
+        println!("Algorithm: {algorithm}");
+        println!("Fingerprint: {}", key_fingerprint(&public_key));
+    }
+}
@@ -1,6 +1,28 @@
+mod bench;
+mod gen_logs;
+mod key;
+mod stats;
+mod verify;
+mod watch;
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
 use base64::{prelude::BASE64_STANDARD, Engine};
-use clap::{Args, Parser, Subcommand};
-use pinenut_log::DefaultFormatter;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pinenut_log::{
+    encrypt::{public_key_from_pem, secret_key_from_pem, Zeroizing},
+    CsvFormatter, CustomDefaultFormatter, DefaultFormatter, Format, KeyAlgorithm, Level,
+    LevelLabels, LogfmtFormatter, OwnedRecord, ParseOptions, Record, SecretKey, TemplateError,
+    TemplateFormatter, Timezone,
+};
+
+use crate::{
+    bench::Bench, gen_logs::GenLogs, key::Key, stats::Stats, verify::Verify, watch::Watch,
+};
 
 #[derive(Parser)]
 #[command(about = "The Pinenut command line tool.")]
@@ -11,18 +33,192 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Command {
-    /// Generates the ECHD key pair.
+    /// Generates the ECHD key pair (of P-256 or X25519).
     GenKeys(GenKeys),
+    /// Inspects the keys: derives the public keys of a secret key, or computes the
+    /// fingerprint of a public key, e.g., to confirm that the public key embedded in
+    /// the app pairs with the secret key held by the backend.
+    Key(Key),
     /// Parses the compressed and encrypted binary log file into readable text file.
     Parse(Parse),
+    /// Parses multiple binary log files, and merges their records into a single
+    /// readable text file in chronological order.
+    Merge(Merge),
+    /// Reports the statistics of the records in the log file, e.g., the counts by
+    /// level and tag, and the most frequent messages.
+    Stats(Stats),
+    /// Salvages a damaged log file, writes out only the valid chunks.
+    Repair(Repair),
+    /// Verifies the integrity of the log file: reads, decrypts, decompresses and
+    /// decodes all chunks without formatting the records, then reports the
+    /// failed chunks and a pass/fail summary. It exits with a non-zero status if
+    /// the verification fails.
+    Verify(Verify),
+    /// Watches the log files of a domain, and prints the records as the chunks are
+    /// written (e.g., by the app running locally) until it is interrupted.
+    Watch(Watch),
+    /// Logs the synthetic records through the logger into a directory, e.g., to
+    /// produce the fixture files or benchmark the parsing without a device.
+    GenLogs(GenLogs),
+    /// Runs a synthetic workload against a temporary domain, and reports the
+    /// throughput of logging.
+    Bench(Bench),
+}
+
+/// Decodes the secret key in base64, or reads it from a PEM file (PKCS#8 or SEC1)
+/// if the key is a path to a file.
+fn secret_key(key: &str) -> Result<SecretKey, String> {
+    if Path::new(key).is_file() {
+        let pem = fs::read_to_string(key).map(Zeroizing::new);
+        let pem = pem.map_err(|err| format!("failed to read the secret key {key}: {err}"))?;
+        return secret_key_from_pem(&pem)
+            .map_err(|err| format!("invalid PEM of the secret key {key}: {err}"));
+    }
+    let secret_key = Zeroizing::new(BASE64_STANDARD.decode(key).unwrap_or_default());
+    secret_key[..].try_into().map_err(|_| {
+        "the secret key must be 32 bytes in Base64 or a path to a PEM file".to_string()
+    })
+}
+
+/// Decodes the secret keys, see [`secret_key`].
+fn secret_keys(keys: &[String]) -> Result<Vec<SecretKey>, String> {
+    keys.iter().map(|key| secret_key(key)).collect()
+}
+
+/// Decodes the public key in Base64, or reads it from a PEM file (SPKI) if the key
+/// is a path to the file. The key is of either algorithm, see
+/// [`KeyAlgorithm::of_public_key`].
+fn public_key(key: &str) -> Option<Vec<u8>> {
+    if let Ok(pem) = std::fs::read_to_string(key) {
+        return public_key_from_pem(&pem).ok().map(Vec::from);
+    }
+    BASE64_STANDARD.decode(key).ok().filter(|k| KeyAlgorithm::of_public_key(k).is_some())
+}
+
+/// Parses the comma-separated labels of all the levels.
+fn parse_level_labels(labels: &str) -> Result<LevelLabels, String> {
+    let labels: Vec<_> = labels.split(',').map(str::trim).collect();
+    let labels: [&str; 5] =
+        labels.try_into().map_err(|_| "the labels of all 5 levels are required".to_string())?;
+    Ok(LevelLabels::new(labels))
+}
+
+/// The encoding of the generated keys.
+#[derive(ValueEnum, Clone, Copy, Default)]
+enum KeyFormat {
+    /// The raw bytes in Base64.
+    #[default]
+    Base64,
+    /// PEM-encoded PKCS#8 (the secret key) and SPKI (the public key), which
+    /// interoperate with OpenSSL.
+    Pem,
+}
+
+/// The key agreement algorithm of the generated keys.
+#[derive(ValueEnum, Clone, Copy, Default)]
+enum Algorithm {
+    /// ECDH over the NIST P-256 curve.
+    #[default]
+    P256,
+    /// X25519, whose public keys are the raw 32 bytes.
+    X25519,
+}
+
+/// The format of the parsed records.
+#[derive(ValueEnum, Clone, Copy, Default)]
+enum OutputFormat {
+    /// The readable text.
+    #[default]
+    Default,
+    /// The CSV rows with a header row.
+    Csv,
+    /// The logfmt lines.
+    Logfmt,
+}
+
+/// The formatter of the output format.
+enum Formatter {
+    Default(CustomDefaultFormatter),
+    Csv(CsvFormatter),
+    Logfmt(LogfmtFormatter),
+    Template(TemplateFormatter),
+}
+
+impl Formatter {
+    fn new(
+        format: OutputFormat,
+        template: Option<&str>,
+        timezone: Timezone,
+        level_labels: Option<LevelLabels>,
+    ) -> Result<Self, TemplateError> {
+        if let Some(template) = template {
+            let formatter = TemplateFormatter::new(template)?;
+            return Ok(Self::Template(formatter.timezone(timezone).level_labels(level_labels)));
+        }
+        Ok(match format {
+            OutputFormat::Default => {
+                Self::Default(DefaultFormatter.timezone(timezone).level_labels(level_labels))
+            }
+            OutputFormat::Csv => Self::Csv(CsvFormatter::new()),
+            OutputFormat::Logfmt => Self::Logfmt(LogfmtFormatter),
+        })
+    }
+}
+
+impl Format for Formatter {
+    fn format(&mut self, record: &Record, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Default(formatter) => formatter.format(record, writer),
+            Self::Csv(formatter) => formatter.format(record, writer),
+            Self::Logfmt(formatter) => formatter.format(record, writer),
+            Self::Template(formatter) => formatter.format(record, writer),
+        }
+    }
 }
 
 #[derive(Args)]
-struct GenKeys;
+struct GenKeys {
+    /// The encoding of the keys.
+    #[arg(short, long, value_enum, default_value_t)]
+    format: KeyFormat,
+    /// The key agreement algorithm of the keys.
+    #[arg(short, long, value_enum, default_value_t)]
+    algorithm: Algorithm,
+}
 
 impl GenKeys {
     fn exec(self) {
-        let (secret_key, public_key) = pinenut_log::encrypt::gen_echd_key_pair();
+        use pinenut_log::encrypt::{
+            gen_echd_key_pair, gen_x25519_key_pair, key_fingerprint, public_key_to_pem,
+            secret_key_to_pem,
+        };
+
+        if let Algorithm::X25519 = self.algorithm {
+            if let KeyFormat::Pem = self.format {
+                return println!("Error: the X25519 keys are only generated in Base64");
+            }
+            let (secret_key, public_key) = gen_x25519_key_pair();
+            println!("X25519 Keys:");
+            println!("-----------");
+            println!("Secret Key: {}", BASE64_STANDARD.encode(secret_key));
+            println!("Public Key: {}", BASE64_STANDARD.encode(public_key));
+            println!("Fingerprint: {}", key_fingerprint(&public_key));
+            return;
+        }
+
+        let (secret_key, public_key) = gen_echd_key_pair();
+        let fingerprint = key_fingerprint(&public_key);
+        if let KeyFormat::Pem = self.format {
+            match (secret_key_to_pem(&secret_key), public_key_to_pem(&public_key)) {
+                (Ok(secret_key), Ok(public_key)) => {
+                    print!("{}{public_key}", secret_key.as_str());
+                    println!("Fingerprint: {fingerprint}");
+                }
+                (Err(err), _) | (_, Err(err)) => println!("Error: {err}"),
+            }
+            return;
+        }
+
         let secret_key = BASE64_STANDARD.encode(secret_key);
         let public_key = BASE64_STANDARD.encode(public_key);
 
@@ -30,6 +226,7 @@ impl GenKeys {
         println!("-----------");
         println!("Secret Key: {}", secret_key);
         println!("Public Key: {}", public_key);
+        println!("Fingerprint: {}", fingerprint);
     }
 }
 
@@ -43,32 +240,156 @@ struct Parse {
     /// directory as `path`.
     #[arg(short, long)]
     output: Option<String>,
-    /// The secret key.
+    /// The secret key (in Base64 or a path to a PEM file), it can be repeated to
+    /// parse the file spanning a key rotation, the key of each chunk is chosen by
+    /// its key fingerprint.
     #[arg(short, long)]
-    secret_key: Option<String>,
+    secret_key: Vec<String>,
+    /// The format of the parsed records.
+    #[arg(short, long, value_enum, default_value_t)]
+    format: OutputFormat,
+    /// The template of the parsed records, e.g.,
+    /// `"{datetime} [{level}] {tag} {file}:{line} - {content}"`.
+    #[arg(short, long, conflicts_with = "format")]
+    template: Option<String>,
+    /// The timezone of the datetimes: `local`, `utc` or an offset such as
+    /// `+08:00`. It doesn't apply to the CSV and logfmt formats, which are in UTC.
+    #[arg(long, default_value_t)]
+    timezone: Timezone,
+    /// The labels of the levels from the most severe one, e.g.,
+    /// `FATAL,WARN,INFO,DEBUG,TRACE`. It doesn't apply to the CSV and logfmt
+    /// formats.
+    #[arg(long, value_parser = parse_level_labels)]
+    level_labels: Option<LevelLabels>,
+    /// The lowest level of the parsed records, e.g., `warn` for the warnings and
+    /// errors only.
+    #[arg(long)]
+    min_level: Option<Level>,
 }
 
 impl Parse {
     fn exec(self) {
         println!("Parsing ...");
         let output = self.output.unwrap_or_else(|| self.path.clone() + ".log");
-        let secret_key = self
-            .secret_key
-            .and_then(|k| BASE64_STANDARD.decode(k).ok())
-            .and_then(|k| k.try_into().ok());
-        let res = pinenut_log::parse_to_file(&self.path, output, secret_key, DefaultFormatter);
+        let secret_keys = match secret_keys(&self.secret_key) {
+            Ok(secret_keys) => secret_keys,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let formatter = match Formatter::new(
+            self.format,
+            self.template.as_deref(),
+            self.timezone,
+            self.level_labels,
+        ) {
+            Ok(formatter) => formatter,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let min_level = self.min_level;
+        let options = ParseOptions::new().secret_keys(&secret_keys).map(min_level.map(|level| {
+            Box::new(move |record: OwnedRecord| (record.level() <= level).then_some(record)) as _
+        }));
+        let res =
+            pinenut_log::parse_to_file_with_options(&self.path, output, None, options, formatter);
+        if let Err(err) = res {
+            println!("Error: {err}");
+        }
+    }
+}
+
+#[derive(Args)]
+struct Merge {
+    /// Paths to log Files.
+    #[arg(required = true)]
+    paths: Vec<String>,
+    /// Path to destnation File.
+    #[arg(short, long)]
+    output: String,
+    /// The secret key, in Base64 or a path to a PEM file.
+    #[arg(short, long)]
+    secret_key: Option<String>,
+    /// The format of the merged records.
+    #[arg(short, long, value_enum, default_value_t)]
+    format: OutputFormat,
+    /// The template of the merged records, see `parse --template`.
+    #[arg(short, long, conflicts_with = "format")]
+    template: Option<String>,
+    /// The timezone of the datetimes: `local`, `utc` or an offset such as
+    /// `+08:00`. It doesn't apply to the CSV and logfmt formats, which are in UTC.
+    #[arg(long, default_value_t)]
+    timezone: Timezone,
+    /// The labels of the levels, see `parse --level-labels`.
+    #[arg(long, value_parser = parse_level_labels)]
+    level_labels: Option<LevelLabels>,
+}
+
+impl Merge {
+    fn exec(self) {
+        println!("Merging ...");
+        let secret_key = match self.secret_key.as_deref().map(secret_key).transpose() {
+            Ok(secret_key) => secret_key,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let formatter = match Formatter::new(
+            self.format,
+            self.template.as_deref(),
+            self.timezone,
+            self.level_labels,
+        ) {
+            Ok(formatter) => formatter,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let res = pinenut_log::merge(&self.paths, secret_key, formatter, self.output);
         if let Err(err) = res {
             println!("Error: {err}");
         }
     }
 }
 
+#[derive(Args)]
+struct Repair {
+    /// Path to the damaged log File.
+    path: String,
+    /// Path to destnation File.
+    ///
+    /// If it is not specified, the default `.repaired.pine` file is generated in
+    /// the same directory as `path`.
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+impl Repair {
+    fn exec(self) {
+        println!("Repairing ...");
+        let output = self.output.unwrap_or_else(|| {
+            let path = self.path.strip_suffix(".pine").unwrap_or(&self.path);
+            format!("{path}.repaired.pine")
+        });
+        match pinenut_log::salvage(&self.path, &output) {
+            Ok(report) => {
+                println!("Chunks: {}", report.chunks);
+                println!("Truncated chunks: {}", report.truncated_chunks);
+                println!("Skipped bytes: {}", report.skipped_bytes);
+                println!("Written to: {output}");
+            }
+            Err(err) => println!("Error: {err}"),
+        }
+    }
+}
+
 impl Command {
     #[inline]
     fn exec(self) {
         match self {
             Self::GenKeys(gen_keys) => gen_keys.exec(),
+            Self::Key(key) => key.exec(),
             Self::Parse(parse) => parse.exec(),
+            Self::Merge(merge) => merge.exec(),
+            Self::Stats(stats) => stats.exec(),
+            Self::Repair(repair) => repair.exec(),
+            Self::Verify(verify) => verify.exec(),
+            Self::Watch(watch) => watch.exec(),
+            Self::GenLogs(gen_logs) => gen_logs.exec(),
+            Self::Bench(bench) => bench.exec(),
         }
     }
 }
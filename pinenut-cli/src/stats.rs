@@ -0,0 +1,113 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use clap::Args;
+use pinenut_log::{ChunkStats, DateTime, Level, ParseOptions};
+
+use crate::secret_key;
+
+#[derive(Args)]
+pub(crate) struct Stats {
+    /// Path to log File.
+    path: String,
+    /// The secret key, in Base64 or a path to a PEM file.
+    #[arg(short, long)]
+    secret_key: Option<String>,
+    /// The number of the most frequent tags and messages to report.
+    #[arg(short, long, default_value_t = 10)]
+    top: usize,
+}
+
+#[derive(Default)]
+struct Summary {
+    records: usize,
+    levels: BTreeMap<Level, usize>,
+    tags: HashMap<String, usize>,
+    messages: HashMap<String, usize>,
+    /// The counts of the records by minute (the timestamp in minutes).
+    minutes: BTreeMap<i64, usize>,
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+}
+
+impl Stats {
+    pub(crate) fn exec(self) {
+        let secret_key = match self.secret_key.as_deref().map(secret_key).transpose() {
+            Ok(secret_key) => secret_key,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let chunks = Rc::new(RefCell::new(Vec::new()));
+        let options = ParseOptions::new().on_chunk(Some(Box::new({
+            let chunks = Rc::clone(&chunks);
+            move |stats: &ChunkStats| chunks.borrow_mut().push(stats.clone())
+        })));
+
+        let mut summary = Summary::default();
+        let res = pinenut_log::parse_with_options(&self.path, secret_key, options, |record| {
+            let (meta, datetime) = (record.meta(), record.meta().datetime());
+            // The repeated records are counted as they were logged.
+            let count = record.repeats() as usize + 1;
+            let tag = meta.tag().unwrap_or_default().to_string();
+            summary.records += count;
+            *summary.levels.entry(meta.level()).or_default() += count;
+            *summary.tags.entry(tag).or_default() += count;
+            *summary.messages.entry(record.content().to_string()).or_default() += count;
+            *summary.minutes.entry(datetime.timestamp().div_euclid(60)).or_default() += count;
+            summary.start = Some(summary.start.map_or(datetime, |s| s.min(datetime)));
+            summary.end = Some(summary.end.map_or(datetime, |e| e.max(datetime)));
+            Ok(())
+        });
+
+        summary.print(&chunks.borrow(), self.top);
+        if let Err(err) = res {
+            println!("Error: {err}");
+        }
+    }
+}
+
+impl Summary {
+    fn print(&self, chunks: &[ChunkStats], top: usize) {
+        println!("Records: {}", self.records);
+        for (level, count) in &self.levels {
+            println!("  {level}: {count}");
+        }
+
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            println!("Time span: {start} ~ {end} ({}s)", (end - start).num_seconds());
+            let minutes = (end - start).num_minutes() + 1;
+            println!("Records per minute: {:.1} (average)", self.records as f64 / minutes as f64);
+            if let Some((minute, count)) = self.minutes.iter().max_by_key(|(_, &count)| count) {
+                let peak = DateTime::from_timestamp(minute * 60, 0).unwrap_or_default();
+                println!("  Peak: {count} at {}", peak.format("%F %R"));
+            }
+        }
+
+        let payload_len: usize = chunks.iter().map(|c| c.payload_len).sum();
+        let decoded_len: usize = chunks.iter().map(|c| c.decoded_len).sum();
+        println!("Chunks: {}", chunks.len());
+        if payload_len > 0 {
+            let ratio = decoded_len as f64 / payload_len as f64;
+            println!("Compression: {decoded_len} / {payload_len} bytes ({ratio:.2}x)");
+        }
+
+        println!("Top tags:");
+        for (tag, count) in Self::top(&self.tags, top) {
+            println!("  {count}\t{}", if tag.is_empty() { "<none>" } else { tag });
+        }
+        println!("Top messages:");
+        for (message, count) in Self::top(&self.messages, top) {
+            println!("  {count}\t{}", message.lines().next().unwrap_or_default());
+        }
+    }
+
+    /// The `n` entries with the largest counts, in descending order.
+    fn top(counts: &HashMap<String, usize>, n: usize) -> Vec<(&str, usize)> {
+        let mut entries: Vec<_> = counts.iter().map(|(k, &v)| (k.as_str(), v)).collect();
+        entries.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        entries.truncate(n);
+        entries
+    }
+}
@@ -0,0 +1,127 @@
+use std::{cell::RefCell, ops::RangeInclusive, process, rc::Rc};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use clap::Args;
+use pinenut_log::{ChunkStats, DateTime, ParseOptions, ParsingError, SigningKey};
+
+use crate::secret_keys;
+
+#[derive(Args)]
+pub(crate) struct Verify {
+    /// Path to log File.
+    path: String,
+    /// The secret key (in Base64 or a path to a PEM file), it can be repeated to
+    /// verify the file spanning a key rotation.
+    #[arg(short, long)]
+    secret_key: Vec<String>,
+    /// The signing key in Base64, the signatures of the chunks are verified if it
+    /// is specified.
+    #[arg(long)]
+    signing_key: Option<String>,
+    /// Reports the diagnostics of every chunk, not only the failed ones.
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+impl Verify {
+    /// Decrypts, decompresses and decodes all chunks without formatting the records,
+    /// and exits with a non-zero status if any of them fails.
+    pub(crate) fn exec(self) {
+        let signing_key = match self.signing_key.as_deref().map(signing_key).transpose() {
+            Ok(key) => key,
+            Err(err) => {
+                println!("Error: {err}");
+                process::exit(2);
+            }
+        };
+        let secret_keys = match secret_keys(&self.secret_key) {
+            Ok(secret_keys) => secret_keys,
+            Err(err) => {
+                println!("Error: {err}");
+                process::exit(2);
+            }
+        };
+
+        let chunks = Rc::new(RefCell::new(Vec::new()));
+        let options = ParseOptions::new()
+            .secret_keys(&secret_keys)
+            .signing_key(signing_key)
+            .on_chunk(Some(Box::new({
+                let chunks = Rc::clone(&chunks);
+                move |stats: &ChunkStats| chunks.borrow_mut().push(stats.clone())
+            })));
+
+        let mut records = 0;
+        let res = pinenut_log::parse_with_options(&self.path, None, options, |record| {
+            records += record.repeats() as usize + 1;
+            Ok(())
+        });
+
+        let chunks = chunks.borrow();
+        let (errors, fatal) = match res {
+            Ok(()) => (Vec::new(), None),
+            Err(ParsingError::Chunks(errors)) => (errors, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            let failed = errors.iter().filter(|err| time_range(err) == Some(&chunk.time_range));
+            let failed: Vec<_> = failed.map(ToString::to_string).collect();
+            if !self.verbose && failed.is_empty() {
+                continue;
+            }
+            let (start, end) = (chunk.time_range.start(), chunk.time_range.end());
+            let status = if failed.is_empty() { "ok" } else { "FAIL" };
+            println!(
+                "Chunk #{index} [{status}] {start} ~ {end}, {} -> {} bytes",
+                chunk.payload_len, chunk.decoded_len
+            );
+            for err in failed {
+                println!("  {err}");
+            }
+            if chunk.unrecoverable_len > 0 {
+                println!("  {} bytes unrecoverable", chunk.unrecoverable_len);
+            }
+        }
+        // The chunks that couldn't be read at all (e.g., encrypted for another key).
+        let is_unread = |err: &&ParsingError| {
+            !chunks.iter().any(|chunk| time_range(err) == Some(&chunk.time_range))
+        };
+        for err in errors.iter().filter(is_unread) {
+            println!("Error: {err}");
+        }
+        if let Some(err) = &fatal {
+            println!("Error: {err}");
+        }
+
+        println!("Chunks: {}", chunks.len());
+        println!("Records: {records}");
+        println!("Errors: {}", errors.len() + fatal.iter().count());
+        if errors.is_empty() && fatal.is_none() {
+            println!("Result: PASS");
+        } else {
+            println!("Result: FAIL");
+            process::exit(1);
+        }
+    }
+}
+
+/// Decodes the signing key in Base64.
+fn signing_key(key: &str) -> Result<SigningKey, String> {
+    let key = BASE64_STANDARD.decode(key).map_err(|err| err.to_string())?;
+    key.try_into().map_err(|_| "the signing key must be 32 bytes".to_string())
+}
+
+/// The time range of the chunk that the error occurred in, if any.
+fn time_range(error: &ParsingError) -> Option<&RangeInclusive<DateTime>> {
+    match error {
+        ParsingError::Decrypt(_, range)
+        | ParsingError::Decompress(_, range)
+        | ParsingError::Decode(_, range)
+        | ParsingError::Skipped(_, _, range)
+        | ParsingError::Chunk(_, range)
+        | ParsingError::Limit(_, range)
+        | ParsingError::KeyMismatch { time_range: range, .. } => Some(range),
+        _ => None,
+    }
+}
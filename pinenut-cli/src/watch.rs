@@ -0,0 +1,133 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+use clap::Args;
+use pinenut_log::{
+    Domain, Format, LevelLabels, ParseOptions, ParsingError, Record, SecretKey, Timezone,
+};
+
+use crate::{parse_level_labels, secret_keys, Formatter, OutputFormat};
+
+#[derive(Args)]
+pub(crate) struct Watch {
+    /// The directory where the log files of the domain are stored.
+    #[arg(short, long)]
+    dir: PathBuf,
+    /// The identifier of the domain.
+    #[arg(short, long)]
+    identifier: String,
+    /// The secret key (in Base64 or a path to a PEM file), it can be repeated to
+    /// parse the files spanning a key rotation.
+    #[arg(short, long)]
+    secret_key: Vec<String>,
+    /// The format of the printed records.
+    #[arg(short, long, value_enum, default_value_t)]
+    format: OutputFormat,
+    /// The template of the printed records, see `parse --template`.
+    #[arg(short, long, conflicts_with = "format")]
+    template: Option<String>,
+    /// The timezone of the datetimes: `local`, `utc` or an offset such as
+    /// `+08:00`. It doesn't apply to the CSV and logfmt formats, which are in UTC.
+    #[arg(long, default_value_t)]
+    timezone: Timezone,
+    /// The labels of the levels, see `parse --level-labels`.
+    #[arg(long, value_parser = parse_level_labels)]
+    level_labels: Option<LevelLabels>,
+    /// Prints the records already in the log files too, by default only the ones
+    /// written after the watch starts are printed.
+    #[arg(short, long)]
+    all: bool,
+    /// The interval of polling the directory, in milliseconds.
+    #[arg(long, default_value_t = 500)]
+    interval: u64,
+}
+
+impl Watch {
+    /// Polls the log files of the domain, and prints the records of the chunks as
+    /// they land until it is interrupted.
+    pub(crate) fn exec(self) {
+        let mut formatter = match Formatter::new(
+            self.format,
+            self.template.as_deref(),
+            self.timezone,
+            self.level_labels,
+        ) {
+            Ok(formatter) => formatter,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let secret_keys = match secret_keys(&self.secret_key) {
+            Ok(secret_keys) => secret_keys,
+            Err(err) => return println!("Error: {err}"),
+        };
+        let domain = Domain::new(self.identifier, self.dir);
+        let mut watcher = Watcher { secret_keys, tails: HashMap::new() };
+
+        if !self.all {
+            watcher.poll(&domain, |_| Ok(()));
+        }
+        let mut stdout = io::stdout().lock();
+        loop {
+            watcher.poll(&domain, |record| formatter.format(record, &mut stdout));
+            _ = stdout.flush();
+            thread::sleep(Duration::from_millis(self.interval));
+        }
+    }
+}
+
+struct Watcher {
+    secret_keys: Vec<SecretKey>,
+    /// The log files parsed so far.
+    tails: HashMap<PathBuf, Tail>,
+}
+
+/// The progress of a log file being watched.
+#[derive(Default)]
+struct Tail {
+    /// The offset of the chunks parsed so far.
+    offset: u64,
+    /// The error of the last poll, which is printed once until it changes.
+    error: Option<String>,
+}
+
+impl Watcher {
+    /// Parses the chunks landed in the log files since the last poll.
+    ///
+    /// The log file failing to be parsed is retried on the next poll, as the error
+    /// may be transient.
+    fn poll(&mut self, domain: &Domain, mut callback: impl FnMut(&Record) -> io::Result<()>) {
+        let paths = match domain.logfiles() {
+            Ok(paths) => paths,
+            // The directory is created by the logger on its first write.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return,
+            Err(err) => return println!("Error: {err}"),
+        };
+        // The log files deleted (e.g., expired) are forgotten.
+        self.tails.retain(|path, _| paths.contains(path));
+        for path in paths {
+            let tail = self.tails.entry(path.clone()).or_default();
+            let options = ParseOptions::new().secret_keys(&self.secret_keys);
+            let offset = &mut tail.offset;
+            match pinenut_log::parse_incremental(&path, offset, None, options, &mut callback) {
+                Ok(()) => tail.error = None,
+                Err(ParsingError::Chunks(errors)) => {
+                    tail.error = None;
+                    for err in errors {
+                        println!("Error: {}: {err}", path.display());
+                    }
+                }
+                Err(err) => {
+                    let err = err.to_string();
+                    if tail.error.as_ref() != Some(&err) {
+                        println!("Error: {}: {err}", path.display());
+                    }
+                    tail.error = Some(err);
+                }
+            }
+        }
+    }
+}
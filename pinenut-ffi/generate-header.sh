@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+
+# Generates the canonical C header (`include/pinenut.h`) from the `#[repr(C)]` types
+# and the `extern "C"` functions of the crate.
+#
+# Usage:
+#   generate-header.sh           Regenerates the header.
+#   generate-header.sh --verify  Checks that the header is up to date.
+
+set -euo pipefail
+
+CRATE_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" &> /dev/null && pwd )"
+HEADER_PATH="$CRATE_DIR/include/pinenut.h"
+
+if ! command -v cbindgen &> /dev/null; then
+  echo "Could not find cbindgen, install it via \`cargo install cbindgen\`"
+  exit 1
+fi
+
+ARGS=("$CRATE_DIR" --config "$CRATE_DIR/cbindgen.toml" --output "$HEADER_PATH" --quiet)
+if [[ "${1:-}" == "--verify" ]]; then
+  if ! cbindgen "${ARGS[@]}" --verify; then
+    echo "$HEADER_PATH is out of date, run $0 to regenerate it"
+    exit 1
+  fi
+else
+  cbindgen "${ARGS[@]}"
+fi
@@ -0,0 +1,454 @@
+#ifndef PINENUT_H
+#define PINENUT_H
+
+/* This file is generated by cbindgen, do not modify it manually, run `pinenut-ffi/generate-header.sh` instead. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum FFICallCode {
+  FFICallSucces = 0,
+  FFICallError,
+  FFICallPanic,
+} FFICallCode;
+
+enum FFILevel
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  Error = 1,
+  Warn,
+  Info,
+  Debug,
+  Verbose,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum FFILevel FFILevel;
+#else
+typedef uint8_t FFILevel;
+#endif // __STDC_VERSION__ >= 202311L
+
+enum FFITimeDimension
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  Day = 1,
+  Hour,
+  Minute,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum FFITimeDimension FFITimeDimension;
+#else
+typedef uint8_t FFITimeDimension;
+#endif // __STDC_VERSION__ >= 202311L
+
+enum FFIMirror
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  FFIMirror_None = 0,
+  FFIMirror_Logcat,
+  FFIMirror_OsLog,
+  FFIMirror_Stderr,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum FFIMirror FFIMirror;
+#else
+typedef uint8_t FFIMirror;
+#endif // __STDC_VERSION__ >= 202311L
+
+enum FFITrackCategory
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  FFITrackCategory_Other = 0,
+  FFITrackCategory_Processing,
+  FFITrackCategory_ChunkOverflow,
+  FFITrackCategory_Io,
+  FFITrackCategory_IoPanic,
+  FFITrackCategory_Runloop,
+  FFITrackCategory_BufferCorrupted,
+  FFITrackCategory_BufferFallback,
+  FFITrackCategory_InsufficientSpace,
+  FFITrackCategory_Trim,
+  FFITrackCategory_WritebackRecovered,
+  FFITrackCategory_IoPaused,
+  FFITrackCategory_IoResumed,
+  FFITrackCategory_BufferReinitialized,
+  FFITrackCategory_ClockRegressed,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum FFITrackCategory FFITrackCategory;
+#else
+typedef uint8_t FFITrackCategory;
+#endif // __STDC_VERSION__ >= 202311L
+
+enum FFISeverity
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  FFISeverity_Info = 1,
+  FFISeverity_Warning,
+  FFISeverity_Error,
+  FFISeverity_Critical,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum FFISeverity FFISeverity;
+#else
+typedef uint8_t FFISeverity;
+#endif // __STDC_VERSION__ >= 202311L
+
+enum FFIHint
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  FFIHint_EnteringBackground = 1,
+  FFIHint_LowMemory,
+  FFIHint_Terminating,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum FFIHint FFIHint;
+#else
+typedef uint8_t FFIHint;
+#endif // __STDC_VERSION__ >= 202311L
+
+typedef struct FFIBytes {
+  const void *ptr;
+  uint64_t len;
+} FFIBytes;
+
+typedef struct FFIBytesBuf {
+  void *ptr;
+  uint64_t len;
+  uint64_t capacity;
+} FFIBytesBuf;
+
+typedef struct FFICallState {
+  enum FFICallCode code;
+  struct FFIBytesBuf err_desc;
+} FFICallState;
+
+typedef struct FFIDomain {
+  struct FFIBytes identifier;
+  struct FFIBytes directory;
+} FFIDomain;
+
+typedef struct FFIConfig {
+  bool use_mmap;
+  uint64_t buffer_len;
+  FFITimeDimension rotation;
+  struct FFIBytes key_str;
+  int32_t compression_level;
+} FFIConfig;
+
+/**
+ * The callback of the tracker, the message is borrowed and is only valid during
+ * the callback.
+ *
+ * The callback may be called on any thread, including the IO thread of the logger.
+ * There is no tracker if the callback is null.
+ */
+typedef void (*FFITrackCallback)(void *context,
+                                 FFITrackCategory category,
+                                 FFISeverity severity,
+                                 const struct FFIBytes *message);
+
+/**
+ * The tracker of the logger, see [`FFITrackCallback`].
+ */
+typedef struct FFITracker {
+  void *context;
+  FFITrackCallback callback;
+} FFITracker;
+
+/**
+ * The second version of [`FFIConfig`], which carries the configurations added
+ * after the first one. The first version is kept for the ABI compatibility.
+ */
+typedef struct FFIConfigV2 {
+  struct FFIConfig base;
+  FFIMirror mirror;
+  /**
+   * The maximum number of the records per second, `0` means there is no limit.
+   */
+  uint32_t rate_limit_per_second;
+  bool rate_limit_per_tag;
+  struct FFITracker tracker;
+} FFIConfigV2;
+
+typedef struct FFIRecord {
+  FFILevel level;
+  int64_t datetime_secs;
+  uint32_t datetime_nsecs;
+  struct FFIBytes tag;
+  struct FFIBytes file;
+  struct FFIBytes func;
+  uint32_t line;
+  uint64_t thread_id;
+  struct FFIBytes content;
+} FFIRecord;
+
+/**
+ * The second version of [`FFIRecord`], whose tag, file and function may be the
+ * strings interned by [`pinenut_intern`]. The first version is kept for the ABI
+ * compatibility.
+ *
+ * The field with the id of `u32::MAX` is taken from the bytes of the base record
+ * instead, and the one with an unknown id is logged as `<unknown>`, which is
+ * reported as an error of the call.
+ */
+typedef struct FFIRecordV2 {
+  struct FFIRecord base;
+  uint32_t tag_id;
+  uint32_t file_id;
+  uint32_t func_id;
+} FFIRecordV2;
+
+/**
+ * The callback of the extraction progress, along with the context in
+ * [`FFIExtractProgress`]. It is invoked on the calling thread of
+ * [`pinenut_extract_with_progress`] before it returns.
+ */
+typedef void (*FFIProgressCallback)(void *context, uint64_t scanned_bytes, uint64_t total_bytes);
+
+/**
+ * The progress reporter of the extraction, there is none if the callback is
+ * null.
+ */
+typedef struct FFIExtractProgress {
+  void *context;
+  FFIProgressCallback callback;
+} FFIExtractProgress;
+
+/**
+ * The key-value pair of the context of a parsed record, see [`FFIRecordView`].
+ */
+typedef struct FFIContextEntry {
+  struct FFIBytes key;
+  struct FFIBytes value;
+} FFIContextEntry;
+
+/**
+ * The view of a parsed record, the strings and the context are borrowed from the
+ * parser and are only valid during the callback (see [`parser::pinenut_parse`]).
+ *
+ * The absent strings are null, the absent line, thread id, session id, elapsed
+ * time and sequence number are the maximum values of their types, the same as
+ * [`FFIRecord`].
+ */
+typedef struct FFIRecordView {
+  FFILevel level;
+  int64_t datetime_secs;
+  uint32_t datetime_nsecs;
+  struct FFIBytes tag;
+  struct FFIBytes file;
+  struct FFIBytes func;
+  uint32_t line;
+  uint64_t thread_id;
+  struct FFIBytes content;
+  struct FFIBytes thread_name;
+  uint64_t session_id;
+  /**
+   * The milliseconds elapsed since the logger was initialized.
+   */
+  uint64_t elapsed;
+  uint64_t sequence;
+  /**
+   * How many times the log was repeated consecutively after it.
+   */
+  uint32_t repeats;
+  /**
+   * Whether the content was truncated by the logger.
+   */
+  bool truncated;
+  const struct FFIContextEntry *context;
+  uint64_t context_len;
+} FFIRecordView;
+
+/**
+ * The callback invoked with each parsed record, along with the context passed
+ * to [`pinenut_parse`].
+ */
+typedef void (*FFIParseCallback)(void *context, const struct FFIRecordView *record);
+
+/**
+ * The metadata of a chunk read from its header, see [`parser::pinenut_inspect`].
+ */
+typedef struct FFIChunkInfo {
+  int64_t start_secs;
+  int64_t end_secs;
+  /**
+   * The length of the compressed and maybe encrypted payload in bytes.
+   */
+  uint64_t payload_len;
+  uint16_t version;
+  bool encrypted;
+} FFIChunkInfo;
+
+/**
+ * The array of [`FFIChunkInfo`]s owned by the caller, which must be deallocated
+ * by [`parser::pinenut_dealloc_chunk_infos`].
+ */
+typedef struct FFIChunkInfoArray {
+  struct FFIChunkInfo *ptr;
+  uint64_t len;
+  uint64_t capacity;
+} FFIChunkInfoArray;
+
+struct FFIBytes pinenut_bytes_null(void);
+
+void pinenut_dealloc_bytes(struct FFIBytesBuf bytes, struct FFICallState *state);
+
+struct FFICallState pinenut_call_state_success(void);
+
+/**
+ * Interns the string (e.g., a tag or a file that is logged repeatedly), returns
+ * its id for [`crate::FFIRecordV2`], so that the string crosses the FFI boundary
+ * only once.
+ *
+ * The same string is always interned with the same id, and the interned strings
+ * are kept for the lifetime of the process. The bytes that are null or not valid
+ * UTF-8 are rejected with `u32::MAX`, which takes the field from the bytes.
+ */
+uint32_t pinenut_intern(struct FFIBytes bytes, struct FFICallState *state);
+
+/**
+ * Parses the level from its name (e.g., `warn`) or initial (e.g., `W`),
+ * case-insensitively, so that the bindings translate the levels the same way.
+ */
+FFILevel pinenut_level_from_str(struct FFIBytes bytes, struct FFICallState *state);
+
+/**
+ * Returns the lowercase name of the level (e.g., `warn`), which is static and
+ * must not be deallocated.
+ */
+struct FFIBytes pinenut_level_name(FFILevel level);
+
+void *pinenut_logger_new(struct FFIDomain domain,
+                         struct FFIConfig config,
+                         struct FFICallState *state);
+
+/**
+ * Constructs a logger with the [`FFIConfigV2`].
+ */
+void *pinenut_logger_new_v2(struct FFIDomain domain,
+                            struct FFIConfigV2 config,
+                            struct FFICallState *state);
+
+void pinenut_logger_log(const void *ptr, struct FFIRecord record, struct FFICallState *state);
+
+/**
+ * Logs the [`FFIRecordV2`], whose fields may be the interned strings.
+ */
+void pinenut_logger_log_v2(const void *ptr, struct FFIRecordV2 record, struct FFICallState *state);
+
+/**
+ * Logs the records in order, crossing the FFI boundary once per batch (e.g.,
+ * for the bridges batching the records upstream).
+ *
+ * The records are borrowed during the call, nothing is logged if the pointer
+ * of the records is null.
+ */
+void pinenut_logger_log_batch(const void *ptr,
+                              const struct FFIRecord *records,
+                              uint64_t len,
+                              struct FFICallState *state);
+
+/**
+ * Logs the [`FFIRecordV2`]s in order, see [`pinenut_logger_log_batch`]. All the
+ * records are logged, and the first unknown id is reported.
+ */
+void pinenut_logger_log_batch_v2(const void *ptr,
+                                 const struct FFIRecordV2 *records,
+                                 uint64_t len,
+                                 struct FFICallState *state);
+
+void pinenut_logger_flush(const void *ptr, struct FFICallState *state);
+
+/**
+ * Performs the behavior appropriate for the change of the application
+ * lifecycle, it may block until the buffered records have been written.
+ */
+void pinenut_logger_hint(const void *ptr, FFIHint hint, struct FFICallState *state);
+
+void pinenut_logger_trim(const void *ptr, uint64_t lifetime, struct FFICallState *state);
+
+void pinenut_logger_shutdown(void *ptr, struct FFICallState *state);
+
+/**
+ * In most cases, the upper layer just calls the [`pinenut_logger_shutdown`]
+ * function when the logger instance is deallocated.
+ */
+void pinenut_dealloc_logger(void *ptr, struct FFICallState *state);
+
+/**
+ * Constructs a cancellation token of the extraction, which must be deallocated
+ * by [`pinenut_dealloc_cancel_token`].
+ */
+void *pinenut_cancel_token_new(struct FFICallState *state);
+
+/**
+ * Cancels the extraction with the token, it can be called on any thread.
+ */
+void pinenut_cancel_token_cancel(const void *ptr, struct FFICallState *state);
+
+void pinenut_dealloc_cancel_token(void *ptr, struct FFICallState *state);
+
+void pinenut_extract(struct FFIDomain domain,
+                     int64_t start_time,
+                     int64_t end_time,
+                     struct FFIBytes dest_path,
+                     struct FFICallState *state);
+
+/**
+ * Extracts the logs like [`pinenut_extract`], reports the progress and fails
+ * once the token (if not null) is cancelled, e.g., when the connectivity drops
+ * during an upload. The destination file written partially is deleted then.
+ */
+void pinenut_extract_with_progress(struct FFIDomain domain,
+                                   int64_t start_time,
+                                   int64_t end_time,
+                                   struct FFIBytes dest_path,
+                                   struct FFIExtractProgress progress,
+                                   const void *cancel_token,
+                                   struct FFICallState *state);
+
+/**
+ * Parses the log file, and invokes the callback with each record in order,
+ * e.g., to render the records in an in-app viewer without writing them to a
+ * text file.
+ *
+ * The callback is invoked on the calling thread before this function returns,
+ * nothing is parsed if it is null.
+ */
+void pinenut_parse(struct FFIBytes path,
+                   struct FFIBytes secret_key,
+                   void *context,
+                   FFIParseCallback callback,
+                   struct FFICallState *state);
+
+void pinenut_parse_to_file(struct FFIBytes path,
+                           struct FFIBytes dest_path,
+                           struct FFIBytes secret_key,
+                           struct FFICallState *state);
+
+/**
+ * Reads the metadata of the chunks in the log file from their headers without
+ * parsing the records, e.g., to show the size and the time span of the logs in
+ * the settings.
+ *
+ * The returned array must be deallocated by [`pinenut_dealloc_chunk_infos`].
+ */
+struct FFIChunkInfoArray pinenut_inspect(struct FFIBytes path, struct FFICallState *state);
+
+void pinenut_dealloc_chunk_infos(struct FFIChunkInfoArray infos, struct FFICallState *state);
+
+#endif  /* PINENUT_H */
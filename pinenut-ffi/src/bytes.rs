@@ -10,6 +10,18 @@ pub struct FFIBytes {
 }
 
 impl FFIBytes {
+    /// Borrows the slice, `None` is represented by the null pointer.
+    #[inline]
+    pub(crate) fn borrow(slice: Option<&[u8]>) -> Self {
+        match slice {
+            Some(slice) => Self {
+                ptr: slice.as_ptr() as *const c_void,
+                len: slice.len().try_into().expect("len cannot fit into u64"),
+            },
+            None => Self { ptr: ptr::null(), len: 0 },
+        }
+    }
+
     #[inline]
     pub(crate) fn is_null(&self) -> bool {
         self.ptr.is_null()
@@ -106,3 +106,11 @@ impl FFIDefault for () {
     #[inline]
     fn default() -> Self {}
 }
+
+/// The invalid id, e.g., of the interned strings.
+impl FFIDefault for u32 {
+    #[inline]
+    fn default() -> Self {
+        u32::MAX
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    error, fmt, str,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::{call::ffi_call_result, FFIBytes, FFICallState};
+
+/// The id of no interned string, the field of the record with it is taken from the
+/// bytes instead.
+const NO_ID: u32 = u32::MAX;
+
+/// The placeholder of the field with an unknown id, so that the misuse shows up in
+/// the logs instead of the field being dropped silently.
+const UNKNOWN: &str = "<unknown>";
+
+/// Errors that can be occurred while interning or resolving the strings.
+#[derive(Debug)]
+pub(crate) enum Error {
+    /// The bytes to intern are null or not valid UTF-8.
+    InvalidString,
+    /// The id was not returned by [`pinenut_intern`] in this process, e.g., it is
+    /// stale or corrupted.
+    UnknownId(u32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidString => write!(f, "string to intern is null or not valid UTF-8"),
+            Self::UnknownId(id) => write!(f, "unknown interned string id {id}"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The strings interned for the lifetime of the process, which are expected to be
+/// the few distinct tags, files and functions.
+#[derive(Default)]
+struct Interner {
+    strings: Vec<&'static str>,
+    ids: HashMap<&'static str, u32>,
+}
+
+#[inline]
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+/// Interns the string, returns its id.
+fn intern(str: &str) -> u32 {
+    if let Some(id) = interner().read().unwrap().ids.get(str) {
+        return *id;
+    }
+    let mut interner = interner().write().unwrap();
+    // The string may have been interned by another thread in the meantime.
+    if let Some(id) = interner.ids.get(str) {
+        return *id;
+    }
+    let id = interner.strings.len().try_into().ok().filter(|id| *id != NO_ID);
+    let id = id.expect("too many interned strings");
+    let str: &'static str = Box::leak(str.into());
+    interner.strings.push(str);
+    interner.ids.insert(str, id);
+    id
+}
+
+/// The interned string of the id, or the string of the bytes if the id is
+/// [`NO_ID`].
+///
+/// The unknown id resolves to a placeholder, along with the error to be reported.
+#[inline]
+pub(crate) unsafe fn resolve(id: u32, bytes: &FFIBytes) -> (Option<&str>, Option<Error>) {
+    match id {
+        NO_ID => (bytes.as_str(), None),
+        id => match interner().read().unwrap().strings.get(id as usize) {
+            Some(str) => (Some(str), None),
+            None => (Some(UNKNOWN), Some(Error::UnknownId(id))),
+        },
+    }
+}
+
+/// Interns the string (e.g., a tag or a file that is logged repeatedly), returns
+/// its id for [`crate::FFIRecordV2`], so that the string crosses the FFI boundary
+/// only once.
+///
+/// The same string is always interned with the same id, and the interned strings
+/// are kept for the lifetime of the process. The bytes that are null or not valid
+/// UTF-8 are rejected with `u32::MAX`, which takes the field from the bytes.
+#[no_mangle]
+pub unsafe extern "C" fn pinenut_intern(bytes: FFIBytes, state: &mut FFICallState) -> u32 {
+    ffi_call_result(state, || {
+        let str = bytes.as_slice().and_then(|b| str::from_utf8(b).ok());
+        str.map(intern).ok_or(Error::InvalidString)
+    })
+}
@@ -2,13 +2,25 @@
 
 mod bytes;
 
-use std::mem;
+use std::{
+    ffi::c_void,
+    mem::{self, ManuallyDrop},
+    ptr,
+};
 
 pub use bytes::*;
 
 mod call;
 pub use call::*;
-use pinenut_log::{Config, DateTime, Domain, Level, Location, Meta, Record, TimeDimension};
+
+mod intern;
+pub use intern::*;
+use pinenut_log::{
+    ChunkInfo, Config, DateTime, Domain, Hint, Level, Location, Meta, Mirror, RateLimit, Record,
+    Severity, TimeDimension, Track, TrackCategory, TrackEvent,
+};
+
+use crate::call::FFIDefault;
 
 #[repr(C)]
 pub struct FFIDomain {
@@ -62,6 +74,165 @@ impl FFIConfig {
     }
 }
 
+/// cbindgen:prefix-with-name
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub enum FFIMirror {
+    None = 0,
+    Logcat,
+    OsLog,
+    Stderr,
+}
+
+impl FFIMirror {
+    #[inline]
+    fn to_mirror(self) -> Option<Mirror> {
+        match self {
+            Self::None => None,
+            Self::Logcat => Some(Mirror::Logcat),
+            Self::OsLog => Some(Mirror::OsLog),
+            Self::Stderr => Some(Mirror::Stderr),
+        }
+    }
+}
+
+/// cbindgen:prefix-with-name
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub enum FFITrackCategory {
+    Other = 0,
+    Processing,
+    ChunkOverflow,
+    Io,
+    IoPanic,
+    Runloop,
+    BufferCorrupted,
+    BufferFallback,
+    InsufficientSpace,
+    Trim,
+    WritebackRecovered,
+    IoPaused,
+    IoResumed,
+    BufferReinitialized,
+    ClockRegressed,
+}
+
+impl FFITrackCategory {
+    #[inline]
+    fn from_category(category: TrackCategory) -> Self {
+        match category {
+            TrackCategory::Processing => Self::Processing,
+            TrackCategory::ChunkOverflow => Self::ChunkOverflow,
+            TrackCategory::Io => Self::Io,
+            TrackCategory::IoPanic => Self::IoPanic,
+            TrackCategory::Runloop => Self::Runloop,
+            TrackCategory::BufferCorrupted => Self::BufferCorrupted,
+            TrackCategory::BufferFallback => Self::BufferFallback,
+            TrackCategory::InsufficientSpace => Self::InsufficientSpace,
+            TrackCategory::Trim => Self::Trim,
+            TrackCategory::WritebackRecovered => Self::WritebackRecovered,
+            TrackCategory::IoPaused => Self::IoPaused,
+            TrackCategory::IoResumed => Self::IoResumed,
+            TrackCategory::BufferReinitialized => Self::BufferReinitialized,
+            TrackCategory::ClockRegressed => Self::ClockRegressed,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// cbindgen:prefix-with-name
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub enum FFISeverity {
+    Info = 1,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl FFISeverity {
+    #[inline]
+    fn from_severity(severity: Severity) -> Self {
+        match severity {
+            Severity::Info => Self::Info,
+            Severity::Warning => Self::Warning,
+            Severity::Error => Self::Error,
+            Severity::Critical => Self::Critical,
+        }
+    }
+}
+
+/// The callback of the tracker, the message is borrowed and is only valid during
+/// the callback.
+///
+/// The callback may be called on any thread, including the IO thread of the logger.
+/// There is no tracker if the callback is null.
+pub type FFITrackCallback = Option<
+    unsafe extern "C" fn(
+        context: *mut c_void,
+        category: FFITrackCategory,
+        severity: FFISeverity,
+        message: &FFIBytes,
+    ),
+>;
+
+/// The tracker of the logger, see [`FFITrackCallback`].
+#[repr(C)]
+pub struct FFITracker {
+    context: *mut c_void,
+    callback: FFITrackCallback,
+}
+
+/// The upper layer guarantees that the context can be used on any thread.
+unsafe impl Send for FFITracker {}
+unsafe impl Sync for FFITracker {}
+
+impl Track for FFITracker {
+    fn track_event(&self, event: TrackEvent) {
+        let Some(callback) = self.callback else { return };
+        let message = match (event.error, event.context) {
+            (Some(error), Some(context)) => format!("{error}: {context}"),
+            (Some(error), None) => error.to_string(),
+            (None, context) => context.unwrap_or_default(),
+        };
+        let message = format!("{message} ({}:{})", event.file, event.line);
+        unsafe {
+            callback(
+                self.context,
+                FFITrackCategory::from_category(event.category),
+                FFISeverity::from_severity(event.severity),
+                &FFIBytes::borrow(Some(message.as_bytes())),
+            )
+        }
+    }
+}
+
+/// The second version of [`FFIConfig`], which carries the configurations added
+/// after the first one. The first version is kept for the ABI compatibility.
+#[repr(C)]
+pub struct FFIConfigV2 {
+    base: FFIConfig,
+    mirror: FFIMirror,
+    /// The maximum number of the records per second, `0` means there is no limit.
+    rate_limit_per_second: u32,
+    rate_limit_per_tag: bool,
+    tracker: FFITracker,
+}
+
+impl FFIConfigV2 {
+    #[inline]
+    unsafe fn into_config(self) -> Config {
+        let rate_limit = (self.rate_limit_per_second != 0)
+            .then(|| RateLimit::new(self.rate_limit_per_second, self.rate_limit_per_tag));
+        let tracker = self.tracker.callback.is_some().then_some(self.tracker);
+        self.base
+            .to_config()
+            .mirror(self.mirror.to_mirror())
+            .rate_limit(rate_limit)
+            .tracker(tracker.map(|t| Box::new(t) as _))
+    }
+}
+
 #[repr(u8)]
 #[derive(Clone, Copy)]
 pub enum FFILevel {
@@ -77,6 +248,47 @@ impl FFILevel {
     unsafe fn to_level(self) -> Level {
         mem::transmute(self as u8)
     }
+
+    #[inline]
+    fn from_level(level: Level) -> Self {
+        match level {
+            Level::Error => Self::Error,
+            Level::Warn => Self::Warn,
+            Level::Info => Self::Info,
+            Level::Debug => Self::Debug,
+            _ => Self::Verbose,
+        }
+    }
+}
+
+impl FFIDefault for FFILevel {
+    #[inline]
+    fn default() -> Self {
+        Self::Info
+    }
+}
+
+pub mod level {
+    use crate::{call::ffi_call_result, FFIBytes, FFICallState, FFILevel};
+
+    /// Parses the level from its name (e.g., `warn`) or initial (e.g., `W`),
+    /// case-insensitively, so that the bindings translate the levels the same way.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_level_from_str(
+        bytes: FFIBytes,
+        state: &mut FFICallState,
+    ) -> FFILevel {
+        ffi_call_result(state, || {
+            bytes.as_str().unwrap_or_default().parse().map(FFILevel::from_level)
+        })
+    }
+
+    /// Returns the lowercase name of the level (e.g., `warn`), which is static and
+    /// must not be deallocated.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_level_name(level: FFILevel) -> FFIBytes {
+        FFIBytes::borrow(Some(level.to_level().as_str().as_bytes()))
+    }
 }
 
 #[repr(C)]
@@ -94,31 +306,220 @@ pub struct FFIRecord {
 
 impl FFIRecord {
     #[inline]
-    unsafe fn to_record(&self) -> Record {
+    unsafe fn to_record(&self) -> Record<'_> {
+        self.to_record_with(self.tag.as_str(), self.file.as_str(), self.func.as_str())
+    }
+
+    #[inline]
+    unsafe fn to_record_with<'a>(
+        &'a self,
+        tag: Option<&'a str>,
+        file: Option<&'a str>,
+        func: Option<&'a str>,
+    ) -> Record<'a> {
         let datetime =
             DateTime::from_timestamp(self.datetime_secs, self.datetime_nsecs).unwrap_or_default();
-        let location = Location::new(
-            self.file.as_str(),
-            self.func.as_str(),
-            (self.line != u32::MAX).then_some(self.line),
-        );
+        let location = Location::new(file, func, (self.line != u32::MAX).then_some(self.line));
         let meta = Meta::new(
             self.level.to_level(),
             datetime,
             location,
-            self.tag.as_str(),
+            tag,
             (self.thread_id != u64::MAX).then_some(self.thread_id),
         );
         Record::new(meta, self.content.as_str().unwrap_or_default())
     }
 }
 
+/// The second version of [`FFIRecord`], whose tag, file and function may be the
+/// strings interned by [`pinenut_intern`]. The first version is kept for the ABI
+/// compatibility.
+///
+/// The field with the id of `u32::MAX` is taken from the bytes of the base record
+/// instead, and the one with an unknown id is logged as `<unknown>`, which is
+/// reported as an error of the call.
+#[repr(C)]
+pub struct FFIRecordV2 {
+    base: FFIRecord,
+    tag_id: u32,
+    file_id: u32,
+    func_id: u32,
+}
+
+impl FFIRecordV2 {
+    /// Converts to the record, along with the error of the first unknown id.
+    #[inline]
+    unsafe fn to_record(&self) -> (Record<'_>, Option<intern::Error>) {
+        let base = &self.base;
+        let (tag, tag_err) = intern::resolve(self.tag_id, &base.tag);
+        let (file, file_err) = intern::resolve(self.file_id, &base.file);
+        let (func, func_err) = intern::resolve(self.func_id, &base.func);
+        (base.to_record_with(tag, file, func), tag_err.or(file_err).or(func_err))
+    }
+}
+
+/// The view of a parsed record, the strings and the context are borrowed from the
+/// parser and are only valid during the callback (see [`parser::pinenut_parse`]).
+///
+/// The absent strings are null, the absent line, thread id, session id, elapsed
+/// time and sequence number are the maximum values of their types, the same as
+/// [`FFIRecord`].
+#[repr(C)]
+pub struct FFIRecordView {
+    level: FFILevel,
+    datetime_secs: i64,
+    datetime_nsecs: u32,
+    tag: FFIBytes,
+    file: FFIBytes,
+    func: FFIBytes,
+    line: u32,
+    thread_id: u64,
+    content: FFIBytes,
+    thread_name: FFIBytes,
+    session_id: u64,
+    /// The milliseconds elapsed since the logger was initialized.
+    elapsed: u64,
+    sequence: u64,
+    /// How many times the log was repeated consecutively after it.
+    repeats: u32,
+    /// Whether the content was truncated by the logger.
+    truncated: bool,
+    context: *const FFIContextEntry,
+    context_len: u64,
+}
+
+/// The key-value pair of the context of a parsed record, see [`FFIRecordView`].
+#[repr(C)]
+pub struct FFIContextEntry {
+    key: FFIBytes,
+    value: FFIBytes,
+}
+
+impl FFIRecordView {
+    /// Constructs the view of the record, the context entries are collected into
+    /// the buffer, which is reused across the records.
+    #[inline]
+    fn new(record: &Record, context: &mut Vec<FFIContextEntry>) -> Self {
+        let (meta, location) = (record.meta(), record.meta().location());
+        let bytes = |str: Option<&str>| FFIBytes::borrow(str.map(str::as_bytes));
+        context.clear();
+        context.extend(record.context().iter().map(|(key, value)| FFIContextEntry {
+            key: bytes(Some(key)),
+            value: bytes(Some(value)),
+        }));
+        Self {
+            level: FFILevel::from_level(meta.level()),
+            datetime_secs: meta.datetime().timestamp(),
+            datetime_nsecs: meta.datetime().timestamp_subsec_nanos(),
+            tag: bytes(meta.tag()),
+            file: bytes(location.file()),
+            func: bytes(location.func()),
+            line: location.line().unwrap_or(u32::MAX),
+            thread_id: meta.thread_id().unwrap_or(u64::MAX),
+            content: bytes(Some(record.content())),
+            thread_name: bytes(meta.thread_name()),
+            session_id: meta.session_id().unwrap_or(u64::MAX),
+            elapsed: meta.elapsed().unwrap_or(u64::MAX),
+            sequence: meta.sequence().unwrap_or(u64::MAX),
+            repeats: record.repeats(),
+            truncated: record.truncated(),
+            context: context.as_ptr(),
+            context_len: context.len().try_into().expect("len cannot fit into u64"),
+        }
+    }
+}
+
+/// The metadata of a chunk read from its header, see [`parser::pinenut_inspect`].
+#[repr(C)]
+pub struct FFIChunkInfo {
+    start_secs: i64,
+    end_secs: i64,
+    /// The length of the compressed and maybe encrypted payload in bytes.
+    payload_len: u64,
+    version: u16,
+    encrypted: bool,
+}
+
+impl FFIChunkInfo {
+    #[inline]
+    fn new(info: &ChunkInfo) -> Self {
+        Self {
+            start_secs: info.time_range.start().timestamp(),
+            end_secs: info.time_range.end().timestamp(),
+            payload_len: info.payload_len.try_into().expect("len cannot fit into u64"),
+            version: info.version,
+            encrypted: info.encrypted,
+        }
+    }
+}
+
+/// The array of [`FFIChunkInfo`]s owned by the caller, which must be deallocated
+/// by [`parser::pinenut_dealloc_chunk_infos`].
+#[repr(C)]
+pub struct FFIChunkInfoArray {
+    ptr: *mut FFIChunkInfo,
+    len: u64,
+    capacity: u64,
+}
+
+impl FFIChunkInfoArray {
+    #[inline]
+    fn new(infos: Vec<FFIChunkInfo>) -> Self {
+        let mut infos = ManuallyDrop::new(infos);
+        let ptr = infos.as_mut_ptr();
+        let len = infos.len().try_into().expect("len cannot fit into u64");
+        let capacity = infos.capacity().try_into().expect("capacity cannot fit into u64");
+        Self { ptr, len, capacity }
+    }
+
+    #[inline]
+    unsafe fn dealloc(self) {
+        if self.ptr.is_null() {
+            return;
+        }
+        let len = self.len.try_into().expect("len cannot fit into usize");
+        let capacity = self.capacity.try_into().expect("capacity cannot fit into usize");
+        assert!(len <= capacity);
+        drop(Vec::from_raw_parts(self.ptr, len, capacity));
+    }
+}
+
+impl FFIDefault for FFIChunkInfoArray {
+    #[inline]
+    fn default() -> Self {
+        Self { ptr: ptr::null_mut(), len: 0, capacity: 0 }
+    }
+}
+
+/// cbindgen:prefix-with-name
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub enum FFIHint {
+    EnteringBackground = 1,
+    LowMemory,
+    Terminating,
+}
+
+impl FFIHint {
+    #[inline]
+    fn to_hint(self) -> Hint {
+        match self {
+            Self::EnteringBackground => Hint::EnteringBackground,
+            Self::LowMemory => Hint::LowMemory,
+            Self::Terminating => Hint::Terminating,
+        }
+    }
+}
+
 pub mod logger {
-    use std::ffi::c_void;
+    use std::{ffi::c_void, slice};
 
     use pinenut_log::Logger;
 
-    use crate::{call::ffi_call, FFICallState, FFIConfig, FFIDomain, FFIRecord};
+    use crate::{
+        call::{ffi_call, ffi_call_result},
+        FFICallState, FFIConfig, FFIConfigV2, FFIDomain, FFIHint, FFIRecord, FFIRecordV2,
+    };
 
     #[no_mangle]
     pub unsafe extern "C" fn pinenut_logger_new(
@@ -132,6 +533,19 @@ pub mod logger {
         })
     }
 
+    /// Constructs a logger with the [`FFIConfigV2`].
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_logger_new_v2(
+        domain: FFIDomain,
+        config: FFIConfigV2,
+        state: &mut FFICallState,
+    ) -> *mut c_void {
+        ffi_call(state, || {
+            let logger = Logger::new(domain.to_domain(), config.into_config());
+            Box::into_raw(Box::new(logger)) as *mut c_void
+        })
+    }
+
     #[no_mangle]
     pub unsafe extern "C" fn pinenut_logger_log(
         ptr: *const c_void,
@@ -146,6 +560,77 @@ pub mod logger {
         })
     }
 
+    /// Logs the [`FFIRecordV2`], whose fields may be the interned strings.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_logger_log_v2(
+        ptr: *const c_void,
+        record: FFIRecordV2,
+        state: &mut FFICallState,
+    ) {
+        ffi_call_result(state, || {
+            if ptr.is_null() {
+                return Ok(());
+            }
+            let logger = &*(ptr as *const Logger);
+            let (record, error) = record.to_record();
+            logger.log(&record);
+            error.map_or(Ok(()), Err)
+        })
+    }
+
+    /// Logs the records in order, crossing the FFI boundary once per batch (e.g.,
+    /// for the bridges batching the records upstream).
+    ///
+    /// The records are borrowed during the call, nothing is logged if the pointer
+    /// of the records is null.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_logger_log_batch(
+        ptr: *const c_void,
+        records: *const FFIRecord,
+        len: u64,
+        state: &mut FFICallState,
+    ) {
+        ffi_call(state, || {
+            if ptr.is_null() || records.is_null() {
+                return;
+            }
+            let logger = &*(ptr as *const Logger);
+            let len = len.try_into().expect("len cannot fit into usize");
+            let records = slice::from_raw_parts(records, len);
+            logger.log_batch(&records.iter().map(|r| r.to_record()).collect::<Vec<_>>());
+        })
+    }
+
+    /// Logs the [`FFIRecordV2`]s in order, see [`pinenut_logger_log_batch`]. All the
+    /// records are logged, and the first unknown id is reported.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_logger_log_batch_v2(
+        ptr: *const c_void,
+        records: *const FFIRecordV2,
+        len: u64,
+        state: &mut FFICallState,
+    ) {
+        ffi_call_result(state, || {
+            if ptr.is_null() || records.is_null() {
+                return Ok(());
+            }
+            let logger = &*(ptr as *const Logger);
+            let len = len.try_into().expect("len cannot fit into usize");
+            let records = slice::from_raw_parts(records, len);
+            let mut error = None;
+            let records: Vec<_> = records
+                .iter()
+                .map(|r| {
+                    let (record, err) = r.to_record();
+                    error = error.take().or(err);
+                    record
+                })
+                .collect();
+            logger.log_batch(&records);
+            error.map_or(Ok(()), Err)
+        })
+    }
+
     #[no_mangle]
     pub unsafe extern "C" fn pinenut_logger_flush(ptr: *const c_void, state: &mut FFICallState) {
         ffi_call(state, || {
@@ -156,6 +641,23 @@ pub mod logger {
         })
     }
 
+    /// Performs the behavior appropriate for the change of the application
+    /// lifecycle, it may block until the buffered records have been written.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_logger_hint(
+        ptr: *const c_void,
+        hint: FFIHint,
+        state: &mut FFICallState,
+    ) {
+        ffi_call_result(state, || {
+            if ptr.is_null() {
+                return Ok(());
+            }
+            let logger = &*(ptr as *const Logger);
+            logger.hint(hint.to_hint())
+        })
+    }
+
     #[no_mangle]
     pub unsafe extern "C" fn pinenut_logger_trim(
         ptr: *const c_void,
@@ -192,9 +694,75 @@ pub mod logger {
 }
 
 pub mod extract {
-    use pinenut_log::{extract, DateTime};
+    use std::{ffi::c_void, sync::Arc};
+
+    use pinenut_log::{
+        extract, extract_with_options, CancelToken, DateTime, ExtractOptions, ExtractionProgress,
+    };
+
+    use crate::{
+        call::{ffi_call, ffi_call_result},
+        FFIBytes, FFICallState, FFIDomain,
+    };
+
+    /// The callback of the extraction progress, along with the context in
+    /// [`FFIExtractProgress`]. It is invoked on the calling thread of
+    /// [`pinenut_extract_with_progress`] before it returns.
+    pub type FFIProgressCallback =
+        Option<unsafe extern "C" fn(context: *mut c_void, scanned_bytes: u64, total_bytes: u64)>;
+
+    /// The progress reporter of the extraction, there is none if the callback is
+    /// null.
+    #[repr(C)]
+    pub struct FFIExtractProgress {
+        context: *mut c_void,
+        callback: FFIProgressCallback,
+    }
+
+    /// The callback is only invoked on the calling thread.
+    unsafe impl Send for FFIExtractProgress {}
+    unsafe impl Sync for FFIExtractProgress {}
+
+    impl FFIExtractProgress {
+        #[inline]
+        fn report(&self, progress: ExtractionProgress) {
+            if let Some(callback) = self.callback {
+                unsafe { callback(self.context, progress.scanned_bytes, progress.total_bytes) }
+            }
+        }
+    }
+
+    /// Constructs a cancellation token of the extraction, which must be deallocated
+    /// by [`pinenut_dealloc_cancel_token`].
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_cancel_token_new(state: &mut FFICallState) -> *mut c_void {
+        ffi_call(state, || Box::into_raw(Box::new(CancelToken::new())) as *mut c_void)
+    }
+
+    /// Cancels the extraction with the token, it can be called on any thread.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_cancel_token_cancel(
+        ptr: *const c_void,
+        state: &mut FFICallState,
+    ) {
+        ffi_call(state, || {
+            if !ptr.is_null() {
+                (*(ptr as *const CancelToken)).cancel();
+            }
+        })
+    }
 
-    use crate::{call::ffi_call_result, FFIBytes, FFICallState, FFIDomain};
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_dealloc_cancel_token(
+        ptr: *mut c_void,
+        state: &mut FFICallState,
+    ) {
+        ffi_call(state, || {
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr as *mut CancelToken));
+            }
+        })
+    }
 
     #[no_mangle]
     pub unsafe extern "C" fn pinenut_extract(
@@ -214,12 +782,79 @@ pub mod extract {
             )
         })
     }
+
+    /// Extracts the logs like [`pinenut_extract`], reports the progress and fails
+    /// once the token (if not null) is cancelled, e.g., when the connectivity drops
+    /// during an upload. The destination file written partially is deleted then.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_extract_with_progress(
+        domain: FFIDomain,
+        start_time: i64,
+        end_time: i64,
+        dest_path: FFIBytes,
+        progress: FFIExtractProgress,
+        cancel_token: *const c_void,
+        state: &mut FFICallState,
+    ) {
+        ffi_call_result(state, || {
+            let start_time = DateTime::from_timestamp(start_time, 0).unwrap_or_default();
+            let end_time = DateTime::from_timestamp(end_time, 0).unwrap_or_default();
+            let on_progress = progress
+                .callback
+                .is_some()
+                .then(|| Arc::new(move |p: ExtractionProgress| progress.report(p)) as _);
+            let cancel_token =
+                (!cancel_token.is_null()).then(|| (*(cancel_token as *const CancelToken)).clone());
+            let options = ExtractOptions::new().on_progress(on_progress).cancel_token(cancel_token);
+            extract_with_options(
+                domain.to_domain(),
+                start_time..=end_time,
+                dest_path.as_str().unwrap_or_default(),
+                options,
+            )
+        })
+    }
 }
 
 pub mod parser {
-    use pinenut_log::{parse_to_file, DefaultFormatter};
+    use std::ffi::c_void;
+
+    use pinenut_log::{inspect, parse, parse_to_file, DefaultFormatter};
 
-    use crate::{call::ffi_call_result, FFIBytes, FFICallState};
+    use crate::{
+        call::{ffi_call, ffi_call_result},
+        FFIBytes, FFICallState, FFIChunkInfo, FFIChunkInfoArray, FFIRecordView,
+    };
+
+    /// The callback invoked with each parsed record, along with the context passed
+    /// to [`pinenut_parse`].
+    pub type FFIParseCallback =
+        Option<unsafe extern "C" fn(context: *mut c_void, record: &FFIRecordView)>;
+
+    /// Parses the log file, and invokes the callback with each record in order,
+    /// e.g., to render the records in an in-app viewer without writing them to a
+    /// text file.
+    ///
+    /// The callback is invoked on the calling thread before this function returns,
+    /// nothing is parsed if it is null.
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_parse(
+        path: FFIBytes,
+        secret_key: FFIBytes,
+        context: *mut c_void,
+        callback: FFIParseCallback,
+        state: &mut FFICallState,
+    ) {
+        ffi_call_result(state, || {
+            let Some(callback) = callback else { return Ok(()) };
+            let secret_key = secret_key.as_slice().and_then(|k| k.try_into().ok());
+            let mut entries = Vec::new();
+            parse(path.as_str().unwrap_or_default(), secret_key, |record| {
+                callback(context, &FFIRecordView::new(record, &mut entries));
+                Ok(())
+            })
+        })
+    }
 
     #[no_mangle]
     pub unsafe extern "C" fn pinenut_parse_to_file(
@@ -238,4 +873,30 @@ pub mod parser {
             )
         })
     }
+
+    /// Reads the metadata of the chunks in the log file from their headers without
+    /// parsing the records, e.g., to show the size and the time span of the logs in
+    /// the settings.
+    ///
+    /// The returned array must be deallocated by [`pinenut_dealloc_chunk_infos`].
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_inspect(
+        path: FFIBytes,
+        state: &mut FFICallState,
+    ) -> FFIChunkInfoArray {
+        ffi_call_result(state, || {
+            let infos = inspect(path.as_str().unwrap_or_default())?;
+            Ok::<_, pinenut_log::ParsingError>(FFIChunkInfoArray::new(
+                infos.iter().map(FFIChunkInfo::new).collect(),
+            ))
+        })
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn pinenut_dealloc_chunk_infos(
+        infos: FFIChunkInfoArray,
+        state: &mut FFICallState,
+    ) {
+        ffi_call(state, || infos.dealloc());
+    }
 }
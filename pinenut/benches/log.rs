@@ -0,0 +1,79 @@
+//! The throughput of logging (records per second) with different configurations.
+//!
+//! Run with `cargo bench -p pinenut-log`.
+
+use std::str::FromStr;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pinenut_log::{
+    encrypt::gen_echd_key_pair, Config, DateTime, Domain, Level, LocationBuilder, MetaBuilder,
+    RecordBuilder,
+};
+use tempfile::tempdir;
+
+/// Logs a typical record repeatedly with the logger of the configuration.
+fn bench_log(c: &mut Criterion, group: &str, cases: Vec<(String, Config)>) {
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Elements(1));
+
+    for (name, config) in cases {
+        let dir = tempdir().expect("failed to create the temp dir");
+        let domain = Domain::new("bench".to_string(), dir.path().to_path_buf());
+        let logger = domain.logger(config.tracker(None));
+
+        let location = LocationBuilder::new().file("main.rs").func("main").line(42).build();
+        let meta = MetaBuilder::new()
+            .level(Level::Info)
+            .datetime(DateTime::from_str("2013-11-18 13:35:12Z").unwrap())
+            .location(location)
+            .tag("network")
+            .thread_id(1)
+            .build();
+        let record = RecordBuilder::new()
+            .meta(meta)
+            .content("The request has been completed, status: 200, elapsed: 42ms")
+            .build();
+
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| logger.log(&record));
+        });
+        logger.shutdown();
+    }
+    group.finish();
+}
+
+fn compression_level(c: &mut Criterion) {
+    let cases = [1, 3, 10, 19]
+        .into_iter()
+        .map(|level| (level.to_string(), Config::new().compression_level(level)))
+        .collect();
+    bench_log(c, "compression_level", cases);
+}
+
+fn encryption(c: &mut Criterion) {
+    let (_, public_key) = gen_echd_key_pair();
+    let cases = vec![
+        ("off".to_string(), Config::new()),
+        ("on".to_string(), Config::new().key(Some(public_key))),
+    ];
+    bench_log(c, "encryption", cases);
+}
+
+fn buffer_len(c: &mut Criterion) {
+    let cases = [64, 320, 1024]
+        .into_iter()
+        .map(|len| (format!("{len}KB"), Config::new().buffer_len(len * 1024)))
+        .collect();
+    bench_log(c, "buffer_len", cases);
+}
+
+fn storage(c: &mut Criterion) {
+    let cases = vec![
+        ("mmap".to_string(), Config::new().use_mmap(true)),
+        ("vec".to_string(), Config::new().use_mmap(false)),
+    ];
+    bench_log(c, "storage", cases);
+}
+
+criterion_group!(benches, compression_level, encryption, buffer_len, storage);
+criterion_main!(benches);
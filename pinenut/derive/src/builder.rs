@@ -1,6 +1,9 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{Data, DataStruct, DeriveInput, Fields};
+use syn::{
+    Attribute, Data, DataStruct, DeriveInput, Fields, GenericArgument, PathArguments, Type,
+    TypePath,
+};
 
 use crate::misc::add_traits_bounds;
 
@@ -21,13 +24,44 @@ pub fn impl_builder(input: DeriveInput) -> TokenStream {
 
     // Generates setter code for all fields.
     let setters = fields.named.into_iter().map(|f| {
+        let into = match is_into(&f.attrs) {
+            Ok(into) => into,
+            Err(err) => return err.into_compile_error(),
+        };
         let (field, ty) = (f.ident.unwrap(), f.ty);
         let doc = format!("Set [`{name}::{field}`].");
+        // The parameter is converted into the field if it is `#[builder(into)]`.
+        let param = |ty: &Type| if into { quote!(impl Into<#ty>) } else { quote!(#ty) };
+        let convert = if into { quote!(Into::into(#field)) } else { quote!(#field) };
+
+        // The `Option` fields are set by the values directly, or by the options.
+        let Some(inner) = option_inner(&ty) else {
+            let param = param(&ty);
+            return quote! {
+                #[doc = #doc]
+                #[inline]
+                #vis fn #field(&mut self, #field: #param) -> &mut Self {
+                    self.0.#field = #convert;
+                    self
+                }
+            };
+        };
+        let field_opt = format_ident!("{field}_opt");
+        let doc_opt = format!("Set [`{name}::{field}`] by the option, e.g., to reset it.");
+        let param = param(inner);
+        let convert_opt = if into { quote!(#field.map(Into::into)) } else { quote!(#field) };
         quote! {
             #[doc = #doc]
             #[inline]
-            #vis fn #field(&mut self, #field: #ty) -> &mut Self {
-                self.0.#field = #field;
+            #vis fn #field(&mut self, #field: #param) -> &mut Self {
+                self.0.#field = Some(#convert);
+                self
+            }
+
+            #[doc = #doc_opt]
+            #[inline]
+            #vis fn #field_opt(&mut self, #field: Option<#param>) -> &mut Self {
+                self.0.#field = #convert_opt;
                 self
             }
         }
@@ -62,3 +96,35 @@ pub fn impl_builder(input: DeriveInput) -> TokenStream {
         }
     }
 }
+
+/// Whether the field is marked with `#[builder(into)]`, whose setters take the
+/// parameters of `impl Into<T>`.
+fn is_into(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut into = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("into") {
+                into = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported builder attribute, expected `into`"))
+            }
+        })?;
+    }
+    Ok(into)
+}
+
+/// The `T` of the `Option<T>` type.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last().filter(|segment| segment.ident == "Option")?;
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first() {
+        Some(GenericArgument::Type(ty)) if arguments.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
@@ -1,8 +1,8 @@
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use syn::{
-    spanned::Spanned, Data, DataStruct, DeriveInput, Fields, GenericParam, Generics, Lifetime,
-    LifetimeParam,
+    spanned::Spanned, Attribute, Data, DataStruct, DeriveInput, Fields, GenericParam, Generics,
+    Lifetime, LifetimeParam,
 };
 
 use crate::misc::add_traits_bounds;
@@ -19,9 +19,16 @@ pub fn impl_encode(input: DeriveInput) -> TokenStream {
     let generics = add_traits_bounds(input.generics, [quote!(crate::codec::Encode)]);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let body = fields.into_iter().enumerate().map(|(i, f)| {
+    let attrs = match field_attrs(&fields) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.into_compile_error(),
+    };
+    let body = fields.into_iter().zip(attrs).enumerate().map(|(i, (f, attr))| {
         let name = f.ident.map(|f| f.into_token_stream()).unwrap_or(i.into_token_stream());
-        quote! { crate::codec::Encode::encode(&self.#name, sink)?; }
+        match attr {
+            FieldAttr::Skip => quote!(),
+            _ => quote! { crate::codec::Encode::encode(&self.#name, sink)?; },
+        }
     });
 
     quote! {
@@ -51,18 +58,31 @@ pub fn impl_decode(input: DeriveInput) -> TokenStream {
     let generics = add_lifetime_bounds(generics.clone());
     let impl_generics = generics.split_for_impl().0;
 
+    let attrs = match field_attrs(&fields) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.into_compile_error(),
+    };
+    let decode = attrs.into_iter().map(|attr| match attr {
+        FieldAttr::None => quote! { crate::codec::Decode::decode(source)? },
+        FieldAttr::Skip => quote! { ::core::default::Default::default() },
+        FieldAttr::Default => quote! {
+            if crate::codec::Source::is_exhausted(source) {
+                ::core::default::Default::default()
+            } else {
+                crate::codec::Decode::decode(source)?
+            }
+        },
+    });
     let construct_body = match fields {
         Fields::Named(fields) => {
-            let decode_fields = fields.named.into_iter().map(|f| {
+            let decode_fields = fields.named.into_iter().zip(decode).map(|(f, decode)| {
                 let name = f.ident.unwrap();
-                quote! { #name: crate::codec::Decode::decode(source)?, }
+                quote! { #name: #decode, }
             });
             quote! { { #(#decode_fields)* } }
         }
-        Fields::Unnamed(fields) => {
-            let decode_fields = fields.unnamed.into_iter().map(|_| {
-                quote! { crate::codec::Decode::decode(source)?, }
-            });
+        Fields::Unnamed(_) => {
+            let decode_fields = decode.map(|decode| quote! { #decode, });
             quote! { ( #(#decode_fields)* ) }
         }
         Fields::Unit => quote!(),
@@ -80,6 +100,54 @@ pub fn impl_decode(input: DeriveInput) -> TokenStream {
     }
 }
 
+/// The codec attribute of a field.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldAttr {
+    None,
+    /// `#[codec(skip)]`: The field is not encoded, and is decoded as default.
+    Skip,
+    /// `#[codec(default)]`: The field is decoded as default if the source has been
+    /// exhausted, i.e., the field was appended after the data was encoded.
+    Default,
+}
+
+/// Parses the codec attributes of the fields.
+///
+/// The fields with `#[codec(default)]` must be trailing, as the source is only
+/// exhausted after the last field.
+fn field_attrs(fields: &Fields) -> syn::Result<Vec<FieldAttr>> {
+    let attrs = fields.iter().map(|f| field_attr(&f.attrs)).collect::<syn::Result<Vec<_>>>()?;
+    let encoded = fields.iter().zip(&attrs).filter(|(_, attr)| **attr != FieldAttr::Skip);
+    let mut trailing = encoded.skip_while(|(_, attr)| **attr != FieldAttr::Default);
+    if let Some((field, _)) = trailing.find(|(_, attr)| **attr == FieldAttr::None) {
+        let err_msg = "the fields after `#[codec(default)]` must be `#[codec(default)]` too";
+        return Err(syn::Error::new(field.span(), err_msg));
+    }
+    Ok(attrs)
+}
+
+/// Parses `#[codec(skip)]` or `#[codec(default)]`, at most one of them.
+fn field_attr(attrs: &[Attribute]) -> syn::Result<FieldAttr> {
+    let mut field_attr = FieldAttr::None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+        attr.parse_nested_meta(|meta| {
+            let parsed = if meta.path.is_ident("skip") {
+                FieldAttr::Skip
+            } else if meta.path.is_ident("default") {
+                FieldAttr::Default
+            } else {
+                return Err(meta.error("unsupported codec attribute, expected `skip` or `default`"));
+            };
+            if field_attr != FieldAttr::None {
+                return Err(meta.error("`skip` and `default` can't be combined or repeated"));
+            }
+            field_attr = parsed;
+            Ok(())
+        })?;
+    }
+    Ok(field_attr)
+}
+
 /// Add the lifetime bounds with every lifetimes to `'de` : `'de: 'a + 'b + 'c`.
 fn add_lifetime_bounds(mut generics: Generics) -> Generics {
     let lifetimes = generics.params.iter().filter_map(|p| {
@@ -17,6 +17,9 @@ mod misc;
 /// `Derive Macro` to automatically implement the `Builder Pattern` for structs that
 /// already implement the `Default` and `Clone` traits.
 ///
+/// The `Option<T>` fields have two setters, `field(T)` and `field_opt(Option<T>)`.
+/// The setters of the fields marked with `#[builder(into)]` take `impl Into<T>`.
+///
 /// # Examples
 ///
 /// Use it as follows:
@@ -27,8 +30,10 @@ mod misc;
 /// #[derive(Builder, Default, Clone)]
 /// pub struct MyStruct<T> {
 ///     field_one: i32,
+///     #[builder(into)]
 ///     field_two: String,
 ///     field_three: T,
+///     field_four: Option<u64>,
 /// }
 /// ```
 ///
@@ -40,6 +45,7 @@ mod misc;
 /// #     field_one: i32,
 /// #     field_two: String,
 /// #     field_three: T,
+/// #     field_four: Option<u64>,
 /// # }
 /// pub struct MyStructBuilder<T: Default + Clone>(MyStruct<T>);
 ///
@@ -63,8 +69,8 @@ mod misc;
 ///         self
 ///     }
 ///
-///     pub fn field_two(&mut self, field_two: String) -> &mut Self {
-///         self.0.field_two = field_two;
+///     pub fn field_two(&mut self, field_two: impl Into<String>) -> &mut Self {
+///         self.0.field_two = Into::into(field_two);
 ///         self
 ///     }
 ///
@@ -72,9 +78,19 @@ mod misc;
 ///         self.0.field_three = field_three;
 ///         self
 ///     }
+///
+///     pub fn field_four(&mut self, field_four: u64) -> &mut Self {
+///         self.0.field_four = Some(field_four);
+///         self
+///     }
+///
+///     pub fn field_four_opt(&mut self, field_four: Option<u64>) -> &mut Self {
+///         self.0.field_four = field_four;
+///         self
+///     }
 /// }
 /// ```
-#[proc_macro_derive(Builder)]
+#[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive_builder(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_builder(input).into()
@@ -82,6 +98,13 @@ pub fn derive_builder(input: TokenStream) -> TokenStream {
 
 /// `Derive Macro` to automatically implement the `Encode` trait for structs.
 ///
+/// The fields with `#[codec(skip)]` are not encoded, e.g., the caches that are
+/// internal to the process. A field has at most one codec attribute.
+///
+/// The attributes only apply to the types using the derives, e.g., not to
+/// `Record`, whose codec is hand-written (its new fields are appended to it, see
+/// `Record::SCHEMA`).
+///
 /// # Examples
 ///
 /// Use it as follows:
@@ -110,7 +133,7 @@ pub fn derive_builder(input: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
-#[proc_macro_derive(Encode)]
+#[proc_macro_derive(Encode, attributes(codec))]
 pub fn derive_encode(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_encode(input).into()
@@ -118,6 +141,16 @@ pub fn derive_encode(input: TokenStream) -> TokenStream {
 
 /// `Derive Macro` to automatically implement the `Decode` trait for structs.
 ///
+/// The fields with `#[codec(skip)]` are decoded as default. The trailing fields
+/// with `#[codec(default)]` are decoded as default if the source has been
+/// exhausted, i.e., they were appended after the data was encoded.
+///
+/// The source is only exhausted at its end, so `#[codec(default)]` only works for
+/// the outermost struct of the source (or the last one of it). It never works for
+/// a struct nested in another one followed by other fields (e.g., `Meta` in
+/// `Record`), whose appended fields would be decoded from the following fields.
+/// Such structs are to be framed (e.g., by a length prefix) before adding fields.
+///
 /// # Examples
 ///
 /// Use it as follows:
@@ -147,7 +180,7 @@ pub fn derive_encode(input: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
-#[proc_macro_derive(Decode)]
+#[proc_macro_derive(Decode, attributes(codec))]
 pub fn derive_decode(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_decode(input).into()
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pinenut_log::fuzzing;
+
+fuzz_target!(|data: &[u8]| {
+    fuzzing::read_chunks(data);
+});
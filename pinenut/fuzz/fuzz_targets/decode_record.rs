@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pinenut_log::{codec::Decode, fuzzing, Record, FORMAT_VERSION};
+
+fuzz_target!(|data: &[u8]| {
+    let mut source = data;
+    _ = Record::decode(&mut source);
+
+    // The records of any format version, which arrive in the pieces of any length
+    // as they are decompressed.
+    if let [version, piece_len, bytes @ ..] = data {
+        let version = u16::from(*version) % FORMAT_VERSION + 1;
+        fuzzing::decode_records(bytes, version, usize::from(*piece_len) * 64 + 1);
+    }
+});
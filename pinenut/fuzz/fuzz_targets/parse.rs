@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pinenut_log::{fuzzing, SecretKey};
+
+/// A valid secret key, which doesn't match the public keys of the chunks unless
+/// the fuzzer finds them.
+const SECRET_KEY: SecretKey = [0x2A; 32];
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte selects whether the log file is parsed with the key.
+    let Some((selector, bytes)) = data.split_first() else {
+        return;
+    };
+    let secret_key = (selector & 1 == 1).then_some(SECRET_KEY);
+    fuzzing::parse(bytes, secret_key);
+});
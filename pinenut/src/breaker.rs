@@ -0,0 +1,151 @@
+//! The circuit breaker of the log file writes.
+
+use std::{collections::VecDeque, mem, ops::Deref, time::Duration};
+
+use crate::{chunk::Chunk, clock::Moment};
+
+/// Configuration of the circuit breaker of the log file writes.
+///
+/// The file system may fail transiently (e.g., the directory becomes read-only
+/// during a backup, or the disk runs out of space). After `failure_threshold`
+/// consecutive failures, the writes are paused instead of failing on every chunk:
+/// the chunks are kept in memory (at most `max_backlog_len` bytes, the oldest ones
+/// are dropped first) and the latest one is also kept in the mmap buffer. The
+/// writes are retried once `retry_interval` has elapsed (by the next chunk, or by
+/// the IO thread itself if no chunk is written meanwhile), and the kept chunks are
+/// written in order once they succeed.
+///
+/// The pause and the resumption are reported to tracker, see
+/// [`TrackCategory::IoPaused`] and [`TrackCategory::IoResumed`].
+///
+/// [`TrackCategory::IoPaused`]: crate::TrackCategory::IoPaused
+/// [`TrackCategory::IoResumed`]: crate::TrackCategory::IoResumed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CircuitBreaker {
+    /// The number of consecutive write failures that pauses the writes.
+    pub failure_threshold: u32,
+    /// How long to wait before retrying the paused writes.
+    pub retry_interval: Duration,
+    /// The maximum length (in bytes) of the chunks kept in memory while paused.
+    pub max_backlog_len: usize,
+}
+
+impl CircuitBreaker {
+    /// Constructs a new `CircuitBreaker`.
+    #[inline]
+    pub fn new(failure_threshold: u32, retry_interval: Duration, max_backlog_len: usize) -> Self {
+        Self { failure_threshold, retry_interval, max_backlog_len }
+    }
+}
+
+impl Default for CircuitBreaker {
+    /// Pauses after 3 failures, retries every 10 seconds and keeps up to 1 MB.
+    #[inline]
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(10), 1024 * 1024)
+    }
+}
+
+/// The state of the circuit breaker.
+pub(crate) struct Breaker {
+    config: CircuitBreaker,
+    /// The number of consecutive write failures.
+    failures: u32,
+    /// When the writes were paused or last retried, `None` if they are not paused.
+    paused_at: Option<Moment>,
+    backlog: VecDeque<Chunk<Vec<u8>>>,
+    backlog_len: usize,
+    /// The number of chunks dropped from the backlog during the pause.
+    dropped: usize,
+}
+
+impl Breaker {
+    #[inline]
+    pub(crate) fn new(config: CircuitBreaker) -> Self {
+        Self {
+            config,
+            failures: 0,
+            paused_at: None,
+            backlog: VecDeque::new(),
+            backlog_len: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Whether the writes are paused.
+    #[inline]
+    pub(crate) fn is_open(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Whether the writes can be attempted, i.e., they are not paused or the retry
+    /// interval has elapsed.
+    #[inline]
+    pub(crate) fn is_due(&self) -> bool {
+        self.retry_in().is_none_or(|remaining| remaining.is_zero())
+    }
+
+    /// The time remaining until the retry is due, `None` if the writes are not
+    /// paused.
+    #[inline]
+    pub(crate) fn retry_in(&self) -> Option<Duration> {
+        // The retry is due if the clock goes backwards.
+        self.paused_at.map(|at| {
+            at.elapsed().map_or(Duration::ZERO, |elapsed| {
+                self.config.retry_interval.saturating_sub(elapsed)
+            })
+        })
+    }
+
+    /// Whether a copy of the chunk is kept.
+    #[inline]
+    pub(crate) fn is_kept(&self, chunk: &[u8]) -> bool {
+        self.backlog.iter().any(|kept| **kept == *chunk)
+    }
+
+    /// Keeps a copy of the chunk, which is written by the next successful retry.
+    pub(crate) fn defer<B>(&mut self, chunk: &Chunk<B>)
+    where
+        B: Deref<Target = [u8]>,
+    {
+        self.backlog_len += chunk.len();
+        self.backlog.push_back(Chunk::bind(chunk.to_vec()));
+
+        while self.backlog_len > self.config.max_backlog_len
+            && let Some(oldest) = self.backlog.pop_front()
+        {
+            self.backlog_len -= oldest.len();
+            self.dropped += 1;
+        }
+    }
+
+    /// Writes the kept chunks in order, the written ones are removed.
+    pub(crate) fn drain<E>(
+        &mut self,
+        mut write: impl FnMut(&Chunk<Vec<u8>>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        while let Some(chunk) = self.backlog.front() {
+            write(chunk)?;
+            self.backlog_len -= chunk.len();
+            self.backlog.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Records a write failure, returns `true` if the writes are paused by it.
+    pub(crate) fn fail(&mut self) -> bool {
+        self.failures = self.failures.saturating_add(1);
+        let was_open = self.is_open();
+        if was_open || self.failures >= self.config.failure_threshold {
+            self.paused_at = Some(Moment::now());
+        }
+        !was_open && self.is_open()
+    }
+
+    /// Records a write success, returns the number of chunks dropped during the
+    /// pause if the writes are resumed by it.
+    pub(crate) fn succeed(&mut self) -> Option<usize> {
+        self.failures = 0;
+        self.paused_at.take().map(|_| mem::take(&mut self.dropped))
+    }
+}
@@ -7,15 +7,26 @@
 //!
 //! ```plain
 //!     ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   n   ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─
-//!    ├──── 8 ─────┬───── (n - 8) / 2 ──────┬───── (n - 8) / 2 ───────┤
+//!    ├──── 28 ────┬───── (n - 28) / 2 ─────┬───── (n - 28) / 2 ──────┤
 //!    ▼────────────▼────────────────────────▼─────────────────────────▼
 //! ┌──│   Header   │         Alpha          │          Beta           │
 //! │  └────────────┴────────────────────────┴─────────────────────────┘
-//! │  ┌───────────┬──────────────┐ (n: length of the underlying memory)
-//! └─▶│   Magic   │  Alpha Side  │                                     
-//!    ▲───────────▲──────────────▲                                     
-//!    └──── 4 ────┴────── 4 ─────┘                                     
+//! │  ┌───────────┬───────────┬──────────────┬───────────┐
+//! └─▶│   Magic   │  Version  │  Alpha Side  │    MAC    │
+//!    ▲───────────▲───────────▲──────────────▲───────────▲
+//!    └──── 4 ────┴──── 4 ────┴────── 4 ─────┴─── 16 ────┘
+//!                                      (n: length of the underlying memory)
 //! ```
+//!
+//! The header is checked by the MAC (HMAC-SHA256 truncated to 16 bytes) of its
+//! other fields, which is keyed by a local random [`Key`]. So a corrupted header
+//! (e.g., written by another process or damaged by the file system), which may
+//! silently swap the buffers, can be detected (see [`is_corrupted`]). It doesn't
+//! protect against deliberate tampering, as the key is stored alongside.
+//!
+//! The buffers written by older versions of Pinenut have the legacy header of 8
+//! bytes (the magic and the alpha side), their chunks are recovered with the legacy
+//! layout, see [`legacy_sides`].
 
 use std::{
     cell::UnsafeCell,
@@ -24,8 +35,14 @@ use std::{
     sync::{Arc, RwLock, RwLockReadGuard},
 };
 
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
 use crate::{mmap::Mmap, Magic, Sealed};
 
+/// The key of the MAC checking the buffer header.
+pub(crate) type Key = [u8; 32];
+
 /// The underlying memory wrapped in the `double-buffering` system.
 #[allow(clippy::len_without_is_empty)]
 pub(crate) trait Memory: DerefMut<Target = [u8]> + Send + Sync + 'static + Sealed {
@@ -43,15 +60,16 @@ pub(crate) trait Memory: DerefMut<Target = [u8]> + Send + Sync + 'static + Seale
 /// the double buffering system.
 pub(crate) type Couple<M> = (Buffer<M>, Buffer<M>);
 
-/// Initializes the double buffering system with the underlying memory.
+/// Initializes the double buffering system with the underlying memory and the key
+/// authenticating its header.
 ///
 /// The return value is a couple of buffers that can operate on the same underlying
 /// data, but at different offsets.
-pub(crate) fn initialize<M>(memory: M) -> Couple<M>
+pub(crate) fn initialize<M>(memory: M, key: Key) -> Couple<M>
 where
     M: Memory,
 {
-    let inner = BufferInner::new(memory);
+    let inner = BufferInner::new(memory, key);
     let inner = Arc::new(RwLock::new(inner));
 
     let left = Buffer { inner: inner.clone(), side: Side::Left };
@@ -88,6 +106,26 @@ where
     pub(crate) fn switch(&mut self) {
         self.inner.write().unwrap().switch();
     }
+
+    /// The length of the underlying memory.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        // SAFETY: The memory is only replaced with the write lock held.
+        unsafe { self.inner.read().unwrap().memory().len() }
+    }
+
+    /// Replaces the underlying memory of the couple buffers, and returns the
+    /// replaced one.
+    ///
+    /// The header is re-initialized, so the data of both sides is discarded.
+    pub(crate) fn replace(&mut self, memory: M) -> M {
+        debug_assert!(memory.len() >= Header::LEN, "the memory is too small");
+        let mut inner = self.inner.write().unwrap();
+        let replaced = mem::replace(inner.memory.get_mut(), memory);
+        let header = Header::new(Side::Left, &inner.key);
+        *inner.header_mut() = header;
+        replaced
+    }
 }
 
 /// A handle for reading and writing the buffer.
@@ -139,6 +177,47 @@ where
     }
 }
 
+/// Checks whether the header of the buffer in the memory fails the check with the
+/// key, i.e., it is corrupted (or of an unknown version).
+///
+/// The memory that has never been initialized as a buffer, or has the legacy header
+/// (see [`legacy_sides`]), is not corrupted.
+pub(crate) fn is_corrupted<M>(memory: &M, key: &Key) -> bool
+where
+    M: Memory,
+{
+    debug_assert!(memory.len() >= Header::LEN, "the memory is too small");
+    // SAFETY: The alignment of Header is `1`, and the memory is large enough.
+    let header = unsafe { &*(memory.as_ptr() as *const Header) };
+    Header::MAGIC == header.magic.into()
+        && legacy_sides(memory).is_none()
+        && !header.authenticate(key)
+}
+
+/// Returns the sides of the buffer (the left and the right components) in the
+/// memory if it has the legacy header, which consists of the magic and the alpha
+/// side only, i.e., it is written by older versions of Pinenut.
+///
+/// The legacy header is told by the alpha side following the magic, where the
+/// version is now.
+pub(crate) fn legacy_sides(memory: &[u8]) -> Option<(&[u8], &[u8])> {
+    const LEGACY_HEADER_LEN: usize = 8;
+
+    let (header, rest) = (memory.get(..LEGACY_HEADER_LEN)?, &memory[LEGACY_HEADER_LEN..]);
+    let (magic, alpha_side) = header.split_at(4);
+    if Header::MAGIC != <[u8; 4]>::try_from(magic).ok()?.into() {
+        return None;
+    }
+    let alpha_side = Side::try_from(<[u8; 4]>::try_from(alpha_side).ok()?).ok()?;
+
+    let len = rest.len() / 2;
+    let (alpha, beta) = (&rest[..len], &rest[len..len * 2]);
+    Some(match alpha_side {
+        Side::Left => (alpha, beta),
+        Side::Right => (beta, alpha),
+    })
+}
+
 // ============ Memorys ============
 
 pub(crate) enum EitherMemory {
@@ -286,28 +365,62 @@ impl Not for Side {
 struct Header {
     /// The header identifier.
     magic: [u8; 4],
+    /// The version of the header layout, in little-endian.
+    version: [u8; 4],
     /// Which side of the alpha component.
     alpha_side: [u8; 4],
+    /// The MAC of the fields above.
+    mac: [u8; 16],
 }
 
 impl Header {
-    /// Length of a header in bytes. (8 bytes)
+    /// Length of a header in bytes. (28 bytes)
     const LEN: usize = mem::size_of::<Self>();
 
     /// It means: `Feed Cat Buffer`.
     const MAGIC: Magic = Magic::new(0xFEEDCA7B);
-}
 
-impl Default for Header {
+    /// The current version of the header layout. The legacy header (see
+    /// [`legacy_sides`]) is of version `0`.
+    const VERSION: u32 = 1;
+
+    /// Constructs a new `Header` with its MAC.
+    #[inline]
+    fn new(alpha_side: Side, key: &Key) -> Self {
+        let mut header = Self {
+            magic: Self::MAGIC.into(),
+            version: Self::VERSION.to_le_bytes(),
+            alpha_side: alpha_side.raw(),
+            mac: [0; 16],
+        };
+        let mac = header.hmac(key).finalize().into_bytes();
+        header.mac.copy_from_slice(&mac[..16]);
+        header
+    }
+
+    /// Checks the version, the alpha side and the MAC of the header.
     #[inline]
-    fn default() -> Self {
-        Self { magic: Self::MAGIC.into(), alpha_side: Side::Left.raw() }
+    fn authenticate(&self, key: &Key) -> bool {
+        u32::from_le_bytes(self.version) == Self::VERSION
+            && Side::try_from(self.alpha_side).is_ok()
+            && self.hmac(key).verify_truncated_left(&self.mac).is_ok()
+    }
+
+    #[inline]
+    fn hmac(&self, key: &Key) -> Hmac<Sha256> {
+        let mut hmac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+        hmac.update(&self.magic);
+        hmac.update(&self.version);
+        hmac.update(&self.alpha_side);
+        hmac
     }
 }
 
 /// The underlying buffer.
-#[repr(transparent)]
-struct BufferInner<M>(UnsafeCell<M>);
+struct BufferInner<M> {
+    memory: UnsafeCell<M>,
+    key: Key,
+}
 
 unsafe impl<M> Send for BufferInner<M> where M: Send {}
 unsafe impl<M> Sync for BufferInner<M> where M: Sync {}
@@ -316,11 +429,11 @@ impl<M> BufferInner<M>
 where
     M: Memory,
 {
-    fn new(memory: M) -> Self {
+    fn new(memory: M, key: Key) -> Self {
         // Check length and alignment.
         // The alignment of Header is `1`, so memory always conforms to this.
         debug_assert!(memory.len() >= Header::LEN, "the memory is too small");
-        let mut buffer = Self(UnsafeCell::new(memory));
+        let mut buffer = Self { memory: UnsafeCell::new(memory), key };
         buffer.initialize();
         buffer
     }
@@ -330,7 +443,7 @@ where
         // If the buffer file is invalid (which is not initialized or modified incorrectly),
         // just re-initialize the header.
         if !self.validate() {
-            *self.header_mut() = Header::default();
+            *self.header_mut() = Header::new(Side::Left, &self.key);
         }
     }
 
@@ -340,15 +453,13 @@ where
     #[inline]
     fn validate(&self) -> bool {
         let header = self.header();
-        (Header::MAGIC == header.magic.into())
-            && ([Side::Left.raw(), Side::Right.raw()].contains(&header.alpha_side))
+        (Header::MAGIC == header.magic.into()) && header.authenticate(&self.key)
     }
 
     #[inline]
     fn switch(&mut self) {
-        let header = self.header_mut();
-        let side = !Side::try_from(header.alpha_side).unwrap_or(Side::Left);
-        header.alpha_side = side.raw();
+        let side = !Side::try_from(self.header().alpha_side).unwrap_or(Side::Left);
+        *self.header_mut() = Header::new(side, &self.key);
     }
 
     #[allow(clippy::mut_from_ref)]
@@ -388,7 +499,7 @@ where
     #[inline]
     #[allow(clippy::mut_from_ref)]
     unsafe fn memory(&self) -> &mut M {
-        &mut *self.0.get()
+        &mut *self.memory.get()
     }
 }
 
@@ -399,10 +510,12 @@ mod tests {
     use tempfile::tempdir;
 
     use crate::{
-        buffer::{self, Memory},
+        buffer::{self, BufferInner, Key, Memory, Side},
         mmap::Mmap,
     };
 
+    const KEY: Key = [0xAB; 32];
+
     #[test]
     fn test_mmap_buffer() -> io::Result<()> {
         let dir = tempdir()?;
@@ -424,7 +537,7 @@ mod tests {
     where
         M: Memory,
     {
-        let (mut left, mut right) = buffer::initialize(memory);
+        let (mut left, mut right) = buffer::initialize(memory, KEY);
 
         left.handle()[0..5].copy_from_slice(b"Alpha");
 
@@ -441,4 +554,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_corrupted_buffer() {
+        let memory = vec![0; 256];
+        assert!(!buffer::is_corrupted(&memory, &KEY));
+
+        let mut inner = BufferInner::new(memory, KEY);
+        inner.switch();
+        let mut memory = inner.memory.into_inner();
+        assert!(!buffer::is_corrupted(&memory, &KEY));
+        assert!(buffer::is_corrupted(&memory, &[0xCD; 32]));
+
+        // Swaps the buffers by corrupting the alpha side.
+        memory[8..12].copy_from_slice(&Side::Left.raw());
+        assert!(buffer::is_corrupted(&memory, &KEY));
+    }
+
+    #[test]
+    fn test_legacy_buffer() {
+        let mut memory = vec![0; 256];
+        memory[..4].copy_from_slice(&[0x7B, 0xCA, 0xED, 0xFE]);
+        memory[4..8].copy_from_slice(&Side::Right.raw());
+        memory[8..12].copy_from_slice(b"Beta");
+        memory[132..137].copy_from_slice(b"Alpha");
+
+        // The alpha component is on the right side.
+        let (left, right) = buffer::legacy_sides(&memory).unwrap();
+        assert_eq!((left.len(), &left[..5]), (124, &b"Alpha"[..]));
+        assert_eq!(&right[..4], b"Beta");
+        assert!(!buffer::is_corrupted(&memory, &KEY));
+
+        // The current header is not the legacy one.
+        let memory = BufferInner::new(memory, KEY).memory.into_inner();
+        assert!(buffer::legacy_sides(&memory).is_none());
+        assert!(!buffer::is_corrupted(&memory, &KEY));
+    }
 }
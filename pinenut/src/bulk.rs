@@ -0,0 +1,374 @@
+//! The `BulkWriter` implementation.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    num::NonZeroUsize,
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    chunk::{Chunk, Extensions, Header},
+    codec::Encode,
+    common::FnSink,
+    compress::{CompressOp, Compressor, ZstdCompressor, ZstdParams},
+    encrypt::{
+        ecdh::{self, EMPTY_PUBLIC_KEY},
+        AesEncryptor, EncryptOp, Encryptor,
+    },
+    runloop, CompressionError, DateTime, EncryptionKey, LoggerError as Error, PublicKey, Record,
+    RunloopError,
+};
+
+/// Configuration of a [`BulkWriter`].
+pub struct BulkConfig {
+    chunk_len: usize,
+    workers: usize,
+    key: Option<PublicKey>,
+    compression_level: i32,
+    session_id: Option<u64>,
+}
+
+impl BulkConfig {
+    /// Constructs a new `BulkConfig`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The length of the encoded records in a chunk before compression.
+    ///
+    /// The default value is `4 MB`.
+    #[inline]
+    pub fn chunk_len(mut self, len: usize) -> Self {
+        self.chunk_len = len;
+        self
+    }
+
+    /// The number of threads used to compress and encrypt chunks.
+    ///
+    /// The default value is the available parallelism of the machine.
+    #[inline]
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// The encryption key, the public key in ECDH.
+    ///
+    /// If the value is `None`, there is no encryption.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn key(mut self, key: Option<PublicKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// The `zstd` compression level.
+    ///
+    /// The default value is `10`.
+    #[inline]
+    pub fn compression_level(mut self, level: i32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// The identifier of the session stamped on the records and chunks.
+    ///
+    /// The default value is `None`, which means that a random session id is
+    /// generated for each writer.
+    #[inline]
+    pub fn session_id(mut self, session_id: Option<u64>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+}
+
+impl Default for BulkConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            chunk_len: 4 * 1024 * 1024,
+            workers: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            key: None,
+            compression_level: ZstdCompressor::DEFAULT_LEVEL,
+            session_id: None,
+        }
+    }
+}
+
+/// A throughput-oriented writer that writes records directly to a Pinenut log file.
+///
+/// It is intended for batch jobs (e.g., server-side archival) rather than clients:
+/// there is no mmap buffer and no asynchronous IO runloop, records are gathered
+/// into large chunks which are compressed and encrypted in parallel, and then
+/// written to the file in order.
+///
+/// Records are expected to be written in chronological order, so that the file
+/// can be extracted by time range. Call [`BulkWriter::finish`] to write the
+/// remaining records, otherwise they will be lost.
+pub struct BulkWriter {
+    writer: BufWriter<File>,
+    chunk_len: usize,
+    session_id: u64,
+    batch: Batch,
+    jobs: Option<mpsc::SyncSender<Job>>,
+    results: mpsc::Receiver<(u64, Result<Vec<u8>, Error>)>,
+    workers: Vec<JoinHandle<()>>,
+    /// The chunks are built on the writing thread instead of the workers, where the
+    /// threads are not supported (e.g., `wasm32`).
+    inline: Option<Inline>,
+    sent: u64,
+    written: u64,
+    pending: BTreeMap<u64, Vec<u8>>,
+}
+
+impl BulkWriter {
+    /// Creates the log file (truncating it if it exists) and constructs a new
+    /// `BulkWriter` to write to it.
+    pub fn new(path: impl AsRef<Path>, config: BulkConfig) -> Result<Self, Error> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        let (pub_key, encryption_key) = match config.key {
+            Some(key) => {
+                let keys = ecdh::Keys::new(&key)?;
+                (keys.public_key, Some(keys.encryption_key))
+            }
+            None => (EMPTY_PUBLIC_KEY, None),
+        };
+        let session_id = config.session_id.unwrap_or_else(|| OsRng.next_u64());
+
+        let context = Arc::new(WorkerContext {
+            compression_level: config.compression_level,
+            pub_key,
+            encryption_key,
+            session_id,
+            instance: OsRng.next_u64(),
+        });
+
+        let (jobs, receiver) = mpsc::sync_channel::<Job>(config.workers);
+        let (sender, results) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let (workers, inline) = if runloop::THREADS_SUPPORTED {
+            let workers = (0..config.workers).map(|_| {
+                let (context, receiver, sender) =
+                    (Arc::clone(&context), Arc::clone(&receiver), sender.clone());
+                thread::spawn(move || work(&context, &receiver, &sender))
+            });
+            (workers.collect(), None)
+        } else {
+            let compressor = ZstdCompressor::new(config.compression_level, &ZstdParams::default());
+            (Vec::new(), Some(Inline { context, compressor }))
+        };
+
+        Ok(Self {
+            writer,
+            chunk_len: config.chunk_len,
+            session_id,
+            batch: Batch::default(),
+            jobs: Some(jobs),
+            results,
+            workers,
+            inline,
+            sent: 0,
+            written: 0,
+            pending: BTreeMap::new(),
+        })
+    }
+
+    /// Writes the record.
+    ///
+    /// Errors occurred in the worker threads are returned by subsequent calls.
+    pub fn write(&mut self, record: &Record) -> Result<(), Error> {
+        // Stamps the session id on the record if it doesn't have one.
+        let mut stamped;
+        let record = if record.meta().session_id().is_none() {
+            stamped = record.clone();
+            stamped.meta_mut().set_session_id(Some(self.session_id));
+            &stamped
+        } else {
+            record
+        };
+
+        self.batch.push(record)?;
+        if self.batch.bytes.len() >= self.chunk_len {
+            self.dispatch()?;
+        }
+
+        // Writes the chunks that have been processed so far.
+        while let Ok((seq, result)) = self.results.try_recv() {
+            self.receive(seq, result)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining records, then waits for all chunks to be written to the
+    /// file.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.dispatch()?;
+        // Closes the job queue so that the workers stop after finishing their jobs.
+        self.jobs = None;
+
+        while self.written < self.sent {
+            let (seq, result) = self.results.recv().map_err(|_| RunloopError)?;
+            self.receive(seq, result)?;
+        }
+        for worker in self.workers.drain(..) {
+            _ = worker.join();
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Sends the current batch to the workers.
+    fn dispatch(&mut self) -> Result<(), Error> {
+        let Some(time_range) = self.batch.time_range.take() else {
+            return Ok(());
+        };
+        let bytes = std::mem::take(&mut self.batch.bytes);
+        let job = Job { seq: self.sent, bytes, time_range };
+
+        if let Some(inline) = &mut self.inline {
+            let result = process(&inline.context, &mut inline.compressor, &job);
+            self.sent += 1;
+            return self.receive(job.seq, result);
+        }
+        let jobs = self.jobs.as_ref().ok_or(RunloopError)?;
+        jobs.send(job).map_err(|_| RunloopError)?;
+        self.sent += 1;
+        Ok(())
+    }
+
+    /// Receives a processed chunk and writes all chunks that are in order.
+    fn receive(&mut self, seq: u64, result: Result<Vec<u8>, Error>) -> Result<(), Error> {
+        self.pending.insert(seq, result?);
+        while let Some(chunk) = self.pending.remove(&self.written) {
+            self.writer.write_all(&chunk)?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+}
+
+// ============ Internal ============
+
+/// The encoded records of a chunk being gathered.
+#[derive(Default)]
+struct Batch {
+    bytes: Vec<u8>,
+    time_range: Option<(DateTime, DateTime)>,
+}
+
+impl Batch {
+    fn push(&mut self, record: &Record) -> Result<(), Error> {
+        let datetime = record.meta().datetime();
+        self.time_range = Some(match self.time_range {
+            Some((start, end)) => (start.min(datetime), end.max(datetime)),
+            None => (datetime, datetime),
+        });
+
+        record.encode(&mut FnSink::new(|bytes: &[u8]| {
+            self.bytes.extend_from_slice(bytes);
+            Ok::<_, Error>(())
+        }))
+    }
+}
+
+/// A job for the workers, the encoded records of a chunk.
+struct Job {
+    seq: u64,
+    bytes: Vec<u8>,
+    time_range: (DateTime, DateTime),
+}
+
+/// The context shared by all workers.
+struct WorkerContext {
+    compression_level: i32,
+    pub_key: PublicKey,
+    encryption_key: Option<EncryptionKey>,
+    session_id: u64,
+    /// The random id of the writer, see [`Extensions::SEQUENCE`].
+    instance: u64,
+}
+
+/// The worker loop, it compresses and encrypts the jobs into chunks.
+fn work(
+    context: &WorkerContext,
+    jobs: &Mutex<mpsc::Receiver<Job>>,
+    results: &mpsc::Sender<(u64, Result<Vec<u8>, Error>)>,
+) {
+    let mut compressor = ZstdCompressor::new(context.compression_level, &ZstdParams::default());
+
+    loop {
+        let Ok(job) = jobs.lock().unwrap().recv() else {
+            break;
+        };
+        let result = process(context, &mut compressor, &job);
+        if results.send((job.seq, result)).is_err() {
+            break;
+        }
+    }
+}
+
+/// The context and the compressor building the chunks on the writing thread, see
+/// [`BulkWriter::inline`].
+struct Inline {
+    context: Arc<WorkerContext>,
+    compressor: Result<ZstdCompressor, CompressionError>,
+}
+
+/// Builds the chunk of the job with the compressor, if it has been created.
+#[inline]
+fn process(
+    context: &WorkerContext,
+    compressor: &mut Result<ZstdCompressor, CompressionError>,
+    job: &Job,
+) -> Result<Vec<u8>, Error> {
+    let compressor = compressor.as_mut().map_err(|e| Error::from(e.clone()))?;
+    build_chunk(context, compressor, job)
+}
+
+/// Compresses and encrypts the encoded records, then returns the bytes of the chunk.
+fn build_chunk(
+    context: &WorkerContext,
+    compressor: &mut ZstdCompressor,
+    job: &Job,
+) -> Result<Vec<u8>, Error> {
+    const AES_BLOCK_SIZE: usize = 16;
+
+    let mut encryptor = context.encryption_key.as_ref().map(AesEncryptor::new);
+
+    // Large enough for the sequence field and the compressed and padded payload.
+    let sequence = Extensions::encode_sequence(context.instance, job.seq);
+    let capacity = Header::LEN
+        + Extensions::field_len(&sequence)
+        + zstd_safe::compress_bound(job.bytes.len())
+        + AES_BLOCK_SIZE;
+    let mut memory = vec![0; capacity];
+
+    let mut chunk = Chunk::bind(memory.as_mut_slice());
+    let (start, end) = job.time_range;
+    chunk.initialize(start, context.pub_key, context.session_id);
+    chunk.set_end_datetime(end);
+    chunk.push_extension(Extensions::SEQUENCE, &sequence)?;
+
+    let mut to_chunk = FnSink::new(|bytes: &[u8]| chunk.write(bytes).map_err(Error::from));
+    let mut to_encryptor =
+        FnSink::new(|bytes: &[u8]| encryptor.encrypt(EncryptOp::Input(bytes), &mut to_chunk));
+
+    compressor.compress(CompressOp::Input(&job.bytes), &mut to_encryptor)?;
+    compressor.compress(CompressOp::End, &mut to_encryptor)?;
+    encryptor.encrypt(EncryptOp::Flush, &mut to_chunk)?;
+
+    let len = chunk.len();
+    memory.truncate(len);
+    Ok(memory)
+}
@@ -8,37 +8,85 @@
 //! # The underlying structure
 //!
 //! ```plain
-//!     ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   n   ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┐              
-//!    ├──── 60 ────┬───────────── n - 60 ──────────────┐              
-//!    ▼────────────▼───────────────────────────────────▼              
-//! ┌──│   Header   │              Payload              │              
-//! │  └────────────┴───────────────────────────────────┘              
-//! │  ┌─────────┬───────────┬──────────┬─────────────┬──────────────┬──────────────┐
-//! └─▶│  Magic  │  Version  │  Length  │  Writeback  │  Time Range  │  Public Key  │
-//!    ▲─────────▲───────────▲──────────▲─────────────▲───┬──────────▲──────────────▲
-//!    └─── 4 ───┴──── 2 ────┴─── 4 ────┴───── 1 ─────┴───┼─ 16 ─────┴───── 33 ─────┘
+//!     ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   n   ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┐
+//!    ├──── 70 ────┬──── e ─────┬──────────────── n - 70 - e ────────────────┐
+//!    ▼────────────▼────────────▼────────────────────────────────────────────▼
+//! ┌──│   Header   │ Extensions │                  Payload                   │
+//! │  └────────────┴────────────┴────────────────────────────────────────────┘
+//! │  ┌─────────┬───────────┬──────────┬─────────────┬──────────────┬──────────────┬───────────┬───────────┐
+//! └─▶│  Magic  │  Version  │  Length  │  Writeback  │  Time Range  │  Public Key  │  Session  │  Ext Len  │
+//!    ▲─────────▲───────────▲──────────▲─────────────▲───┬──────────▲──────────────▲───────────▲───────────▲
+//!    └─── 4 ───┴──── 2 ────┴─── 4 ────┴───── 1 ─────┴───┼─ 16 ─────┴───── 33 ─────┴──── 8 ────┴──── 2 ────┘
 //!                                                       │     ┌─────────┬─────────┐
 //!                                                       └────▶│  Start  │   End   │
 //!                                                             ▲─────────▲─────────▲
 //!                                                             └─── 8 ───┴─── 8 ───┘
 //! ```
+//!
+//! The extension area (`e` = `Ext Len` bytes) consists of TLV (tag-length-value)
+//! fields, see [`Extensions`]. New header fields are added as extensions, and the
+//! unknown ones are skipped by readers, so they don't require a format version bump.
+//!
+//! If a signing key is configured, the chunk is signed with an HMAC-SHA256 over
+//! its header fields (except the length and the writeback flag), the other
+//! extension fields (except the checksum field) and the payload, which is stored in
+//! the signature extension field (see [`Extensions::SIGNATURE`]).
+//!
+//! If the index is enabled, the chunk carries the numbers of its records by level
+//! and a filter of their tags (see [`Index`]), so that the readers can skip the
+//! chunks by them without decrypting the payloads.
+//!
+//! The chunk is sealed with a CRC32C checksum of its header, its extension area and
+//! its payload once it has been finalized (see [`Extensions::CHECKSUM`]), so that
+//! the chunk damaged in the mmap buffer (e.g., by the file system corruption) is
+//! detected on opening instead of being written as is, and the damaged chunk in the
+//! log file is reported by the parsing.
+//!
+//! The chunks written by a logger instance carry increasing sequence numbers (see
+//! [`Extensions::SEQUENCE`]), which keep the order they are written in even if
+//! their time ranges are out of order after the clock jumped backwards. The chunks
+//! of a log file are in the order of their time ranges, the chunk that would be
+//! earlier than the ones written before it is written to another log file.
+//!
+//! A sealed log file ends with a footer chunk, which has no payload and carries the
+//! minute index of the file (see [`Extensions::FOOTER`]).
+//!
+//! Chunks of format version `1` have neither the session field nor the extension
+//! area, so their headers are only 60 bytes long.
 
 use std::{
     fmt::{Display, Formatter},
+    io::Cursor,
     mem,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
 };
 
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use thiserror::Error;
 
-use crate::{encrypt::ecdh::PublicKey, DateTime, Magic, FORMAT_VERSION};
+use crate::{
+    encrypt::ecdh::PublicKey, parse::SUPPORTED_VERSIONS, DateTime, Level, Magic, SigningKey,
+    FORMAT_VERSION,
+};
 
-/// Errors that can be occurred during chunk write operations.
+/// Errors that can be occurred during chunk operations.
 #[derive(Error, Clone, Debug)]
 pub enum Error {
     /// The chunk has overflowed, the input bytes are too large.
     #[error("chunk overflow")]
     Overflow,
+    /// The format version of the chunk is not supported by this version of Pinenut.
+    #[error("unsupported chunk version: {0}")]
+    UnsupportedVersion(u16),
+    /// The signature of the chunk doesn't match its contents, or it is missing,
+    /// that is, the chunk may have been tampered with.
+    #[error("chunk signature mismatch")]
+    SignatureMismatch,
+    /// The checksum of the chunk doesn't match its contents, that is, the chunk has
+    /// been damaged.
+    #[error("chunk checksum mismatch")]
+    ChecksumMismatch,
 }
 
 /// Represents the `Chunk` structure.
@@ -84,15 +132,27 @@ pub(crate) struct Header {
     magic: [u8; 4],
     version: [u8; 2],
     length: [u8; 4],
-    writeback: bool,
+    // Not a `bool`, as the header is read from the untrusted bytes, on which any
+    // value other than `0` and `1` would be undefined behavior.
+    writeback: u8,
     time_range: TimeRange,
     pub_key: PublicKey,
+    session_id: [u8; 8],
+    extensions_len: [u8; 2],
 }
 
 impl Header {
-    /// Length of a header in bytes. (8 bytes)
+    /// Length of a header in bytes, excluding the extension area. (70 bytes)
     pub(crate) const LEN: usize = mem::size_of::<Self>();
 
+    /// Length of a header of format version `1`, which has neither the session
+    /// field nor the extension area. (60 bytes)
+    pub(crate) const LEGACY_LEN: usize =
+        Self::LEN - mem::size_of::<[u8; 8]>() - mem::size_of::<[u8; 2]>();
+
+    /// The first format version with the session field and the extension area.
+    const EXTENDED_VERSION: u16 = 2;
+
     /// It means: `Feed Cat Chunk`.
     const MAGIC: Magic = Magic::new(0xFEEDCA7C);
 
@@ -108,6 +168,27 @@ impl Header {
         u16::from_le_bytes(self.version)
     }
 
+    /// Length of the header in bytes (excluding the extension area), which depends
+    /// on the format version.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        if self.version() >= Self::EXTENDED_VERSION {
+            Self::LEN
+        } else {
+            Self::LEGACY_LEN
+        }
+    }
+
+    /// Length of the extension area following the header in bytes.
+    #[inline]
+    pub(crate) fn extensions_len(&self) -> usize {
+        if self.version() >= Self::EXTENDED_VERSION {
+            u16::from_le_bytes(self.extensions_len) as usize
+        } else {
+            0
+        }
+    }
+
     /// The length of the chunk payload.
     #[inline]
     pub(crate) fn payload_len(&self) -> usize {
@@ -117,7 +198,7 @@ impl Header {
     /// Represents a chunk to be written back.
     #[inline]
     pub(crate) fn writeback(&self) -> bool {
-        self.writeback
+        self.writeback != 0
     }
 
     /// The time range spanned by the chunk.
@@ -132,7 +213,16 @@ impl Header {
         self.pub_key
     }
 
+    /// The identifier of the logger session that produced the chunk. `None` if the
+    /// chunk has no session field.
+    #[inline]
+    pub(crate) fn session_id(&self) -> Option<u64> {
+        (self.version() >= Self::EXTENDED_VERSION).then(|| u64::from_le_bytes(self.session_id))
+    }
+
     /// Converts header to bytes representation.
+    ///
+    /// Only the first [`Header::len`] bytes are meaningful.
     #[inline]
     pub(crate) fn bytes(self) -> [u8; Self::LEN] {
         // SAFETY: Here the length is guaranteed to be correct.
@@ -152,10 +242,51 @@ where
         Self(inner)
     }
 
+    /// Length of the chunk header in bytes, including the extension area.
+    #[inline]
+    pub(crate) fn header_len(&self) -> usize {
+        self.header().len() + self.header().extensions_len()
+    }
+
     /// Checks the correctness of the chunk.
     #[inline]
     pub(crate) fn validate(&self) -> bool {
-        self.header().validate() && self.header().payload_len() <= self.capacity()
+        self.header().validate()
+            && self.header_len() <= self.0.len()
+            && self.header().payload_len() <= self.capacity()
+            && self.is_checksum_matched()
+    }
+
+    /// Whether the chunk is written back, i.e., its payload may end without being
+    /// finalized.
+    #[inline]
+    pub(crate) fn is_writeback(&self) -> bool {
+        self.header().writeback()
+    }
+
+    /// Whether the chunk has been initialized, i.e., it starts with the magic, even
+    /// if it fails the validation.
+    #[inline]
+    pub(crate) fn is_initialized(&self) -> bool {
+        self.header().validate()
+    }
+
+    /// Whether the checksum matches the contents of the chunk, the chunks that
+    /// haven't been sealed (see [`Chunk::seal`]) always match.
+    #[inline]
+    fn is_checksum_matched(&self) -> bool {
+        let extensions = self.extensions();
+        extensions.checksum().is_none_or(|value| {
+            let mut checksum = Checksum::new(self.header(), extensions);
+            checksum.update(&self[self.header_len()..]);
+            checksum.matches(value)
+        })
+    }
+
+    /// The extension fields of the chunk.
+    #[inline]
+    pub(crate) fn extensions(&self) -> Extensions<'_> {
+        Extensions(&self.0[self.header().len()..self.header_len()])
     }
 
     /// The start datetime of the chunk.
@@ -164,22 +295,46 @@ where
         self.header().time_range().start()
     }
 
+    /// The end datetime of the chunk.
+    #[inline]
+    pub(crate) fn end_datetime(&self) -> DateTime {
+        self.header().time_range().end()
+    }
+
     /// The length of the chunk payload.
     #[inline]
     pub(crate) fn payload_len(&self) -> usize {
         self.header().payload_len()
     }
 
+    /// The ratio of the payload length to the capacity, from `0` to `1`.
+    #[inline]
+    pub(crate) fn usage(&self) -> f64 {
+        self.payload_len() as f64 / self.capacity().max(1) as f64
+    }
+
     /// Checks whether the chunk is almost full.
     pub(crate) fn is_almost_full(&self) -> bool {
         const RATIO: f64 = 0.8;
         self.payload_len() as f64 >= RATIO * self.capacity() as f64
     }
 
+    /// The underlying bytes, including those beyond the payload.
+    #[inline]
+    pub(crate) fn as_raw(&self) -> &[u8] {
+        &self.0
+    }
+
     /// The capacity of the chunk payload.
     #[inline]
-    fn capacity(&self) -> usize {
-        self.0.len() - Header::LEN
+    pub(crate) fn capacity(&self) -> usize {
+        self.0.len().saturating_sub(self.header_len())
+    }
+
+    /// The length of the payload that can still be written.
+    #[inline]
+    pub(crate) fn remaining_len(&self) -> usize {
+        self.capacity().saturating_sub(self.payload_len())
     }
 
     #[inline]
@@ -200,18 +355,46 @@ where
 {
     /// Initialize the chunk.
     #[inline]
-    pub(crate) fn initialize(&mut self, datetime: DateTime, pub_key: PublicKey) {
+    pub(crate) fn initialize(&mut self, datetime: DateTime, pub_key: PublicKey, session_id: u64) {
         let header = self.header_mut();
         header.magic = Header::MAGIC.into();
         header.version = FORMAT_VERSION.to_le_bytes();
         header.length = 0u32.to_le_bytes();
-        header.writeback = false;
+        header.writeback = 0;
         header.pub_key = pub_key;
+        header.session_id = session_id.to_le_bytes();
+        header.extensions_len = 0u16.to_le_bytes();
 
         let datetime = datetime.timestamp().to_le_bytes();
         header.time_range = TimeRange { start: datetime, end: datetime };
     }
 
+    /// Appends an extension field to the header.
+    ///
+    /// It must be called before any bytes are written to the payload, as the
+    /// payload follows the extension area.
+    pub(crate) fn push_extension(&mut self, tag: u8, value: &[u8]) -> Result<(), Error> {
+        debug_assert_eq!(self.payload_len(), 0, "the payload has been written");
+
+        let len = Extensions::field_len(value);
+        let extensions_len = u16::try_from(self.header().extensions_len() + len);
+        let (Ok(value_len), Ok(extensions_len)) = (u16::try_from(value.len()), extensions_len)
+        else {
+            return Err(Error::Overflow);
+        };
+        if len > self.capacity() {
+            return Err(Error::Overflow);
+        }
+
+        let start = self.header_len();
+        let field = &mut self.0[start..start + len];
+        field[0] = tag;
+        field[1..3].copy_from_slice(&value_len.to_le_bytes());
+        field[3..].copy_from_slice(value);
+        self.header_mut().extensions_len = extensions_len.to_le_bytes();
+        Ok(())
+    }
+
     /// Writes bytes to the payload of the chunk.
     #[inline]
     pub(crate) fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
@@ -229,10 +412,19 @@ where
         Ok(())
     }
 
-    /// Sets the current chunk to be written back.
+    /// Sets whether the current chunk is written back, i.e., its payload may end
+    /// without being finalized.
+    #[inline]
+    pub(crate) fn set_writeback(&mut self, flag: bool) {
+        self.header_mut().writeback = flag.into();
+    }
+
+    /// Sets the format version of the chunk, e.g., to write the chunks of the older
+    /// versions in the tests.
+    #[cfg(test)]
     #[inline]
-    pub(crate) fn set_writeback(&mut self) {
-        self.header_mut().writeback = true;
+    pub(crate) fn set_version(&mut self, version: u16) {
+        self.header_mut().version = version.to_le_bytes();
     }
 
     /// Sets the end datetime of the chunk.
@@ -241,10 +433,69 @@ where
         self.header_mut().time_range.end = datetime.timestamp().to_le_bytes();
     }
 
-    /// Clears the payload of the chunk.
+    /// Clears the payload of the chunk, which is unsealed as well.
     #[inline]
     pub(crate) fn clear(&mut self) {
         self.set_payload_len(0);
+        self.set_checksum([0; Extensions::CHECKSUM_LEN]);
+    }
+
+    /// Seals the chunk that has been finalized, i.e., writes the checksum of its
+    /// contents to the checksum field, which has been reserved when the chunk was
+    /// initialized.
+    ///
+    /// Only the writeback flag of the sealed chunk may be changed. It does nothing
+    /// if there is no checksum field.
+    pub(crate) fn seal(&mut self) {
+        let mut checksum = Checksum::new(self.header(), self.extensions());
+        checksum.update(&self[self.header_len()..]);
+        self.set_checksum(checksum.finalize());
+    }
+
+    /// Signs the chunk, i.e., writes the signature of its current contents to the
+    /// signature field, which has been reserved when the chunk was initialized.
+    ///
+    /// It does nothing if there is no signature field.
+    pub(crate) fn sign(&mut self, key: &SigningKey) {
+        let mut signer = signer(key, self.header(), self.extensions());
+        signer.update(&self[self.header_len()..]);
+        let signature = signer.finalize().into_bytes();
+
+        let Some(range) = self.extensions().value_range(Extensions::SIGNATURE) else {
+            return;
+        };
+        let start = self.header().len();
+        self.0[start + range.start..start + range.end].copy_from_slice(&signature);
+    }
+
+    /// Adds the record of the level and the tag to the index of the chunk, see
+    /// [`Index`].
+    ///
+    /// It does nothing if there is no index field.
+    pub(crate) fn index_record(&mut self, level: Level, tag: Option<&str>) {
+        let Some(range) = self.extensions().value_range(Extensions::INDEX) else {
+            return;
+        };
+        let start = self.header().len();
+        let value = &mut self.0[start + range.start..start + range.end];
+        if let Some(mut index) = Index::from_bytes(value) {
+            index.add(level, tag);
+            value.copy_from_slice(&index.to_bytes());
+        }
+    }
+
+    /// Writes the value of the checksum field (if any).
+    #[inline]
+    fn set_checksum(&mut self, value: [u8; Extensions::CHECKSUM_LEN]) {
+        let Some(range) = self.extensions().value_range(Extensions::CHECKSUM) else {
+            return;
+        };
+        let start = self.header().len();
+        if let Some(field) = self.0.get_mut(start + range.start..start + range.end)
+            && field.len() == value.len()
+        {
+            field.copy_from_slice(&value);
+        }
     }
 
     #[inline]
@@ -255,7 +506,8 @@ where
 
     #[inline]
     fn payload_mut(&mut self) -> &mut [u8] {
-        &mut self.0[Header::LEN..]
+        let header_len = self.header_len();
+        &mut self.0[header_len..]
     }
 
     #[inline]
@@ -278,11 +530,360 @@ where
 
     #[inline]
     fn deref(&self) -> &Self::Target {
-        let len = self.payload_len().min(self.capacity()) + Header::LEN;
+        let len = self.payload_len().min(self.capacity()) + self.header_len();
         &self.0[..len]
     }
 }
 
+impl Chunk<Vec<u8>> {
+    /// Copies the readable chunk at the start of the bytes (see [`locate`]).
+    ///
+    /// If its payload is cut off by the end of the bytes (e.g., the writing was
+    /// interrupted), the remaining payload is copied and the chunk is marked as
+    /// written back, so that it can be parsed without being finalized.
+    pub(crate) fn recover(bytes: &[u8]) -> Option<Self> {
+        let (header_len, payload_len) = locate(bytes)?;
+        let len = (header_len + payload_len).min(bytes.len());
+
+        let mut inner = bytes[..len].to_vec();
+        inner.resize(len.max(Header::LEN), 0);
+        let mut chunk = Self::bind(inner);
+        if len < header_len + payload_len {
+            chunk.set_payload_len(len - header_len);
+            chunk.set_writeback(true);
+            chunk.set_checksum([0; Extensions::CHECKSUM_LEN]);
+        }
+        Some(chunk)
+    }
+}
+
+/// Reads the chunk header at the start of the bytes, and returns the lengths of
+/// the header (including the extension area) and the payload.
+///
+/// Returns `None` if there is no readable header of a supported format version.
+pub(crate) fn locate(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut reader = Reader::new(Cursor::new(bytes));
+    let header = reader.read_header_or_reach_to_end().ok()??;
+    SUPPORTED_VERSIONS
+        .contains(&header.version())
+        .then(|| (header.len() + header.extensions_len(), header.payload_len()))
+}
+
+/// Finds the start of the first readable chunk (see [`locate`]) within the range of
+/// the bytes, by scanning for the chunk magic.
+pub(crate) fn find(bytes: &[u8], mut range: Range<usize>) -> Option<usize> {
+    let magic = Header::MAGIC.raw();
+    loop {
+        let end = (range.end + magic.len() - 1).min(bytes.len());
+        let offset = bytes.get(range.start..end)?.windows(magic.len()).position(|w| w == magic)?;
+        let start = range.start + offset;
+        if locate(&bytes[start..]).is_some() {
+            return Some(start);
+        }
+        range.start = start + 1;
+    }
+}
+
+/// Starts the signature of a chunk with its header fields and its extension fields
+/// (except the signature and the checksum fields, which are written after it), the
+/// payload is fed to the returned HMAC afterwards.
+pub(crate) fn signer(key: &SigningKey, header: &Header, extensions: Extensions) -> Hmac<Sha256> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+    hmac.update(&header.version);
+    hmac.update(&header.time_range.start);
+    hmac.update(&header.time_range.end);
+    hmac.update(&header.pub_key);
+    hmac.update(&header.session_id);
+    let fields = extensions
+        .iter()
+        .filter(|(tag, _)| !matches!(*tag, Extensions::SIGNATURE | Extensions::CHECKSUM));
+    for (tag, value) in fields {
+        hmac.update(&[tag]);
+        hmac.update(&(value.len() as u16).to_le_bytes());
+        hmac.update(value);
+    }
+    hmac
+}
+
+/// The checksum of a chunk (see [`Extensions::CHECKSUM`]), which is started with
+/// its header, then fed with its payload.
+pub(crate) struct Checksum(u32);
+
+impl Checksum {
+    /// Starts the checksum with the header and the extension area of a chunk, in
+    /// which the writeback flag and the value of the checksum field are taken as
+    /// zeros, as they are written after the chunk is finalized.
+    pub(crate) fn new(header: &Header, extensions: Extensions) -> Self {
+        let mut header = header.clone();
+        header.writeback = 0;
+        let len = header.len();
+        let crc = crc32c::crc32c(&header.bytes()[..len]);
+
+        let bytes = extensions.as_bytes();
+        let crc = match extensions.value_range(Extensions::CHECKSUM) {
+            Some(range) => {
+                let crc = crc32c::crc32c_append(crc, &bytes[..range.start]);
+                let crc = crc32c::crc32c_append(crc, &vec![0; range.len()]);
+                crc32c::crc32c_append(crc, &bytes[range.end..])
+            }
+            None => crc32c::crc32c_append(crc, bytes),
+        };
+        Self(crc)
+    }
+
+    /// Feeds the bytes of the payload.
+    #[inline]
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        self.0 = crc32c::crc32c_append(self.0, bytes);
+    }
+
+    /// Returns the value of the checksum field.
+    #[inline]
+    pub(crate) fn finalize(self) -> [u8; Extensions::CHECKSUM_LEN] {
+        self.0.to_le_bytes()
+    }
+
+    /// Whether the checksum matches the value of the checksum field.
+    #[inline]
+    pub(crate) fn matches(self, value: &[u8]) -> bool {
+        value == self.finalize()
+    }
+}
+
+/// Represents the extension area of a chunk header, which consists of TLV
+/// (tag-length-value) fields.
+///
+/// Each field is encoded as: tag (1 byte), length of the value (2 bytes, little
+/// endian) and the value. Readers skip the fields with unknown tags.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Extensions<'a>(&'a [u8]);
+
+impl<'a> Extensions<'a> {
+    /// Length of the tag and length parts of a field. (3 bytes)
+    const FIELD_HEADER_LEN: usize = 3;
+
+    /// Tag of the producer process id field, see [`Producer`].
+    ///
+    /// [`Producer`]: crate::Producer
+    pub(crate) const PID: u8 = 1;
+    /// Tag of the producer app version field.
+    pub(crate) const APP_VERSION: u8 = 2;
+    /// Tag of the producer OS version field.
+    pub(crate) const OS_VERSION: u8 = 3;
+    /// Tag of the field recording why the previous chunk was rotated, see
+    /// [`RotateReason`].
+    ///
+    /// [`RotateReason`]: crate::RotateReason
+    pub(crate) const ROTATE_REASON: u8 = 4;
+    /// Tag of the HMAC-SHA256 signature field of the chunk, see
+    /// [`Config::signing_key`].
+    ///
+    /// [`Config::signing_key`]: crate::Config::signing_key
+    pub(crate) const SIGNATURE: u8 = 5;
+
+    /// Length of the value of the signature field. (32 bytes)
+    pub(crate) const SIGNATURE_LEN: usize = 32;
+    /// Tag of the field of the fingerprint of the public key that the chunk is
+    /// encrypted for, see [`key_fingerprint`].
+    ///
+    /// [`key_fingerprint`]: crate::encrypt::key_fingerprint
+    pub(crate) const KEY_FINGERPRINT: u8 = 6;
+    /// Tag of the field of the key agreement algorithm that the chunk is encrypted
+    /// with, see [`KeyAlgorithm`]. The chunks without it use the default one.
+    ///
+    /// [`KeyAlgorithm`]: crate::KeyAlgorithm
+    pub(crate) const KEY_ALGORITHM: u8 = 7;
+    /// Tag of the field of the index of the records in the chunk, see [`Index`].
+    pub(crate) const INDEX: u8 = 8;
+    /// Tag of the field of the minute index of the log file, which is only carried
+    /// by its footer chunk, see [`MinuteIndex`].
+    ///
+    /// [`MinuteIndex`]: crate::logfile::MinuteIndex
+    pub(crate) const FOOTER: u8 = 9;
+    /// Tag of the checksum field of the chunk, which is the CRC32C of its header
+    /// (except the writeback flag), its extension area (except the value of the
+    /// checksum field) and its payload, see [`Checksum`].
+    ///
+    /// It is zeros until the chunk is sealed (see [`Chunk::seal`]), e.g., the chunk
+    /// in progress, which is not verified. So is the sealed chunk whose checksum
+    /// happens to be zero.
+    pub(crate) const CHECKSUM: u8 = 10;
+
+    /// Length of the value of the checksum field. (4 bytes, little endian)
+    pub(crate) const CHECKSUM_LEN: usize = 4;
+    /// Tag of the field of the time dimension by which the chunks are rotated (see
+    /// [`Config::rotation`]), so that the extraction knows which file dimension the
+    /// chunks of a log file are in. The chunks without it are of the `Minute`
+    /// rotation. (1 byte)
+    ///
+    /// [`Config::rotation`]: crate::Config::rotation
+    pub(crate) const ROTATION: u8 = 11;
+    /// Tag of the sequence number field of the chunk, which increases with the
+    /// chunks written by a logger instance, so that they can be ordered even if the
+    /// clock jumps backwards. It is preceded by the random id of the instance, as
+    /// the numbers restart with each instance, even of the same session.
+    /// (8 + 8 bytes, little endian)
+    pub(crate) const SEQUENCE: u8 = 12;
+
+    /// Length of the value of the sequence number field. (16 bytes)
+    pub(crate) const SEQUENCE_LEN: usize = 16;
+
+    /// Length of the field with the value in bytes.
+    #[inline]
+    pub(crate) fn field_len(value: &[u8]) -> usize {
+        Self::FIELD_HEADER_LEN + value.len()
+    }
+
+    /// The raw bytes of the extension area.
+    #[inline]
+    pub(crate) fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Returns the value of the first field with the tag. `None` if there is no such
+    /// field.
+    pub(crate) fn get(&self, tag: u8) -> Option<&'a [u8]> {
+        self.value_range(tag).map(|range| &self.0[range])
+    }
+
+    /// Returns the value of the checksum field of the sealed chunk. `None` if there
+    /// is no such field or the chunk hasn't been sealed, see
+    /// [`Extensions::CHECKSUM`].
+    #[inline]
+    pub(crate) fn checksum(&self) -> Option<&'a [u8]> {
+        self.get(Self::CHECKSUM).filter(|value| value.iter().any(|b| *b != 0))
+    }
+
+    /// Returns the range of the value of the first field with the tag within the
+    /// extension area. `None` if there is no such field.
+    pub(crate) fn value_range(&self, tag: u8) -> Option<Range<usize>> {
+        let mut start = 0;
+        self.iter().find_map(|(t, value)| {
+            let range = start + Self::FIELD_HEADER_LEN..start + Self::field_len(value);
+            start = range.end;
+            (t == tag).then_some(range)
+        })
+    }
+
+    /// Whether the chunk is the footer of a log file, which carries no records, see
+    /// [`Extensions::FOOTER`].
+    #[inline]
+    pub(crate) fn is_footer(&self) -> bool {
+        self.get(Self::FOOTER).is_some()
+    }
+
+    /// Encodes the value of the sequence number field, see [`Extensions::SEQUENCE`].
+    #[inline]
+    pub(crate) fn encode_sequence(instance: u64, sequence: u64) -> [u8; Extensions::SEQUENCE_LEN] {
+        let mut value = [0; Self::SEQUENCE_LEN];
+        value[..8].copy_from_slice(&instance.to_le_bytes());
+        value[8..].copy_from_slice(&sequence.to_le_bytes());
+        value
+    }
+
+    /// Returns the id of the logger instance that wrote the chunk and the sequence
+    /// number of the chunk, see [`Extensions::SEQUENCE`]. `None` if there is no such
+    /// field or it is malformed.
+    #[inline]
+    pub(crate) fn sequence(&self) -> Option<(u64, u64)> {
+        let value = self.get(Self::SEQUENCE).filter(|v| v.len() == Self::SEQUENCE_LEN)?;
+        let (instance, sequence) = value.split_at(8);
+        Some((
+            u64::from_le_bytes(instance.try_into().ok()?),
+            u64::from_le_bytes(sequence.try_into().ok()?),
+        ))
+    }
+
+    /// Returns an iterator over the fields, the malformed tail (if any) is ignored.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u8, &'a [u8])> {
+        let mut bytes = self.0;
+        std::iter::from_fn(move || {
+            let header = bytes.get(..Self::FIELD_HEADER_LEN)?;
+            let len = u16::from_le_bytes([header[1], header[2]]) as usize;
+            let value = bytes.get(Self::FIELD_HEADER_LEN..Self::FIELD_HEADER_LEN + len)?;
+            let tag = header[0];
+            bytes = &bytes[Self::FIELD_HEADER_LEN + len..];
+            Some((tag, value))
+        })
+    }
+}
+
+/// The index of the records in a chunk, which is stored in the index field (see
+/// [`Extensions::INDEX`]) and updated as the records are written.
+///
+/// It consists of the numbers of the records by level (4 bytes each, little
+/// endian) and a 64-bit bloom filter of their tags, so a chunk can be skipped if it
+/// has no records of the levels, or of the tag for sure. The time range of the
+/// records is in the header already.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub(crate) struct Index {
+    level_counts: [u32; Index::LEVELS],
+    tag_filter: u64,
+}
+
+impl Index {
+    /// Number of the levels counted, see [`Level`].
+    const LEVELS: usize = 5;
+
+    /// Length of the value of the index field. (28 bytes)
+    pub(crate) const LEN: usize = Self::LEVELS * 4 + 8;
+
+    #[inline]
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; Self::LEN] = bytes.try_into().ok()?;
+        let mut index = Self::default();
+        for (count, bytes) in index.level_counts.iter_mut().zip(bytes.chunks_exact(4)) {
+            *count = u32::from_le_bytes(bytes.try_into().ok()?);
+        }
+        index.tag_filter = u64::from_le_bytes(bytes[Self::LEVELS * 4..].try_into().ok()?);
+        Some(index)
+    }
+
+    #[inline]
+    pub(crate) fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut bytes = [0; Self::LEN];
+        for (bytes, count) in bytes.chunks_exact_mut(4).zip(self.level_counts) {
+            bytes.copy_from_slice(&count.to_le_bytes());
+        }
+        bytes[Self::LEVELS * 4..].copy_from_slice(&self.tag_filter.to_le_bytes());
+        bytes
+    }
+
+    /// Adds the record of the level and the tag.
+    #[inline]
+    pub(crate) fn add(&mut self, level: Level, tag: Option<&str>) {
+        if let Some(count) = self.level_counts.get_mut(level.primitive() as usize - 1) {
+            *count = count.saturating_add(1);
+        }
+        if let Some(tag) = tag {
+            self.tag_filter |= Self::tag_bits(tag);
+        }
+    }
+
+    /// Whether there may be records at the level or more severe levels.
+    #[inline]
+    pub(crate) fn may_contain_level(&self, level: Level) -> bool {
+        let len = (level.primitive() as usize).min(Self::LEVELS);
+        self.level_counts[..len].iter().any(|count| *count > 0)
+    }
+
+    /// Whether there may be records of the tag, false positives are possible.
+    #[inline]
+    pub(crate) fn may_contain_tag(&self, tag: &str) -> bool {
+        let bits = Self::tag_bits(tag);
+        self.tag_filter & bits == bits
+    }
+
+    /// The two bits of the tag in the bloom filter, which are derived from its
+    /// FNV-1a hash.
+    fn tag_bits(tag: &str) -> u64 {
+        let hash = tag.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        (1 << (hash % 64)) | (1 << ((hash >> 32) % 64))
+    }
+}
+
 pub(crate) use reader::{Error as ReadError, Reader};
 
 /// The internal module that implements the chunk reader.
@@ -292,7 +893,7 @@ pub(crate) mod reader {
     use thiserror::Error;
 
     use crate::{
-        chunk::Header,
+        chunk::{Extensions, Header},
         common::{BytesBuf, Sink},
         BUFFER_LEN,
     };
@@ -337,16 +938,14 @@ pub(crate) mod reader {
             let buffer = self.buffer.as_buffer_mut_slice();
             assert!(buffer.len() >= Header::LEN, "buffer is too small");
 
-            let mut read_len = 0;
-            while read_len < Header::LEN {
-                match self.inner.read(&mut buffer[read_len..Header::LEN]) {
-                    Ok(0) => {
-                        return if read_len == 0 { Ok(None) } else { Err(Error::UnexpectedEnd) }
-                    }
-                    Ok(len) => read_len += len,
-                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
-                    Err(e) => return Err(e.into()),
-                }
+            // Reads the part of the header that is common to all format versions first.
+            if !read_exact_or_reach_to_end(&mut self.inner, &mut buffer[..Header::LEGACY_LEN])? {
+                return Ok(None);
+            }
+            // The zero-filled space (e.g., preallocated for the log file) marks the end of
+            // the chunks.
+            if buffer[..Header::LEGACY_LEN].iter().all(|b| *b == 0) {
+                return Ok(None);
             }
 
             // SAFETY: Here the length is guaranteed to be correct. The alignment of Header is
@@ -356,11 +955,44 @@ pub(crate) mod reader {
                 &*ptr
             };
 
-            if header.validate() {
-                Ok(Some(header))
-            } else {
-                Err(Error::Invalid)
+            if !header.validate() {
+                return Err(Error::Invalid);
             }
+
+            // Then reads the rest of the header, which depends on the format version.
+            let header_len = header.len();
+            buffer[header_len..Header::LEN].fill(0);
+            if !read_exact_or_reach_to_end(
+                &mut self.inner,
+                &mut buffer[Header::LEGACY_LEN..header_len],
+            )? {
+                return Err(Error::UnexpectedEnd);
+            }
+
+            // Finally reads the extension area.
+            // SAFETY: Same as above.
+            let extensions_len = unsafe { &*(buffer.as_ptr() as *const Header) }.extensions_len();
+            let Some(extensions) = buffer.get_mut(Header::LEN..Header::LEN + extensions_len) else {
+                return Err(Error::Invalid);
+            };
+            if !read_exact_or_reach_to_end(&mut self.inner, extensions)? {
+                return Err(Error::UnexpectedEnd);
+            }
+
+            // SAFETY: Same as above.
+            Ok(Some(unsafe { &*(buffer.as_ptr() as *const Header) }))
+        }
+
+        /// The extension fields of the header that has been read last.
+        ///
+        /// It must be called before the payload is read, as the buffer is reused.
+        #[inline]
+        pub(crate) fn extensions(&self) -> Extensions<'_> {
+            let buffer = self.buffer.as_buffer_slice();
+            // SAFETY: The buffer is large enough for a header, and the alignment of Header is
+            // `1`.
+            let header = unsafe { &*(buffer.as_ptr() as *const Header) };
+            Extensions(&buffer[Header::LEN..Header::LEN + header.extensions_len()])
         }
 
         /// Reads the payload of the chunk with payload length.
@@ -380,9 +1012,9 @@ pub(crate) mod reader {
                         sink.sink(&buffer[..len]).inspect_err(|_| {
                             // Reader needs to ensure that it has pointed to the next chunk to be
                             // read.
-                            _ = self.inner.seek(io::SeekFrom::Current(
-                                remaining.try_into().expect("payload is too large"),
-                            ));
+                            if let Ok(remaining) = remaining.try_into() {
+                                _ = self.inner.seek(io::SeekFrom::Current(remaining));
+                            }
                         })?;
                     }
                     Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
@@ -396,9 +1028,151 @@ pub(crate) mod reader {
         /// Skips the current payload with payload length.
         #[inline]
         pub(crate) fn skip(&mut self, len: usize) -> Result<(), Error> {
-            let len: i64 = len.try_into().expect("chunk is too large");
+            let len = len.try_into().map_err(|_| Error::Invalid)?;
             self.inner.seek(io::SeekFrom::Current(len))?;
             Ok(())
         }
+
+        /// The current position of the underlying reader.
+        #[inline]
+        pub(crate) fn stream_position(&mut self) -> Result<u64, Error> {
+            Ok(self.inner.stream_position()?)
+        }
+    }
+
+    /// Reads the exact number of bytes required to fill the buffer.
+    ///
+    /// Returns `false` if the reader has reached the end before any bytes are read.
+    fn read_exact_or_reach_to_end(
+        reader: &mut impl io::Read,
+        buf: &mut [u8],
+    ) -> Result<bool, Error> {
+        let mut read_len = 0;
+        while read_len < buf.len() {
+            match reader.read(&mut buf[read_len..]) {
+                Ok(0) => return if read_len == 0 { Ok(false) } else { Err(Error::UnexpectedEnd) },
+                Ok(len) => read_len += len,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{
+        chunk::{Chunk, Extensions, Header, Index, ReadError, Reader},
+        common::FnSink,
+        DateTime, Level,
+    };
+
+    #[test]
+    fn test_index() {
+        let mut chunk = Chunk::bind(vec![0; 256]);
+        chunk.initialize(DateTime::default(), [0; 33], 1);
+        chunk.push_extension(Extensions::INDEX, &[0; Index::LEN]).unwrap();
+        chunk.index_record(Level::Warn, Some("net"));
+        chunk.index_record(Level::Info, None);
+
+        let index = Index::from_bytes(chunk.extensions().get(Extensions::INDEX).unwrap()).unwrap();
+        assert!(index.may_contain_level(Level::Info));
+        assert!(!index.may_contain_level(Level::Error));
+        assert!(index.may_contain_level(Level::Warn));
+        assert!(index.may_contain_tag("net"));
+        assert!(!index.may_contain_tag("ui"));
+    }
+
+    #[test]
+    fn test_extensions() {
+        let mut chunk = Chunk::bind(vec![0; 256]);
+        chunk.initialize(DateTime::default(), [0; 33], 1);
+        chunk.push_extension(1, b"cipher").unwrap();
+        chunk.push_extension(2, &[]).unwrap();
+        chunk.write(b"payload").unwrap();
+
+        let extensions = chunk.extensions();
+        assert_eq!(extensions.get(1), Some(&b"cipher"[..]));
+        assert_eq!(extensions.get(2), Some(&[][..]));
+        assert_eq!(extensions.get(3), None);
+
+        // Reads the chunk back, the extension area is skipped before the payload.
+        let mut reader = Reader::new(Cursor::new(chunk.to_vec()));
+        let header = reader.read_header_or_reach_to_end().unwrap().unwrap();
+        assert_eq!(header.session_id(), Some(1));
+        let payload_len = header.payload_len();
+        assert_eq!(
+            reader.extensions().iter().collect::<Vec<_>>(),
+            [(1, &b"cipher"[..]), (2, &[][..])]
+        );
+
+        let mut payload = Vec::new();
+        let mut sink = FnSink::<_, ReadError>::new(|bytes: &[u8]| {
+            payload.extend_from_slice(bytes);
+            Ok(())
+        });
+        reader.read_payload(payload_len, &mut sink).unwrap();
+        assert_eq!(payload, b"payload");
+        assert!(reader.read_header_or_reach_to_end().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_legacy_headers() {
+        // The headers of format version `1` have neither the session field nor the
+        // extension area.
+        let layouts = [(1, Header::LEGACY_LEN), (2, Header::LEN)];
+        for (version, len) in layouts {
+            let mut chunk = Chunk::bind(vec![0; 256]);
+            chunk.initialize(DateTime::default(), [0; 33], 42);
+            chunk.set_version(version);
+            chunk.write(b"payload").unwrap();
+            assert_eq!(chunk.len(), len + b"payload".len());
+
+            let mut reader = Reader::new(Cursor::new(chunk.to_vec()));
+            let header = reader.read_header_or_reach_to_end().unwrap().unwrap();
+            assert_eq!(header.session_id(), (version >= 2).then_some(42));
+            let payload_len = header.payload_len();
+            assert_eq!(reader.extensions().iter().count(), 0);
+
+            let mut payload = Vec::new();
+            let mut sink = FnSink::<_, ReadError>::new(|bytes: &[u8]| {
+                payload.extend_from_slice(bytes);
+                Ok(())
+            });
+            reader.read_payload(payload_len, &mut sink).unwrap();
+            assert_eq!(payload, b"payload");
+            assert!(reader.read_header_or_reach_to_end().unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut chunk = Chunk::bind(vec![0; 256]);
+        chunk.initialize(DateTime::default(), [0; 33], 1);
+        chunk.push_extension(Extensions::CHECKSUM, &[0; Extensions::CHECKSUM_LEN]).unwrap();
+        chunk.write(b"payload").unwrap();
+        // The chunk in progress is not verified.
+        assert!(chunk.extensions().checksum().is_none());
+
+        chunk.seal();
+        assert!(chunk.extensions().checksum().is_some());
+        assert!(chunk.validate());
+        // The writeback flag is not covered.
+        chunk.set_writeback(true);
+        assert!(chunk.validate());
+
+        let len = chunk.len();
+        chunk.0[len - 1] ^= 0xFF;
+        assert!(!chunk.validate());
+        chunk.0[len - 1] ^= 0xFF;
+        chunk.set_end_datetime(DateTime::from_timestamp(1, 0).unwrap());
+        assert!(!chunk.validate());
+
+        // The cleared chunk is unsealed.
+        chunk.clear();
+        assert!(chunk.validate());
     }
 }
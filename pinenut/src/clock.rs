@@ -0,0 +1,136 @@
+//! The time source of the record timestamps.
+
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::DateTime;
+
+/// Represents where the logger obtains the current time from, see
+/// [`Config::timestamp_source`].
+///
+/// [`Config::timestamp_source`]: crate::Config::timestamp_source
+#[derive(Default)]
+pub enum TimestampSource {
+    /// The wall clock of the device, the records keep the datetimes they were
+    /// built with.
+    #[default]
+    Wallclock,
+    /// The callback that returns the corrected current time (e.g., adjusted by the
+    /// time of the server or NTP).
+    ///
+    /// The records are shifted by the difference between the corrected time and
+    /// the wall clock, so the device clocks that are wrong don't break the
+    /// correlation across devices.
+    Callback(Box<dyn Fn() -> DateTime + Send + Sync>),
+}
+
+impl TimestampSource {
+    /// Shares the source, returns another source obtaining the time from the same
+    /// one (e.g., for the split stream, see [`Config::split_by_level`]).
+    ///
+    /// [`Config::split_by_level`]: crate::Config::split_by_level
+    pub(crate) fn share(&mut self) -> Self {
+        let Self::Callback(callback) = mem::take(self) else {
+            return Self::Wallclock;
+        };
+        let callback: Arc<dyn Fn() -> DateTime + Send + Sync> = Arc::from(callback);
+        let shared = Arc::clone(&callback);
+        *self = Self::Callback(Box::new(move || callback()));
+        Self::Callback(Box::new(move || shared()))
+    }
+}
+
+/// The clock of the logger, it stamps the records with the time from the source.
+///
+/// The stamped datetimes are monotonic, a datetime never precedes the previous
+/// one, so that the internal ordering (e.g., chunk rotation) remains consistent when
+/// the corrected time moves backwards.
+pub(crate) struct Clock {
+    source: TimestampSource,
+    /// The last stamped datetime, the clock is shared with the IO thread along with
+    /// the logger context.
+    last: Mutex<Option<DateTime>>,
+    /// When the logger was initialized, if the elapsed time is stamped, see
+    /// [`Config::elapsed_time`].
+    ///
+    /// [`Config::elapsed_time`]: crate::Config::elapsed_time
+    started: Option<Instant>,
+}
+
+impl Clock {
+    /// Constructs a new `Clock`.
+    #[inline]
+    pub(crate) fn new(source: TimestampSource, elapsed_time: bool) -> Self {
+        let started = (elapsed_time && INSTANT_SUPPORTED).then(Instant::now);
+        Self { source, last: Mutex::new(None), started }
+    }
+
+    /// The milliseconds elapsed since the logger was initialized, `None` if the
+    /// elapsed time is not stamped.
+    #[inline]
+    pub(crate) fn elapsed(&self) -> Option<u64> {
+        self.started.map(|started| started.elapsed().as_millis() as u64)
+    }
+
+    /// The current time.
+    #[inline]
+    pub(crate) fn now(&self) -> DateTime {
+        match &self.source {
+            TimestampSource::Wallclock => chrono::Utc::now(),
+            TimestampSource::Callback(callback) => callback(),
+        }
+    }
+
+    /// Stamps the datetime of a record, which is captured by the wall clock.
+    ///
+    /// Returns `None` if the datetime doesn't need to be changed.
+    pub(crate) fn stamp(&self, datetime: DateTime) -> Option<DateTime> {
+        let TimestampSource::Callback(callback) = &self.source else {
+            return None;
+        };
+        let stamped = datetime + (callback() - chrono::Utc::now());
+        let mut last = self.last.lock().unwrap();
+        let stamped = last.map_or(stamped, |last| stamped.max(last));
+        *last = Some(stamped);
+        Some(stamped)
+    }
+}
+
+/// Whether `Instant` is supported on the target, it panics on
+/// `wasm32-unknown-unknown`.
+const INSTANT_SUPPORTED: bool = !cfg!(all(target_family = "wasm", target_os = "unknown"));
+
+/// A moment of the monotonic clock, for measuring the elapsed time (e.g., the
+/// retry interval or the logging latency).
+///
+/// The wall clock is used instead where `Instant` is unsupported.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Moment {
+    Instant(Instant),
+    Wallclock(DateTime),
+}
+
+impl Moment {
+    /// The current moment.
+    #[inline]
+    pub(crate) fn now() -> Self {
+        if INSTANT_SUPPORTED {
+            Self::Instant(Instant::now())
+        } else {
+            Self::Wallclock(chrono::Utc::now())
+        }
+    }
+
+    /// The time elapsed since the moment, `None` if the wall clock has gone
+    /// backwards.
+    #[inline]
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        match self {
+            Self::Instant(instant) => Some(instant.elapsed()),
+            Self::Wallclock(datetime) => (chrono::Utc::now() - *datetime).to_std().ok(),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Encoding & Decoding.
 
-use std::{any::type_name, slice, str};
+use std::{any::type_name, collections::BTreeMap, slice, str};
 
 use thiserror::Error;
 
@@ -19,23 +19,6 @@ pub enum EncodingError {
     None,
 }
 
-/// Represents a target for encoded data.
-pub(crate) trait Sink = crate::Sink<EncodingError>;
-
-/// Any data type that can be encoded.
-///
-/// `Pinenut` encodes the data into a stream of compact binary bytes and outputs to
-/// the `Sink`.
-///
-/// This trait will be automatically implemented if you add `#[derive(Encode)]` to a
-/// struct.
-pub(crate) trait Encode {
-    /// Encode the data and write encoded bytes to `Sink`.
-    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
-    where
-        S: Sink;
-}
-
 /// Errors that can be occurred by decoding a type.
 #[derive(Error, Clone, Debug)]
 #[non_exhaustive]
@@ -68,31 +51,124 @@ pub enum DecodingError {
     /// nanosecond.
     #[error("failed to decode date & time")]
     DateTime,
+    /// The bytes were left over after the value was decoded (`codec::decode`).
+    #[error("{len} bytes were left over after decoding")]
+    TrailingBytes {
+        /// How many bytes were left over.
+        len: usize,
+    },
 }
 
-/// Represents a provider for encoded data.
-pub(crate) trait Source<'de> {
-    type Error: From<DecodingError>;
+// The traits are only public with the `codec` feature, so that the format can be
+// extended by the users without committing to the whole of the codec.
+#[cfg(feature = "codec")]
+pub use traits::{Decode, Encode, Sink, Source};
+#[cfg(not(feature = "codec"))]
+pub(crate) use traits::{Decode, Encode, Sink, Source};
+
+mod traits {
+    use super::{DecodingError, EncodingError};
+
+    /// Represents a target for encoded data.
+    pub trait Sink = crate::common::Sink<EncodingError>;
+
+    /// Any data type that can be encoded.
+    ///
+    /// `Pinenut` encodes the data into a stream of compact binary bytes and outputs
+    /// to the `Sink`.
+    ///
+    /// This trait will be automatically implemented if you add `#[derive(Encode)]`
+    /// to a struct inside `Pinenut`, outside of it (with the `codec` feature) it
+    /// is implemented by hand, see
+    /// [`Record::extension`](crate::Record::extension).
+    pub trait Encode {
+        /// Encode the data and write encoded bytes to `Sink`.
+        fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+        where
+            S: Sink;
+
+        /// Encode the elements of a slice one after another, without its length.
+        ///
+        /// It can be overridden by the types whose slices are encoded at once, e.g.,
+        /// the bytes.
+        #[inline]
+        fn encode_slice<S>(slice: &[Self], sink: &mut S) -> Result<(), S::Error>
+        where
+            Self: Sized,
+            S: Sink,
+        {
+            slice.iter().try_for_each(|item| item.encode(sink))
+        }
+    }
+
+    /// Represents a provider for encoded data.
+    pub trait Source<'de> {
+        type Error: From<DecodingError>;
+
+        /// Take a length and attempt to read that many bytes.
+        fn read_bytes(&mut self, len: usize) -> Result<&'de [u8], Self::Error>;
+
+        /// Whether all the bytes have been read, so that the trailing fields missing
+        /// from the data encoded earlier are decoded as default, see
+        /// `#[codec(default)]` of `#[derive(Decode)]`.
+        ///
+        /// The default implementation returns `false`, that is, the fields are
+        /// always decoded.
+        // No field inside `Pinenut` has `#[codec(default)]` yet.
+        #[cfg_attr(not(feature = "codec"), allow(dead_code))]
+        #[inline]
+        fn is_exhausted(&self) -> bool {
+            false
+        }
+    }
 
-    /// Take a length and attempt to read that many bytes.
-    fn read_bytes(&mut self, len: usize) -> Result<&'de [u8], Self::Error>;
+    /// Any data type that can be decoded.
+    ///
+    /// `Pinenut` decodes the data by continuously reading a stream of compact binary
+    /// bytes from the `Source`.
+    ///
+    /// The `'de` lifetime is what enables `Pinenut` to safely perform efficient
+    /// zero-copy decoding across a variety of data formats.
+    ///
+    /// This trait will be automatically implemented if you add `#[derive(Decode)]`
+    /// to a struct inside `Pinenut`, outside of it (with the `codec` feature) it
+    /// is implemented by hand.
+    pub trait Decode<'de>: Sized {
+        /// Decode the data from `Source`.
+        fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+        where
+            S: Source<'de>;
+    }
 }
 
-/// Any data type that can be decoded.
-///
-/// `Pinenut` decodes the data by continuously reading a stream of compact binary
-/// bytes from the `Source`.
-///
-/// The `'de` lifetime is what enables `Pinenut` to safely perform efficient
-/// zero-copy decoding across a variety of data formats.
+/// Encodes the value into bytes, e.g., the extension of a record
+/// ([`Record::extension`](crate::Record::extension)).
+#[cfg(feature = "codec")]
+pub fn encode<T>(value: &T) -> Result<Vec<u8>, EncodingError>
+where
+    T: Encode + ?Sized,
+{
+    let mut bytes = Vec::new();
+    value.encode(&mut FnSink::new(|b: &[u8]| {
+        bytes.extend_from_slice(b);
+        Ok::<_, EncodingError>(())
+    }))?;
+    Ok(bytes)
+}
+
+/// Decodes the value from the bytes, which borrows from them if it can.
 ///
-/// This trait will be automatically implemented if you add `#[derive(Decode)]` to a
-/// struct.
-pub(crate) trait Decode<'de>: Sized {
-    /// Decode the data from `Source`.
-    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
-    where
-        S: Source<'de>;
+/// The bytes are expected to be fully consumed by the value.
+#[cfg(feature = "codec")]
+pub fn decode<'de, T>(mut bytes: &'de [u8]) -> Result<T, DecodingError>
+where
+    T: Decode<'de>,
+{
+    let value = T::decode(&mut bytes)?;
+    match bytes.len() {
+        0 => Ok(value),
+        len => Err(DecodingError::TrailingBytes { len }),
+    }
 }
 
 /// Used to accumulate the data generated during encoding and reduce the callback
@@ -157,6 +233,14 @@ impl Encode for u8 {
     {
         sink.sink(slice::from_ref(self))
     }
+
+    #[inline]
+    fn encode_slice<S>(slice: &[Self], sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        sink.sink(slice)
+    }
 }
 
 impl<'de> Decode<'de> for u8 {
@@ -171,6 +255,34 @@ impl<'de> Decode<'de> for u8 {
     }
 }
 
+impl Encode for bool {
+    #[inline]
+    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        u8::from(*self).encode(sink)
+    }
+}
+
+impl<'de> Decode<'de> for bool {
+    #[inline]
+    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+    where
+        S: Source<'de>,
+    {
+        match u8::decode(source)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(DecodingError::UnexpectedVariant {
+                type_name: type_name::<Self>(),
+                found_byte: byte,
+            }
+            .into()),
+        }
+    }
+}
+
 /// Implements `Encode` and `Decode` traits for specified integral type, using
 /// `varint` (variable length integer) encoding.
 ///
@@ -252,15 +364,18 @@ impl<'de: 'a, 'a, const N: usize> Decode<'de> for &'a [u8; N] {
     }
 }
 
-impl Encode for &[u8] {
+impl<T> Encode for &[T]
+where
+    T: Encode,
+{
     #[inline]
     fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
     where
         S: Sink,
     {
-        // Encode the length first, then the payload.
+        // Encode the length first, then the elements.
         self.len().encode(sink)?;
-        sink.sink(self)
+        T::encode_slice(self, sink)
     }
 }
 
@@ -276,6 +391,109 @@ impl<'de: 'a, 'a> Decode<'de> for &'a [u8] {
     }
 }
 
+impl<T> Encode for Vec<T>
+where
+    T: Encode,
+{
+    #[inline]
+    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        self.as_slice().encode(sink)
+    }
+}
+
+impl<'de, T> Decode<'de> for Vec<T>
+where
+    T: Decode<'de>,
+{
+    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+    where
+        S: Source<'de>,
+    {
+        // Decode the length first, then the elements. The length is not trusted
+        // for the capacity, as it may be corrupted.
+        let len = usize::decode(source)?;
+        let mut vec = Vec::with_capacity(len.min(MAX_PREALLOCATED_LEN));
+        for _ in 0..len {
+            vec.push(T::decode(source)?);
+        }
+        Ok(vec)
+    }
+}
+
+/// The maximum number of elements that are allocated ahead of decoding them.
+const MAX_PREALLOCATED_LEN: usize = 1024;
+
+/// The map is encoded as the length followed by the key-value pairs, in the order
+/// of the keys.
+impl<K, V> Encode for BTreeMap<K, V>
+where
+    K: Encode,
+    V: Encode,
+{
+    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        self.len().encode(sink)?;
+        self.iter().try_for_each(|(key, value)| {
+            key.encode(sink)?;
+            value.encode(sink)
+        })
+    }
+}
+
+impl<'de, K, V> Decode<'de> for BTreeMap<K, V>
+where
+    K: Decode<'de> + Ord,
+    V: Decode<'de>,
+{
+    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+    where
+        S: Source<'de>,
+    {
+        let len = usize::decode(source)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let key = K::decode(source)?;
+            map.insert(key, V::decode(source)?);
+        }
+        Ok(map)
+    }
+}
+
+/// The pair is encoded as its elements in order.
+impl<A, B> Encode for (A, B)
+where
+    A: Encode,
+    B: Encode,
+{
+    #[inline]
+    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        self.0.encode(sink)?;
+        self.1.encode(sink)
+    }
+}
+
+impl<'de, A, B> Decode<'de> for (A, B)
+where
+    A: Decode<'de>,
+    B: Decode<'de>,
+{
+    #[inline]
+    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+    where
+        S: Source<'de>,
+    {
+        Ok((A::decode(source)?, B::decode(source)?))
+    }
+}
+
 // `&[u8]` is also a `Source`.
 impl<'a> Source<'a> for &'a [u8] {
     type Error = DecodingError;
@@ -289,6 +507,11 @@ impl<'a> Source<'a> for &'a [u8] {
             Err(DecodingError::UnexpectedEnd { extra_len: len - self.len() })
         }
     }
+
+    #[inline]
+    fn is_exhausted(&self) -> bool {
+        self.is_empty()
+    }
 }
 
 impl Encode for &str {
@@ -416,6 +639,10 @@ impl<'de> Decode<'de> for DateTime {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
+    use pinenut_derive::{Decode, Encode};
+
     use crate::{
         codec::{Decode, DecodingError, Encode},
         DateTime,
@@ -466,6 +693,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_collections() {
+        // The bytes are encoded as the other slices.
+        assert_eq!(test_coding!(&[u8], &[0x01, 0x02]), [0x02, 0x01, 0x02]);
+        assert_eq!(test_coding!(Vec<u8>, vec![0x01, 0x02]), [0x02, 0x01, 0x02]);
+        assert_eq!(test_coding!(Vec<u32>, vec![]), [0x00]);
+        assert_eq!(test_coding!(Vec<u32>, vec![0x7F, 0x80]), [0x02, 0x7F, 0x80, 0x01]);
+        assert_eq!(test_coding!(Vec<&str>, vec!["a", ""]), [0x02, 0x01, 0x61, 0x00]);
+
+        let map = BTreeMap::from([("b", Some(2u32)), ("a", None)]);
+        assert_eq!(
+            test_coding!(BTreeMap<&str, Option<u32>>, map.clone()),
+            [0x02, 0x01, 0x61, 0x00, 0x01, 0x62, 0x01, 0x02]
+        );
+
+        // The corrupted length doesn't allocate ahead.
+        let mut source = [0xFF, 0xFF, 0xFF, 0xFF, 0x0F].as_slice();
+        assert!(matches!(
+            Vec::<u64>::decode(&mut source),
+            Err(DecodingError::UnexpectedEnd { .. })
+        ));
+    }
+
+    #[test]
+    fn test_field_attributes() {
+        #[derive(Encode, Decode, PartialEq, Debug)]
+        struct Value<'a> {
+            name: &'a str,
+            #[codec(skip)]
+            cache: Option<u32>,
+            #[codec(default)]
+            count: u32,
+        }
+
+        let value = Value { name: "a", cache: Some(1), count: 2 };
+        let mut sink = Vec::new();
+        value.encode(&mut sink).unwrap();
+        assert_eq!(sink, [0x01, 0x61, 0x02]);
+        let decoded = Value::decode(&mut sink.as_slice()).unwrap();
+        assert_eq!(decoded, Value { cache: None, ..value });
+
+        // The value encoded before the trailing field was appended.
+        let decoded = Value::decode(&mut [0x01, 0x61].as_slice()).unwrap();
+        assert_eq!(decoded, Value { name: "a", cache: None, count: 0 });
+    }
+
     #[test]
     fn test_datetime() {
         let datetime = chrono::Utc::now();
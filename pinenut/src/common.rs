@@ -7,11 +7,37 @@ use std::{
     ptr, slice,
 };
 
+/// The space (in bytes) available to unprivileged users on the file system
+/// containing the path.
+///
+/// If the path doesn't exist yet, its nearest existing ancestor is used.
+#[cfg(unix)]
+pub(crate) fn available_space(path: &Path) -> Result<u64, io::Error> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = path.ancestors().find(|p| p.exists()).unwrap_or(path);
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: The path is a valid C string, and the stat is written by the call.
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        stat.assume_init()
+    };
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn available_space(_: &Path) -> Result<u64, io::Error> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
 /// Represents a target for processed data.
 ///
 /// For the sake of generality, generics are used to define the type of errors that
 /// can be occurred during a series of processing steps.
-pub(crate) trait Sink<E> {
+pub trait Sink<E> {
     /// Type of errors that can be occurred by self.
     type Error: From<E>;
 
@@ -114,6 +140,12 @@ impl BytesBuf {
         self.0.len()
     }
 
+    #[inline]
+    pub(crate) fn as_buffer_slice(&self) -> &[u8] {
+        // SAFETY: Here the length is guaranteed to be correct.
+        unsafe { slice::from_raw_parts(self.0.as_ptr(), self.0.capacity()) }
+    }
+
     #[inline]
     pub(crate) fn as_buffer_mut_slice(&mut self) -> &mut [u8] {
         // SAFETY: Here the length is guaranteed to be correct.
@@ -217,6 +249,17 @@ pub trait Sealed {}
 
 impl<T> Sealed for Option<T> where T: Sealed {}
 
+/// Strips the prefix from the path if it ends at a component boundary, and the
+/// separators following it, e.g., `/build/app` is stripped from
+/// `/build/app/src/main.rs` but not from `/build/application/src/main.rs`.
+pub(crate) fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    const SEPARATORS: [char; 2] = ['/', '\\'];
+    let rest = path.strip_prefix(prefix)?;
+    let is_boundary =
+        rest.is_empty() || rest.starts_with(SEPARATORS) || prefix.ends_with(SEPARATORS);
+    is_boundary.then(|| rest.trim_start_matches(SEPARATORS))
+}
+
 /// Decodes the hex string to bytes slice.
 #[allow(dead_code)] // Maybe we'll use it later...
 pub(crate) fn decode_hex(str: &str) -> Option<Vec<u8>> {
@@ -234,7 +277,7 @@ pub(crate) fn decode_hex(str: &str) -> Option<Vec<u8>> {
 mod tests {
     use zstd_safe::WriteBuf;
 
-    use crate::common::BytesBuf;
+    use crate::common::{strip_path_prefix, BytesBuf};
 
     #[test]
     fn test_bytesbuf() {
@@ -249,4 +292,14 @@ mod tests {
         assert_eq!(buffer.buffer(&[1, 2, 3, 4, 5, 6]), 3);
         assert_eq!(buffer.as_slice(), &[4, 1, 2, 3]);
     }
+
+    #[test]
+    fn test_strip_path_prefix() {
+        assert_eq!(strip_path_prefix("/build/app/src/main.rs", "/build/app"), Some("src/main.rs"));
+        assert_eq!(strip_path_prefix("/build/app/src/main.rs", "/build/app/"), Some("src/main.rs"));
+        assert_eq!(strip_path_prefix("C:\\app\\main.rs", "C:\\app"), Some("main.rs"));
+        assert_eq!(strip_path_prefix("/build/app", "/build/app"), Some(""));
+        assert_eq!(strip_path_prefix("/build/application/main.rs", "/build/app"), None);
+        assert_eq!(strip_path_prefix("/elsewhere/main.rs", "/build/app"), None);
+    }
 }
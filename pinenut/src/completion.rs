@@ -0,0 +1,90 @@
+//! A runtime-agnostic future for waiting on the logger's asynchronous operations.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+/// Creates a pair of [`Notifier`] and [`Completion`].
+#[inline]
+pub(crate) fn channel() -> (Notifier, Completion) {
+    let state = Arc::new(Mutex::new(State { completed: false, waker: None }));
+    (Notifier(Arc::clone(&state)), Completion(vec![state]))
+}
+
+struct State {
+    completed: bool,
+    waker: Option<Waker>,
+}
+
+/// Completes the paired [`Completion`] when it is dropped.
+///
+/// Being notified on drop ensures that the future never hangs, even if the
+/// notifier is discarded (e.g., the IO runloop has stopped).
+pub(crate) struct Notifier(Arc<Mutex<State>>);
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        state.completed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A future that resolves when the paired [`Notifier`] is dropped, or all of the
+/// notifiers if it is joined (see [`Completion::join`]).
+///
+/// It doesn't depend on any async runtime.
+pub(crate) struct Completion(Vec<Arc<Mutex<State>>>);
+
+impl Future for Completion {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut is_ready = true;
+        for state in &self.0 {
+            let mut state = state.lock().unwrap();
+            if !state.completed {
+                state.waker = Some(cx.waker().clone());
+                is_ready = false;
+            }
+        }
+        if is_ready {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Completion {
+    /// Joins the completion, the joined one resolves once both of them resolve.
+    #[inline]
+    pub(crate) fn join(mut self, other: Completion) -> Completion {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Blocks the current thread until it resolves.
+    pub(crate) fn wait(self) {
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut context = Context::from_waker(&waker);
+        let mut completion = self;
+        while Pin::new(&mut completion).poll(&mut context).is_pending() {
+            thread::park();
+        }
+    }
+}
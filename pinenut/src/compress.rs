@@ -1,5 +1,7 @@
 //! Compression & Decompression.
 
+use std::{ops::RangeInclusive, time::Duration};
+
 use thiserror::Error;
 
 use crate::Sealed;
@@ -38,6 +40,112 @@ pub(crate) trait Compressor: Sealed {
     fn compress<S>(&mut self, operation: CompressOp, sink: &mut S) -> Result<(), S::Error>
     where
         S: Sink;
+
+    /// Sets the compression level, which takes effect from the next frame.
+    fn set_level(&mut self, level: i32) -> Result<(), Error>;
+}
+
+/// The adaptive compression level, which is stepped down or up within the bounds
+/// to keep the logging latency under the target, see
+/// [`Config::compression_level_auto`].
+///
+/// The time spent processing (mostly compressing) the records is measured for
+/// each chunk. When the chunk is rotated, the level is stepped down if the average
+/// latency of a record exceeds the target, or stepped up if it is below half of
+/// the target.
+///
+/// [`Config::compression_level_auto`]: crate::Config::compression_level_auto
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AdaptiveCompression {
+    levels: RangeInclusive<i32>,
+    target: Duration,
+}
+
+impl AdaptiveCompression {
+    /// Constructs a new `AdaptiveCompression` with the bounds of the levels and the
+    /// target latency of logging a record.
+    #[inline]
+    pub fn new(levels: RangeInclusive<i32>, target: Duration) -> Self {
+        Self { levels, target }
+    }
+}
+
+/// The advanced parameters of `zstd`, see [`Config::zstd_params`].
+///
+/// They are beneficial to large volumes of logs (e.g., on the desktop), where the
+/// chunks are big enough for the long range matching.
+///
+/// [`Config::zstd_params`]: crate::Config::zstd_params
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct ZstdParams {
+    /// The maximum back-reference distance as a power of two, from `10` to `30`.
+    /// The decompression of the chunks needs at least as much memory.
+    ///
+    /// `None` means that it is decided by the compression level.
+    pub window_log: Option<u32>,
+    /// Whether or not to enable the long distance matching, which improves the
+    /// compression ratio of the long repetitive records, at the cost of memory.
+    pub enable_ldm: bool,
+    /// The number of the threads compressing in the background, which only takes
+    /// effect with the `zstdmt` feature enabled.
+    ///
+    /// `None` means compressing on the logging thread.
+    pub workers: Option<u32>,
+}
+
+/// Tunes the compression level with the measured latencies, see
+/// [`AdaptiveCompression`].
+pub(crate) struct LevelTuner {
+    config: AdaptiveCompression,
+    level: i32,
+    elapsed: Duration,
+    records: u32,
+}
+
+impl LevelTuner {
+    /// Constructs a new `LevelTuner` starting at the level, which is clamped into
+    /// the bounds.
+    #[inline]
+    pub(crate) fn new(config: AdaptiveCompression, level: i32) -> Self {
+        let level = level.clamp(*config.levels.start(), *config.levels.end());
+        Self { config, level, elapsed: Duration::ZERO, records: 0 }
+    }
+
+    /// The current compression level.
+    #[inline]
+    pub(crate) fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Records the time spent processing a record.
+    #[inline]
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        self.elapsed += elapsed;
+        self.records += 1;
+    }
+
+    /// Steps the level by the average latency of the records since the last
+    /// tuning, returns the new level if it is changed.
+    pub(crate) fn tune(&mut self) -> Option<i32> {
+        if self.records == 0 {
+            return None;
+        }
+        let latency = self.elapsed / self.records;
+        (self.elapsed, self.records) = (Duration::ZERO, 0);
+
+        let (min, max) = (*self.config.levels.start(), *self.config.levels.end());
+        let level = if latency > self.config.target {
+            (self.level - 1).max(min)
+        } else if latency < self.config.target / 2 {
+            (self.level + 1).min(max)
+        } else {
+            self.level
+        };
+        (level != self.level).then(|| {
+            self.level = level;
+            level
+        })
+    }
 }
 
 /// Represents a decompressor that decompresses data to its target (`Sink`).
@@ -47,19 +155,24 @@ pub(crate) trait Decompressor: Sealed {
         S: Sink;
 }
 
-pub(crate) use zstd::{Compressor as ZstdCompressor, Decompressor as ZstdDecompressor};
+pub(crate) use zstd::{
+    Compressor as ZstdCompressor, DecompressReader as ZstdDecompressReader,
+    Decompressor as ZstdDecompressor,
+};
 
 /// `Comporessor` and `Decompressor` for the `Zstandard` compression algorithm.
 pub(crate) mod zstd {
+    use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
     use zstd_safe::{
         get_error_name, max_c_level, min_c_level, zstd_sys::ZSTD_EndDirective, CCtx, CParameter,
-        DCtx, ErrorCode, InBuffer, OutBuffer,
+        DCtx, DParameter, ErrorCode, InBuffer, OutBuffer,
     };
 
     use crate::{
         compress::{
             CompressOp, Compressor as CompressorTrait, Decompressor as DecompressorTrait, Error,
-            Sink,
+            Sink, ZstdParams,
         },
         Sealed,
     };
@@ -72,6 +185,12 @@ pub(crate) mod zstd {
         }
     }
 
+    /// The maximum window log supported on both 32-bit and 64-bit platforms.
+    const WINDOW_LOG_MAX: u32 = 30;
+
+    /// The minimum window log.
+    const WINDOW_LOG_MIN: u32 = 10;
+
     impl From<CompressOp<'_>> for ZSTD_EndDirective {
         #[inline]
         fn from(value: CompressOp) -> Self {
@@ -93,13 +212,18 @@ pub(crate) mod zstd {
         /// The default compression level for `Pinenut`.
         pub(crate) const DEFAULT_LEVEL: i32 = 10;
 
+        /// The default compression level for the records stored in chunks of their
+        /// own, they are rare and usually big, so a higher level is used.
+        pub(crate) const ISOLATED_LEVEL: i32 = 19;
+
         /// Length of `output buffer`.
         ///
         /// An output buffer of 256 bytes should be sufficient for compression of a
         /// log.
         const BUFFER_LEN: usize = 256;
 
-        /// Constructs a new `Compressor` with compression level.
+        /// Constructs a new `Compressor` with compression level and the advanced
+        /// parameters.
         ///
         /// `zstd` supports compression levels from 1 up to 22, it also offers
         /// negative compression levels, which extend the range of speed vs.
@@ -108,11 +232,23 @@ pub(crate) mod zstd {
         /// As the `std`'s documentation says: The lower the level, the faster the
         /// speed (at the cost of compression).
         #[allow(clippy::uninit_vec)]
-        pub(crate) fn new(level: i32) -> Result<Self, Error> {
+        pub(crate) fn new(level: i32, params: &ZstdParams) -> Result<Self, Error> {
             let mut context = CCtx::create();
             let level = level.min(max_c_level()).max(min_c_level());
             context.set_parameter(CParameter::CompressionLevel(level))?;
 
+            if let Some(window_log) = params.window_log {
+                let window_log = window_log.clamp(WINDOW_LOG_MIN, WINDOW_LOG_MAX);
+                context.set_parameter(CParameter::WindowLog(window_log))?;
+            }
+            if params.enable_ldm {
+                context.set_parameter(CParameter::EnableLongDistanceMatching(true))?;
+            }
+            #[cfg(feature = "zstdmt")]
+            if let Some(workers) = params.workers {
+                context.set_parameter(CParameter::NbWorkers(workers))?;
+            }
+
             let mut output_buffer = Vec::with_capacity(Self::BUFFER_LEN);
             // SAFETY: Here the length is guaranteed to be correct.
             unsafe {
@@ -156,6 +292,13 @@ pub(crate) mod zstd {
                 }
             }
         }
+
+        #[inline]
+        fn set_level(&mut self, level: i32) -> Result<(), Error> {
+            let level = level.min(max_c_level()).max(min_c_level());
+            self.context.set_parameter(CParameter::CompressionLevel(level))?;
+            Ok(())
+        }
     }
 
     impl Sealed for Compressor {}
@@ -173,16 +316,25 @@ pub(crate) mod zstd {
         const BUFFER_LEN: usize = 1024;
 
         /// Constructs a new `Decompressor`.
+        ///
+        /// It accepts the frames of any window log that the compressor may produce,
+        /// see [`ZstdParams::window_log`].
         #[inline]
-        #[allow(clippy::uninit_vec)]
         pub(crate) fn new() -> Decompressor {
+            Self::with_max_window_log(None)
+        }
+
+        /// Constructs a new `Decompressor`, which only accepts the frames of the
+        /// window log up to the maximum one (if any).
+        #[inline]
+        #[allow(clippy::uninit_vec)]
+        pub(crate) fn with_max_window_log(max_window_log: Option<u32>) -> Decompressor {
             let mut output_buffer = Vec::with_capacity(Self::BUFFER_LEN);
             // SAFETY: Here the length is guaranteed to be correct.
             unsafe {
                 output_buffer.set_len(output_buffer.capacity());
             }
-
-            Self { context: DCtx::create(), output_buffer }
+            Self { context: decompression_context(max_window_log), output_buffer }
         }
     }
 
@@ -214,6 +366,80 @@ pub(crate) mod zstd {
     }
 
     impl Sealed for Decompressor {}
+
+    /// Creates the decompression context, which accepts the frames of the window
+    /// log up to the maximum one, or of any window log that the compressor may
+    /// produce.
+    fn decompression_context(max_window_log: Option<u32>) -> DCtx<'static> {
+        let mut context = DCtx::create();
+        let window_log = max_window_log.map_or(WINDOW_LOG_MAX, |log| log.min(WINDOW_LOG_MAX));
+        // It hardly fails as the value is clamped to the supported range.
+        _ = context.set_parameter(DParameter::WindowLogMax(window_log.max(WINDOW_LOG_MIN)));
+        context
+    }
+
+    /// The reader that decompresses the `Zstandard` frames read from the inner
+    /// reader as it is read, so the decompressed bytes are never held in memory
+    /// entirely.
+    ///
+    /// It is only sought forwards (by reading through), as the frames can't be
+    /// sought.
+    pub(crate) struct DecompressReader<R> {
+        inner: R,
+        context: DCtx<'static>,
+        /// The position in the decompressed bytes.
+        position: u64,
+    }
+
+    impl<R> DecompressReader<R> {
+        /// Constructs a new `DecompressReader`, which only accepts the frames of the
+        /// window log up to the maximum one (if any).
+        #[inline]
+        pub(crate) fn new(inner: R, max_window_log: Option<u32>) -> Self {
+            Self { inner, context: decompression_context(max_window_log), position: 0 }
+        }
+    }
+
+    impl<R: BufRead> Read for DecompressReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                let input = self.inner.fill_buf()?;
+                // The truncated frame ends here as well, it is reported by the reader of
+                // the decompressed bytes.
+                if input.is_empty() || buf.is_empty() {
+                    return Ok(0);
+                }
+                let mut input = InBuffer::around(input);
+                let mut output = OutBuffer::around(&mut *buf);
+                self.context.decompress_stream(&mut output, &mut input).map_err(|code| {
+                    io::Error::new(io::ErrorKind::InvalidData, Error::from(code))
+                })?;
+                let (consumed, len) = (input.pos, output.pos());
+                self.inner.consume(consumed);
+                if len > 0 {
+                    self.position += len as u64;
+                    return Ok(len);
+                }
+            }
+        }
+    }
+
+    impl<R: BufRead> Seek for DecompressReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            let target = match pos {
+                SeekFrom::Start(target) => Some(target),
+                SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+                SeekFrom::End(_) => None,
+            };
+            let Some(len) = target.and_then(|target| target.checked_sub(self.position)) else {
+                let message = "the Zstandard frames are only sought forwards";
+                return Err(io::Error::new(io::ErrorKind::Unsupported, message));
+            };
+            // Stops at the end of the frames if the target is beyond it.
+            io::copy(&mut self.by_ref().take(len), &mut io::sink())?;
+            Ok(self.position)
+        }
+    }
 }
 
 impl<T> Compressor for Option<T>
@@ -234,6 +460,11 @@ where
             },
         }
     }
+
+    #[inline]
+    fn set_level(&mut self, level: i32) -> Result<(), Error> {
+        self.as_mut().map_or(Ok(()), |compressor| compressor.set_level(level))
+    }
 }
 
 impl<T> Decompressor for Option<T>
@@ -255,12 +486,19 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::slice;
+    use std::{
+        io::{BufReader, Read, Seek, SeekFrom},
+        slice,
+        time::Duration,
+    };
 
-    use crate::compress::{CompressOp, Compressor, Decompressor, ZstdCompressor, ZstdDecompressor};
+    use crate::compress::{
+        AdaptiveCompression, CompressOp, Compressor, Decompressor, LevelTuner, ZstdCompressor,
+        ZstdDecompressReader, ZstdDecompressor, ZstdParams,
+    };
 
-    fn zstd_compress(input: &[u8]) -> Vec<u8> {
-        let mut compressor = ZstdCompressor::new(3).unwrap();
+    fn zstd_compress(input: &[u8], params: &ZstdParams) -> Vec<u8> {
+        let mut compressor = ZstdCompressor::new(3, params).unwrap();
         let mut sink = Vec::new();
         compressor.compress(CompressOp::Input(input), &mut sink).unwrap();
         compressor.compress(CompressOp::End, &mut sink).unwrap();
@@ -268,7 +506,7 @@ mod tests {
     }
 
     fn zstd_compress_mul(input: &[u8]) -> Vec<u8> {
-        let mut compressor = ZstdCompressor::new(3).unwrap();
+        let mut compressor = ZstdCompressor::new(3, &ZstdParams::default()).unwrap();
         let mut sink = Vec::new();
         for byte in input {
             compressor.compress(CompressOp::Input(slice::from_ref(byte)), &mut sink).unwrap();
@@ -298,10 +536,67 @@ mod tests {
     #[test]
     fn test_zstd() {
         let data = b"Hello, I'm Tangent, nice to meet you.";
-        assert_eq!(zstd_decompress(&zstd_compress(data)), data);
+        let params = ZstdParams::default();
+        assert_eq!(zstd_decompress(&zstd_compress(data, &params)), data);
         assert_eq!(zstd_decompress(&zstd_compress_mul(data)), data);
 
         // Empty data.
-        assert_eq!(zstd_decompress(&zstd_compress(&[])), &[]);
+        assert_eq!(zstd_decompress(&zstd_compress(&[], &params)), &[]);
+
+        // Advanced parameters, the window log is beyond the default limit of the
+        // decompression.
+        let params = ZstdParams { window_log: Some(30), enable_ldm: true, workers: Some(2) };
+        let data = data.repeat(1024);
+        assert_eq!(zstd_decompress(&zstd_compress(&data, &params)), data);
+    }
+
+    #[test]
+    fn test_zstd_reader() {
+        let data = b"Hello, I'm Tangent, nice to meet you.".repeat(64);
+        let compressed = zstd_compress(&data, &ZstdParams::default());
+        // The inner reader is read in small pieces.
+        let mut reader =
+            ZstdDecompressReader::new(BufReader::with_capacity(3, &compressed[..]), None);
+
+        let mut buf = [0; 5];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[..5]);
+        assert_eq!(reader.seek(SeekFrom::Current(100)).unwrap(), 105);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[105..110]);
+        // It can't be sought backwards.
+        assert!(reader.seek(SeekFrom::Start(0)).is_err());
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, data[110..]);
+        assert_eq!(reader.stream_position().unwrap(), data.len() as u64);
+
+        // The invalid frame.
+        let mut reader = ZstdDecompressReader::new(&b"invalid frame"[..], None);
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_level_tuner() {
+        let config = AdaptiveCompression::new(1..=5, Duration::from_micros(100));
+        let mut tuner = LevelTuner::new(config, 10);
+        assert_eq!(tuner.level(), 5);
+        assert_eq!(tuner.tune(), None);
+
+        // Too slow.
+        tuner.record(Duration::from_micros(150));
+        tuner.record(Duration::from_micros(250));
+        assert_eq!(tuner.tune(), Some(4));
+
+        // Within the target.
+        tuner.record(Duration::from_micros(80));
+        assert_eq!(tuner.tune(), None);
+
+        // Fast enough.
+        tuner.record(Duration::from_micros(10));
+        assert_eq!(tuner.tune(), Some(5));
+        tuner.record(Duration::from_micros(10));
+        assert_eq!(tuner.tune(), None);
     }
 }
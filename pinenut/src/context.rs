@@ -0,0 +1,106 @@
+//! The context of the records (a.k.a. MDC, Mapped Diagnostic Context), which is
+//! stamped onto the records logged on the current thread, e.g., to correlate them
+//! to the requests or sessions.
+//!
+//! ```
+//! let _guard = pinenut_log::context::push("request_id", "42");
+//! // The records logged by `Logger::log` on this thread carry `request_id=42`,
+//! // until the guard is dropped.
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+};
+
+use crate::Record;
+
+thread_local! {
+    /// The key-value pairs of the current thread, in the order they are pushed.
+    static CONTEXT: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// Whether a record stamped on the current thread is being logged, e.g., the
+    /// record sink may forward it to another logger, which must not stamp it again.
+    static STAMPING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Pushes the key-value pair onto the context of the current thread, which is
+/// stamped onto the records logged on it (see [`Record::context`]).
+///
+/// The pair is popped when the returned guard is dropped, along with the pairs
+/// pushed after it. The pushed key doesn't replace the same key pushed earlier,
+/// both are stamped and the later one comes later.
+#[inline]
+pub fn push(key: impl Into<String>, value: impl Into<String>) -> ContextGuard {
+    CONTEXT.with_borrow_mut(|context| {
+        let guard = ContextGuard { len: context.len(), _not_send: PhantomData };
+        context.push((key.into(), value.into()));
+        guard
+    })
+}
+
+/// Clears the context of the current thread, e.g., before a pooled thread runs
+/// an unrelated task.
+#[inline]
+pub fn clear() {
+    CONTEXT.with_borrow_mut(Vec::clear);
+}
+
+/// Returns the key-value pairs of the context of the current thread.
+#[inline]
+pub fn current() -> Vec<(String, String)> {
+    CONTEXT.with_borrow(Clone::clone)
+}
+
+/// The guard that pops the pair pushed by [`push`] when dropped.
+///
+/// It must be dropped on the thread that pushed the pair.
+#[must_use = "the pair is popped when the guard is dropped"]
+pub struct ContextGuard {
+    /// The length of the context before the pair was pushed.
+    len: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    #[inline]
+    fn drop(&mut self) {
+        // The context may be being torn down along with the thread.
+        _ = CONTEXT.try_with(|context| context.borrow_mut().truncate(self.len));
+    }
+}
+
+/// Calls the closure with the record stamped with the context of the current
+/// thread, which is prepended to the context of the record itself.
+///
+/// The record is passed as is if the context is empty, so that it is not
+/// allocated, or if it is logged while logging a stamped record, which already
+/// carries the context.
+///
+/// The context is copied out before calling the closure, so the closure (e.g., a
+/// record sink or a stage) may push or clear the context.
+pub(crate) fn stamp<R>(record: &Record, f: impl FnOnce(&Record) -> R) -> R {
+    if STAMPING.get() {
+        return f(record);
+    }
+    let context = CONTEXT.with_borrow(|context| (!context.is_empty()).then(|| context.clone()));
+    let Some(context) = context else {
+        return f(record);
+    };
+
+    let mut stamped = record.clone();
+    let pairs = context.iter().map(|(key, value)| (key.as_str(), value.as_str()));
+    stamped.set_context(pairs.chain(record.context().iter().copied()).collect());
+
+    // Resets the flag even if the closure panics.
+    struct Stamping;
+    impl Drop for Stamping {
+        #[inline]
+        fn drop(&mut self) {
+            STAMPING.set(false);
+        }
+    }
+    STAMPING.set(true);
+    let _stamping = Stamping;
+    f(&stamped)
+}
@@ -0,0 +1,137 @@
+//! The log file that is written in place through memory mapping.
+
+use std::{fs, io::Error, mem};
+
+use crate::{chunk::Chunk, logfile::Logfile, mmap::Mmap};
+
+/// The storage of the chunks in the direct mode, see [`Config::direct_mmap`].
+///
+/// A window at the position of the active log file is mapped, the chunk being
+/// logged to lives in the window, so the chunks are compressed and encrypted
+/// directly into the log file, and committing a chunk just advances the position.
+///
+/// [`Config::direct_mmap`]: crate::Config::direct_mmap
+pub(crate) struct Direct {
+    /// The window is declared before the logfile, so that it is unmapped before the
+    /// unwritten space of the logfile is released.
+    window: Option<Window>,
+    logfile: Logfile,
+    /// The capacity of the chunk being logged to.
+    chunk_len: usize,
+    /// The chunk is staged here while the logfile can't be mapped (e.g., the disk
+    /// is full), and is written to the logfile on commit.
+    spare: Vec<u8>,
+}
+
+/// The mapped region of the logfile.
+struct Window {
+    mmap: Mmap,
+    /// The offset of the start of the mapping in the logfile.
+    offset: u64,
+}
+
+impl Direct {
+    /// Constructs a new `Direct` on the logfile, the logfile must be in write mode.
+    pub(crate) fn new(logfile: Logfile, chunk_len: usize) -> Result<Self, Error> {
+        let mut direct = Self { window: None, logfile, chunk_len, spare: Vec::new() };
+        direct.reserve(chunk_len)?;
+        Ok(direct)
+    }
+
+    #[inline]
+    pub(crate) fn logfile(&self) -> &Logfile {
+        &self.logfile
+    }
+
+    /// Extracts a mutable slice of the chunk being logged to.
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+        let position = self.logfile.position();
+        match &mut self.window {
+            Some(window) => {
+                let start = (position - window.offset) as usize;
+                &mut window.mmap[start..start + self.chunk_len]
+            }
+            None => {
+                self.spare.resize(self.chunk_len, 0);
+                &mut self.spare
+            }
+        }
+    }
+
+    /// Commits the chunk being logged to, which has been finalized in place.
+    ///
+    /// The empty chunk is not committed, it is overwritten by the next one.
+    pub(crate) fn commit(&mut self) -> Result<(), Error> {
+        let committed = {
+            let mut chunk = Chunk::bind(self.as_mut_slice());
+            chunk.set_writeback(false);
+            (chunk.payload_len() > 0).then(|| (chunk.len(), chunk.end_datetime()))
+        };
+
+        if let Some((len, end)) = committed {
+            match &self.window {
+                Some(window) => {
+                    window.mmap.flush_async()?;
+                    self.logfile.advance(len)?;
+                }
+                None => self.logfile.write(&self.spare[..len])?,
+            }
+            self.logfile.add_end(end);
+        }
+        self.reserve(self.chunk_len)
+    }
+
+    /// Appends the finalized chunk to the logfile.
+    ///
+    /// The chunk being logged to is overwritten, so it must be empty, and it needs
+    /// to be initialized again afterwards.
+    pub(crate) fn append(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        self.reserve(chunk.len())?;
+        let position = self.logfile.position();
+        if let Some(window) = &mut self.window {
+            let start = (position - window.offset) as usize;
+            window.mmap[start..start + chunk.len()].copy_from_slice(chunk);
+            window.mmap.flush_async()?;
+        }
+        self.logfile.advance(chunk.len())?;
+        self.logfile.add_end(Chunk::bind(chunk).end_datetime());
+        self.reserve(self.chunk_len)
+    }
+
+    /// Rotates to the new logfile, the chunk being logged to is discarded and needs
+    /// to be initialized again.
+    ///
+    /// The previous logfile is deleted if nothing has been written to it.
+    pub(crate) fn rotate(&mut self, logfile: Logfile) -> Result<(), Error> {
+        self.window = None;
+        let previous = mem::replace(&mut self.logfile, logfile);
+        if previous.position() == 0 {
+            previous.delete()?;
+        }
+        self.reserve(self.chunk_len)
+    }
+
+    /// Ensures that the window covers the `len` bytes after the position, it is
+    /// remapped with the space of another chunk ahead if it doesn't.
+    fn reserve(&mut self, len: usize) -> Result<(), Error> {
+        let end = self.logfile.position() + len as u64;
+        if self.window.as_ref().is_some_and(|w| w.offset + w.mmap.len() as u64 >= end) {
+            return Ok(());
+        }
+
+        self.window = None;
+        let (mmap, offset) = self.logfile.map(len + self.chunk_len)?;
+        self.window = Some(Window { mmap, offset });
+        Ok(())
+    }
+}
+
+impl Drop for Direct {
+    fn drop(&mut self) {
+        // The logfile is mapped in advance, so it is removed if nothing has been written
+        // to it, as the buffered mode never creates it.
+        if self.logfile.position() == 0 {
+            _ = fs::remove_file(self.logfile.path());
+        }
+    }
+}
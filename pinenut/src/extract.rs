@@ -1,17 +1,36 @@
 use std::{
+    collections::HashMap,
+    fmt, fs,
+    fs::File,
     io,
-    io::{BufReader, BufWriter, Read, Seek, Write},
-    ops::RangeInclusive,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    mem,
+    ops::{Range, RangeInclusive},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 use thiserror::Error;
 
-use crate::{chunk, common, common::LazyFileWriter, logfile, logfile::Logfile, DateTime, Domain};
+use crate::{
+    chunk,
+    chunk::{Extensions, Header},
+    common,
+    common::LazyFileWriter,
+    compress::{CompressOp, Compressor, ZstdCompressor},
+    logfile,
+    logfile::{Logfile, MinuteIndex},
+    storage::FileSystem,
+    CompressionError, DateTime, Domain, Query, SharedStorage, TimeDimension, ZstdParams,
+    FILE_EXTENSION,
+};
 
 /// Errors that can be occurred during the log extraction process ([`extract`]).
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] io::Error),
@@ -21,26 +40,339 @@ pub enum Error {
     FileIncomplete(PathBuf),
     #[error("logs in the specified time range were not found")]
     NotFound,
+    #[error(transparent)]
+    Compression(#[from] CompressionError),
+    #[error("the extraction has been cancelled")]
+    Cancelled,
+}
+
+/// The callback of the extraction progress, see [`ExtractOptions::on_progress`].
+pub type ProgressCallback = Arc<dyn Fn(ExtractionProgress) + Send + Sync>;
+
+/// The progress of the log extraction, see [`ExtractOptions::on_progress`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExtractionProgress {
+    /// The length (in bytes) of the log files that have been scanned, i.e., the
+    /// chunks skipped and the ones extracted so far.
+    pub scanned_bytes: u64,
+    /// The total length (in bytes) of the log files to be scanned.
+    pub total_bytes: u64,
+}
+
+/// The token to cancel the log extraction, see [`ExtractOptions::cancel_token`].
+///
+/// The clones of the token share the same state, so the extraction can be
+/// cancelled from another thread (e.g., when the connectivity drops).
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Constructs a new `CancelToken`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the extraction, which fails with [`Error::Cancelled`] before the
+    /// next chunk is extracted. The destination file (or the segments) written
+    /// partially is deleted.
+    #[inline]
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Options of the log extraction, see [`extract_with_options`].
+#[derive(Default, Clone)]
+pub struct ExtractOptions {
+    strict: bool,
+    storage: Option<SharedStorage>,
+    query: Option<Query>,
+    zstd_frame: Option<i32>,
+    on_progress: Option<ProgressCallback>,
+    cancel_token: Option<CancelToken>,
+}
+
+impl ExtractOptions {
+    /// Constructs a new `ExtractOptions`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Whether or not to extract the complete minutes only.
+    ///
+    /// When enabled, the chunks that end in the current minute (which may still be
+    /// logged to) are excluded, and so is the chunk at the end of a log file that
+    /// is still being written, instead of failing with
+    /// [`Error::FileIncomplete`]. It suits the automated periodic exporters, which
+    /// should never upload half-written chunks.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn strict(mut self, flag: bool) -> Self {
+        self.strict = flag;
+        self
+    }
+
+    /// The storage of the log files, which must be the one of the logger (see
+    /// [`crate::Config::storage`]).
+    ///
+    /// The default value is `None`, which means the log files are stored in the
+    /// file system.
+    #[inline]
+    pub fn storage(mut self, storage: Option<SharedStorage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// The query that the extracted chunks may have records matching, the chunks
+    /// are skipped by their indexes (see [`crate::Config::chunk_index`]) without
+    /// being decrypted. The chunks without indexes are always extracted.
+    ///
+    /// Only the levels, the tags and the time range of the query are checked,
+    /// so the extracted chunks may still contain records not matching it.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn query(mut self, query: Option<Query>) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// The compression level to wrap the extracted chunks in a standard `zstd`
+    /// frame at, so that the destination file is a single self-describing artifact
+    /// that the backend can checksum and store as is.
+    ///
+    /// The frame is unwrapped by `zstd -d`, and [`parse`] unwraps it transparently
+    /// as it is read. It only applies to [`extract_report`] and
+    /// [`extract_with_options`].
+    ///
+    /// The default value is `None`, which means the chunks are written as they are.
+    ///
+    /// [`parse`]: crate::parse
+    #[inline]
+    pub fn zstd_frame(mut self, level: Option<i32>) -> Self {
+        self.zstd_frame = level;
+        self
+    }
+
+    /// The callback of the extraction progress, which is called on the extracting
+    /// thread as the chunks are extracted, e.g., to show the progress of an upload.
+    /// It only applies to [`extract_report`], [`extract_with_options`] and
+    /// [`extract_segmented`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn on_progress(mut self, callback: Option<ProgressCallback>) -> Self {
+        self.on_progress = callback;
+        self
+    }
+
+    /// The token to cancel the extraction, see [`CancelToken`]. It only applies to
+    /// [`extract_report`], [`extract_with_options`] and [`extract_segmented`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn cancel_token(mut self, token: Option<CancelToken>) -> Self {
+        self.cancel_token = token;
+        self
+    }
+}
+
+impl fmt::Debug for ExtractOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractOptions")
+            .field("strict", &self.strict)
+            .field("storage", &self.storage.is_some())
+            .field("query", &self.query)
+            .field("zstd_frame", &self.zstd_frame)
+            .field("on_progress", &self.on_progress.is_some())
+            .field("cancel_token", &self.cancel_token)
+            .finish()
+    }
 }
 
 /// Extracts the logs for the specified time range and writes them to the destination
 /// file.
 ///
+/// The chunks overlapping the time range are extracted as a whole, so the time
+/// range has the granularity of the chunk rotation (see
+/// [`crate::Config::rotation`]), which is a minute by default.
+///
 /// Errors may be occurred during log writing, and the destination file may have been
 /// created by then. The caller is responsible for managing the destination file
 /// (e.g., deleting it) afterwards.
+#[inline]
 pub fn extract(
     domain: Domain,
     time_range: RangeInclusive<DateTime>,
     dest_path: impl AsRef<Path>,
 ) -> Result<(), Error> {
-    let dest_path = dest_path.as_ref();
-    let mut writer = BufWriter::new(LazyFileWriter::new(dest_path));
+    extract_with_options(domain, time_range, dest_path, ExtractOptions::default())
+}
+
+/// Extracts the logs for the specified time range with options, and writes them to
+/// the destination file.
+///
+/// See [`extract`] for the details.
+#[inline]
+pub fn extract_with_options(
+    domain: Domain,
+    time_range: RangeInclusive<DateTime>,
+    dest_path: impl AsRef<Path>,
+    options: ExtractOptions,
+) -> Result<(), Error> {
+    extract_report(domain, time_range, dest_path, options)?.found().map(|_| ())
+}
 
-    for mut logfile in logfiles(domain, &time_range)? {
-        let mut reader = BufReader::new(logfile.open()?);
-        extract_chunks(&mut reader, &mut writer, &time_range)
-            .map_err(|err| Error::from_chunk_error(err, logfile.path()))?;
+/// Extracts the logs for the specified time range with options, writes them to the
+/// destination file, and reports what has been extracted.
+///
+/// Unlike [`extract_with_options`], it doesn't fail with [`Error::NotFound`] if no
+/// logs were found, the report just has no chunks, and the destination file is not
+/// created. So that the upload pipelines can tell the empty time ranges from the
+/// errors, and log what they actually shipped.
+///
+/// See [`extract`] for the details.
+pub fn extract_report(
+    domain: Domain,
+    time_range: RangeInclusive<DateTime>,
+    dest_path: impl AsRef<Path>,
+    options: ExtractOptions,
+) -> Result<ExtractionReport, Error> {
+    let mut writer = BufWriter::new(LazyFileWriter::new(dest_path.as_ref()));
+    let result = match options.zstd_frame {
+        Some(level) => ZstdFrame::new(level, &mut writer).and_then(|mut frame| {
+            let report = extract_range(domain, time_range, options, &mut frame)?;
+            // The destination file is not created if no logs were found.
+            if report.chunks > 0 {
+                frame.finish()?;
+            }
+            Ok(report)
+        }),
+        None => extract_range(domain, time_range, options, &mut writer),
+    };
+
+    // The destination file written partially is deleted once cancelled, so that it
+    // is not mistaken for the complete one.
+    if let Err(Error::Cancelled) = result {
+        let (file, _) = writer.into_parts();
+        let is_created = !file.is_empty();
+        drop(file);
+        if is_created {
+            fs::remove_file(dest_path)?;
+        }
+        return result;
+    }
+    let report = result?;
+    writer.into_inner().map_err(|err| err.into_error())?;
+    Ok(report)
+}
+
+/// Extracts the logs for the specified time range with options, and writes them to
+/// the segments in the destination directory, each of which is at most
+/// `max_segment_len` bytes long.
+///
+/// The logs are split at the chunk boundaries, so each segment is a log file that
+/// can be uploaded and parsed on its own, e.g., for the upload endpoints capping
+/// the request bodies. The segments are named `{identifier}-segment{number}.pine`
+/// with the numbers from `1`, which are never mistaken for the log files (e.g., if
+/// the destination directory is the one of the domain), and a chunk longer than the
+/// maximum length is written to a segment of its own. The options apply except
+/// [`ExtractOptions::zstd_frame`].
+///
+/// Returns the paths of the segments in order.
+///
+/// Errors may be occurred during log writing, and some segments may have been
+/// created by then. The caller is responsible for managing the segments (e.g.,
+/// deleting them) afterwards, except that they are deleted once the extraction is
+/// cancelled.
+pub fn extract_segmented(
+    domain: Domain,
+    time_range: RangeInclusive<DateTime>,
+    dest_dir: impl AsRef<Path>,
+    max_segment_len: u64,
+    options: ExtractOptions,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut segments = Segments {
+        directory: dest_dir.as_ref(),
+        identifier: domain.identifier.clone(),
+        max_len: max_segment_len,
+        current: None,
+        current_len: 0,
+        paths: Vec::new(),
+    };
+    let result = extract_range(domain, time_range, options, &mut segments);
+
+    // The segments written partially are deleted once cancelled, so that they are not
+    // mistaken for the complete ones.
+    if let Err(Error::Cancelled) = result {
+        segments.delete()?;
+        return Err(Error::Cancelled);
+    }
+    result?.found()?;
+    segments.finish()
+}
+
+/// Extracts the logs for the specified time range from multiple domains (e.g., the
+/// main app, its extensions and background services), and writes them to a single
+/// destination file.
+///
+/// The chunks from the domains are interleaved by their start datetimes. The
+/// domains share the options, of which the strict mode, the storage and the query
+/// apply.
+///
+/// Errors may be occurred during log writing, and the destination file may have been
+/// created by then. The caller is responsible for managing the destination file
+/// (e.g., deleting it) afterwards.
+pub fn extract_many(
+    domains: &[Domain],
+    time_range: RangeInclusive<DateTime>,
+    dest_path: impl AsRef<Path>,
+    options: ExtractOptions,
+) -> Result<(), Error> {
+    let cutoff = options.strict.then(strict_cutoff);
+    let mut filter = query_filter(range_filter(&time_range, cutoff), options.query.as_ref());
+    let storage = options.storage.clone().unwrap_or_else(FileSystem::shared);
+
+    let mut selected = Vec::new();
+    for domain in domains {
+        let storage = Arc::clone(&storage);
+        selected.extend(logfiles(domain.clone(), &time_range, storage)?);
+    }
+    let paths: Vec<_> = selected.iter().map(Logfile::path).collect();
+    // The files of the custom storages without the random access are read as a whole.
+    let mut readers = selected.iter_mut().map(Logfile::reader).collect::<Result<Vec<_>, _>>()?;
+
+    // Locates the chunks to be extracted in all domains first.
+    let mut located = Vec::new();
+    for (file, reader) in readers.iter_mut().enumerate() {
+        let limit = options.strict.then(|| reader.len()).transpose()?;
+        seek_to(reader, Some(*time_range.start()))?;
+        let mut reader = BufReader::new(reader);
+        locate_chunks(&mut reader, limit, &mut filter, &Monitor::default(), file, &mut located)
+            .map_err(|err| Error::from_chunk_error(err, paths[file].clone()))?;
+    }
+
+    // The sort is stable, so the chunks with the same start datetime remain in the
+    // order of the domains.
+    located.sort_by_key(|chunk| chunk.start);
+
+    let mut writer = BufWriter::new(LazyFileWriter::new(dest_path.as_ref()));
+    for chunk in located {
+        let reader = &mut readers[chunk.file];
+        let len = chunk.range.end - chunk.range.start;
+        reader.seek(SeekFrom::Start(chunk.range.start))?;
+        if io::copy(&mut reader.take(len), &mut writer)? < len {
+            return Err(Error::FileIncomplete(paths[chunk.file].clone()));
+        }
     }
 
     if writer.into_inner().map_err(|err| err.into_error())?.is_empty() {
@@ -50,63 +382,535 @@ pub fn extract(
     }
 }
 
+/// Extracts the logs produced in the specified logger session (e.g., an app
+/// launch) and writes them to the destination file.
+///
+/// All log files in the domain are scanned, because a session may span any length of
+/// time. See [`crate::Config::session_id`].
+///
+/// Of the options, the strict mode, the storage and the query apply.
+///
+/// Errors may be occurred during log writing, and the destination file may have been
+/// created by then. The caller is responsible for managing the destination file
+/// (e.g., deleting it) afterwards.
+pub fn extract_session(
+    domain: Domain,
+    session_id: u64,
+    dest_path: impl AsRef<Path>,
+    options: ExtractOptions,
+) -> Result<(), Error> {
+    let storage = options.storage.clone().unwrap_or_else(FileSystem::shared);
+    let mut logfiles =
+        Logfile::logfiles(&Arc::new(domain), logfile::Mode::Read, storage)?.collect::<Vec<_>>();
+    logfiles.sort_by_key(|f| f.datetime());
+
+    let mut writer = BufWriter::new(LazyFileWriter::new(dest_path.as_ref()));
+    let monitor = Monitor::default();
+    let session_filter = |header: &Header, _: Extensions| {
+        if header.session_id() == Some(session_id) {
+            Filter::Copy
+        } else {
+            Filter::Skip
+        }
+    };
+    let filter = query_filter(session_filter, options.query.as_ref());
+    let report = extract_logfiles(logfiles, &mut writer, None, options.strict, monitor, filter)?;
+    writer.into_inner().map_err(|err| err.into_error())?;
+    report.found().map(|_| ())
+}
+
+/// The report of the log extraction, see [`extract_report`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtractionReport {
+    /// The number of log files that have been scanned.
+    pub files: usize,
+    /// The number of chunks that have been extracted.
+    pub chunks: usize,
+    /// The total length (in bytes) of the extracted chunks, i.e., the length of the
+    /// destination file.
+    pub bytes: u64,
+    /// The time range covered by the extracted chunks, from the earliest start to
+    /// the latest end of them. `None` if no chunks were extracted.
+    pub time_range_covered: Option<RangeInclusive<DateTime>>,
+}
+
+impl ExtractionReport {
+    /// Adds the extracted chunk to the report.
+    fn add(&mut self, chunk: &LocatedChunk) {
+        let (start, end) = (chunk.start, chunk.end);
+        self.chunks += 1;
+        self.bytes += chunk.range.end - chunk.range.start;
+        self.time_range_covered = Some(match self.time_range_covered.take() {
+            Some(range) => start.min(*range.start())..=end.max(*range.end()),
+            None => start..=end,
+        });
+    }
+
+    /// Fails with [`Error::NotFound`] if no chunks were extracted.
+    #[inline]
+    fn found(self) -> Result<Self, Error> {
+        if self.chunks == 0 {
+            Err(Error::NotFound)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
 // ============ Internal ============
 
-fn extract_chunks<R, W>(
-    reader: &mut R,
-    writer: &mut W,
-    time_range: &RangeInclusive<DateTime>,
-) -> Result<(), chunk::ReadError>
+/// Extracts the logs for the specified time range with options, and writes them to
+/// the destination.
+fn extract_range(
+    domain: Domain,
+    time_range: RangeInclusive<DateTime>,
+    options: ExtractOptions,
+    writer: &mut impl Destination,
+) -> Result<ExtractionReport, Error> {
+    let cutoff = options.strict.then(strict_cutoff);
+
+    let storage = options.storage.clone().unwrap_or_else(FileSystem::shared);
+    let logfiles = logfiles(domain, &time_range, storage)?;
+    let filter = range_filter(&time_range, cutoff);
+    let start = Some(*time_range.start());
+    let monitor = Monitor {
+        on_progress: options.on_progress.as_ref(),
+        cancel_token: options.cancel_token.as_ref(),
+        ..Default::default()
+    };
+    let filter = query_filter(filter, options.query.as_ref());
+    extract_logfiles(logfiles, writer, start, options.strict, monitor, filter)
+}
+
+/// The destination that the extracted chunks are written to.
+trait Destination: Write {
+    /// It is called before the chunk of the length is written.
+    #[inline]
+    fn begin_chunk(&mut self, _len: u64) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W> Destination for BufWriter<W> where W: Write {}
+
+/// The destination wrapping the chunks in a `zstd` frame, see
+/// [`ExtractOptions::zstd_frame`].
+struct ZstdFrame<W> {
+    compressor: ZstdCompressor,
+    inner: W,
+}
+
+impl<W> ZstdFrame<W>
 where
-    R: Read + Seek,
     W: Write,
 {
-    let mut reader = chunk::Reader::new(reader);
-    while let Some(header) = reader.read_header_or_reach_to_end()? {
-        if header.time_range().start().gt(time_range.end()) {
+    #[inline]
+    fn new(level: i32, inner: W) -> Result<Self, Error> {
+        Ok(Self { compressor: ZstdCompressor::new(level, &ZstdParams::default())?, inner })
+    }
+
+    /// Ends the frame.
+    #[inline]
+    fn finish(mut self) -> Result<(), Error> {
+        self.compress(CompressOp::End)
+    }
+
+    fn compress(&mut self, operation: CompressOp) -> Result<(), Error> {
+        let inner = &mut self.inner;
+        let mut sink =
+            common::FnSink::new(|bytes: &[u8]| inner.write_all(bytes).map_err(Error::from));
+        self.compressor.compress(operation, &mut sink)
+    }
+}
+
+impl<W> Destination for ZstdFrame<W> where W: Write {}
+
+impl<W> Write for ZstdFrame<W>
+where
+    W: Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.compress(CompressOp::Input(buf)).map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The destination splitting the chunks into the segments, see
+/// [`extract_segmented`].
+struct Segments<'a> {
+    directory: &'a Path,
+    identifier: String,
+    max_len: u64,
+    current: Option<BufWriter<File>>,
+    /// The length of the current segment.
+    current_len: u64,
+    /// The paths of the segments that have been created.
+    paths: Vec<PathBuf>,
+}
+
+impl Segments<'_> {
+    /// Flushes the last segment, and returns the paths of the segments.
+    fn finish(self) -> Result<Vec<PathBuf>, Error> {
+        if let Some(mut current) = self.current {
+            current.flush()?;
+        }
+        Ok(self.paths)
+    }
+
+    /// Deletes the segments that have been created.
+    fn delete(self) -> Result<(), Error> {
+        drop(self.current);
+        for path in &self.paths {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Destination for Segments<'_> {
+    fn begin_chunk(&mut self, len: u64) -> io::Result<()> {
+        if self.current.is_some() && self.current_len + len <= self.max_len {
             return Ok(());
         }
+        // Starts a new segment, as the chunk doesn't fit in the current one.
+        if let Some(mut current) = self.current.take() {
+            current.flush()?;
+        }
+        fs::create_dir_all(self.directory)?;
+        let number = self.paths.len() + 1;
+        let name = format!("{}-segment{}.{}", self.identifier, number, FILE_EXTENSION);
+        let path = self.directory.join(name);
+        self.current = Some(BufWriter::new(File::create(&path)?));
+        self.current_len = 0;
+        self.paths.push(path);
+        Ok(())
+    }
+}
 
-        let payload_len = header.payload_len();
+impl Write for Segments<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let current = self.current.as_mut().ok_or(io::ErrorKind::NotConnected)?;
+        let len = current.write(buf)?;
+        self.current_len += len as u64;
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.current.as_mut().map_or(Ok(()), |current| current.flush())
+    }
+}
 
-        if header.time_range().end().lt(time_range.start()) {
-            reader.skip(payload_len)?;
-            continue;
+/// Filters the chunks in the specified time range, and ending before the cutoff (if
+/// any).
+///
+/// The chunks of a log file are in order, even if the clock has jumped backwards
+/// (see [`Logfile::open_for_chunk`]), so the rest of the log file is skipped after a
+/// chunk starting after the range.
+fn range_filter(
+    time_range: &RangeInclusive<DateTime>,
+    cutoff: Option<DateTime>,
+) -> impl FnMut(&Header, Extensions) -> Filter + '_ {
+    move |header, _| {
+        if header.time_range().start().gt(time_range.end()) {
+            Filter::Stop
+        } else if header.time_range().end().lt(time_range.start())
+            || cutoff.is_some_and(|cutoff| header.time_range().end() >= cutoff)
+        {
+            Filter::Skip
+        } else {
+            Filter::Copy
         }
+    }
+}
 
-        // Write header.
-        let header_bytes = header.clone().bytes();
-        writer.write_all(header_bytes.as_ref())?;
+/// Skips the chunks passing the filter that have no records matching the query (if
+/// any) by their indexes, see [`ExtractOptions::query`].
+fn query_filter<'a>(
+    mut filter: impl FnMut(&Header, Extensions) -> Filter + 'a,
+    query: Option<&'a Query>,
+) -> impl FnMut(&Header, Extensions) -> Filter + 'a {
+    move |header, extensions| match (filter(header, extensions), query) {
+        (Filter::Copy, Some(query)) if !query.may_match_chunk(header, extensions) => Filter::Skip,
+        (filter, _) => filter,
+    }
+}
+
+/// Represents the location of a chunk in a log file.
+struct LocatedChunk {
+    start: DateTime,
+    end: DateTime,
+    /// The logger instance that wrote the chunk and the sequence number of the chunk
+    /// in it, see [`Extensions::SEQUENCE`].
+    sequence: Option<(u64, u64)>,
+    /// The index of the log file.
+    file: usize,
+    /// The byte range of the entire chunk in the log file.
+    range: Range<u64>,
+}
 
-        type FnSink<F> = common::FnSink<F, chunk::ReadError>;
+/// Orders the located chunks of each logger instance by their sequence numbers, so
+/// that they are extracted in the order they were written, even if their log files
+/// are out of order after the clock jumped backwards (see
+/// [`Logfile::open_for_chunk`]).
+///
+/// The instances remain in the order of their first chunks, even if they share the
+/// session id (see [`crate::Config::session_id`]), and the chunks without sequence
+/// numbers (e.g., of the older format versions) remain in place.
+fn order_by_sequence(located: &mut Vec<LocatedChunk>) {
+    let mut instances = HashMap::new();
+    let mut keyed: Vec<_> = mem::take(located)
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| match chunk.sequence {
+            Some((instance, sequence)) => {
+                ((*instances.entry(instance).or_insert(index), sequence), chunk)
+            }
+            None => ((index, 0), chunk),
+        })
+        .collect();
+    keyed.sort_by_key(|(key, _)| *key);
+    located.extend(keyed.into_iter().map(|(_, chunk)| chunk));
+}
+
+/// Monitors the extraction: reports its progress and checks whether it has been
+/// cancelled, see [`ExtractOptions::on_progress`] and
+/// [`ExtractOptions::cancel_token`].
+#[derive(Default)]
+struct Monitor<'a> {
+    on_progress: Option<&'a ProgressCallback>,
+    cancel_token: Option<&'a CancelToken>,
+    /// The total length of the log files that have been scanned.
+    scanned_bytes: u64,
+    total_bytes: u64,
+}
 
-        // Write payload.
-        reader.read_payload(
-            payload_len,
-            &mut FnSink::new(|bytes: &[u8]| writer.write_all(bytes).map_err(Into::into)),
-        )?;
+impl Monitor<'_> {
+    /// Reports the progress.
+    #[inline]
+    fn progress(&self) {
+        if let Some(on_progress) = self.on_progress {
+            let scanned_bytes = self.scanned_bytes.min(self.total_bytes);
+            on_progress(ExtractionProgress { scanned_bytes, total_bytes: self.total_bytes });
+        }
     }
+
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_some_and(CancelToken::is_cancelled)
+    }
+}
+
+/// What to do with a chunk during extraction.
+enum Filter {
+    /// Copies the chunk to the destination.
+    Copy,
+    /// Skips the chunk.
+    Skip,
+    /// Skips the chunk and the rest of the log file.
+    Stop,
+}
+
+/// Extracts the chunks passing the filter from the log files.
+///
+/// The chunks are located in all log files first, then copied in the order they
+/// were written, see [`order_by_sequence`].
+///
+/// If `start` is specified, the chunks ending before it are skipped by the minute
+/// indexes of the log files (see [`seek_to`]). If `strict` is `true`, the
+/// incomplete chunks at the end of the log files (e.g., the ones still being
+/// written) are skipped instead of failing.
+fn extract_logfiles(
+    mut logfiles: Vec<Logfile>,
+    writer: &mut impl Destination,
+    start: Option<DateTime>,
+    strict: bool,
+    mut monitor: Monitor,
+    mut filter: impl FnMut(&Header, Extensions) -> Filter,
+) -> Result<ExtractionReport, Error> {
+    let mut report = ExtractionReport::default();
+    if monitor.on_progress.is_some() {
+        monitor.total_bytes = logfiles.iter().map(Logfile::len).sum::<Result<u64, _>>()?;
+    }
+
+    let paths: Vec<_> = logfiles.iter().map(Logfile::path).collect();
+    let mut readers = logfiles.iter_mut().map(Logfile::reader).collect::<Result<Vec<_>, _>>()?;
+    let mut located = Vec::new();
+    for (file, reader) in readers.iter_mut().enumerate() {
+        // The snapshot of the file length, the bytes appended afterwards are ignored.
+        let limit = strict.then(|| reader.len()).transpose()?;
+        seek_to(reader, start)?;
+        let mut reader = BufReader::new(reader);
+        report.files += 1;
+        locate_chunks(&mut reader, limit, &mut filter, &monitor, file, &mut located)
+            .map_err(|err| Error::from_chunk_error(err, paths[file].clone()))?;
+        if monitor.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+    }
+
+    // The bytes of the chunks not extracted have been scanned by now, and the rest
+    // are scanned as the chunks are copied.
+    let located_bytes: u64 = located.iter().map(|chunk| chunk.range.end - chunk.range.start).sum();
+    monitor.scanned_bytes = monitor.total_bytes.saturating_sub(located_bytes);
+    order_by_sequence(&mut located);
+    for chunk in located {
+        if monitor.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        let reader = &mut readers[chunk.file];
+        let len = chunk.range.end - chunk.range.start;
+        reader.seek(SeekFrom::Start(chunk.range.start))?;
+        writer.begin_chunk(len)?;
+        if io::copy(&mut reader.take(len), writer)? < len {
+            return Err(Error::FileIncomplete(paths[chunk.file].clone()));
+        }
+        report.add(&chunk);
+        monitor.scanned_bytes += len;
+        monitor.progress();
+    }
+
+    Ok(report)
+}
+
+/// Locates the chunks passing the filter in the reader of the log file, without
+/// reading their payloads, they are added to `located`.
+///
+/// If `limit` is specified, the chunk exceeding it is treated as the end of the
+/// reader, as it is incomplete. It returns early if the extraction has been
+/// cancelled.
+fn locate_chunks<R>(
+    reader: &mut R,
+    limit: Option<u64>,
+    filter: &mut impl FnMut(&Header, Extensions) -> Filter,
+    monitor: &Monitor,
+    file: usize,
+    located: &mut Vec<LocatedChunk>,
+) -> Result<(), chunk::ReadError>
+where
+    R: Read + Seek,
+{
+    let mut reader = chunk::Reader::new(reader);
+    loop {
+        if monitor.is_cancelled() {
+            return Ok(());
+        }
+        let start = reader.stream_position()?;
+        let header = match reader.read_header_or_reach_to_end() {
+            Ok(Some(header)) => header,
+            Ok(None) => return Ok(()),
+            Err(chunk::ReadError::UnexpectedEnd) if limit.is_some() => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let header = header.clone();
+        let payload_len = header.payload_len();
+        let extensions = reader.extensions();
+        // The footer of the log file is never extracted.
+        let filter = match extensions.is_footer() {
+            true => Filter::Skip,
+            false => filter(&header, extensions),
+        };
+        let sequence = extensions.sequence();
+        let end = reader.stream_position()? + payload_len as u64;
+        if limit.is_some_and(|limit| end > limit) {
+            return Ok(());
+        }
+
+        match filter {
+            Filter::Copy => located.push(LocatedChunk {
+                start: header.time_range().start(),
+                end: header.time_range().end(),
+                sequence,
+                file,
+                range: start..end,
+            }),
+            Filter::Skip => {}
+            Filter::Stop => return Ok(()),
+        }
+        reader.skip(payload_len)?;
+    }
+}
+
+/// Seeks the reader of a log file to the first chunk that may end at or after the
+/// datetime, by the minute index of the log file (see
+/// [`crate::Config::minute_index`]). The reader is rewound to the start of the file
+/// if there is no datetime or no index.
+fn seek_to<R>(reader: &mut R, datetime: Option<DateTime>) -> Result<(), io::Error>
+where
+    R: Read + Seek,
+{
+    let offset = match datetime {
+        Some(datetime) => MinuteIndex::read(reader)?.map_or(0, |index| index.offset(datetime)),
+        None => 0,
+    };
+    reader.seek(SeekFrom::Start(offset))?;
     Ok(())
 }
 
-fn logfiles(domain: Domain, time_range: &RangeInclusive<DateTime>) -> Result<Vec<Logfile>, Error> {
+/// The start of the current minute, the chunks ending from then on are excluded in
+/// strict mode, see [`ExtractOptions::strict`].
+#[inline]
+fn strict_cutoff() -> DateTime {
+    TimeDimension::Minute.truncate(chrono::Utc::now())
+}
+
+/// Selects the log files that may have the chunks in the time range, in the order
+/// of their datetimes.
+///
+/// The chunks of a log file may end long after its datetime, but they are always in
+/// the same file dimension as it (see [`TimeDimension::file_dimension`]), so the
+/// log files before the time range are selected by their periods.
+fn logfiles(
+    domain: Domain,
+    time_range: &RangeInclusive<DateTime>,
+    storage: SharedStorage,
+) -> Result<Vec<Logfile>, Error> {
     let mut original =
-        Logfile::logfiles(&Arc::new(domain), logfile::Mode::Read)?.collect::<Vec<_>>();
+        Logfile::logfiles(&Arc::new(domain), logfile::Mode::Read, storage)?.collect::<Vec<_>>();
     original.sort_by_key(|f| f.datetime());
 
-    let mut logfiles = Vec::new();
+    // The log files are rotated by a day at most, so the ones of the earlier days
+    // end before the time range.
+    let day = TimeDimension::Day.truncate(*time_range.start());
+    let mut logfiles: Vec<(Logfile, Option<DateTime>)> = Vec::new();
 
-    for logfile in original {
-        if logfile.datetime().ge(time_range.end()) {
+    for mut logfile in original.into_iter().filter(|f| f.datetime().ge(&day)) {
+        if logfile.datetime().gt(time_range.end()) {
             break;
         }
-        if logfile.datetime().le(time_range.start()) && !logfiles.is_empty() {
-            logfiles.clear();
+        // Only the periods of the log files before the time range matter.
+        let mut period_end = None;
+        if logfile.datetime().le(time_range.start()) {
+            logfiles.retain(|(_, end)| end.is_some_and(|end| end.ge(time_range.start())));
+            period_end = Some(period_end_of(&mut logfile)?);
         }
-        logfiles.push(logfile);
+        logfiles.push((logfile, period_end));
     }
 
-    Ok(logfiles)
+    Ok(logfiles.into_iter().map(|(logfile, _)| logfile).collect())
+}
+
+/// The end of the period of the log file, i.e., of the file dimension of its
+/// datetime, by the rotation recorded in its first chunk (see
+/// [`Extensions::ROTATION`]).
+fn period_end_of(logfile: &mut Logfile) -> Result<DateTime, Error> {
+    let datetime = logfile.datetime();
+    let mut reader = chunk::Reader::new(logfile.reader()?);
+    // The damaged log file is reported as it is extracted.
+    let rotation = match reader.read_header_or_reach_to_end() {
+        Ok(Some(_)) => reader.extensions().get(Extensions::ROTATION),
+        Ok(None) | Err(_) => None,
+    };
+    let rotation = rotation
+        .and_then(|rotation| TimeDimension::try_from(*rotation.first()?).ok())
+        .unwrap_or(TimeDimension::Minute);
+    Ok(rotation.file_dimension().end_of(datetime))
 }
 
 impl Error {
@@ -0,0 +1,54 @@
+//! The entry points of the fuzz targets (see `fuzz/`), which feed the arbitrary
+//! bytes to the internals of the parsing. They are not a part of the public API.
+
+use std::io::Cursor;
+
+use crate::{chunk, common::FnSink, SecretKey};
+
+/// Decodes the records from the decompressed bytes of a chunk in the format
+/// version, which arrive in the pieces of the length. Returns how many records
+/// are decoded.
+pub fn decode_records(bytes: &[u8], version: u16, piece_len: usize) -> usize {
+    let mut count = 0;
+    _ = crate::parse::decode_records(bytes, version, piece_len, |_| {
+        count += 1;
+        Ok(())
+    });
+    count
+}
+
+/// Reads the chunks from the bytes of a log file, without processing their
+/// payloads. Returns how many chunks are read.
+pub fn read_chunks(bytes: &[u8]) -> usize {
+    let mut reader = chunk::Reader::new(Cursor::new(bytes));
+    let mut count = 0;
+    while let Ok(Some(header)) = reader.read_header_or_reach_to_end() {
+        let header = header.clone();
+        let extensions = reader.extensions();
+        _ = (header.time_range().start(), header.time_range().end(), header.session_id());
+        _ = extensions.iter().count();
+        let checksum_value = extensions.checksum().map(<[u8]>::to_vec);
+        let mut checksum = chunk::Checksum::new(&header, extensions);
+
+        let mut sink = FnSink::<_, chunk::ReadError>::new(|bytes: &[u8]| {
+            checksum.update(bytes);
+            Ok(())
+        });
+        if reader.read_payload(header.payload_len(), &mut sink).is_err() {
+            break;
+        }
+        _ = checksum_value.map(|value| checksum.matches(&value));
+        count += 1;
+    }
+    count
+}
+
+/// Parses the bytes of a log file, returns how many records are parsed.
+pub fn parse(bytes: &[u8], secret_key: Option<SecretKey>) -> usize {
+    let mut count = 0;
+    _ = crate::parse::parse_bytes(bytes, secret_key, |_| {
+        count += 1;
+        Ok(())
+    });
+    count
+}
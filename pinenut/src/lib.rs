@@ -43,6 +43,27 @@
 //! compression and encryption, and we can parse the log files using the parsing
 //! capabilities provided by Pinenut.
 //!
+//! With the `serde` feature, the parsed records ([`Record`] and [`OwnedRecord`])
+//! implement `Serialize` and `Deserialize`, so that they can be converted into JSON,
+//! CBOR, etc. directly. The borrowed [`Record`] can only be deserialized from the
+//! formats whose strings can be borrowed (e.g., the JSON strings without escapes),
+//! otherwise deserialize [`OwnedRecord`] instead.
+//!
+//! With the `codec` feature, the `Encode` and `Decode` traits of [`codec`] are
+//! public, so that the structured data can be encoded into the extension of the
+//! records ([`Record::extension`]) in the same compact binary format as the records
+//! themselves, rather than being serialized into their contents.
+//!
+//! ### WebAssembly
+//!
+//! Pinenut compiles to `wasm32`, where `mmap` is unavailable and the buffer is kept
+//! in memory. Without the thread support, the IO operations are performed on the
+//! logging thread, and [`Logger::shutdown_async`] is unavailable. The log files are
+//! written via `std::fs`, which is only backed by a file system on WASI, elsewhere
+//! a [`Storage`] (e.g., an OPFS or IndexedDB adapter) can be supplied by the host.
+//! On `wasm32-unknown-unknown`, the clock and the random numbers are provided by the
+//! JavaScript host.
+//!
 //! ## Usage
 //!
 //! Pinenut's APIs are generally similar regardless of the language used.
@@ -86,7 +107,8 @@
 //! ### Extraction
 //!
 //! Just call the `extract` method to extract the logs for the specified time range
-//! (with minute granularity) and write them to the destination file.
+//! (the chunks overlapping it are extracted as a whole, see [`Config::rotation`])
+//! and write them to the destination file.
 //!
 //! ```rust,no_run
 //! # use std::ops::Sub;
@@ -146,27 +168,30 @@
 #![feature(let_chains)]
 #![feature(option_take_if)]
 
-use std::path::PathBuf;
+use std::{io, path::PathBuf, sync::Arc};
 
 use base64::{prelude::BASE64_STANDARD, Engine};
-use chrono::Timelike;
+use chrono::{TimeDelta, Timelike};
 
-use crate::compress::ZstdCompressor;
+use crate::{compress::ZstdCompressor, logfile::Logfile};
 
 pub mod record;
 pub use record::*;
 
 pub mod compress;
-pub use compress::{CompressionError, DecompressionError};
+pub use compress::{AdaptiveCompression, CompressionError, DecompressionError, ZstdParams};
 
 pub mod encrypt;
 pub use encrypt::{
-    DecryptionError, EncryptionError, EncryptionKey, PublicKey, SecretKey, PUBLIC_KEY_LEN,
+    DecryptionError, EncryptionError, EncryptionKey, KeyAlgorithm, PublicKey, SecretKey,
+    SigningKey, X25519PublicKey, PUBLIC_KEY_LEN, X25519_PUBLIC_KEY_LEN,
 };
 
 pub mod codec;
 pub use codec::{DecodingError, EncodingError};
 
+pub mod context;
+
 pub mod chunk;
 pub use chunk::Error as ChunkError;
 
@@ -174,18 +199,116 @@ pub mod runloop;
 pub use runloop::Error as RunloopError;
 
 mod logger;
-pub use logger::{Error as LoggerError, Logger};
+pub use logger::{BufferUsage, Error as LoggerError, Health, Logger, StorageKind, TrimReport};
+
+mod multi;
+pub use multi::MultiLogger;
+
+mod memory_logger;
+pub use memory_logger::MemoryLogger;
 
 mod extract;
-pub use extract::{extract, Error as ExtractionError};
+pub use extract::{
+    extract, extract_many, extract_report, extract_segmented, extract_session,
+    extract_with_options, CancelToken, Error as ExtractionError, ExtractOptions,
+    ExtractionProgress, ExtractionReport, ProgressCallback,
+};
 
 mod parse;
-pub use parse::{parse, parse_to_file, DefaultFormatter, Error as ParsingError, Format};
+pub use parse::{
+    inspect, merge, parse, parse_incremental, parse_to_file, parse_to_file_with_options,
+    parse_with_options, ChunkCallback, ChunkInfo, ChunkStats, CsvFormatter, CustomDefaultFormatter,
+    DefaultFormatter, Error as ParsingError, Format, LevelLabels, LogfmtFormatter, ParseLimit,
+    ParseOptions, ProducerCallback, RecordMapper, RotateCallback,
+};
+
+mod salvage;
+pub use salvage::{salvage, Error as SalvageError, SalvageReport};
+
+mod template;
+pub use template::{Error as TemplateError, TemplateFormatter};
+
+mod timezone;
+pub use timezone::Timezone;
+
+mod manifest;
+pub use manifest::{Error as ManifestError, Manifest};
+
+mod query;
+pub use query::{query, query_domain, Query};
+
+mod limit;
+pub use limit::RateLimit;
+
+mod bulk;
+pub use bulk::{BulkConfig, BulkWriter};
+
+mod mirror;
+pub use mirror::Mirror;
+
+mod producer;
+pub use producer::Producer;
+
+mod clock;
+pub use clock::TimestampSource;
+
+mod track;
+pub use track::{Severity, Track, TrackCategory, TrackEvent, Tracker};
+
+mod storage;
+pub use storage::{FileSystem, SharedStorage, Storage};
+
+mod breaker;
+pub use breaker::CircuitBreaker;
+
+mod completion;
+
+mod staging;
+
+mod redact;
+#[cfg(feature = "regex")]
+pub use redact::RegexRedactor;
+pub use redact::{Redact, Redactor};
+
+mod pipeline;
+pub use pipeline::{PipelineStage, Verdict};
+
+pub mod prelude;
+
+mod macros;
 
 mod common;
 use common::*;
 
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+
+/// Implementation details of the exported macros, not public API.
+#[doc(hidden)]
+pub mod __private {
+    /// Makes the path relative to the manifest directory, if it is absolute.
+    #[inline]
+    pub fn prune_path<'a>(path: &'a str, manifest_dir: &str) -> &'a str {
+        crate::common::strip_path_prefix(path, manifest_dir).unwrap_or(path)
+    }
+
+    /// Extracts the path of the enclosing function from the type name of a function
+    /// item defined in it.
+    #[inline]
+    pub fn function_name(name: &'static str) -> &'static str {
+        let mut name = name.strip_suffix("::f").unwrap_or(name);
+        while let Some(stripped) = name.strip_suffix("::{{closure}}") {
+            name = stripped;
+        }
+        name
+    }
+
+    pub use crate::macros::thread_id;
+}
+
 mod buffer;
+mod direct;
 mod logfile;
 mod mmap;
 
@@ -193,17 +316,46 @@ mod mmap;
 ///
 /// The current version of Pinenut will use the `zstd` compression algorithm and
 /// `AES` encryption algorithm to process the logs.
-pub const FORMAT_VERSION: u16 = 1;
+///
+/// Version history:
+///
+/// - `1`: The initial format.
+/// - `2`: Chunk headers carry the logger session id and an extensible TLV
+///   (tag-length-value) extension area. Records are prefixed with their schemas and
+///   the lengths of their fields (see [`Record::SCHEMA`]), and carry the thread
+///   name, the logger session id, the milliseconds elapsed since the logger was
+///   initialized, their sequence numbers in the logger, the count of their
+///   consecutive repetitions, whether their contents are truncated, the opaque
+///   extension and the context.
+pub const FORMAT_VERSION: u16 = 2;
 
 /// The extension of the Pinenut mmap buffer file.
 pub const MMAP_BUFFER_EXTENSION: &str = "pinebuf";
 
+/// The extension of the key file that checks the header of the Pinenut mmap buffer
+/// file for corruption.
+pub const MMAP_BUFFER_KEY_EXTENSION: &str = "pinekey";
+
+/// The extension of the quarantined Pinenut mmap buffer file, which is corrupted.
+pub const QUARANTINED_BUFFER_EXTENSION: &str = "pinebuf-quarantined";
+
 /// The extension of the Pinenut log file.
 pub const FILE_EXTENSION: &str = "pine";
 
 /// The extension of the Pinenut plain log file.
 pub const PLAIN_FILE_EXTENSION: &str = "log";
 
+/// The extension of the file that the chunks written back are quarantined to, see
+/// [`Writeback::Quarantine`].
+pub const RECOVERED_FILE_EXTENSION: &str = "recovered.pine";
+
+/// The extension of the manifest of the domain, see [`Manifest`].
+pub const MANIFEST_EXTENSION: &str = "pinemeta";
+
+/// The suffix of the identifier of the domain that the records split by level are
+/// written to, see [`Config::split_by_level`].
+pub const SPLIT_IDENTIFIER_SUFFIX: &str = "errors";
+
 /// The default buffer length (320 KB) for Pinenut.
 pub const BUFFER_LEN: usize = 320 * 1024;
 
@@ -215,13 +367,65 @@ pub struct Domain {
     pub identifier: String,
     /// Used to specify the directory where the log files for this domian are stored.
     pub directory: PathBuf,
+    /// Used to specify how the log files for this domain are named.
+    pub file_naming: FileNaming,
+    /// Used to specify how the log files for this domain are organized in the
+    /// directory.
+    pub file_layout: FileLayout,
 }
 
 impl Domain {
     /// Constructs a new `Domain`.
     #[inline]
     pub fn new(identifier: String, directory: PathBuf) -> Self {
-        Self { identifier, directory }
+        Self {
+            identifier,
+            directory,
+            file_naming: FileNaming::default(),
+            file_layout: FileLayout::default(),
+        }
+    }
+
+    /// Specifies how the log files are named, see [`FileNaming`].
+    #[inline]
+    pub fn file_naming(mut self, file_naming: FileNaming) -> Self {
+        self.file_naming = file_naming;
+        self
+    }
+
+    /// Specifies how the log files are organized in the directory, see
+    /// [`FileLayout`].
+    #[inline]
+    pub fn file_layout(mut self, file_layout: FileLayout) -> Self {
+        self.file_layout = file_layout;
+        self
+    }
+
+    /// The domain that the records split by level are written to, see
+    /// [`Config::split_by_level`].
+    ///
+    /// Its identifier is suffixed with [`SPLIT_IDENTIFIER_SUFFIX`] (e.g.,
+    /// `MyApp-errors`), so its log files can be extracted on their own.
+    #[inline]
+    pub fn split_domain(&self) -> Self {
+        let identifier = format!("{}-{}", self.identifier, SPLIT_IDENTIFIER_SUFFIX);
+        Self { identifier, ..self.clone() }
+    }
+
+    /// Reads the manifest of the domain, which is written by the logger on
+    /// initialization if [`Config::manifest`] is enabled.
+    #[inline]
+    pub fn manifest(&self) -> Result<Manifest, ManifestError> {
+        Manifest::read(self)
+    }
+
+    /// The paths of the log files of the domain, in chronological order.
+    pub fn logfiles(&self) -> io::Result<Vec<PathBuf>> {
+        let domain = Arc::new(self.clone());
+        let mut logfiles: Vec<_> =
+            Logfile::logfiles(&domain, logfile::Mode::Read, FileSystem::shared())?.collect();
+        logfiles.sort_by_key(|f| f.datetime());
+        Ok(logfiles.iter().map(Logfile::path).collect())
     }
 
     /// Obtains a logger with a specified configuration.
@@ -237,6 +441,44 @@ impl Domain {
     }
 }
 
+/// Represents how the log files are named, see [`Domain::file_naming`].
+///
+/// The log files are recognized by all naming schemes when they are read (e.g.,
+/// extracted or trimmed), so the scheme can be changed without losing the log
+/// files written before.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum FileNaming {
+    /// `{identifier}-{unix timestamp}.pine`, e.g., `MyApp-1384781712.pine`.
+    #[default]
+    Timestamp,
+    /// `{identifier}-{YYYY-MM-DD-HH}.pine` in UTC, e.g., `MyApp-2013-11-18-13.pine`,
+    /// which is readable by humans.
+    ///
+    /// The datetime of the log file is truncated to the hour, so the records of the
+    /// same hour are written to the same log file.
+    Hourly,
+}
+
+/// Represents how the log files are organized in the directory of the domain, see
+/// [`Domain::file_layout`].
+///
+/// Like [`FileNaming`], the log files are recognized in all layouts when they are
+/// read.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum FileLayout {
+    /// All log files are stored in the directory directly.
+    #[default]
+    Flat,
+    /// The log files are stored in the subdirectories by date (in UTC), e.g.,
+    /// `directory/2013/11/18/`.
+    ///
+    /// It keeps the number of files in a directory small for the domains logging
+    /// for months, which matters on some file systems (e.g., FAT).
+    Dated,
+}
+
 /// Represents the dimension of datetime, used for log rotation.
 #[repr(u8)]
 #[non_exhaustive]
@@ -265,34 +507,222 @@ impl TimeDimension {
 
         is_matched
     }
+
+    /// Truncates the datetime to the start of the dimension it is in.
+    fn truncate(self, datetime: DateTime) -> DateTime {
+        let truncated = match self {
+            Self::Day => datetime.with_hour(0).and_then(|d| d.with_minute(0)),
+            Self::Hour => datetime.with_minute(0),
+            Self::Minute => Some(datetime),
+        };
+        truncated
+            .and_then(|d| d.with_second(0))
+            .and_then(|d| d.with_nanosecond(0))
+            .unwrap_or(datetime)
+    }
+
+    /// The dimension of the log file rotation for the chunk rotation of the
+    /// dimension, all chunks of a log file are in the same file dimension as it.
+    #[inline]
+    fn file_dimension(self) -> Self {
+        match self {
+            Self::Minute => Self::Hour,
+            Self::Hour | Self::Day => Self::Day,
+        }
+    }
+
+    /// The end of the dimension the datetime is in, i.e., the last nanosecond of it.
+    fn end_of(self, datetime: DateTime) -> DateTime {
+        let len = match self {
+            Self::Day => TimeDelta::days(1),
+            Self::Hour => TimeDelta::hours(1),
+            Self::Minute => TimeDelta::minutes(1),
+        };
+        self.truncate(datetime)
+            .checked_add_signed(len - TimeDelta::nanoseconds(1))
+            .unwrap_or(datetime)
+    }
+}
+
+impl TryFrom<u8> for TimeDimension {
+    type Error = u8;
+
+    #[inline]
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Day),
+            2 => Ok(Self::Hour),
+            3 => Ok(Self::Minute),
+            _ => Err(value),
+        }
+    }
 }
 
-/// Represents a tracker used to track errors occurred from the logger operations.
-pub trait Track {
-    /// Handles the error on the code location.
-    fn track(&self, error: LoggerError, file: &'static str, line: u32);
+/// Represents a change of the application lifecycle, see [`Logger::hint`].
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Hint {
+    /// The application is about to enter the background, the buffered records are
+    /// written to the log file before returning (see [`Logger::rotate_now`]).
+    EnteringBackground,
+    /// The application has received a memory warning, the buffered records are
+    /// flushed asynchronously, and the staging buffers (see
+    /// [`Config::thread_staging_len`]) are released.
+    LowMemory,
+    /// The application is about to terminate, the buffered records are written to
+    /// the log file before returning.
+    Terminating,
+}
+
+/// Represents what to do when a panic occurs in the IO thread of the logger, see
+/// [`Config::io_panic_policy`].
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum IoPanicPolicy {
+    /// Reports the panic via the tracker, and keeps the IO thread running, the
+    /// current log file is reopened on the next write.
+    Restart,
+    /// Reports the panic via the tracker, then aborts the process.
+    Abort,
+    /// Reports the panic via the tracker, then stops the IO thread. The records
+    /// logged afterwards are still buffered, but no longer written to the log
+    /// files.
+    #[default]
+    Report,
+}
+
+/// Represents what to do with the chunks written back, i.e., the chunks left in the
+/// mmap buffer by the previous process (e.g., it crashed), see
+/// [`Config::writeback`].
+///
+/// The chunk in progress is written back without being finalized, so its payload
+/// may be truncated, which the strict parsers reject.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Writeback {
+    /// Appends the chunks to the log files like the others.
+    #[default]
+    Always,
+    /// Drops the chunks.
+    Skip,
+    /// Appends the chunks to a separate file (`{identifier}.recovered.pine`, see
+    /// [`RECOVERED_FILE_EXTENSION`]) in the domain directory instead of the log
+    /// files, so that the log files are kept clean. It can be parsed like the
+    /// extracted files.
+    Quarantine,
 }
 
-impl<F> Track for F
+/// Represents how a record is compressed, see [`Logger::log_with_hint`].
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum CompressionHint {
+    /// The record is compressed together with the other records in the buffer.
+    ///
+    /// The record that is too large to fit in the buffer is stored as if it were
+    /// [`CompressionHint::Isolated`], instead of being lost.
+    #[default]
+    Shared,
+    /// The record bypasses the buffer and is stored in a chunk of its own, which is
+    /// compressed with the isolated compression level (see
+    /// [`Config::isolated_compression_level`]).
+    ///
+    /// It is intended for occasional big payloads (e.g., giant JSON dumps), which
+    /// would otherwise fill up the buffer and cause frequent rotations for ordinary
+    /// records.
+    Isolated,
+}
+
+/// Represents why the chunk is rotated, see [`Logger::rotate`].
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RotateReason {
+    /// The rotation is initiated by the user (e.g., from a debug menu).
+    UserInitiated = 1,
+    /// The records are about to be extracted and uploaded.
+    PreUpload,
+    /// The application has received a memory warning.
+    MemoryPressure,
+    /// The application is about to enter the background, where it may be
+    /// terminated without notice, see [`Logger::rotate_now`].
+    Background,
+}
+
+impl TryFrom<u8> for RotateReason {
+    type Error = u8;
+
+    #[inline]
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::UserInitiated),
+            2 => Ok(Self::PreUpload),
+            3 => Ok(Self::MemoryPressure),
+            4 => Ok(Self::Background),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Represents a secondary sink that receives the records logged by the logger.
+///
+/// It receives every record that passes the filters of the logger (e.g., rate
+/// limiting), before the record is compressed. It can be used to forward records
+/// to other systems (e.g., crash-reporting breadcrumbs), while Pinenut remains the
+/// durable store.
+pub trait RecordSink {
+    /// Receives the record.
+    fn receive(&self, record: &Record);
+}
+
+impl<F> RecordSink for F
 where
-    F: Fn(LoggerError, &'static str, u32),
+    F: Fn(&Record),
 {
     #[inline]
-    fn track(&self, error: LoggerError, file: &'static str, line: u32) {
-        self(error, file, line)
+    fn receive(&self, record: &Record) {
+        self(record)
     }
 }
 
-/// Trait object type for [`Track`].
-pub type Tracker = Box<dyn Track + Send + Sync>;
-
 /// Configuration of a logger instance.
 pub struct Config {
     use_mmap: bool,
+    direct_mmap: bool,
     buffer_len: usize,
+    thread_staging_len: Option<usize>,
     rotation: TimeDimension,
+    io_panic_policy: IoPanicPolicy,
+    writeback: Writeback,
+    circuit_breaker: Option<CircuitBreaker>,
     key: Option<PublicKey>,
+    key_algorithm: KeyAlgorithm,
+    signing_key: Option<SigningKey>,
+    chunk_index: bool,
     compression_level: i32,
+    compression_level_auto: Option<AdaptiveCompression>,
+    zstd_params: ZstdParams,
+    isolated_compression_level: i32,
+    deduplicate: bool,
+    rate_limit: Option<RateLimit>,
+    session_id: Option<u64>,
+    preallocation: Option<u64>,
+    minute_index: bool,
+    manifest: bool,
+    min_free_space: Option<u64>,
+    trim_on_low_space: bool,
+    timestamp_source: TimestampSource,
+    elapsed_time: bool,
+    record_sequence: bool,
+    app_version: Option<String>,
+    os_version: Option<String>,
+    strip_path_prefix: Option<String>,
+    redactor: Option<Redactor>,
+    stages: Vec<Box<dyn PipelineStage>>,
+    split_levels: Vec<Level>,
+    max_record_len: Option<usize>,
+    mirror: Option<Mirror>,
+    record_sink: Option<Box<dyn RecordSink + Send + Sync>>,
+    storage: Option<SharedStorage>,
     tracker: Option<Tracker>,
 }
 
@@ -310,6 +740,13 @@ impl Config {
     /// then when the logger is restarted, the log data is written back to the log
     /// file, avoiding loss of log data.
     ///
+    /// The header of the mmap buffer file is checked by a MAC keyed by a random key,
+    /// which is stored alongside (see [`MMAP_BUFFER_KEY_EXTENSION`]). It detects the
+    /// corruption (e.g., by the file system or another process), but not deliberate
+    /// tampering, as the key is as accessible as the buffer file. If the check
+    /// fails, the buffer file is moved aside (see [`QUARANTINED_BUFFER_EXTENSION`])
+    /// and a new one is created.
+    ///
     /// It is enabled by default.
     #[inline]
     pub fn use_mmap(mut self, flag: bool) -> Self {
@@ -317,12 +754,35 @@ impl Config {
         self
     }
 
+    /// Whether or not to map the active log file itself and write the chunks in
+    /// place (**experimental**).
+    ///
+    /// The records are compressed and encrypted directly into the mapping of the
+    /// log file, so there is neither the buffer file nor the copy from the buffer to
+    /// the log file, which reduces the overhead for very high-volume loggers. If
+    /// the application terminates unexpectedly, the chunk being written remains in
+    /// the log file and is parsed like a written back one.
+    ///
+    /// The chunks are limited to half of the [`Config::buffer_len`], and
+    /// [`Config::use_mmap`] and [`Config::preallocation`] have no effect. If the log
+    /// file can't be mapped, the logger falls back to the buffer.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn direct_mmap(mut self, flag: bool) -> Self {
+        self.direct_mmap = flag;
+        self
+    }
+
     /// The buffer length.
     ///
     /// If mmap is used, it is rounded up to a multiple of pagesize.
     /// Pinenut uses a double cache system, so the buffer that is actually written
     /// to will be less than half of this.
     ///
+    /// If it is changed, the chunks left in the mmap buffer file are written back
+    /// before the file is resized, so that the buffered records are not lost.
+    ///
     /// The default value is `320 KB`.
     #[inline]
     pub fn buffer_len(mut self, len: usize) -> Self {
@@ -330,7 +790,33 @@ impl Config {
         self
     }
 
-    /// Time granularity of log extraction.
+    /// The length (in bytes) of the per-thread staging buffers.
+    ///
+    /// If it is specified, each thread encodes the records it logs into a staging
+    /// buffer of its own without locking the logger, and the staged records are
+    /// drained into the logger (in the order of their datetimes) once the staging
+    /// buffer of any thread exceeds the length, or the logger is flushed. It removes
+    /// the lock contention of [`Logger::log`] for heavily multi-threaded
+    /// applications.
+    ///
+    /// The staged records are not in the buffer yet, so they are lost if the
+    /// application terminates unexpectedly before they are drained.
+    ///
+    /// The default value is `None`, which means the records are logged directly.
+    #[inline]
+    pub fn thread_staging_len(mut self, len: Option<usize>) -> Self {
+        self.thread_staging_len = len;
+        self
+    }
+
+    /// The time dimension by which the chunks are rotated, which is also the time
+    /// granularity of log extraction, as the chunks overlapping the time range are
+    /// extracted as a whole (e.g., the chunk of `10:00..=10:59` is extracted for
+    /// `10:20..=10:40` with the `Hour` rotation).
+    ///
+    /// The log files are rotated by the next coarser dimension (e.g., hourly for
+    /// the `Minute` rotation). The dimension is recorded in the chunks, so the
+    /// extraction doesn't need to be told about it.
     ///
     /// The default value is `Minute`.
     #[inline]
@@ -339,6 +825,39 @@ impl Config {
         self
     }
 
+    /// What to do when a panic occurs in the IO thread, see [`IoPanicPolicy`].
+    ///
+    /// The default value is `Report`.
+    #[inline]
+    pub fn io_panic_policy(mut self, policy: IoPanicPolicy) -> Self {
+        self.io_panic_policy = policy;
+        self
+    }
+
+    /// What to do with the chunks left in the mmap buffer by the previous process,
+    /// see [`Writeback`].
+    ///
+    /// It has no effect in the direct mode (see [`Config::direct_mmap`]), where the
+    /// chunks are left in the log files in place.
+    ///
+    /// The default value is `Always`.
+    #[inline]
+    pub fn writeback(mut self, writeback: Writeback) -> Self {
+        self.writeback = writeback;
+        self
+    }
+
+    /// The circuit breaker that pauses the log file writes after consecutive
+    /// failures, see [`CircuitBreaker`].
+    ///
+    /// The default value is `Some(CircuitBreaker::default())`. If it is `None`, the
+    /// chunks that fail to be written are dropped.
+    #[inline]
+    pub fn circuit_breaker(mut self, circuit_breaker: Option<CircuitBreaker>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
     /// The encryption key, the public key in ECDH.
     ///
     /// It is used to negotiate the key for symmetric encryption of the log.
@@ -348,19 +867,65 @@ impl Config {
     #[inline]
     pub fn key(mut self, key: Option<PublicKey>) -> Self {
         self.key = key;
+        self.key_algorithm = KeyAlgorithm::P256;
+        self
+    }
+
+    /// The encryption key, the public key in X25519 (see [`gen_x25519_key_pair`]),
+    /// which replaces the one set by [`Config::key`].
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`gen_x25519_key_pair`]: crate::encrypt::gen_x25519_key_pair
+    #[inline]
+    pub fn x25519_key(mut self, key: Option<X25519PublicKey>) -> Self {
+        self.key = key.as_ref().map(encrypt::x25519::embed);
+        self.key_algorithm = KeyAlgorithm::X25519;
         self
     }
 
     /// The encryption key, the public key in ECDH, represented in `Base64`.
     ///
     /// It is used to negotiate the key for symmetric encryption of the log.
-    /// If the value is `None` or invalid, there is no encryption.
+    /// If the value is `None` or invalid, there is no encryption. The key is of
+    /// either algorithm, see [`KeyAlgorithm::of_public_key`].
     ///
     /// The default value is `None`.
     #[inline]
     pub fn key_str(self, key: Option<impl AsRef<[u8]>>) -> Self {
-        let key = key.and_then(|k| BASE64_STANDARD.decode(k).ok()).and_then(|k| k.try_into().ok());
-        self.key(key)
+        let key = key.and_then(|k| BASE64_STANDARD.decode(k).ok()).unwrap_or_default();
+        match KeyAlgorithm::of_public_key(&key) {
+            Some(KeyAlgorithm::X25519) => self.x25519_key(key.try_into().ok()),
+            _ => self.key(key.try_into().ok()),
+        }
+    }
+
+    /// The signing key of the chunks, see [`SigningKey`].
+    ///
+    /// If it is set, each chunk is signed with an HMAC-SHA256 over its header
+    /// fields and its payload, which is stored in the header. It makes the logs
+    /// tamper-evident, the signatures are verified by [`parse`] if the key is
+    /// provided (see [`ParseOptions::signing_key`]).
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn signing_key(mut self, key: Option<SigningKey>) -> Self {
+        self.signing_key = key;
+        self
+    }
+
+    /// Whether or not to write the index of the records to the chunks.
+    ///
+    /// The index consists of the numbers of the records by level and a filter of
+    /// their tags, so that the chunks can be skipped by them without being
+    /// decrypted, see [`query_domain`] and [`ExtractOptions::query`]. It costs 31
+    /// bytes per chunk.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn chunk_index(mut self, flag: bool) -> Self {
+        self.chunk_index = flag;
+        self
     }
 
     /// The compression level.
@@ -378,7 +943,320 @@ impl Config {
         self
     }
 
-    /// The tracker used to track errors occurred from the logger operations.
+    /// The adaptive compression level, which starts at the compression level (see
+    /// [`Config::compression_level`]) and is tuned within the bounds by the
+    /// measured logging latency, see [`AdaptiveCompression`].
+    ///
+    /// The current level is reported by [`Logger::health`].
+    ///
+    /// The default value is `None`, the compression level is fixed.
+    #[inline]
+    pub fn compression_level_auto(mut self, auto: Option<AdaptiveCompression>) -> Self {
+        self.compression_level_auto = auto;
+        self
+    }
+
+    /// The advanced parameters of `zstd` (e.g., the window log), which apply to all
+    /// of the chunks, see [`ZstdParams`].
+    ///
+    /// The default value is [`ZstdParams::default`], the parameters are decided by
+    /// the compression level.
+    #[inline]
+    pub fn zstd_params(mut self, params: ZstdParams) -> Self {
+        self.zstd_params = params;
+        self
+    }
+
+    /// The compression level for the records logged with
+    /// [`CompressionHint::Isolated`].
+    ///
+    /// The default value is `19`.
+    #[inline]
+    pub fn isolated_compression_level(mut self, level: i32) -> Self {
+        self.isolated_compression_level = level;
+        self
+    }
+
+    /// Whether or not to deduplicate identical consecutive records.
+    ///
+    /// When enabled, consecutive records with identical level, tag and content are
+    /// coalesced into the first one of them, which carries the count of
+    /// repetitions (see [`Record::repeats`]). Note that the last record is held
+    /// back until a different record is logged or the logger is flushed, so it
+    /// may be lost if the application terminates unexpectedly.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn deduplicate(mut self, flag: bool) -> Self {
+        self.deduplicate = flag;
+        self
+    }
+
+    /// The rate limiting of the records.
+    ///
+    /// Records logged in excess of the limit are dropped and coalesced into a
+    /// synthetic record (`suppressed N similar records`), which protects the disk
+    /// and battery of the device from flood-level logging.
+    ///
+    /// The default value is `None`, which means there is no limit.
+    #[inline]
+    pub fn rate_limit(mut self, rate_limit: Option<RateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// The identifier of the logger session, it is stamped on all records and
+    /// chunks produced by the logger, see [`extract_session`].
+    ///
+    /// The default value is `None`, which means that a random session id is
+    /// generated for each logger instance (e.g., each app launch).
+    #[inline]
+    pub fn session_id(mut self, session_id: Option<u64>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// The step (in bytes) by which the log files are preallocated.
+    ///
+    /// Preallocation keeps the file system from fragmenting the log files across
+    /// the small appends, which improves the write throughput on some flash
+    /// storages. The space that is not written is released once the log file is
+    /// closed.
+    ///
+    /// The default value is `None`, which means there is no preallocation.
+    #[inline]
+    pub fn preallocation(mut self, step: Option<u64>) -> Self {
+        self.preallocation = step;
+        self
+    }
+
+    /// Whether or not to write the minute index to the end of the log files once
+    /// they are rotated.
+    ///
+    /// The index maps the minutes to the offsets of the chunks, so that the
+    /// extraction (see [`extract`]) seeks to the chunks of the time range directly
+    /// instead of walking all the chunk headers of the log files, which is slow for
+    /// the large hourly or daily log files on devices. The index is removed if the
+    /// log file is appended to again, and the log files without it are walked as
+    /// before.
+    ///
+    /// It has no effect in the direct mode (see [`Config::direct_mmap`]) or with a
+    /// custom storage (see [`Config::storage`]).
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn minute_index(mut self, flag: bool) -> Self {
+        self.minute_index = flag;
+        self
+    }
+
+    /// Whether or not to write the manifest of the domain (see [`Manifest`]) to the
+    /// domain directory when the logger is initialized.
+    ///
+    /// The manifest records the format version, the cipher, the rotation, the key
+    /// fingerprint and the versions of the SDK and the application, so that the
+    /// backend parsers can tell how the log files are written. It can be read by
+    /// [`Domain::manifest`].
+    ///
+    /// It has no effect with a custom storage (see [`Config::storage`]).
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn manifest(mut self, flag: bool) -> Self {
+        self.manifest = flag;
+        self
+    }
+
+    /// The minimum space (in bytes) that must remain free on the file system of
+    /// the domain directory.
+    ///
+    /// When the free space drops below it, no new log files are created, the chunks
+    /// that would have been written to them are dropped, and
+    /// [`LoggerError::InsufficientSpace`] is reported to the tracker. In the direct
+    /// mode (see [`Config::direct_mmap`]), the records keep being written to the
+    /// active log file instead.
+    ///
+    /// It only takes effect on Unix, where the free space can be obtained.
+    ///
+    /// The default value is `None`, which means there is no limit.
+    #[inline]
+    pub fn min_free_space(mut self, bytes: Option<u64>) -> Self {
+        self.min_free_space = bytes;
+        self
+    }
+
+    /// Whether or not to delete the oldest log files when the free space drops
+    /// below [`Config::min_free_space`], until there is enough free space again.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn trim_on_low_space(mut self, flag: bool) -> Self {
+        self.trim_on_low_space = flag;
+        self
+    }
+
+    /// Where the logger obtains the current time from, see [`TimestampSource`].
+    ///
+    /// The default value is `Wallclock`.
+    #[inline]
+    pub fn timestamp_source(mut self, source: TimestampSource) -> Self {
+        self.timestamp_source = source;
+        self
+    }
+
+    /// Whether or not to stamp the records with the milliseconds elapsed since the
+    /// logger was initialized (see [`Meta::elapsed`]), so that they can be ordered
+    /// correctly even if the wall clock changes. The records that have one already
+    /// keep it.
+    ///
+    /// It has no effect on `wasm32-unknown-unknown`, where the monotonic clock is
+    /// unavailable.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn elapsed_time(mut self, flag: bool) -> Self {
+        self.elapsed_time = flag;
+        self
+    }
+
+    /// Whether or not to number the records in the order they are logged (see
+    /// [`Meta::sequence`]), so that the lost records can be detected by the gaps in
+    /// the sequence. The records that have one already keep it.
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn record_sequence(mut self, flag: bool) -> Self {
+        self.record_sequence = flag;
+        self
+    }
+
+    /// The version of the application, it is written to the header of each chunk
+    /// along with the process id, see [`Producer`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn app_version(mut self, version: Option<impl Into<String>>) -> Self {
+        self.app_version = version.map(Into::into);
+        self
+    }
+
+    /// The version of the operating system, it is written to the header of each
+    /// chunk along with the process id, see [`Producer`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn os_version(mut self, version: Option<impl Into<String>>) -> Self {
+        self.os_version = version.map(Into::into);
+        self
+    }
+
+    /// The prefix stripped from the code files of the records (see
+    /// [`Location::file`]), so that the logs don't leak the absolute paths of the
+    /// build machine and the parsed output stays short.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn strip_path_prefix(mut self, prefix: Option<impl Into<String>>) -> Self {
+        self.strip_path_prefix = prefix.map(Into::into);
+        self
+    }
+
+    /// The redactor applied to the content of the records before they are
+    /// compressed, so that sensitive information (e.g., PII) is never written to
+    /// the log files, see [`Redact`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn redactor(mut self, redactor: Option<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Adds a custom stage to the processing pipeline of the records, see
+    /// [`PipelineStage`].
+    ///
+    /// The stages are applied in the order they are added, after the redactor (see
+    /// [`Config::redactor`]). A stage replaces the stage of the same name that has
+    /// been added before it.
+    #[inline]
+    pub fn pipeline_stage(mut self, stage: Box<dyn PipelineStage>) -> Self {
+        match self.stages.iter_mut().find(|s| s.name() == stage.name()) {
+            Some(existing) => *existing = stage,
+            None => self.stages.push(stage),
+        }
+        self
+    }
+
+    /// The levels of the records that are also written to a separate stream of log
+    /// files, e.g., `MyApp-errors-1384781712.pine` (see [`Domain::split_domain`]).
+    ///
+    /// The split stream has its own buffer (a quarter of [`Config::buffer_len`]) and
+    /// chunk rotation, so it stays small and can be uploaded on its own, while the
+    /// verbose log files are kept on the device. The records are split after they
+    /// have been processed by the stages (see [`PipelineStage`]).
+    ///
+    /// The default value is empty, which means there is no split stream.
+    #[inline]
+    pub fn split_by_level(mut self, levels: &[Level]) -> Self {
+        self.split_levels = levels.to_vec();
+        self
+    }
+
+    /// The maximum length (in bytes) of the content of the records.
+    ///
+    /// The content that exceeds it is truncated at a UTF-8 character boundary, and
+    /// the record is marked as truncated (see [`Record::truncated`]), so that the
+    /// runaway dumps don't blow up the chunks and the log files.
+    ///
+    /// The default value is `None`, which means there is no limit.
+    #[inline]
+    pub fn max_record_len(mut self, len: Option<usize>) -> Self {
+        self.max_record_len = len;
+        self
+    }
+
+    /// The platform console to which the records are mirrored, in addition to
+    /// being written to the log files.
+    ///
+    /// Mirroring only takes effect in debug builds, so that the records can be
+    /// viewed in the console during development without instrumenting another
+    /// logging system.
+    ///
+    /// The default value is `None`, which means there is no mirroring.
+    #[inline]
+    pub fn mirror(mut self, mirror: Option<Mirror>) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// The secondary sink that receives the records logged by the logger, see
+    /// [`RecordSink`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn record_sink(mut self, sink: Option<Box<dyn RecordSink + Send + Sync>>) -> Self {
+        self.record_sink = sink;
+        self
+    }
+
+    /// The storage of the log files, see [`Storage`].
+    ///
+    /// The direct mode (see [`Config::direct_mmap`]) and the manifest (see
+    /// [`Config::manifest`]) are ignored with a custom storage, and so are the
+    /// preallocation and the minute index unless the storage opens the files for
+    /// the random access (see [`Storage::open`]). Pass the same storage to
+    /// [`ExtractOptions::storage`] to extract the logs from it.
+    ///
+    /// The default value is `None`, which means the log files are stored in the
+    /// file system (see [`FileSystem`]).
+    #[inline]
+    pub fn storage(mut self, storage: Option<SharedStorage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// The tracker used to track errors and notable events occurred from the logger
+    /// operations, see [`TrackEvent`].
     ///
     /// Errors are printed to standard output by default.
     #[inline]
@@ -399,10 +1277,42 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             use_mmap: true,
+            direct_mmap: false,
             buffer_len: BUFFER_LEN,
+            thread_staging_len: None,
             rotation: TimeDimension::Minute,
+            io_panic_policy: IoPanicPolicy::default(),
+            writeback: Writeback::default(),
+            circuit_breaker: Some(CircuitBreaker::default()),
             key: None,
+            key_algorithm: KeyAlgorithm::default(),
+            signing_key: None,
+            chunk_index: false,
             compression_level: ZstdCompressor::DEFAULT_LEVEL,
+            compression_level_auto: None,
+            zstd_params: ZstdParams::default(),
+            isolated_compression_level: ZstdCompressor::ISOLATED_LEVEL,
+            deduplicate: false,
+            rate_limit: None,
+            session_id: None,
+            preallocation: None,
+            minute_index: false,
+            manifest: false,
+            min_free_space: None,
+            trim_on_low_space: false,
+            timestamp_source: TimestampSource::default(),
+            elapsed_time: false,
+            record_sequence: false,
+            app_version: None,
+            os_version: None,
+            strip_path_prefix: None,
+            redactor: None,
+            stages: Vec::new(),
+            split_levels: Vec::new(),
+            max_record_len: None,
+            mirror: None,
+            record_sink: None,
+            storage: None,
             tracker: Some(Box::new(|err, file, line| {
                 println!("[Pinenut Error] {file}:{line} | {err}")
             })),
@@ -0,0 +1,181 @@
+//! Record sampling & rate limiting.
+
+use std::collections::HashMap;
+
+use crate::{DateTime, Level, Location, Meta, Record};
+
+/// Configuration of the logger's rate limiting.
+///
+/// When more than `max_per_second` records are logged within the same second, the
+/// excess records are dropped and coalesced into a synthetic record such as
+/// `suppressed 42 similar records`, which is logged once the window is over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RateLimit {
+    /// The maximum number of records that can be logged per second.
+    pub max_per_second: u32,
+    /// Whether the limit is applied to each tag separately, otherwise it is applied
+    /// to all records as a whole.
+    pub per_tag: bool,
+}
+
+impl RateLimit {
+    /// Constructs a new `RateLimit`.
+    #[inline]
+    pub fn new(max_per_second: u32, per_tag: bool) -> Self {
+        Self { max_per_second, per_tag }
+    }
+}
+
+/// Represents the records that have been suppressed in a window.
+pub(crate) struct Suppressed {
+    level: Level,
+    datetime: DateTime,
+    tag: Option<String>,
+    session_id: Option<u64>,
+    count: u64,
+}
+
+impl Suppressed {
+    /// Builds the synthetic record and passes it to the callback.
+    pub(crate) fn with_record<R>(&self, callback: impl FnOnce(&Record) -> R) -> R {
+        let content = format!("suppressed {} similar records", self.count);
+        let mut meta =
+            Meta::new(self.level, self.datetime, Location::default(), self.tag.as_deref(), None);
+        meta.set_session_id(self.session_id);
+        callback(&Record::new(meta, &content))
+    }
+}
+
+/// The rate limiter, it counts records in windows of one second.
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    /// The windows of the tagged records, used only when the limit is per tag.
+    tagged: HashMap<String, Window>,
+    /// The window of the untagged records, or of all records.
+    untagged: Option<Window>,
+}
+
+/// The counting window for a specified key (tag).
+struct Window {
+    second: i64,
+    accepted: u32,
+    suppressed: Option<Suppressed>,
+}
+
+impl RateLimiter {
+    /// The number of windows above which the stale ones are pruned.
+    const PRUNE_THRESHOLD: usize = 256;
+
+    /// Constructs a new `RateLimiter`.
+    #[inline]
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self { limit, tagged: HashMap::new(), untagged: None }
+    }
+
+    /// Checks whether the record can be logged.
+    ///
+    /// Returns the result of the check and the records suppressed in the previous
+    /// window of the same key, if there are any.
+    pub(crate) fn check(&mut self, record: &Record) -> (bool, Option<Suppressed>) {
+        let meta = record.meta();
+        let second = meta.datetime().timestamp();
+        let limit = self.limit;
+        let window = self.window(meta.tag().filter(|_| limit.per_tag), second);
+
+        let mut previous = None;
+        if window.second != second {
+            previous = window.suppressed.take();
+            window.second = second;
+            window.accepted = 0;
+        }
+
+        if window.accepted < limit.max_per_second {
+            window.accepted += 1;
+            return (true, previous);
+        }
+
+        let suppressed = window.suppressed.get_or_insert_with(|| Suppressed {
+            level: meta.level(),
+            datetime: meta.datetime(),
+            tag: meta.tag().map(ToOwned::to_owned),
+            session_id: meta.session_id(),
+            count: 0,
+        });
+        // Keeps the most severe level among the suppressed records.
+        suppressed.level = suppressed.level.min(meta.level());
+        suppressed.datetime = meta.datetime();
+        suppressed.count += 1;
+
+        (false, previous)
+    }
+
+    /// Looks up the window of the key (tag), the tag is copied only when its window
+    /// is inserted, so that the checks don't allocate.
+    fn window(&mut self, key: Option<&str>, second: i64) -> &mut Window {
+        let new = || Window { second, accepted: 0, suppressed: None };
+        let Some(tag) = key else {
+            return self.untagged.get_or_insert_with(new);
+        };
+
+        if !self.tagged.contains_key(tag) {
+            if self.tagged.len() > Self::PRUNE_THRESHOLD {
+                self.tagged.retain(|_, w| w.second == second || w.suppressed.is_some());
+            }
+            self.tagged.insert(tag.to_owned(), new());
+        }
+        self.tagged.get_mut(tag).expect("the window has been inserted")
+    }
+
+    /// Takes all the records that have been suppressed so far.
+    pub(crate) fn drain(&mut self) -> Vec<Suppressed> {
+        let windows = self.tagged.values_mut().chain(&mut self.untagged);
+        let mut drained: Vec<_> = windows.filter_map(|w| w.suppressed.take()).collect();
+        drained.sort_by_key(|s| s.datetime);
+        drained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        limit::{RateLimit, RateLimiter},
+        DateTime, Level, MetaBuilder, RecordBuilder,
+    };
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(RateLimit::new(2, true));
+
+        let check = |limiter: &mut RateLimiter, datetime: &str, tag: &str| {
+            let datetime = DateTime::from_str(datetime).unwrap();
+            let meta = MetaBuilder::new().datetime(datetime).tag(tag).build();
+            let record = RecordBuilder::new().meta(meta).content("flood").build();
+            let (accepted, suppressed) = limiter.check(&record);
+            let suppressed = suppressed.map(|s| {
+                s.with_record(|r| (r.content().to_string(), r.meta().tag().map(str::to_string)))
+            });
+            (accepted, suppressed)
+        };
+
+        assert_eq!(check(&mut limiter, "2013-11-18 13:35:12Z", "a"), (true, None));
+        assert_eq!(check(&mut limiter, "2013-11-18 13:35:12Z", "a"), (true, None));
+        assert_eq!(check(&mut limiter, "2013-11-18 13:35:12Z", "a"), (false, None));
+        assert_eq!(check(&mut limiter, "2013-11-18 13:35:12Z", "a"), (false, None));
+        // Other tags are not affected.
+        assert_eq!(check(&mut limiter, "2013-11-18 13:35:12Z", "b"), (true, None));
+
+        let summary = ("suppressed 2 similar records".to_string(), Some("a".to_string()));
+        assert_eq!(check(&mut limiter, "2013-11-18 13:35:13Z", "a"), (true, Some(summary)));
+
+        check(&mut limiter, "2013-11-18 13:35:13Z", "b");
+        check(&mut limiter, "2013-11-18 13:35:13Z", "b");
+        check(&mut limiter, "2013-11-18 13:35:13Z", "b");
+        let drained = limiter.drain();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].level, Level::Info);
+        assert_eq!(drained[0].count, 1);
+        assert!(limiter.drain().is_empty());
+    }
+}
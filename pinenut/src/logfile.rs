@@ -1,14 +1,23 @@
 //! The `Logfile` implementation.
 
 use std::{
-    fs,
-    fs::{DirEntry, File},
-    io::{Error, Seek, SeekFrom, Write},
-    path::PathBuf,
+    fs::File,
+    io::{self, BufReader, Cursor, Error, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
-use crate::{DateTime, Domain, FILE_EXTENSION};
+use chrono::{NaiveDate, TimeDelta, Timelike};
+
+use crate::{
+    chunk,
+    chunk::{Chunk, Extensions, Header},
+    encrypt::ecdh::EMPTY_PUBLIC_KEY,
+    mmap,
+    mmap::Mmap,
+    storage::FileSystem,
+    DateTime, Domain, FileLayout, FileNaming, SharedStorage, FILE_EXTENSION,
+};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Mode {
@@ -19,16 +28,126 @@ pub(crate) enum Mode {
 pub(crate) struct Logfile {
     domain: Arc<Domain>,
     datetime: DateTime,
+    directory: PathBuf,
+    file_naming: FileNaming,
     mode: Mode,
     lazy_file: Option<File>,
+    /// The step by which the file is preallocated for writing.
+    preallocation: Option<u64>,
+    /// The position where the next bytes are written, it is determined once the
+    /// file is opened for writing.
+    position: u64,
+    /// The length of the file, including the preallocated space.
+    allocated_len: u64,
+    /// The storage of the file, which is the file system by default.
+    storage: SharedStorage,
+    /// The minute index of the chunks written to the file, `None` if it is
+    /// disabled.
+    index: Option<MinuteIndex>,
+    /// The latest end datetime of the chunks in the file, which is known once the
+    /// file is opened for writing.
+    last_end: Option<DateTime>,
 }
 
 impl Logfile {
     const NAME_SEPARATOR: &'static str = "-";
+    const HOURLY_DATE_FORMAT: &'static str = "%Y-%m-%d";
+
+    /// Constructs a new `Logfile` named in the naming scheme, which may differ from
+    /// the one of the domain, see [`Logfile::open_for_chunk`].
+    fn named(domain: Arc<Domain>, datetime: DateTime, mode: Mode, file_naming: FileNaming) -> Self {
+        let datetime = match file_naming {
+            FileNaming::Timestamp => datetime,
+            FileNaming::Hourly => Self::truncate_to_hour(datetime),
+        };
+        let directory = match domain.file_layout {
+            FileLayout::Flat => domain.directory.clone(),
+            FileLayout::Dated => ["%Y", "%m", "%d"]
+                .iter()
+                .fold(domain.directory.clone(), |dir, f| dir.join(datetime.format(f).to_string())),
+        };
+        Self::with_fields(domain, datetime, directory, file_naming, mode)
+    }
+
+    #[inline]
+    fn with_fields(
+        domain: Arc<Domain>,
+        datetime: DateTime,
+        directory: PathBuf,
+        file_naming: FileNaming,
+        mode: Mode,
+    ) -> Self {
+        Self {
+            domain,
+            datetime,
+            directory,
+            file_naming,
+            mode,
+            lazy_file: None,
+            preallocation: None,
+            position: 0,
+            allocated_len: 0,
+            storage: FileSystem::shared(),
+            index: None,
+            last_end: None,
+        }
+    }
+
+    /// Opens the log file for writing the chunk starting at the datetime, which is
+    /// configured by the closure.
+    ///
+    /// The chunks of each log file are kept in order, so that the extraction stops
+    /// at the first chunk after the time range. If the chunk would be out of order
+    /// in the existing log file (see [`Logfile::is_out_of_order`]), e.g., the clock
+    /// has jumped backwards, the existing one is sealed again and the chunk is
+    /// written to a log file named by its timestamp instead.
+    pub(crate) fn open_for_chunk(
+        domain: Arc<Domain>,
+        datetime: DateTime,
+        tolerance: TimeDelta,
+        configure: impl Fn(Self) -> Self,
+    ) -> Result<Self, Error> {
+        let file_naming = domain.file_naming;
+        let logfile =
+            |naming| configure(Self::named(Arc::clone(&domain), datetime, Mode::Write, naming));
+        let mut existing = logfile(file_naming);
+        existing.open()?;
+        if !existing.is_out_of_order(datetime, tolerance) {
+            return Ok(existing);
+        }
+        existing.seal()?;
+        Ok(logfile(FileNaming::Timestamp))
+    }
+
+    /// Stores the file in the storage instead of the file system.
+    #[inline]
+    pub(crate) fn storage(mut self, storage: SharedStorage) -> Self {
+        self.storage = storage;
+        self
+    }
 
+    /// Preallocates the file by the step for writing, so that the file system
+    /// doesn't fragment the file across the small appends.
+    ///
+    /// The preallocated space is zero-filled, which is treated as the end of the
+    /// chunks by the readers, and is released once the logfile is closed.
     #[inline]
-    pub(crate) fn new(domain: Arc<Domain>, datetime: DateTime, mode: Mode) -> Self {
-        Self { domain, datetime, mode, lazy_file: None }
+    pub(crate) fn preallocate(mut self, step: Option<u64>) -> Self {
+        self.preallocation = step.filter(|step| *step > 0);
+        self
+    }
+
+    /// Indexes the chunks written to the file by minute, and writes the index to
+    /// the end of the file once it is sealed, see [`Logfile::seal`].
+    ///
+    /// The files without the random access are not indexed, as they can't be
+    /// truncated, see [`Storage::open`].
+    ///
+    /// [`Storage::open`]: crate::Storage::open
+    #[inline]
+    pub(crate) fn minute_index(mut self, flag: bool) -> Self {
+        self.index = flag.then(MinuteIndex::default);
+        self
     }
 
     #[inline]
@@ -36,87 +155,500 @@ impl Logfile {
         self.datetime
     }
 
+    /// Whether the chunk starting at the datetime would be out of order in the
+    /// file, i.e., it is earlier than the chunks in the file beyond the tolerance.
+    ///
+    /// The chunks written before the file is opened for writing are unknown.
+    #[inline]
+    pub(crate) fn is_out_of_order(&self, datetime: DateTime, tolerance: TimeDelta) -> bool {
+        self.last_end.is_some_and(|end| end - datetime > tolerance)
+    }
+
+    /// Adds the end datetime of the chunk written to the file, see
+    /// [`Logfile::is_out_of_order`].
     #[inline]
+    pub(crate) fn add_end(&mut self, end: DateTime) {
+        self.last_end = self.last_end.max(Some(end));
+    }
+
     pub(crate) fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
-        let file = self.open()?;
-        file.seek(SeekFrom::End(0))?;
-        file.write_all(bytes)
+        if self.open()?.is_none() {
+            self.storage.append(&self.path(), bytes)?;
+            self.position += bytes.len() as u64;
+            return Ok(());
+        }
+        let end = self.position + bytes.len() as u64;
+
+        // SAFETY: The file has been opened above.
+        let file = unsafe { self.lazy_file.as_mut().unwrap_unchecked() };
+        if let Some(step) = self.preallocation.filter(|_| end > self.allocated_len) {
+            let len = end.div_ceil(step) * step;
+            Self::allocate(file, len)?;
+            self.allocated_len = len;
+        }
+
+        // The cursor of the file always follows the position, so there is no need to
+        // seek.
+        file.write_all(bytes)?;
+        self.position = end;
+        Ok(())
+    }
+
+    /// Writes the chunk to the file, which is added to the minute index if it is
+    /// enabled.
+    pub(crate) fn write_chunk<B>(&mut self, chunk: &Chunk<B>) -> Result<(), Error>
+    where
+        B: std::ops::Deref<Target = [u8]>,
+    {
+        if self.index.is_some() {
+            // Determines the position of the chunk.
+            self.open()?;
+            let position = self.position;
+            if let Some(index) = self.index.as_mut() {
+                index.add(chunk.end_datetime(), position);
+            }
+        }
+        self.write(chunk)?;
+        self.add_end(chunk.end_datetime());
+        Ok(())
+    }
+
+    /// Writes the minute index to the end of the file as its footer chunk, so that
+    /// the readers can seek to the chunks of a time range directly, see
+    /// [`MinuteIndex`].
+    ///
+    /// It is called once no more chunks are written to the file (i.e., the file is
+    /// rotated). The footer is removed if the file is opened for writing again. It
+    /// does nothing if the index is disabled or empty.
+    pub(crate) fn seal(&mut self) -> Result<(), Error> {
+        let Some(footer) = self.index.take().and_then(|index| index.to_footer(self.datetime))
+        else {
+            return Ok(());
+        };
+        self.write(&footer)?;
+        self.flush()
+    }
+
+    /// Maps the file from the position for writing in place, the file is extended
+    /// to cover the `len` bytes after the position, see [`Config::direct_mmap`].
+    ///
+    /// Returns the mapping and the offset of its start in the file, which is the
+    /// position rounded down to a multiple of the page size.
+    ///
+    /// [`Config::direct_mmap`]: crate::Config::direct_mmap
+    pub(crate) fn map(&mut self, len: usize) -> Result<(Mmap, u64), Error> {
+        if self.open()?.is_none() {
+            return Err(ErrorKind::Unsupported.into());
+        }
+        let end = self.position + len as u64;
+
+        // SAFETY: The file has been opened above.
+        let file = unsafe { self.lazy_file.as_mut().unwrap_unchecked() };
+        if end > self.allocated_len {
+            Self::allocate(file, end)?;
+            self.allocated_len = end;
+        }
+
+        let offset = self.position - self.position % mmap::page_size() as u64;
+        let mmap = Mmap::map_file(file, offset, (end - offset) as usize)?;
+        Ok((mmap, offset))
+    }
+
+    /// The position where the next bytes are written.
+    #[inline]
+    pub(crate) fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Advances the position by the length of the bytes that have been written
+    /// through the mapping, see [`Logfile::map`].
+    #[inline]
+    pub(crate) fn advance(&mut self, len: usize) -> Result<(), Error> {
+        self.position += len as u64;
+        let position = self.position;
+        // Keeps the cursor of the file following the position.
+        if let Some(file) = self.open()? {
+            file.seek(SeekFrom::Start(position))?;
+        }
+        Ok(())
     }
 
     #[inline]
     pub(crate) fn flush(&mut self) -> Result<(), Error> {
-        let file = self.open()?;
+        // The appended bytes are flushed by the storage.
+        let Some(file) = self.open()? else {
+            return Ok(());
+        };
         file.flush()?;
         file.sync_all()
     }
 
+    /// The length of the log file in bytes.
     #[inline]
+    pub(crate) fn len(&self) -> Result<u64, Error> {
+        self.storage.len(&self.path())
+    }
+
     pub(crate) fn delete(mut self) -> Result<(), Error> {
         self.lazy_file = None;
-        fs::remove_file(self.path())
+        self.storage.delete(&self.path())?;
+
+        // Removes the dated subdirectories that become empty, the removal fails if the
+        // directory is not empty.
+        for directory in self.directory.ancestors().take_while(|d| *d != self.domain.directory) {
+            if self.storage.remove_dir(directory).is_err() {
+                break;
+            }
+        }
+        Ok(())
     }
 
-    pub(crate) fn open(&mut self) -> Result<&mut File, Error> {
+    /// Opens the file for the random access, `None` if the storage doesn't support
+    /// it, see [`Storage::open`]. The minute index is disabled then.
+    ///
+    /// [`Storage::open`]: crate::Storage::open
+    pub(crate) fn open(&mut self) -> Result<Option<&mut File>, Error> {
         if self.lazy_file.is_none() {
-            let path = self.path();
             let to_write = self.mode == Mode::Write;
+            let Some(file) = self.storage.open(&self.path(), to_write) else {
+                self.index = None;
+                return Ok(None);
+            };
+            let mut file = file?;
 
-            // Creates all intermediate directories if they are missing.
-            if to_write && let Some(parent_path) = path.parent() {
-                fs::create_dir_all(parent_path)?;
+            if to_write {
+                self.allocated_len = file.metadata()?.len();
+                let scan = Self::scan(&mut file, self.allocated_len)?;
+                self.position = scan.chunks_len;
+                // The footer is removed, as the chunks are appended to the file again.
+                if scan.footer {
+                    file.set_len(self.position)?;
+                    self.allocated_len = self.position;
+                }
+                if let Some(index) = self.index.as_mut() {
+                    *index = scan.index;
+                }
+                self.last_end = scan.last_end;
+                file.seek(SeekFrom::Start(self.position))?;
             }
 
-            let file = fs::OpenOptions::new()
-                .read(!to_write)
-                .append(to_write)
-                .create(to_write)
-                .open(path)?;
-
             self.lazy_file = Some(file);
         }
+        Ok(self.lazy_file.as_mut())
+    }
 
-        // SAFETY: a `None` variant for `lazy_file` would have been replaced by a `Some`
-        // variant in the code above.
-        Ok(unsafe { self.lazy_file.as_mut().unwrap_unchecked() })
+    /// Opens the file for reading, the whole file is read if the storage doesn't
+    /// support the random access.
+    pub(crate) fn reader(&mut self) -> Result<Reader<'_>, Error> {
+        if self.open()?.is_none() {
+            return Ok(Reader::Bytes(Cursor::new(self.storage.read(&self.path())?)));
+        }
+        // SAFETY: The file has been opened above.
+        Ok(Reader::File(unsafe { self.lazy_file.as_mut().unwrap_unchecked() }))
     }
 
     #[inline]
     pub(crate) fn path(&self) -> PathBuf {
-        self.domain.directory.join(Self::name(&self.domain, self.datetime))
+        self.directory.join(self.name())
     }
 
     #[inline]
-    fn name(Domain { identifier, .. }: &Domain, datetime: DateTime) -> String {
-        format!("{}{}{}.{}", identifier, Self::NAME_SEPARATOR, datetime.timestamp(), FILE_EXTENSION)
+    fn name(&self) -> String {
+        let datetime = self.datetime;
+        let formatted = match self.file_naming {
+            FileNaming::Timestamp => datetime.timestamp().to_string(),
+            FileNaming::Hourly => {
+                format!(
+                    "{}{}{:02}",
+                    datetime.format(Self::HOURLY_DATE_FORMAT),
+                    Self::NAME_SEPARATOR,
+                    datetime.hour()
+                )
+            }
+        };
+        let identifier = &self.domain.identifier;
+        format!("{}{}{}.{}", identifier, Self::NAME_SEPARATOR, formatted, FILE_EXTENSION)
+    }
+
+    /// Parses the datetime part of the name, which is in any of the naming schemes.
+    fn parse_datetime(datetime: &str) -> Option<(DateTime, FileNaming)> {
+        // For chunk, time accuracy does not have to be down to nanoseconds.
+        if let Ok(timestamp) = datetime.parse() {
+            return Some((DateTime::from_timestamp(timestamp, 0)?, FileNaming::Timestamp));
+        }
+        let (date, hour) = datetime.rsplit_once(Self::NAME_SEPARATOR)?;
+        let date = NaiveDate::parse_from_str(date, Self::HOURLY_DATE_FORMAT).ok()?;
+        Some((date.and_hms_opt(hour.parse().ok()?, 0, 0)?.and_utc(), FileNaming::Hourly))
+    }
+
+    /// Walks the chunk headers of the file to find where the next bytes are
+    /// written, and indexes the chunks by minute.
+    fn scan(file: &mut File, len: u64) -> Result<Scan, Error> {
+        let mut scan =
+            Scan { chunks_len: len, footer: false, index: MinuteIndex::default(), last_end: None };
+        if len == 0 {
+            return Ok(scan);
+        }
+        let mut reader = chunk::Reader::new(BufReader::new(&mut *file));
+        // The start of the footer chunk, if it is the last one.
+        let mut footer = None;
+        scan.chunks_len = loop {
+            let Ok(position) = reader.stream_position() else {
+                break len;
+            };
+            match reader.read_header_or_reach_to_end() {
+                Ok(Some(header)) => {
+                    let (end, payload_len) = (header.time_range().end(), header.payload_len());
+                    if reader.extensions().is_footer() {
+                        footer = Some(position);
+                    } else {
+                        footer = None;
+                        scan.index.add(end, position);
+                        scan.last_end = scan.last_end.max(Some(end));
+                    }
+                    if reader.skip(payload_len).is_err() {
+                        break len;
+                    }
+                }
+                Ok(None) => {
+                    scan.footer = footer.is_some();
+                    break footer.unwrap_or(position).min(len);
+                }
+                Err(_) => break len,
+            }
+        };
+        Ok(scan)
+    }
+
+    /// Allocates the space of the file up to the length.
+    fn allocate(file: &File, len: u64) -> Result<(), Error> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            use std::os::fd::AsRawFd;
+
+            // SAFETY: The file descriptor is valid during the call.
+            let ret = unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len as libc::off_t) };
+            if ret == 0 {
+                return Ok(());
+            }
+        }
+        // Falls back to extending the file, e.g., the file system doesn't support it.
+        file.set_len(len)
+    }
+
+    #[inline]
+    fn truncate_to_hour(datetime: DateTime) -> DateTime {
+        datetime
+            .with_minute(0)
+            .and_then(|d| d.with_second(0))
+            .and_then(|d| d.with_nanosecond(0))
+            .unwrap_or(datetime)
+    }
+}
+
+/// The result of walking the chunk headers of a file, see [`Logfile::scan`].
+struct Scan {
+    /// The length of the chunks in the file, which is where the next bytes are
+    /// written. The zero-filled space (i.e., preallocated) and the footer chunk
+    /// after the chunks are excluded.
+    ///
+    /// If the file is invalid, its whole length is used, so that the bytes written
+    /// before are never overwritten.
+    chunks_len: u64,
+    /// Whether the chunks are followed by the footer chunk.
+    footer: bool,
+    /// The minute index of the chunks.
+    index: MinuteIndex,
+    /// The latest end datetime of the chunks.
+    last_end: Option<DateTime>,
+}
+
+impl Drop for Logfile {
+    fn drop(&mut self) {
+        // Releases the preallocated space that is not written.
+        if let Some(file) = self.lazy_file.as_ref().filter(|_| self.allocated_len > self.position) {
+            _ = file.set_len(self.position);
+        }
     }
 }
 
 impl Logfile {
     #[inline]
+    /// The log files of the domain in the storage.
     pub(crate) fn logfiles(
         domain: &Arc<Domain>,
         mode: Mode,
+        storage: SharedStorage,
     ) -> Result<impl Iterator<Item = Self> + '_, Error> {
-        Ok(fs::read_dir(&domain.directory)?
-            .filter_map(|entry| entry.ok())
-            .filter_map(move |entry| Self::from_entry(entry, Arc::clone(domain), mode)))
+        let paths = storage.list(&domain.directory)?;
+        Ok(paths.into_iter().filter_map(move |path| {
+            Some(Self::from_path(&path, Arc::clone(domain), mode)?.storage(Arc::clone(&storage)))
+        }))
     }
 
-    fn from_entry(entry: DirEntry, domain: Arc<Domain>, mode: Mode) -> Option<Self> {
-        let name = PathBuf::from(entry.file_name());
+    fn from_path(path: &Path, domain: Arc<Domain>, mode: Mode) -> Option<Self> {
+        let name = PathBuf::from(path.file_name()?);
         if name.extension() != Some(FILE_EXTENSION.as_ref()) {
             return None;
         }
 
-        let (identifier, timestamp) =
-            name.file_stem()?.to_str()?.rsplit_once(Self::NAME_SEPARATOR)?;
+        let datetime = name
+            .file_stem()?
+            .to_str()?
+            .strip_prefix(domain.identifier.as_str())?
+            .strip_prefix(Self::NAME_SEPARATOR)?;
+        let (datetime, file_naming) = Self::parse_datetime(datetime)?;
+
+        let directory = path.parent()?.to_path_buf();
+        Some(Self::with_fields(domain, datetime, directory, file_naming, mode))
+    }
+}
+
+/// The reader of the log file, see [`Logfile::reader`].
+pub(crate) enum Reader<'a> {
+    File(&'a mut File),
+    Bytes(Cursor<Vec<u8>>),
+}
 
-        if identifier != domain.identifier {
+impl Reader<'_> {
+    /// The length of the log file in bytes.
+    #[inline]
+    pub(crate) fn len(&self) -> Result<u64, Error> {
+        match self {
+            Self::File(file) => file.metadata().map(|m| m.len()),
+            Self::Bytes(bytes) => Ok(bytes.get_ref().len() as u64),
+        }
+    }
+}
+
+impl Read for Reader<'_> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::File(file) => file.read(buf),
+            Self::Bytes(bytes) => bytes.read(buf),
+        }
+    }
+}
+
+impl Seek for Reader<'_> {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::File(file) => file.seek(pos),
+            Self::Bytes(bytes) => bytes.seek(pos),
+        }
+    }
+}
+
+/// The minute index of a log file, which maps the minutes to the offsets of the
+/// chunks, so that the readers can seek to the chunks of a time range directly
+/// instead of walking all the chunk headers of the file.
+///
+/// There is an entry for each minute in which a chunk ends later than all the
+/// chunks before it, so the chunks before the offset of an entry all end before
+/// its minute, even if the clock has gone backwards.
+///
+/// It is stored in the footer chunk of the file (see [`Extensions::FOOTER`]), which
+/// consists of the entries (the timestamp of the minute and the offset, 8 bytes
+/// each, little endian) and the length of the footer chunk (4 bytes, little
+/// endian), so that the footer is located from the end of the file.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct MinuteIndex(Vec<(i64, u64)>);
+
+impl MinuteIndex {
+    /// Length of an entry in bytes. (16 bytes)
+    const ENTRY_LEN: usize = 16;
+
+    /// Length of the trailing length of the footer chunk in bytes. (4 bytes)
+    const TRAILER_LEN: usize = 4;
+
+    /// The maximum number of entries, so that they fit in an extension field.
+    const MAX_ENTRIES: usize = (u16::MAX as usize - Self::TRAILER_LEN) / Self::ENTRY_LEN;
+
+    /// Adds the chunk at the offset, which ends at the datetime.
+    pub(crate) fn add(&mut self, end: DateTime, offset: u64) {
+        let minute = end.timestamp().div_euclid(60) * 60;
+        if self.0.last().is_none_or(|(last, _)| minute > *last) {
+            self.0.push((minute, offset));
+        }
+    }
+
+    /// The offset from which the chunks ending at or after the datetime are, the
+    /// chunks before it can be skipped.
+    pub(crate) fn offset(&self, datetime: DateTime) -> u64 {
+        let minute = datetime.timestamp().div_euclid(60) * 60;
+        match self.0.partition_point(|(m, _)| *m <= minute) {
+            0 => 0,
+            index => self.0[index - 1].1,
+        }
+    }
+
+    /// Reads the minute index from the footer chunk at the end of the reader.
+    /// Returns `None` if there is no footer (e.g., the file hasn't been sealed).
+    pub(crate) fn read<R>(reader: &mut R) -> Result<Option<Self>, Error>
+    where
+        R: Read + Seek,
+    {
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < Self::TRAILER_LEN as u64 {
+            return Ok(None);
+        }
+        let mut trailer = [0; Self::TRAILER_LEN];
+        reader.seek(SeekFrom::End(-(Self::TRAILER_LEN as i64)))?;
+        reader.read_exact(&mut trailer)?;
+        let footer_len = u32::from_le_bytes(trailer) as u64;
+        if footer_len < (Header::LEN + Self::TRAILER_LEN) as u64 || footer_len > len {
+            return Ok(None);
+        }
+
+        reader.seek(SeekFrom::Start(len - footer_len))?;
+        let mut reader = chunk::Reader::new(reader);
+        let Ok(Some(header)) = reader.read_header_or_reach_to_end() else {
+            return Ok(None);
+        };
+        let header_len = header.len() + header.extensions_len();
+        if header.payload_len() != 0 || header_len as u64 != footer_len {
+            return Ok(None);
+        }
+        let Some(value) = reader.extensions().get(Extensions::FOOTER) else {
+            return Ok(None);
+        };
+        Ok(Self::from_bytes(&value[..value.len().saturating_sub(Self::TRAILER_LEN)]))
+    }
+
+    /// Builds the footer chunk of the log file with the index. Returns `None` if it
+    /// is empty, or has too many entries to fit in the footer.
+    fn to_footer(&self, datetime: DateTime) -> Option<Vec<u8>> {
+        if self.0.is_empty() || self.0.len() > Self::MAX_ENTRIES {
             return None;
         }
-        // For chunk, time accuracy does not have to be down to nanoseconds.
-        let datetime = DateTime::from_timestamp(timestamp.parse().ok()?, 0)?;
+        let mut value = Vec::with_capacity(self.0.len() * Self::ENTRY_LEN + Self::TRAILER_LEN);
+        for (minute, offset) in &self.0 {
+            value.extend_from_slice(&minute.to_le_bytes());
+            value.extend_from_slice(&offset.to_le_bytes());
+        }
+        // The trailer is the length of the whole footer chunk, which includes itself.
+        let footer_len = Header::LEN + Extensions::field_len(&value) + Self::TRAILER_LEN;
+        value.extend_from_slice(&(footer_len as u32).to_le_bytes());
 
-        Some(Self::new(domain, datetime, mode))
+        let mut chunk = Chunk::bind(vec![0; footer_len]);
+        chunk.initialize(datetime, EMPTY_PUBLIC_KEY, 0);
+        let last = self.0.last().map_or(0, |(minute, _)| *minute);
+        chunk.set_end_datetime(DateTime::from_timestamp(last, 0).unwrap_or(datetime));
+        chunk.push_extension(Extensions::FOOTER, &value).ok()?;
+        Some(chunk.as_raw().to_vec())
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(Self::ENTRY_LEN) {
+            return None;
+        }
+        let entries = bytes.chunks_exact(Self::ENTRY_LEN).map(|entry| {
+            let (minute, offset) = entry.split_at(8);
+            let minute = i64::from_le_bytes(minute.try_into().unwrap_or_default());
+            (minute, u64::from_le_bytes(offset.try_into().unwrap_or_default()))
+        });
+        Some(Self(entries.collect()))
     }
 }
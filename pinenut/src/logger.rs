@@ -1,28 +1,52 @@
 //! The `Logger` implementation.
 
 use std::{
-    io,
+    any::Any,
+    fmt, fs,
+    future::Future,
+    io, mem,
     ops::{Deref, DerefMut},
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::Duration,
 };
 
+use rand_core::{OsRng, RngCore};
 use thiserror::Error;
 
 use crate::{
-    buffer::{self, Buffer, EitherMemory, Memory},
-    chunk::Chunk,
-    codec::{AccumulationEncoder, EncodingError},
+    breaker::Breaker,
+    buffer::{self, Buffer, BufferHandle, EitherMemory, Memory},
+    chunk::{self, Chunk, Extensions, Header, Index},
+    clock::{Clock, Moment},
+    codec::{self, AccumulationEncoder, Decode, Encode, EncodingError},
     common,
-    compress::{CompressOp, CompressionError, Compressor, ZstdCompressor},
+    completion::{self, Completion, Notifier},
+    compress::{CompressOp, CompressionError, Compressor, LevelTuner, ZstdCompressor},
+    context,
+    direct::Direct,
     encrypt::{
-        ecdh::{self, PublicKey, EMPTY_PUBLIC_KEY},
+        self,
+        ecdh::{self, Fingerprint, PublicKey, EMPTY_PUBLIC_KEY},
         AesEncryptor, EncryptOp, EncryptionError, Encryptor,
     },
+    limit::RateLimiter,
     logfile::{self, Logfile},
-    mmap::Mmap,
+    mmap::{self, Mmap},
+    pipeline::{PipelineStage, RedactStage, Verdict},
     runloop::{self, Handle as RunloopHandle, Runloop},
-    ChunkError, Config, Domain, Record, RunloopError, TimeDimension, Tracker,
-    MMAP_BUFFER_EXTENSION,
+    staging::Staging,
+    storage::FileSystem,
+    track, AdaptiveCompression, ChunkError, CircuitBreaker, CompressionHint, Config, DateTime,
+    Domain, Hint, IoPanicPolicy, KeyAlgorithm, Level, Manifest, Meta, Mirror, Producer, Record,
+    RecordSink, RotateReason, RunloopError, SharedStorage, SigningKey, TimeDimension,
+    TrackCategory, TrackEvent, Tracker, Writeback, MMAP_BUFFER_EXTENSION,
+    MMAP_BUFFER_KEY_EXTENSION, QUARANTINED_BUFFER_EXTENSION, RECOVERED_FILE_EXTENSION,
 };
 
 /// The error type for [`Logger`].
@@ -42,26 +66,81 @@ pub enum Error {
     IoRunloop(#[from] RunloopError),
     #[error("IO: {0}")]
     Io(#[from] io::Error),
+    #[error("IO thread panicked: {0}")]
+    IoPanic(String),
+    #[error("mmap buffer is corrupted, quarantined to {0:?}")]
+    BufferCorrupted(PathBuf),
+    #[error("insufficient disk space: {available} bytes available, {required} bytes required")]
+    InsufficientSpace { available: u64, required: u64 },
 }
 
 /// The `Pinenut` logger.
 pub struct Logger {
     inner: Mutex<LoggerInner>,
+    staging: Option<Staging>,
 }
 
 impl Logger {
     /// Constructs a new `Logger`.
     #[inline]
     pub fn new(domain: Domain, config: Config) -> Self {
-        Self { inner: Mutex::new(LoggerInner::new_inner(domain, config)) }
+        let staging = config.thread_staging_len.map(Staging::new);
+        Self { inner: Mutex::new(LoggerInner::new_inner(domain, config)), staging }
     }
 
     /// Logs the record.
     ///
-    /// The low-level IO operations are performed asynchronously.
+    /// The low-level IO operations are performed asynchronously. Once the buffers
+    /// are warmed up, logging on the calling thread doesn't allocate, except for
+    /// the content rewritten by the redactor (see [`Config::redactor`]) and the
+    /// context of the thread (see [`context::push`]).
+    ///
+    /// If [`Config::thread_staging_len`] is specified, the record is staged by the
+    /// current thread without locking the logger.
     #[inline]
     pub fn log(&self, record: &Record) {
-        self.inner.lock().unwrap().on(Operation::Input(record));
+        context::stamp(record, |record| match &self.staging {
+            // Drains the staged records once the stage of the current thread is full.
+            Some(staging) => {
+                if staging.stage(record) {
+                    drop(self.lock());
+                }
+            }
+            None => self.lock().log(record, CompressionHint::Shared),
+        })
+    }
+
+    /// Logs the record with the metadata and the formatted content.
+    ///
+    /// It backs the logging macros (e.g., [`info!`]), the content is allocated only
+    /// when it has formatting arguments.
+    ///
+    /// [`info!`]: crate::info
+    pub fn log_fmt(&self, meta: Meta, args: fmt::Arguments) {
+        match args.as_str() {
+            Some(content) => self.log(&Record::new(meta, content)),
+            None => self.log(&Record::new(meta, &args.to_string())),
+        }
+    }
+
+    /// Logs the records in order under a single lock, e.g., for the bridges that
+    /// batch the records upstream.
+    ///
+    /// The staged records (see [`Config::thread_staging_len`]) are logged before
+    /// them.
+    pub fn log_batch<'r, 'a: 'r>(&self, records: impl IntoIterator<Item = &'r Record<'a>>) {
+        let mut inner = self.lock();
+        for record in records {
+            context::stamp(record, |record| inner.log(record, CompressionHint::Shared));
+        }
+    }
+
+    /// Logs the record with the compression hint.
+    ///
+    /// See [`CompressionHint`] for the details.
+    #[inline]
+    pub fn log_with_hint(&self, record: &Record, hint: CompressionHint) {
+        context::stamp(record, |record| self.lock().log(record, hint));
     }
 
     /// Flushes any buffered records asynchronously.
@@ -69,7 +148,113 @@ impl Logger {
     /// The low-level IO operations are performed asynchronously.
     #[inline]
     pub fn flush(&self) {
-        self.inner.lock().unwrap().on(Operation::Rotate);
+        self.lock().rotate();
+    }
+
+    /// Rotates the chunk with the reason, which flushes any buffered records like
+    /// [`Logger::flush`].
+    ///
+    /// The reason is recorded in the header of the chunk started by the rotation, so
+    /// the parsed output can show why the chunk boundary occurred, see
+    /// [`ParseOptions::on_rotate`].
+    ///
+    /// [`ParseOptions::on_rotate`]: crate::ParseOptions::on_rotate
+    #[inline]
+    pub fn rotate(&self, reason: RotateReason) {
+        self.lock().rotate_with_reason(reason);
+    }
+
+    /// Flushes any buffered records, and returns a future that resolves once the
+    /// records have been written to the log file.
+    ///
+    /// The future doesn't depend on any async runtime, and waiting on it doesn't
+    /// block the current thread.
+    pub fn flush_async(&self) -> impl Future<Output = Result<(), Error>> {
+        let completion = self.lock().flush_with_completion();
+        async move {
+            completion?.await;
+            Ok(())
+        }
+    }
+
+    /// Rotates the chunk (see [`RotateReason::Background`]), and blocks the current
+    /// thread until the buffered records have been written to the log file.
+    ///
+    /// It is intended to be called before the application enters the background
+    /// (e.g., on iOS), where the process may be killed without running any crash
+    /// hooks.
+    pub fn rotate_now(&self) -> Result<(), Error> {
+        let completion = {
+            let mut inner = self.lock();
+            inner.rotate_with_reason(RotateReason::Background);
+            inner.barrier()?
+        };
+        // Waits without holding the lock, so that logging is not blocked.
+        completion.wait();
+        Ok(())
+    }
+
+    /// Performs the behavior appropriate for the change of the application
+    /// lifecycle, so that the platform lifecycle callbacks can be mapped to the
+    /// logger in one call.
+    ///
+    /// See [`Hint`] for the details, it may block the current thread until the
+    /// buffered records have been written to the log file.
+    pub fn hint(&self, hint: Hint) -> Result<(), Error> {
+        match hint {
+            Hint::EnteringBackground => self.rotate_now(),
+            Hint::LowMemory => {
+                self.lock().rotate_with_reason(RotateReason::MemoryPressure);
+                if let Some(staging) = &self.staging {
+                    staging.shrink();
+                }
+                Ok(())
+            }
+            Hint::Terminating => {
+                let completion = self.lock().flush_with_completion()?;
+                completion.wait();
+                Ok(())
+            }
+        }
+    }
+
+    /// Switches the logger to a minimal buffer while the application is under memory
+    /// pressure (`true`), and restores it afterwards (`false`).
+    ///
+    /// The buffered records are written to the log file first, so it blocks the
+    /// current thread until the IO operations have completed. While it is under
+    /// memory pressure, the memory buffer (see [`StorageKind::Memory`]) is shrunk to
+    /// a few KB, so the chunks are rotated and written soon after the records are
+    /// logged. The mmap buffer is backed by the buffer file whose pages can be
+    /// reclaimed by the OS, so it is kept as it is.
+    pub fn set_memory_pressure(&self, flag: bool) -> Result<(), Error> {
+        for _ in 0..MEMORY_PRESSURE_ATTEMPTS {
+            let (completion, inputs) = {
+                let mut inner = self.lock();
+                inner.rotate();
+                (inner.barrier()?, inner.inputs)
+            };
+            // Waits without holding the lock, so that logging is not blocked.
+            completion.wait();
+            if self.lock().set_memory_pressure(flag, inputs) {
+                return Ok(());
+            }
+        }
+        // The records keep being logged while waiting, waits with the lock held as
+        // the last resort.
+        let mut inner = self.lock();
+        inner.rotate();
+        inner.barrier()?.wait();
+        let inputs = inner.inputs;
+        inner.set_memory_pressure(flag, inputs);
+        Ok(())
+    }
+
+    /// The usage of the chunk being logged to, it can be used to rotate the chunk
+    /// proactively (see [`Logger::rotate_now`]).
+    #[inline]
+    pub fn buffer_usage(&self) -> BufferUsage {
+        self.lock().buffer_usage()
     }
 
     /// Deletes the expired log files with lifetime (seconds).
@@ -77,7 +262,27 @@ impl Logger {
     /// The low-level IO operations are performed asynchronously.
     #[inline]
     pub fn trim(&self, lifetime: u64) {
-        self.inner.lock().unwrap().trim(lifetime);
+        self.lock().trim(lifetime);
+    }
+
+    /// Deletes the expired log files with lifetime (seconds), and returns the
+    /// report of the deletion.
+    ///
+    /// Unlike [`Logger::trim`], it blocks the current thread until the IO
+    /// operations are completed. Errors occurred while deleting the files are
+    /// collected into the report instead of being tracked.
+    pub fn trim_with_report(&self, lifetime: u64) -> Result<TrimReport, Error> {
+        let receiver = self.lock().trim_with_report(lifetime)?;
+        // Waits for the report without holding the lock, so that logging is not blocked.
+        receiver.recv().map_err(|_| RunloopError.into())
+    }
+
+    /// Checks the health of the logger, so that the degraded states (e.g., the
+    /// buffer is not persistent, or the log files can't be written) can be
+    /// reported.
+    #[inline]
+    pub fn health(&self) -> Health {
+        self.lock().health()
     }
 
     /// Flushes then Shuts down the logger.
@@ -86,35 +291,274 @@ impl Logger {
     #[inline]
     pub fn shutdown(self) {
         let mut inner = self.inner.into_inner().unwrap();
-        inner.on(Operation::Rotate);
+        if let Some(staging) = &self.staging {
+            inner.drain(staging);
+        }
+        inner.rotate();
         inner.shutdown();
     }
+
+    /// Flushes then Shuts down the logger, and returns a future that resolves once
+    /// all asynchronous IO operations have completed.
+    ///
+    /// Unlike [`Logger::shutdown`], it doesn't block the current thread (e.g., a
+    /// worker thread of an async runtime) while waiting for the IO thread to
+    /// finish, the shutdown is performed on a separate thread instead.
+    ///
+    /// Where the threads are not supported (e.g., `wasm32`), the IO operations are
+    /// performed on the logging thread, so it shuts down before returning.
+    pub fn shutdown_async(self) -> impl Future<Output = ()> {
+        let (notifier, completion) = completion::channel();
+        let shutdown = move || {
+            self.shutdown();
+            drop(notifier);
+        };
+        if runloop::THREADS_SUPPORTED {
+            thread::spawn(shutdown);
+        } else {
+            shutdown();
+        }
+        completion
+    }
+
+    /// Logs the record along with its encoded bytes, see [`MultiLogger`].
+    ///
+    /// [`MultiLogger`]: crate::MultiLogger
+    #[inline]
+    pub(crate) fn log_encoded(&self, record: &Record, encoded: Option<&[u8]>) {
+        self.lock().log_encoded(record, encoded, CompressionHint::Shared);
+    }
+
+    /// The session id stamped on the records that don't have one.
+    #[inline]
+    pub(crate) fn session_id(&self) -> u64 {
+        self.lock().context.session_id
+    }
+
+    /// Locks the logger, the staged records are drained into it first so that they
+    /// are ordered before the subsequent operations.
+    fn lock(&self) -> MutexGuard<'_, LoggerInner> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(staging) = &self.staging {
+            inner.drain(staging);
+        }
+        inner
+    }
+}
+
+/// The report of the expired log files deletion, see [`Logger::trim_with_report`].
+#[derive(Debug, Default)]
+pub struct TrimReport {
+    /// The number of log files that have been deleted.
+    pub deleted_files: usize,
+    /// The total length (in bytes) of the deleted log files.
+    pub freed_bytes: u64,
+    /// Errors occurred while reading the directory or deleting the log files.
+    pub errors: Vec<io::Error>,
+}
+
+/// The health report of the logger, see [`Logger::health`].
+#[derive(Debug, Clone)]
+pub struct Health {
+    /// Where the records are stored before they are written to the log file.
+    pub storage: StorageKind,
+    /// Whether the IO thread is running, the records are no longer written to the
+    /// log files once it has stopped (e.g., it panicked).
+    pub io_alive: bool,
+    /// The last error occurred in the IO operations, if any.
+    pub last_io_error: Option<String>,
+    /// The space (in bytes) available on the file system of the domain directory,
+    /// it is `None` if the space can't be obtained.
+    pub available_space: Option<u64>,
+    /// The utilization of the chunk being logged to, from `0` to `1`.
+    pub buffer_utilization: f64,
+    /// The current compression level of the chunks, it changes over time with the
+    /// adaptive compression level (see [`Config::compression_level_auto`]).
+    pub compression_level: i32,
+}
+
+/// The usage of the chunk being logged to, see [`Logger::buffer_usage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferUsage {
+    /// The length (in bytes) of the payload that has been written.
+    pub used: usize,
+    /// The capacity (in bytes) of the payload.
+    pub capacity: usize,
+}
+
+/// Represents where the records are stored before they are written to the log
+/// file, see [`Health::storage`].
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StorageKind {
+    /// The mmap buffer, the records survive the unexpected terminations.
+    Mmap,
+    /// The buffer in memory, which is used when mmap is disabled or fails to map
+    /// the buffer file. The buffered records are lost on unexpected terminations.
+    Memory,
+    /// The log file itself, see [`Config::direct_mmap`].
+    Direct,
 }
 
 // ============ Internal ============
 
+/// Returns a closure that reports the error to tracker.
+///
+/// The category of the event is derived from the error, unless it is specified.
+macro_rules! track {
+    ($tracker:expr) => {{
+        |err| {
+            if let Some(ref tracker) = $tracker {
+                tracker.track_event(TrackEvent::from_error(err.into(), file!(), line!()));
+            }
+        }
+    }};
+    ($tracker:expr, $category:expr) => {{
+        |err| {
+            if let Some(ref tracker) = $tracker {
+                let event = TrackEvent::new($category, file!(), line!());
+                tracker.track_event(event.error(err.into()));
+            }
+        }
+    }};
+}
+
+/// Reports the event without error to tracker.
+macro_rules! track_event {
+    ($tracker:expr, $category:expr, $context:expr) => {
+        if let Some(ref tracker) = $tracker {
+            tracker.track_event(TrackEvent::new($category, file!(), line!()).context($context));
+        }
+    };
+}
+
+/// Returns a closure that keeps the error as the last IO error of the context
+/// (see [`Health::last_io_error`]), then reports it to tracker.
+macro_rules! track_io {
+    ($context:expr) => {{
+        |err| {
+            let err: Error = err.into();
+            *$context.last_io_error.lock().unwrap() = Some(err.to_string());
+            if let Some(ref tracker) = $context.tracker {
+                tracker.track_event(TrackEvent::from_error(err, file!(), line!()));
+            }
+        }
+    }};
+}
+
 /// Represents the logger context.
 struct Context {
     domain: Arc<Domain>,
     pub_key: PublicKey,
+    session_id: u64,
+    producer: Producer,
+    /// The fingerprint of the public key that the chunks are encrypted for.
+    key_fingerprint: Option<Fingerprint>,
+    /// The key agreement algorithm recorded in the chunks, `None` if the chunks
+    /// are not encrypted or use the default algorithm.
+    key_algorithm: Option<KeyAlgorithm>,
+    signing_key: Option<SigningKey>,
+    /// Whether or not to write the index of the records to the chunks.
+    index: bool,
+    clock: Clock,
+    preallocation: Option<u64>,
+    /// Whether or not to write the minute index to the end of the log files.
+    minute_index: bool,
+    min_free_space: Option<u64>,
+    trim_on_low_space: bool,
+    strip_path_prefix: Option<String>,
     rotation: TimeDimension,
+    io_panic_policy: IoPanicPolicy,
+    writeback: Writeback,
+    circuit_breaker: Option<CircuitBreaker>,
+    max_record_len: Option<usize>,
+    /// The storage of the log files, see [`Config::storage`].
+    storage: SharedStorage,
+    storage_kind: StorageKind,
+    last_io_error: Mutex<Option<String>>,
+    /// The random id of the logger instance and the sequence number of its next
+    /// chunk, see [`Extensions::SEQUENCE`].
+    instance: u64,
+    sequence: AtomicU64,
+    /// The count of the chunks of the double buffering system handled by the IO
+    /// thread, see `Core::switches`.
+    written_chunks: AtomicU64,
+    /// The sequence number of the next record if the records are numbered, see
+    /// [`Config::record_sequence`].
+    record_sequence: Option<AtomicU64>,
     tracker: Option<Tracker>,
 }
 
 impl Context {
+    /// Initializes the chunk, then writes its sequence number, the rotation, the
+    /// producer and the key fingerprint to its header, and reserves the index field
+    /// and the signature field if they are enabled.
+    pub(crate) fn initialize_chunk<T>(&self, chunk: &mut Chunk<T>, datetime: DateTime)
+    where
+        T: DerefMut<Target = [u8]>,
+    {
+        chunk.initialize(datetime, self.pub_key, self.session_id);
+        // It is written when the chunk is sealed.
+        chunk
+            .push_extension(Extensions::CHECKSUM, &[0; Extensions::CHECKSUM_LEN])
+            .unwrap_or_else(track!(self.tracker));
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let sequence = Extensions::encode_sequence(self.instance, sequence);
+        chunk.push_extension(Extensions::SEQUENCE, &sequence).unwrap_or_else(track!(self.tracker));
+        chunk
+            .push_extension(Extensions::ROTATION, &[self.rotation as u8])
+            .unwrap_or_else(track!(self.tracker));
+        self.producer.write_to(chunk).unwrap_or_else(track!(self.tracker));
+        if let Some(fingerprint) = &self.key_fingerprint {
+            chunk
+                .push_extension(Extensions::KEY_FINGERPRINT, fingerprint)
+                .unwrap_or_else(track!(self.tracker));
+        }
+        if let Some(algorithm) = self.key_algorithm {
+            chunk
+                .push_extension(Extensions::KEY_ALGORITHM, &[algorithm as u8])
+                .unwrap_or_else(track!(self.tracker));
+        }
+        if self.index {
+            chunk
+                .push_extension(Extensions::INDEX, &[0; Index::LEN])
+                .unwrap_or_else(track!(self.tracker));
+        }
+        if self.signing_key.is_some() {
+            chunk
+                .push_extension(Extensions::SIGNATURE, &[0; Extensions::SIGNATURE_LEN])
+                .unwrap_or_else(track!(self.tracker));
+        }
+    }
+
+    /// Length of the extension fields written by [`Context::initialize_chunk`].
     #[inline]
-    fn new(
-        domain: Domain,
-        pub_key: Option<PublicKey>,
-        rotation: TimeDimension,
-        tracker: Option<Tracker>,
-    ) -> Self {
-        Self {
-            domain: Arc::new(domain),
-            pub_key: pub_key.unwrap_or(EMPTY_PUBLIC_KEY),
-            rotation,
-            tracker,
+    pub(crate) fn extensions_len(&self) -> usize {
+        let fingerprint_len = self.key_fingerprint.as_ref().map_or(0, |f| Extensions::field_len(f));
+        let algorithm_len = self.key_algorithm.map_or(0, |a| Extensions::field_len(&[a as u8]));
+        let index_len = Extensions::field_len(&[0; Index::LEN]);
+        let signature_len = Extensions::field_len(&[0; Extensions::SIGNATURE_LEN]);
+        Extensions::field_len(&[0; Extensions::CHECKSUM_LEN])
+            + Extensions::field_len(&[0; Extensions::SEQUENCE_LEN])
+            + Extensions::field_len(&[self.rotation as u8])
+            + self.producer.extensions_len()
+            + fingerprint_len
+            + algorithm_len
+            + if self.index { index_len } else { 0 }
+            + if self.signing_key.is_some() { signature_len } else { 0 }
+    }
+
+    /// Signs the chunk that has been finalized if the chunks are signed, then seals
+    /// it with its checksum (see [`Chunk::seal`]).
+    #[inline]
+    pub(crate) fn seal_chunk<T>(&self, chunk: &mut Chunk<T>)
+    where
+        T: DerefMut<Target = [u8]>,
+    {
+        if let Some(key) = &self.signing_key {
+            chunk.sign(key);
         }
+        chunk.seal();
     }
 
     /// Determines whether the chunk needs to be rotated.
@@ -126,13 +570,77 @@ impl Context {
         !self.chunk_dimension().check_match(chunk.start_datetime(), new_record.meta().datetime())
     }
 
-    /// Determines whether the log file needs to be rotated.
-    #[inline]
-    pub(crate) fn rotate_file<B>(&self, logfile: &Logfile, new_chunk: &Chunk<B>) -> bool
+    /// Checks whether the clock has jumped backwards, i.e., the new record is
+    /// earlier than the records in the chunk beyond the tolerance, and tracks it.
+    pub(crate) fn is_clock_regressed<B>(&self, chunk: &Chunk<B>, new_record: &Record) -> bool
     where
         B: Deref<Target = [u8]>,
     {
-        !self.file_dimension().check_match(new_chunk.start_datetime(), logfile.datetime())
+        let (end, datetime) = (chunk.end_datetime(), new_record.meta().datetime());
+        let is_regressed = chunk.payload_len() > 0 && end - datetime > CLOCK_REGRESSION_TOLERANCE;
+        if is_regressed {
+            let context = format!("from {end} to {datetime}");
+            track_event!(self.tracker, TrackCategory::ClockRegressed, context);
+        }
+        is_regressed
+    }
+
+    /// Determines whether the log file needs to be rotated for the new chunk
+    /// starting at the datetime, i.e., the chunk is in another file dimension, or it
+    /// would be out of order in the log file after the clock jumped backwards.
+    #[inline]
+    pub(crate) fn rotate_file(&self, logfile: &Logfile, datetime: DateTime) -> bool {
+        !self.file_dimension().check_match(datetime, logfile.datetime())
+            || logfile.is_out_of_order(datetime, CLOCK_REGRESSION_TOLERANCE)
+    }
+
+    /// Opens the log file for writing the new chunk starting at the datetime, see
+    /// [`Logfile::open_for_chunk`].
+    #[inline]
+    fn open_logfile(
+        &self,
+        datetime: DateTime,
+        configure: impl Fn(Logfile) -> Logfile,
+    ) -> Result<Logfile, io::Error> {
+        let domain = Arc::clone(&self.domain);
+        Logfile::open_for_chunk(domain, datetime, CLOCK_REGRESSION_TOLERANCE, configure)
+    }
+
+    /// Checks whether there is enough free space to create a new log file, the
+    /// oldest log files (except the active one) are deleted to free up the space if
+    /// it is configured.
+    fn check_free_space(&self, active: Option<&Logfile>) -> Result<(), Error> {
+        let Some(required) = self.min_free_space else {
+            return Ok(());
+        };
+        let available = || common::available_space(&self.domain.directory);
+        let mut space = match available() {
+            // The free space can't be obtained on the platform (e.g., `wasm32`).
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => return Ok(()),
+            result => result?,
+        };
+
+        if space < required && self.trim_on_low_space {
+            let active = active.map(Logfile::path);
+            let mut logfiles =
+                Logfile::logfiles(&self.domain, logfile::Mode::Read, Arc::clone(&self.storage))?
+                    .filter(|f| active.as_ref() != Some(&f.path()))
+                    .collect::<Vec<_>>();
+            logfiles.sort_by_key(|f| f.datetime());
+
+            for logfile in logfiles {
+                if space >= required {
+                    break;
+                }
+                logfile.delete()?;
+                space = available()?;
+            }
+        }
+
+        if space < required {
+            return Err(Error::InsufficientSpace { available: space, required });
+        }
+        Ok(())
     }
 
     /// Time dimension for chunk rotation.
@@ -144,24 +652,21 @@ impl Context {
     /// Time dimension for log file rotation.
     #[inline]
     fn file_dimension(&self) -> TimeDimension {
-        match self.chunk_dimension() {
-            TimeDimension::Minute => TimeDimension::Hour,
-            TimeDimension::Hour => TimeDimension::Day,
-            TimeDimension::Day => TimeDimension::Day,
-        }
+        self.chunk_dimension().file_dimension()
     }
 }
 
-/// Returns a closure that reports the error to tracker.
-macro_rules! track {
-    ($tracker:expr) => {{
-        |err| {
-            if let Some(ref tracker) = $tracker {
-                tracker.track(err.into(), file!(), line!());
-            }
-        }
-    }};
-}
+/// How far the record may be earlier than the previous ones before it is considered
+/// as a clock regression, as the records logged concurrently may arrive slightly out
+/// of order.
+const CLOCK_REGRESSION_TOLERANCE: chrono::TimeDelta = chrono::TimeDelta::seconds(1);
+
+/// The length of the memory buffer while the application is under memory pressure.
+const MEMORY_PRESSURE_BUFFER_LEN: usize = 16 * 1024;
+
+/// The attempts to shrink or restore the memory buffer without blocking logging,
+/// see [`Logger::set_memory_pressure`].
+const MEMORY_PRESSURE_ATTEMPTS: usize = 3;
 
 /// The `Core Logger` associated with the specified `Compressor`, `Encryptor` and
 /// `Memory`.
@@ -170,58 +675,442 @@ macro_rules! track {
 /// `AES` encryption algorithm to process the logs.
 type LoggerInner = Core<Option<ZstdCompressor>, Option<AesEncryptor>, EitherMemory>;
 
+/// The chunks recovered from the mmap buffer, which are written back on the
+/// initialization of the logger.
+type Recovered = Vec<Chunk<Vec<u8>>>;
+
 impl LoggerInner {
     #[inline]
-    pub fn new_inner(domain: Domain, config: Config) -> Self {
-        let memory = Self::initialize_memory(&domain, &config);
+    pub fn new_inner(domain: Domain, mut config: Config) -> Self {
+        let session_id = config.session_id.unwrap_or_else(|| OsRng.next_u64());
+        let split = Self::new_split(&domain, &mut config, session_id);
+
+        let domain = Arc::new(domain);
+        let clock = Clock::new(mem::take(&mut config.timestamp_source), config.elapsed_time);
+        let (backing, storage_kind) = Self::initialize_backing(&domain, &clock, &config);
+
+        let keys = config.key.and_then(|k| {
+            ecdh::Keys::with_algorithm(config.key_algorithm, &k)
+                .map_err(track!(config.tracker))
+                .ok()
+        });
+        let encryptor = || keys.as_ref().map(|k| AesEncryptor::new(&k.encryption_key));
+        let compressor = |level| {
+            ZstdCompressor::new(level, &config.zstd_params).map_err(track!(config.tracker)).ok()
+        };
 
-        let keys =
-            config.key.and_then(|k| ecdh::Keys::new(&k).map_err(track!(config.tracker)).ok());
-        let encryptor = keys.as_ref().map(|k| AesEncryptor::new(&k.encryption_key));
+        let processor = Processor::new(
+            compressor(config.compression_level),
+            config.compression_level,
+            encryptor(),
+            config.deduplicate,
+        );
+        let processor = processor.adaptive(config.compression_level_auto.take());
+        let isolator = Processor::new(
+            compressor(config.isolated_compression_level),
+            config.isolated_compression_level,
+            encryptor(),
+            false,
+        );
+
+        let limiter = config.rate_limit.map(RateLimiter::new);
+
+        if config.manifest && config.storage.is_none() {
+            let key = config.key.filter(|_| keys.is_some());
+            let key = key.map(|k| {
+                (config.key_algorithm, encrypt::key_fingerprint(config.key_algorithm.unembed(&k)))
+            });
+            Manifest::current(config.rotation, key, config.app_version.clone(), clock.now())
+                .write(&domain)
+                .unwrap_or_else(track!(config.tracker));
+        }
 
-        let compressor =
-            ZstdCompressor::new(config.compression_level).map_err(track!(config.tracker)).ok();
+        let context = Context {
+            domain,
+            pub_key: keys.as_ref().map_or(EMPTY_PUBLIC_KEY, |k| k.public_key),
+            session_id,
+            producer: Producer::current(config.app_version, config.os_version),
+            key_fingerprint: config
+                .key
+                .filter(|_| keys.is_some())
+                .map(|k| ecdh::fingerprint(config.key_algorithm.unembed(&k))),
+            key_algorithm: keys
+                .as_ref()
+                .and(Some(config.key_algorithm))
+                .filter(|a| *a != KeyAlgorithm::default()),
+            signing_key: config.signing_key,
+            index: config.chunk_index,
+            clock,
+            preallocation: config.preallocation,
+            minute_index: config.minute_index,
+            min_free_space: config.min_free_space,
+            trim_on_low_space: config.trim_on_low_space,
+            strip_path_prefix: config.strip_path_prefix,
+            rotation: config.rotation,
+            io_panic_policy: config.io_panic_policy,
+            writeback: config.writeback,
+            circuit_breaker: config.circuit_breaker,
+            max_record_len: config.max_record_len,
+            storage: config.storage.unwrap_or_else(FileSystem::shared),
+            storage_kind,
+            last_io_error: Mutex::new(None),
+            instance: OsRng.next_u64(),
+            sequence: AtomicU64::new(0),
+            written_chunks: AtomicU64::new(0),
+            record_sequence: config.record_sequence.then(|| AtomicU64::new(0)),
+            tracker: config.tracker,
+        };
 
-        let context =
-            Context::new(domain, keys.map(|k| k.public_key), config.rotation, config.tracker);
+        let mut core = Self::new(
+            context,
+            processor,
+            isolator,
+            backing,
+            limiter,
+            config.mirror,
+            config.record_sink,
+        );
+        // The redaction is always the first stage.
+        let redact = config.redactor.map(|r| Box::new(RedactStage(r)) as Box<dyn PipelineStage>);
+        core.stages = redact.into_iter().chain(config.stages).collect();
+        core.split = split;
+        core
+    }
 
-        Self::new(context, compressor, encryptor, memory)
+    /// Constructs the core of the split stream, which shares the session, the time
+    /// source and the tracker with the logger, see [`Config::split_by_level`].
+    fn new_split(
+        domain: &Domain,
+        config: &mut Config,
+        session_id: u64,
+    ) -> Option<Split<Option<ZstdCompressor>, Option<AesEncryptor>, EitherMemory>> {
+        if config.split_levels.is_empty() {
+            return None;
+        }
+        // The records have been stamped, processed by the stages and rate limited
+        // before they are split.
+        let split_config = Config {
+            use_mmap: config.use_mmap,
+            buffer_len: config.buffer_len / 4,
+            rotation: config.rotation,
+            io_panic_policy: config.io_panic_policy,
+            writeback: config.writeback,
+            circuit_breaker: config.circuit_breaker,
+            key: config.key,
+            key_algorithm: config.key_algorithm,
+            signing_key: config.signing_key,
+            chunk_index: config.chunk_index,
+            compression_level: config.compression_level,
+            zstd_params: config.zstd_params.clone(),
+            isolated_compression_level: config.isolated_compression_level,
+            session_id: Some(session_id),
+            minute_index: config.minute_index,
+            manifest: config.manifest,
+            min_free_space: config.min_free_space,
+            trim_on_low_space: config.trim_on_low_space,
+            timestamp_source: config.timestamp_source.share(),
+            app_version: config.app_version.clone(),
+            os_version: config.os_version.clone(),
+            storage: config.storage.clone(),
+            tracker: track::share(&mut config.tracker),
+            ..Default::default()
+        };
+        let core = Self::new_inner(domain.split_domain(), split_config);
+        Some(Split { levels: mem::take(&mut config.split_levels), core: Box::new(core) })
+    }
+
+    /// Initializes the storage of the chunks, which is the mapped log file in the
+    /// direct mode, or the buffer otherwise.
+    fn initialize_backing(
+        domain: &Arc<Domain>,
+        clock: &Clock,
+        config: &Config,
+    ) -> (Backing<EitherMemory>, StorageKind) {
+        // The custom storage can't be mapped.
+        (config.direct_mmap && config.storage.is_none())
+            .then(|| {
+                let datetime = clock.now();
+                let tolerance = CLOCK_REGRESSION_TOLERANCE;
+                let logfile =
+                    Logfile::open_for_chunk(Arc::clone(domain), datetime, tolerance, |f| f)?;
+                // The chunks are limited to a side of the double buffer like the buffered mode.
+                Direct::new(logfile, config.buffer_len / 2)
+            })
+            .and_then(|direct| {
+                direct.map_err(track!(config.tracker, TrackCategory::BufferFallback)).ok()
+            })
+            .map(|direct| (Backing::Direct(direct), StorageKind::Direct))
+            .unwrap_or_else(|| {
+                let (memory, buffer_key, moved) = Self::initialize_memory(domain, config);
+                let kind = match memory {
+                    EitherMemory::Mmap(_) => StorageKind::Mmap,
+                    EitherMemory::Vec(_) => StorageKind::Memory,
+                };
+                (Backing::Buffer(buffer::initialize(memory, buffer_key), moved), kind)
+            })
     }
 
-    fn initialize_memory(domain: &Domain, config: &Config) -> EitherMemory {
+    /// Initializes the memory of the buffer and the key checking its header, along
+    /// with the chunks recovered from the moved sides of the buffer file (see
+    /// [`LoggerInner::recover_moved`]).
+    fn initialize_memory(
+        domain: &Domain,
+        config: &Config,
+    ) -> (EitherMemory, buffer::Key, Option<Recovered>) {
         config
             .use_mmap
-            .then(|| {
-                let path =
-                    domain.directory.join(&domain.identifier).with_extension(MMAP_BUFFER_EXTENSION);
-                Mmap::new(path, config.buffer_len).map(EitherMemory::Mmap)
+            .then(|| Self::initialize_mmap(domain, config))
+            .and_then(|mmap| {
+                mmap.map_err(track!(config.tracker, TrackCategory::BufferFallback)).ok()
             })
-            .and_then(|mmap| mmap.map_err(track!(config.tracker)).ok())
             .unwrap_or_else(|| {
-                let mut vec = Vec::with_capacity(config.buffer_len);
-                #[allow(clippy::uninit_vec)]
-                unsafe {
-                    vec.set_len(config.buffer_len);
-                }
-                EitherMemory::Vec(vec)
+                // The memory is not persistent, so the key doesn't need to be either.
+                let mut key = buffer::Key::default();
+                OsRng.fill_bytes(&mut key);
+                (Self::vec_memory(config.buffer_len), key, None)
             })
     }
+
+    /// Allocates the memory of the buffer, which is not persistent.
+    fn vec_memory(len: usize) -> EitherMemory {
+        let mut vec = Vec::with_capacity(len);
+        #[allow(clippy::uninit_vec)]
+        unsafe {
+            vec.set_len(len);
+        }
+        EitherMemory::Vec(vec)
+    }
+
+    /// Shrinks the memory buffer to reclaim the memory while the application is
+    /// under memory pressure, or restores it afterwards.
+    ///
+    /// The buffered records must have been written to the log file before that,
+    /// i.e., no record has been input since the `inputs` whose rotation has been
+    /// awaited. Otherwise the buffer is kept, and `false` is returned to retry.
+    fn set_memory_pressure(&mut self, flag: bool, inputs: u64) -> bool {
+        let Storage::Buffered(buffer) = &mut self.storage else {
+            return true;
+        };
+        // The mmap buffer is backed by the file, so its pages can be reclaimed by the OS.
+        if self.context.storage_kind != StorageKind::Memory {
+            return true;
+        }
+        if self.inputs != inputs {
+            return false;
+        }
+        let len = match (flag, self.shrunk_len) {
+            (true, None) => {
+                self.shrunk_len = Some(buffer.len());
+                MEMORY_PRESSURE_BUFFER_LEN
+            }
+            (false, Some(len)) => {
+                self.shrunk_len = None;
+                len
+            }
+            _ => return true,
+        };
+
+        drop(buffer.replace(Self::vec_memory(len)));
+        self.initialize_chunk(self.context.clock.now());
+        true
+    }
+
+    /// Maps the buffer file, and loads the key persisted alongside.
+    ///
+    /// If the buffer file is corrupted, it is quarantined and a new one is mapped.
+    /// If its length differs from the configured one or it has the legacy header,
+    /// the chunks left in it are recovered before it is resized or re-initialized.
+    fn initialize_mmap(
+        domain: &Domain,
+        config: &Config,
+    ) -> Result<(EitherMemory, buffer::Key, Option<Recovered>), Error> {
+        let path = domain.directory.join(&domain.identifier).with_extension(MMAP_BUFFER_EXTENSION);
+        let key_path = path.with_extension(MMAP_BUFFER_KEY_EXTENSION);
+
+        let len = fs::metadata(&path).map_or(0, |m| m.len() as usize);
+        let moved = if len > 0 {
+            let configured_len = mmap::round_up_page_size(config.buffer_len);
+            Self::recover_moved(&path, len, configured_len, &Self::load_buffer_key(&key_path)?)?
+        } else {
+            None
+        };
+
+        let mut mmap = Mmap::new(&path, config.buffer_len)?;
+        let key = Self::load_buffer_key(&key_path)?;
+
+        if buffer::is_corrupted(&mmap, &key) {
+            drop(mmap);
+            let quarantined_path = path.with_extension(QUARANTINED_BUFFER_EXTENSION);
+            fs::rename(&path, &quarantined_path)?;
+            Err(Error::BufferCorrupted(quarantined_path)).unwrap_or_else(track!(config.tracker));
+            mmap = Mmap::new(&path, config.buffer_len)?;
+        }
+
+        Ok((EitherMemory::Mmap(mmap), key, moved))
+    }
+
+    /// Recovers the non-empty chunks left in the buffer file of the length, if its
+    /// sides are to be moved, i.e., the length differs from the configured one
+    /// (i.e., [`Config::buffer_len`] has been changed) or it has the legacy header
+    /// (see [`buffer::legacy_sides`]).
+    ///
+    /// The chunks beyond the new length are cut off by the resizing, so they are
+    /// recovered with the old layout before that. The corrupted buffer file is left
+    /// to be quarantined.
+    fn recover_moved(
+        path: &Path,
+        len: usize,
+        configured_len: usize,
+        key: &buffer::Key,
+    ) -> Result<Option<Recovered>, Error> {
+        let mmap = Mmap::new(path, len)?;
+        if let Some((input, output)) = buffer::legacy_sides(&mmap) {
+            let mut chunks = Self::recover_chunks(output, false);
+            chunks.extend(Self::recover_chunks(input, true));
+            return Ok(Some(chunks));
+        }
+        if len == configured_len {
+            return Ok(None);
+        }
+        if buffer::is_corrupted(&mmap, key) {
+            return Ok(Some(Vec::new()));
+        }
+
+        let (mut input, mut output) = buffer::initialize(EitherMemory::Mmap(mmap), *key);
+        let (input_chunk, output_chunk) =
+            (Chunk::bind(input.handle()), Chunk::bind(output.handle()));
+        // The output chunk has been rotated before the input chunk in progress.
+        let mut chunks = Self::recover_chunks(output_chunk.as_raw(), false);
+        chunks.extend(Self::recover_chunks(input_chunk.as_raw(), true));
+        Ok(Some(chunks))
+    }
+
+    /// Loads the key from the file, or creates a random one if it doesn't exist or
+    /// is invalid.
+    fn load_buffer_key(path: &Path) -> Result<buffer::Key, io::Error> {
+        if let Some(key) = fs::read(path).ok().and_then(|key| key.try_into().ok()) {
+            return Ok(key);
+        }
+        let mut key = buffer::Key::default();
+        OsRng.fill_bytes(&mut key);
+        fs::write(path, key)?;
+        Ok(key)
+    }
 }
 
 /// Operation for `Core` and `Processor`.
 #[derive(Clone, Copy)]
 enum Operation<'a> {
-    Input(&'a Record<'a>),
+    /// Inputs the record, along with its encoded bytes if they are shared by the
+    /// loggers (see [`MultiLogger`]).
+    ///
+    /// [`MultiLogger`]: crate::MultiLogger
+    Input(&'a Record<'a>, Option<&'a [u8]>),
     Rotate,
     Writeback,
 }
 
+/// The storage that the `Core Logger` is constructed with.
+enum Backing<M> {
+    /// The double buffer, the `Core Logger` writes to one side of it and the IO
+    /// handler writes the other side to the log file.
+    ///
+    /// It comes with the chunks recovered from the buffer file if its sides have
+    /// been moved, see [`LoggerInner::recover_moved`].
+    Buffer(buffer::Couple<M>, Option<Recovered>),
+    /// The log file written in place, see [`Config::direct_mmap`].
+    Direct(Direct),
+}
+
+/// The storage of the chunk that the `Core Logger` writes to.
+enum Storage<M> {
+    Buffered(Buffer<M>),
+    Direct(Direct),
+}
+
+impl<M> Storage<M>
+where
+    M: Memory,
+{
+    /// Prepare to read or write the chunk.
+    #[inline]
+    fn handle(&mut self) -> StorageHandle<'_, M> {
+        match self {
+            Self::Buffered(buffer) => StorageHandle::Buffered(buffer.handle()),
+            Self::Direct(direct) => StorageHandle::Direct(direct.as_mut_slice()),
+        }
+    }
+}
+
+/// A handle for reading and writing the chunk in the [`Storage`].
+enum StorageHandle<'a, M> {
+    Buffered(BufferHandle<'a, M>),
+    Direct(&'a mut [u8]),
+}
+
+impl<M> Deref for StorageHandle<'_, M>
+where
+    M: Memory,
+{
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Buffered(handle) => handle,
+            Self::Direct(slice) => slice,
+        }
+    }
+}
+
+impl<M> DerefMut for StorageHandle<'_, M>
+where
+    M: Memory,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Buffered(handle) => handle,
+            Self::Direct(slice) => slice,
+        }
+    }
+}
+
 /// Represents the `Core Logger`.
 struct Core<C, E, M> {
     context: Arc<Context>,
     processor: Processor<C, E>,
-    buffer: Buffer<M>,
+    /// The processor for the records stored in chunks of their own.
+    isolator: Processor<C, E>,
+    storage: Storage<M>,
     io_runloop: Runloop<IoEvent>,
+    limiter: Option<RateLimiter>,
+    mirror: Option<Mirror>,
+    record_sink: Option<Box<dyn RecordSink + Send + Sync>>,
+    /// The custom stages of the processing pipeline, see
+    /// [`Config::pipeline_stage`].
+    stages: Vec<Box<dyn PipelineStage>>,
+    /// The buffers of the content and the encoded bytes of the record being
+    /// processed by the stages, which are reused across the records.
+    stage_buffers: (String, Vec<u8>),
+    /// The original length of the memory buffer while it is shrunk, see
+    /// [`Logger::set_memory_pressure`].
+    shrunk_len: Option<usize>,
+    /// The count of the records input to the buffer, so that the records logged
+    /// while waiting for the IO operations are noticed.
+    inputs: u64,
+    /// The times the double buffering system has been switched, each of which
+    /// hands a chunk to the IO thread.
+    switches: u64,
+    /// The split stream of the records, see [`Config::split_by_level`].
+    split: Option<Split<C, E, M>>,
+}
+
+/// The records of the levels that are also written to a separate stream of log
+/// files, see [`Config::split_by_level`].
+struct Split<C, E, M> {
+    levels: Vec<Level>,
+    core: Box<Core<C, E, M>>,
 }
 
 impl<C, E, M> Core<C, E, M>
@@ -230,22 +1119,65 @@ where
     E: Encryptor,
     M: Memory,
 {
-    fn new(context: Context, compressor: C, encryptor: E, memory: M) -> Self {
+    fn new(
+        context: Context,
+        processor: Processor<C, E>,
+        isolator: Processor<C, E>,
+        backing: Backing<M>,
+        limiter: Option<RateLimiter>,
+        mirror: Option<Mirror>,
+        record_sink: Option<Box<dyn RecordSink + Send + Sync>>,
+    ) -> Self {
         let context = Arc::new(context);
-        let processor = Processor::new(compressor, encryptor);
-
-        let (input_buffer, output_buffer) = Self::initialize_buffer(memory, &context);
-        let io_runloop = Io::new(Arc::clone(&context), output_buffer).run();
 
-        let mut core = Self { context, processor, buffer: input_buffer, io_runloop };
+        let (storage, output_buffer, recovered) = match backing {
+            Backing::Buffer(buffer, moved) => {
+                let ((input_buffer, output_buffer), recovered) =
+                    Self::initialize_buffer(buffer, moved, &context);
+                (Storage::Buffered(input_buffer), Some(output_buffer), recovered)
+            }
+            Backing::Direct(direct) => (Storage::Direct(direct), None, Vec::new()),
+        };
+        let io_runloop = Io::new(Arc::clone(&context), output_buffer, recovered).run();
+
+        let mut core = Self {
+            context,
+            processor,
+            isolator,
+            storage,
+            io_runloop,
+            limiter,
+            mirror,
+            record_sink,
+            stages: Vec::new(),
+            stage_buffers: Default::default(),
+            shrunk_len: None,
+            inputs: 0,
+            switches: 0,
+            split: None,
+        };
+        if let Storage::Direct(_) = core.storage {
+            core.initialize_chunk(core.context.clock.now());
+        }
         // Attempts to write previously unwritten chunk to the logfile.
         core.on(Operation::Writeback);
 
         core
     }
 
-    fn initialize_buffer(memory: M, context: &Context) -> (Buffer<M>, Buffer<M>) {
-        let (mut input, mut output) = buffer::initialize(memory);
+    /// Initializes the chunks of the buffer, and returns the chunks recovered from
+    /// the buffer before that.
+    ///
+    /// If the sides of the buffer have been moved, the chunks have been recovered
+    /// with the old layout (`moved`), and the moved ones are not scanned again.
+    fn initialize_buffer(
+        buffer: buffer::Couple<M>,
+        moved: Option<Recovered>,
+        context: &Context,
+    ) -> (buffer::Couple<M>, Recovered) {
+        let (mut input, mut output) = buffer;
+        let is_moved = moved.is_some();
+        let mut recovered = moved.unwrap_or_default();
         {
             let (mut input_chunk, mut output_chunk) =
                 (Chunk::bind(input.handle()), Chunk::bind(output.handle()));
@@ -253,27 +1185,76 @@ where
             // If either side of the buffer is invalid, both sides need to be initialized.
             // Due to the internal structure of the double buffer system, when the buffer length
             // configuration is changed, one chunk must be invalid.
-            if !input_chunk.validate() || !output_chunk.validate() {
-                let now = chrono::Utc::now();
-                input_chunk.initialize(now, context.pub_key);
-                output_chunk.initialize(now, context.pub_key);
+            if is_moved || !input_chunk.validate() || !output_chunk.validate() {
+                // Before that, the chunks left in the mmap buffer are recovered by scanning
+                // both sides for the chunk headers, which may be damaged. The memory buffer is
+                // never left over.
+                if context.storage_kind == StorageKind::Mmap && !is_moved {
+                    // The chunks that have been initialized are damaged, e.g., by the file
+                    // system corruption.
+                    if input_chunk.is_initialized() || output_chunk.is_initialized() {
+                        let sides: Vec<_> = [("input", &input_chunk), ("output", &output_chunk)]
+                            .into_iter()
+                            .filter_map(|(side, chunk)| (!chunk.validate()).then_some(side))
+                            .collect();
+                        let category = TrackCategory::BufferReinitialized;
+                        track_event!(
+                            context.tracker,
+                            category,
+                            format!("{} chunk", sides.join(", "))
+                        );
+                    }
+                    for (chunk, is_input) in [(&input_chunk, true), (&output_chunk, false)] {
+                        recovered.extend(Self::recover_chunks(chunk.as_raw(), is_input));
+                    }
+                }
+
+                let now = context.clock.now();
+                context.initialize_chunk(&mut input_chunk, now);
+                context.initialize_chunk(&mut output_chunk, now);
+            }
+        }
+        ((input, output), recovered)
+    }
+
+    /// Recovers the non-empty chunks in the bytes of a buffer side.
+    ///
+    /// The chunks of the input side are in progress, so they are marked as written
+    /// back.
+    fn recover_chunks(bytes: &[u8], is_input: bool) -> Vec<Chunk<Vec<u8>>> {
+        let mut chunks = Vec::new();
+        let mut pos = 0;
+        while let Some(start) = chunk::find(bytes, pos..bytes.len())
+            && let Some(mut chunk) = Chunk::recover(&bytes[start..])
+        {
+            pos = start + chunk.len();
+            if chunk.payload_len() > 0 {
+                if is_input {
+                    chunk.set_writeback(true);
+                }
+                chunks.push(chunk);
             }
         }
-        (input, output)
+        chunks
     }
 
     fn on(&mut self, operation: Operation) {
-        let mut chunk = Chunk::bind(self.buffer.handle());
+        let mut chunk = Chunk::bind(self.storage.handle());
 
         let write_operation = match operation {
             Operation::Rotate => Some(operation),
             // Writes back if chunk payload is not empty.
             Operation::Writeback => (chunk.payload_len() > 0).then(|| {
-                chunk.set_writeback();
+                chunk.set_writeback(true);
+                let context = format!("{} bytes", chunk.payload_len());
+                track_event!(self.context.tracker, TrackCategory::WritebackRecovered, context);
                 operation
             }),
-            // Checks if rotation is required.
-            Operation::Input(record) => (chunk.is_almost_full()
+            // Checks if rotation is required. The records after the clock jumped backwards
+            // start a new chunk, so that the time range of each chunk remains ordered.
+            Operation::Input(record, _) => (chunk.is_almost_full()
+                || chunk.remaining_len() < Self::payload_len_bound(record.encoded_len_bound())
+                || self.context.is_clock_regressed(&chunk, record)
                 || self.context.rotate_chunk(&chunk, record))
             .then_some(Operation::Rotate),
         };
@@ -282,46 +1263,384 @@ where
             self.processor
                 .process(write_operation, &mut chunk)
                 .unwrap_or_else(track!(self.context.tracker));
+            self.context.seal_chunk(&mut chunk);
 
-            // If the length of the chunk is greater than 0, it means that there are bytes to be
-            // written to the file, we need to switch the buffer and perform IO write operation,
-            // otherwise we can reuse the chunk and not perform IO write operation.
-            if chunk.payload_len() > 0 {
-                // Switches the double buffering system then rebinds the chunk.
-                drop(chunk);
-                self.buffer.switch();
-                chunk = Chunk::bind(self.buffer.handle());
+            let is_empty = chunk.payload_len() == 0;
+            drop(chunk);
+
+            // Re-initialize the chunk.
+            let datetime = match operation {
+                Operation::Input(record, _) => record.meta().datetime(),
+                Operation::Rotate | Operation::Writeback => self.context.clock.now(),
+            };
+            self.commit(is_empty, datetime);
+            self.initialize_chunk(datetime);
+            chunk = Chunk::bind(self.storage.handle());
+        }
+
+        if let Operation::Input(record, _) = operation {
+            self.inputs += 1;
+            self.processor
+                .process(operation, &mut chunk)
+                .unwrap_or_else(track!(self.context.tracker));
+            if self.context.index {
+                chunk.index_record(record.meta().level(), record.meta().tag());
+            }
+        }
+    }
+
+    /// Commits the chunk that has been finalized, the next chunk starts at the
+    /// datetime.
+    fn commit(&mut self, is_empty: bool, datetime: DateTime) {
+        match &mut self.storage {
+            // If the chunk is not empty, it means that there are bytes to be written to the
+            // file, we need to switch the buffer and perform IO write operation, otherwise we
+            // can reuse the chunk and not perform IO write operation.
+            Storage::Buffered(_) if !is_empty => {
+                // The side is reused only once its chunk has been handled by the IO
+                // thread, e.g., the chunks are rotated in quick succession.
+                if self.context.written_chunks.load(Ordering::Acquire) < self.switches {
+                    let completion = self.barrier();
+                    completion.map(Completion::wait).unwrap_or_else(track!(self.context.tracker));
+                }
+
+                // Switches the double buffering system.
+                let Storage::Buffered(buffer) = &mut self.storage else {
+                    unreachable!("the storage is buffered");
+                };
+                buffer.switch();
+                self.switches += 1;
 
                 // Performs asynchronous file write IO operation.
                 self.io_runloop
                     .on(IoEvent::WriteChunk)
                     .unwrap_or_else(track!(self.context.tracker));
             }
+            Storage::Buffered(_) => {}
+            // The chunk has been written in place, the log file is rotated here instead of
+            // in the IO handler.
+            Storage::Direct(direct) => {
+                let result = direct.commit().map_err(Error::from).and_then(|_| {
+                    if !self.context.rotate_file(direct.logfile(), datetime) {
+                        return Ok(());
+                    }
+                    self.context.check_free_space(Some(direct.logfile()))?;
+                    Ok(direct.rotate(self.context.open_logfile(datetime, |logfile| logfile)?)?)
+                });
+                result.unwrap_or_else(track_io!(self.context));
+            }
+        }
+    }
 
-            // Re-initialize the chunk.
-            let datetime = match operation {
-                Operation::Input(record) => record.meta().datetime(),
-                Operation::Rotate | Operation::Writeback => chrono::Utc::now(),
+    /// Initializes the chunk that the records are written to.
+    ///
+    /// In the direct mode, the chunk is marked as written back until it is
+    /// committed, so that the chunk left by a crash can still be parsed.
+    fn initialize_chunk(&mut self, datetime: DateTime) {
+        let is_direct = matches!(self.storage, Storage::Direct(_));
+        let mut chunk = Chunk::bind(self.storage.handle());
+        self.context.initialize_chunk(&mut chunk, datetime);
+        chunk.set_writeback(is_direct);
+    }
+
+    /// Logs the record, the rate limiting is applied if it is configured.
+    #[inline]
+    fn log(&mut self, record: &Record, hint: CompressionHint) {
+        self.log_encoded(record, None, hint);
+    }
+
+    /// Logs the record along with its encoded bytes (if any), which are shared by
+    /// the loggers of a [`MultiLogger`].
+    ///
+    /// The encoded bytes are discarded if the record is changed by the logger
+    /// (e.g., stamped or rewritten by the stages), it is encoded again instead.
+    ///
+    /// [`MultiLogger`]: crate::MultiLogger
+    fn log_encoded(&mut self, record: &Record, encoded: Option<&[u8]>, hint: CompressionHint) {
+        // Stamps the session id, the elapsed time and the sequence number on the record
+        // if it doesn't have them, stamps the datetime from the time source, and strips
+        // the path prefix from its code file.
+        let mut record = record.clone();
+        let mut is_changed = false;
+        let meta = record.meta_mut();
+        if meta.session_id().is_none() {
+            meta.set_session_id(Some(self.context.session_id));
+            is_changed = true;
+        }
+        if meta.elapsed().is_none()
+            && let Some(elapsed) = self.context.clock.elapsed()
+        {
+            meta.set_elapsed(Some(elapsed));
+            is_changed = true;
+        }
+        if meta.sequence().is_none()
+            && let Some(sequence) = &self.context.record_sequence
+        {
+            meta.set_sequence(Some(sequence.fetch_add(1, Ordering::Relaxed)));
+            is_changed = true;
+        }
+        if let Some(datetime) = self.context.clock.stamp(meta.datetime()) {
+            meta.set_datetime(datetime);
+            is_changed = true;
+        }
+        if let Some(prefix) = &self.context.strip_path_prefix {
+            let file = meta.location().file();
+            meta.location_mut().strip_file_prefix(prefix);
+            is_changed |= meta.location().file() != file;
+        }
+
+        let encoded = encoded.filter(|_| !is_changed);
+        let (mut content, mut bytes) = mem::take(&mut self.stage_buffers);
+        self.log_stamped(record, encoded, hint, &mut content, &mut bytes);
+        self.stage_buffers = (content, bytes);
+    }
+
+    /// Logs the stamped record, which is passed through the stages with the
+    /// buffers of its content and encoded bytes.
+    fn log_stamped<'a>(
+        &mut self,
+        mut record: Record<'a>,
+        mut encoded: Option<&'a [u8]>,
+        hint: CompressionHint,
+        content: &'a mut String,
+        bytes: &'a mut Vec<u8>,
+    ) {
+        // Passes the record through the stages before it is encoded, the content
+        // rewritten by a stage is passed to the next stages.
+        if !self.stages.is_empty() {
+            content.clear();
+            content.push_str(record.content());
+            let mut is_rewritten = false;
+            for stage in &mut self.stages {
+                match stage.process(&record, content) {
+                    Verdict::Keep => {}
+                    Verdict::Drop => return,
+                    Verdict::Rewrite => is_rewritten = true,
+                }
+            }
+            if is_rewritten {
+                record.set_content(content);
+                encoded = None;
+            }
+        }
+        // Truncates the content that exceeds the maximum length at a character boundary.
+        if let Some(max_len) = self.context.max_record_len
+            && record.content().len() > max_len
+        {
+            let content = record.content();
+            let len = (0..=max_len).rev().find(|&i| content.is_char_boundary(i)).unwrap_or(0);
+            record.set_content(&content[..len]);
+            record.set_truncated(true);
+            encoded = None;
+        }
+        // Passes the encoded bytes through the stages before they are compressed.
+        if !self.stages.is_empty() {
+            bytes.clear();
+            let result = match encoded {
+                Some(encoded) => {
+                    bytes.extend_from_slice(encoded);
+                    Ok(())
+                }
+                None => record.encode(&mut common::FnSink::new(|encoded: &[u8]| {
+                    bytes.extend_from_slice(encoded);
+                    Ok::<_, Error>(())
+                })),
+            };
+            let Ok(()) = result.map_err(track!(self.context.tracker)) else {
+                return;
             };
-            chunk.initialize(datetime, self.context.pub_key);
+            for stage in &mut self.stages {
+                if stage.process_encoded(&record, bytes) == Verdict::Drop {
+                    return;
+                }
+            }
+            encoded = Some(bytes);
         }
+        let record = &record;
 
-        if let Operation::Input(record) = operation {
-            self.processor
-                .process(Operation::Input(record), &mut chunk)
-                .unwrap_or_else(track!(self.context.tracker));
+        let Some(limiter) = self.limiter.as_mut() else {
+            return self.input(record, encoded, hint);
+        };
+
+        let (accepted, suppressed) = limiter.check(record);
+        if let Some(suppressed) = suppressed {
+            suppressed.with_record(|record| self.input(record, None, CompressionHint::Shared));
+        }
+        if accepted {
+            self.input(record, encoded, hint);
+        }
+    }
+
+    /// Inputs the record to the processor, it is also passed to the record sink and
+    /// mirrored to the console in debug builds.
+    fn input(&mut self, record: &Record, encoded: Option<&[u8]>, hint: CompressionHint) {
+        if let Some(mirror) = self.mirror.filter(|_| cfg!(debug_assertions)) {
+            mirror.write(record);
+        }
+        if let Some(sink) = &self.record_sink {
+            sink.receive(record);
+        }
+        if let Some(split) = &mut self.split
+            && split.levels.contains(&record.meta().level())
+        {
+            split.core.input(record, encoded, hint);
+        }
+        match hint {
+            // The record that may overflow even an empty chunk is stored in a chunk of its
+            // own instead.
+            CompressionHint::Shared if !self.is_oversized(record, encoded) => {
+                self.on(Operation::Input(record, encoded))
+            }
+            CompressionHint::Shared | CompressionHint::Isolated => {
+                self.isolate(record, encoded).unwrap_or_else(track!(self.context.tracker))
+            }
+        }
+    }
+
+    /// Checks whether the record may not fit in the chunk of the storage, even if
+    /// the chunk is empty.
+    #[inline]
+    fn is_oversized(&mut self, record: &Record, encoded: Option<&[u8]>) -> bool {
+        let capacity = Chunk::bind(self.storage.handle()).capacity();
+        let encoded_len = encoded.map_or_else(|| record.encoded_len_bound(), <[u8]>::len);
+        Self::payload_len_bound(encoded_len) > capacity
+    }
+
+    /// An upper bound of the length of the record with the encoded length in the
+    /// chunk payload, after it is compressed and encrypted.
+    #[inline]
+    fn payload_len_bound(encoded_len: usize) -> usize {
+        const AES_BLOCK_SIZE: usize = 16;
+        zstd_safe::compress_bound(encoded_len) + AES_BLOCK_SIZE
+    }
+
+    /// Stores the record in a chunk of its own, which is written to the logfile
+    /// directly instead of going through the buffer.
+    fn isolate(&mut self, record: &Record, encoded: Option<&[u8]>) -> Result<(), Error> {
+        // Rotates the shared chunk first, so that the chunks in the logfile remain in
+        // chronological order.
+        self.on(Operation::Rotate);
+
+        let mut encoded_len = encoded.map_or(0, <[u8]>::len);
+        if encoded.is_none() {
+            record.encode(&mut common::FnSink::new(|bytes: &[u8]| {
+                encoded_len += bytes.len();
+                Ok::<_, EncodingError>(())
+            }))?;
+        }
+
+        // Large enough for the compressed and padded payload.
+        let capacity =
+            Header::LEN + self.context.extensions_len() + Self::payload_len_bound(encoded_len);
+        let mut chunk = Chunk::bind(vec![0; capacity]);
+        self.context.initialize_chunk(&mut chunk, record.meta().datetime());
+
+        self.isolator.process(Operation::Input(record, encoded), &mut chunk)?;
+        chunk.index_record(record.meta().level(), record.meta().tag());
+        self.isolator.process(Operation::Rotate, &mut chunk)?;
+        self.context.seal_chunk(&mut chunk);
+
+        match &mut self.storage {
+            Storage::Buffered(_) => self.io_runloop.on(IoEvent::WriteIsolatedChunk(chunk))?,
+            Storage::Direct(direct) => {
+                let result = direct.append(&chunk);
+                self.initialize_chunk(self.context.clock.now());
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Logs the records staged by the threads, see [`Config::thread_staging_len`].
+    fn drain(&mut self, staging: &Staging) {
+        let result = staging.drain(|record| self.log(record, CompressionHint::Shared));
+        if let Err(err) = result {
+            let context = format!("staged records dropped: {err}");
+            track_event!(self.context.tracker, TrackCategory::Processing, context);
+        }
+    }
+
+    /// Logs the pending suppressed records (if any), then rotates the chunk.
+    fn rotate(&mut self) {
+        if let Some(limiter) = self.limiter.as_mut() {
+            for suppressed in limiter.drain() {
+                suppressed.with_record(|record| self.input(record, None, CompressionHint::Shared));
+            }
+        }
+        self.on(Operation::Rotate);
+        if let Some(split) = &mut self.split {
+            split.core.rotate();
         }
     }
 
     #[inline]
     fn trim(&mut self, lifetime: u64) {
-        self.io_runloop.on(IoEvent::Trim { lifetime }).unwrap_or_else(track!(self.context.tracker));
+        self.io_runloop
+            .on(IoEvent::Trim { lifetime, reporter: None })
+            .unwrap_or_else(track!(self.context.tracker));
+        if let Some(split) = &mut self.split {
+            split.core.trim(lifetime);
+        }
+    }
+
+    #[inline]
+    fn trim_with_report(&mut self, lifetime: u64) -> Result<mpsc::Receiver<TrimReport>, Error> {
+        let (sender, receiver) = mpsc::channel();
+        self.io_runloop.on(IoEvent::Trim { lifetime, reporter: Some(sender) })?;
+        Ok(receiver)
+    }
+
+    /// Rotates the chunk, then records the reason in the header of the chunk started
+    /// by the rotation.
+    fn rotate_with_reason(&mut self, reason: RotateReason) {
+        self.rotate();
+        Chunk::bind(self.storage.handle())
+            .push_extension(Extensions::ROTATE_REASON, &[reason as u8])
+            .unwrap_or_else(track!(self.context.tracker));
+    }
+
+    fn health(&mut self) -> Health {
+        let context = &self.context;
+        Health {
+            storage: context.storage_kind,
+            io_alive: self.io_runloop.is_alive(),
+            last_io_error: context.last_io_error.lock().unwrap().clone(),
+            available_space: common::available_space(&context.domain.directory).ok(),
+            buffer_utilization: Chunk::bind(self.storage.handle()).usage(),
+            compression_level: self.processor.compression_level,
+        }
+    }
+
+    fn buffer_usage(&mut self) -> BufferUsage {
+        let chunk = Chunk::bind(self.storage.handle());
+        BufferUsage { used: chunk.payload_len(), capacity: chunk.capacity() }
+    }
+
+    /// Flushes the buffered records, and returns the completion that resolves once
+    /// the IO operations before it have completed.
+    fn flush_with_completion(&mut self) -> Result<Completion, Error> {
+        self.rotate();
+        self.barrier()
+    }
+
+    /// Returns the completion that resolves once the IO operations before it have
+    /// completed, including the ones of the split stream.
+    fn barrier(&mut self) -> Result<Completion, Error> {
+        let (notifier, completion) = completion::channel();
+        self.io_runloop.on(IoEvent::Barrier(notifier))?;
+        match &mut self.split {
+            Some(split) => Ok(completion.join(split.core.barrier()?)),
+            None => Ok(completion),
+        }
     }
 
     #[inline]
     fn shutdown(self) {
         self.io_runloop.on(IoEvent::Shutdown).unwrap_or_else(track!(self.context.tracker));
         _ = self.io_runloop.join();
+        if let Some(split) = self.split {
+            split.core.shutdown();
+        }
     }
 }
 
@@ -337,7 +1656,12 @@ where
 struct Processor<C, E> {
     encoder: AccumulationEncoder,
     compressor: C,
+    compression_level: i32,
+    /// The tuner of the adaptive compression level, see
+    /// [`Config::compression_level_auto`].
+    tuner: Option<LevelTuner>,
     encryptor: E,
+    deduplicator: Option<Deduplicator>,
 }
 
 impl<C, E> Processor<C, E>
@@ -351,12 +1675,51 @@ where
     const ENCODER_BUFFER_LEN: usize = 256;
 
     #[inline]
-    fn new(compressor: C, encryptor: E) -> Self {
+    fn new(compressor: C, compression_level: i32, encryptor: E, deduplicate: bool) -> Self {
         let encoder = AccumulationEncoder::new(Self::ENCODER_BUFFER_LEN);
-        Self { encoder, compressor, encryptor }
+        let deduplicator = deduplicate.then(Deduplicator::new);
+        Self { encoder, compressor, compression_level, tuner: None, encryptor, deduplicator }
+    }
+
+    /// Tunes the compression level adaptively, see
+    /// [`Config::compression_level_auto`].
+    #[inline]
+    fn adaptive(mut self, auto: Option<AdaptiveCompression>) -> Self {
+        if let Some(auto) = auto {
+            let tuner = LevelTuner::new(auto, self.compression_level);
+            // The level is clamped to the supported ones, so it hardly fails.
+            if self.compressor.set_level(tuner.level()).is_ok() {
+                self.compression_level = tuner.level();
+                self.tuner = Some(tuner);
+            }
+        }
+        self
     }
 
     fn process<B>(&mut self, operation: Operation, chunk: &mut Chunk<B>) -> Result<(), Error>
+    where
+        B: DerefMut<Target = [u8]>,
+    {
+        let start = self.tuner.is_some().then(Moment::now);
+        let result = self.process_inner(operation, chunk);
+
+        if let (Some(tuner), Some(start)) = (self.tuner.as_mut(), start) {
+            match operation {
+                Operation::Input(..) => tuner.record(start.elapsed().unwrap_or_default()),
+                // The new level takes effect from the next chunk.
+                Operation::Rotate => {
+                    if let Some(level) = tuner.tune() {
+                        self.compressor.set_level(level)?;
+                        self.compression_level = level;
+                    }
+                }
+                Operation::Writeback => {}
+            }
+        }
+        result
+    }
+
+    fn process_inner<B>(&mut self, operation: Operation, chunk: &mut Chunk<B>) -> Result<(), Error>
     where
         B: DerefMut<Target = [u8]>,
     {
@@ -373,13 +1736,27 @@ where
         });
 
         match operation {
-            Operation::Input(record) => {
-                self.encoder.encode(record, &mut to_compressor)?;
+            Operation::Input(record, encoded) => {
+                if let Some(deduplicator) = self.deduplicator.as_mut() {
+                    if deduplicator.repeat(record) {
+                        chunk.set_end_datetime(record.meta().datetime());
+                        return Ok(());
+                    }
+                    deduplicator.release(&mut self.encoder, &mut to_compressor)?;
+                    deduplicator.hold(record, encoded)?;
+                } else if let Some(encoded) = encoded {
+                    crate::Sink::<Error>::sink(&mut to_compressor, encoded)?;
+                } else {
+                    self.encoder.encode(record, &mut to_compressor)?;
+                }
                 self.compressor.compress(CompressOp::Flush, &mut to_encryptor)?;
                 chunk.set_end_datetime(record.meta().datetime());
             }
 
             Operation::Rotate => {
+                if let Some(deduplicator) = self.deduplicator.as_mut() {
+                    deduplicator.release(&mut self.encoder, &mut to_compressor)?;
+                }
                 self.compressor.compress(CompressOp::End, &mut to_encryptor)?;
                 self.encryptor.encrypt(EncryptOp::Flush, &mut to_chunk)?;
             }
@@ -391,22 +1768,115 @@ where
     }
 }
 
+/// Holds back the last record, so that identical consecutive records can be
+/// coalesced into a single one with the count of repetitions.
+struct Deduplicator {
+    /// The encoded bytes of the pending record.
+    pending: Vec<u8>,
+    /// The level, the tag and the content of the pending record, which are copied
+    /// out of it, so that the records are compared without decoding it. The level
+    /// is `None` if there is no pending record.
+    level: Option<Level>,
+    tag: Option<String>,
+    content: String,
+    repeats: u32,
+}
+
+impl Deduplicator {
+    #[inline]
+    fn new() -> Self {
+        Self { pending: Vec::new(), level: None, tag: None, content: String::new(), repeats: 0 }
+    }
+
+    /// The pending record, it is decoded from the held bytes without copying.
+    #[inline]
+    fn pending(&self) -> Option<Record<'_>> {
+        Record::decode(&mut self.pending.as_slice()).ok()
+    }
+
+    /// Counts the record as a repetition if it is identical to the pending one in
+    /// level, tag and content, regardless of the other metadata.
+    fn repeat(&mut self, record: &Record) -> bool {
+        let meta = record.meta();
+        let is_repeated = self.level == Some(meta.level())
+            && self.tag.as_deref() == meta.tag()
+            && self.content == record.content();
+        if is_repeated {
+            self.repeats = self.repeats.saturating_add(1);
+        }
+        is_repeated
+    }
+
+    /// Holds the record as the pending one.
+    fn hold(&mut self, record: &Record, encoded: Option<&[u8]>) -> Result<(), Error> {
+        self.pending.clear();
+        self.level = None;
+        self.repeats = 0;
+        match encoded {
+            Some(encoded) => self.pending.extend_from_slice(encoded),
+            None => record.encode(&mut common::FnSink::new(|bytes: &[u8]| {
+                self.pending.extend_from_slice(bytes);
+                Ok::<_, Error>(())
+            }))?,
+        }
+        self.level = Some(record.meta().level());
+        match (&mut self.tag, record.meta().tag()) {
+            (Some(held), Some(tag)) => {
+                held.clear();
+                held.push_str(tag);
+            }
+            (held, tag) => *held = tag.map(String::from),
+        }
+        self.content.clear();
+        self.content.push_str(record.content());
+        Ok(())
+    }
+
+    /// Releases the pending record (if any) with the count of repetitions to the
+    /// sink.
+    fn release<S>(
+        &mut self,
+        encoder: &mut AccumulationEncoder,
+        sink: &mut S,
+    ) -> Result<(), S::Error>
+    where
+        S: codec::Sink,
+    {
+        if let Some(mut record) = self.pending() {
+            record.set_repeats(record.repeats().saturating_add(self.repeats));
+            encoder.encode(&record, sink)?;
+        }
+        self.pending.clear();
+        self.level = None;
+        self.repeats = 0;
+        Ok(())
+    }
+}
+
 /// The IO handler. It is responsible for all file IO interactions.
 ///
 /// It implements the [`runloop::Handle`] trait so that it can invoke a runloop to
 /// handle IO events asynchronously.
 struct Io<M> {
     context: Arc<Context>,
-    buffer: Buffer<M>,
+    /// The output side of the double buffer, there is none in the direct mode.
+    buffer: Option<Buffer<M>>,
     logfile: Option<Logfile>,
+    breaker: Option<Breaker>,
 }
 
 /// IO events that the [`Io`] handler can receive.
 enum IoEvent {
     /// Writes chunk to log file.
     WriteChunk,
-    /// Deletes the expired log files.
-    Trim { lifetime: u64 },
+    /// Writes the chunk of an isolated record to log file.
+    WriteIsolatedChunk(Chunk<Vec<u8>>),
+    /// Deletes the expired log files, the report is sent back if there is a
+    /// reporter.
+    Trim { lifetime: u64, reporter: Option<mpsc::Sender<TrimReport>> },
+    /// Notifies the notifier (by dropping it) once the previous events have been
+    /// handled.
+    Barrier(Notifier),
     /// Shuts down the IO handler.
     Shutdown,
 }
@@ -415,59 +1885,230 @@ impl<M> Io<M>
 where
     M: Memory,
 {
-    #[inline]
-    fn new(context: Arc<Context>, buffer: Buffer<M>) -> Self {
-        let mut io = Io { context, buffer, logfile: None };
+    fn new(
+        context: Arc<Context>,
+        buffer: Option<Buffer<M>>,
+        recovered: Vec<Chunk<Vec<u8>>>,
+    ) -> Self {
+        let breaker = context.circuit_breaker.map(Breaker::new);
+        let mut io = Io { context, buffer, logfile: None, breaker };
+        // Writes the chunks recovered from the damaged buffer.
+        for chunk in recovered {
+            let context = format!("{} bytes salvaged", chunk.payload_len());
+            if !Self::write_back(&io.context, &chunk) {
+                Self::write(&io.context, &mut io.logfile, &mut io.breaker, Some(&chunk), false);
+            }
+            track_event!(io.context.tracker, TrackCategory::WritebackRecovered, context);
+        }
         // Attempts to write previously unwritten chunk to the logfile.
-        if Chunk::bind(io.buffer.handle()).payload_len() > 0 {
+        let len = io.buffer.as_mut().map_or(0, |b| Chunk::bind(b.handle()).payload_len());
+        if len > 0 {
             io.write_chunk();
+            let context = format!("{len} bytes");
+            track_event!(io.context.tracker, TrackCategory::WritebackRecovered, context);
         }
         io
     }
 
     /// Writes chunk to log file.
     fn write_chunk(&mut self) {
-        let mut chunk = Chunk::bind(self.buffer.handle());
+        let Some(buffer) = self.buffer.as_mut() else {
+            return;
+        };
+        let mut chunk = Chunk::bind(buffer.handle());
         // The chunk is empty, there is no need to write to the logfile.
         if chunk.payload_len() == 0 {
             return;
         }
+        if Self::write_back(&self.context, &chunk) {
+            chunk.clear();
+            return;
+        }
+
+        // The deferred chunk is kept in the buffer until the side is reused, so that it
+        // can be written back if the process is terminated during the pause.
+        let retry = self.breaker.as_ref().is_none_or(|b| b.is_due());
+        if Self::write(&self.context, &mut self.logfile, &mut self.breaker, Some(&chunk), retry) {
+            // Sets the chunk length to 0 to indicate that the chunk has finished writing to
+            // the logfile and will not be written again.
+            chunk.clear();
+        }
+    }
+
+    /// Handles the chunk written back by the writeback policy, see [`Writeback`].
+    ///
+    /// Returns `false` if the chunk is not written back or the policy is `Always`,
+    /// that is, it needs to be written to the log file as usual.
+    fn write_back<B>(context: &Context, chunk: &Chunk<B>) -> bool
+    where
+        B: Deref<Target = [u8]>,
+    {
+        if !chunk.is_writeback() {
+            return false;
+        }
+        match context.writeback {
+            Writeback::Always => false,
+            Writeback::Skip => true,
+            Writeback::Quarantine => {
+                let domain = &context.domain;
+                let path = domain
+                    .directory
+                    .join(&domain.identifier)
+                    .with_extension(RECOVERED_FILE_EXTENSION);
+                context.storage.append(&path, chunk).unwrap_or_else(track_io!(context));
+                true
+            }
+        }
+    }
 
-        self.logfile.take_if(|f| self.context.rotate_file(f, &chunk));
+    /// Writes the chunk through the circuit breaker, the chunks deferred by the
+    /// breaker are written first. While the writes are paused, they are attempted
+    /// only if `retry` is `true`, otherwise the chunk is deferred.
+    ///
+    /// Returns `false` if the chunk is deferred, otherwise it has been written (or
+    /// dropped due to the failure).
+    ///
+    /// It takes the fields instead of `self`, so that the chunk can be bound to the
+    /// buffer of `self` meanwhile.
+    fn write<B>(
+        context: &Context,
+        current: &mut Option<Logfile>,
+        breaker: &mut Option<Breaker>,
+        chunk: Option<&Chunk<B>>,
+        retry: bool,
+    ) -> bool
+    where
+        B: Deref<Target = [u8]>,
+    {
+        let Some(breaker) = breaker else {
+            if let Some(chunk) = chunk {
+                Self::write_to_logfile(context, current, chunk).unwrap_or_else(track_io!(context));
+            }
+            return true;
+        };
 
-        let logfile = if let Some(logfile) = &mut self.logfile {
+        if breaker.is_open() && !retry {
+            if let Some(chunk) = chunk {
+                breaker.defer(chunk);
+            }
+            return false;
+        }
+
+        let result = breaker
+            .drain(|deferred| Self::write_to_logfile(context, current, deferred))
+            .and_then(|_| chunk.map_or(Ok(()), |c| Self::write_to_logfile(context, current, c)));
+        let err = match result {
+            Ok(_) => {
+                if let Some(dropped) = breaker.succeed() {
+                    let details = format!("{dropped} chunks dropped during the pause");
+                    track_event!(context.tracker, TrackCategory::IoResumed, details);
+                }
+                return true;
+            }
+            Err(err) => err,
+        };
+
+        // The logfile is reopened by the next write, as it may have been broken.
+        *current = None;
+        let was_open = breaker.is_open();
+        if !breaker.fail() && !was_open {
+            Err(err).unwrap_or_else(track_io!(context));
+            return true;
+        }
+
+        // Only the transitions are reported to tracker during the pause.
+        *context.last_io_error.lock().unwrap() = Some(err.to_string());
+        if !was_open {
+            Err(err).unwrap_or_else(track!(context.tracker, TrackCategory::IoPaused));
+        }
+        if let Some(chunk) = chunk {
+            breaker.defer(chunk);
+        }
+        false
+    }
+
+    /// Retries the paused writes regardless of the retry interval (e.g., before
+    /// shutting down).
+    fn retry(&mut self) {
+        let Some(breaker) = self.breaker.as_ref().filter(|b| b.is_open()) else {
+            return;
+        };
+        // The chunk left in the buffer has been deferred, unless the logger has switched
+        // the buffer while the IO thread is idle.
+        let handle = self.buffer.as_mut().map(Buffer::handle);
+        let handle = handle.filter(|h| breaker.is_kept(&Chunk::bind(h.as_slice())));
+
+        let chunk: Option<&Chunk<Vec<u8>>> = None;
+        if Self::write(&self.context, &mut self.logfile, &mut self.breaker, chunk, true)
+            && let Some(handle) = handle
+        {
+            Chunk::bind(handle).clear();
+        }
+    }
+
+    /// Writes the chunk to the current logfile, the logfile is rotated if needed.
+    fn write_to_logfile<B>(
+        context: &Context,
+        current: &mut Option<Logfile>,
+        chunk: &Chunk<B>,
+    ) -> Result<(), Error>
+    where
+        B: Deref<Target = [u8]>,
+    {
+        // The rotated logfile is sealed, as no more chunks are written to it.
+        if let Some(mut rotated) =
+            current.take_if(|f| context.rotate_file(f, chunk.start_datetime()))
+        {
+            rotated.seal().unwrap_or_else(track_io!(context));
+        }
+
+        let logfile = if let Some(logfile) = current {
             logfile
         } else {
-            self.logfile = Some(Logfile::new(
-                Arc::clone(&self.context.domain),
-                chunk.start_datetime(),
-                logfile::Mode::Write,
-            ));
+            // The chunk is dropped if there is not enough space for a new logfile.
+            context.check_free_space(None)?;
+            *current = Some(context.open_logfile(chunk.start_datetime(), |logfile| {
+                logfile
+                    .preallocate(context.preallocation)
+                    .storage(Arc::clone(&context.storage))
+                    .minute_index(context.minute_index)
+            })?);
             // SAFETY: a `None` variant for `logfile` would have been replaced by a `Some`
             // variant in the code above.
-            unsafe { self.logfile.as_mut().unwrap_unchecked() }
+            unsafe { current.as_mut().unwrap_unchecked() }
         };
 
-        logfile.write(&chunk).unwrap_or_else(track!(self.context.tracker));
-        logfile.flush().unwrap_or_else(track!(self.context.tracker));
-
-        // Sets the chunk length to 0 to indicate that the chunk has finished writing to the
-        // logfile and will not be written again.
-        chunk.clear();
+        logfile.write_chunk(chunk)?;
+        logfile.flush()?;
+        Ok(())
     }
 
     /// Deletes the expired log files.
-    #[inline]
-    fn trim(&mut self, lifetime: u64) {
+    fn trim(&mut self, lifetime: u64) -> TrimReport {
         let expires = chrono::Utc::now().timestamp().saturating_sub_unsigned(lifetime);
+        let mut report = TrimReport::default();
+
+        let storage = Arc::clone(&self.context.storage);
+        let logfiles = match Logfile::logfiles(&self.context.domain, logfile::Mode::Read, storage) {
+            Ok(logfiles) => logfiles,
+            Err(err) => {
+                report.errors.push(err);
+                return report;
+            }
+        };
 
-        if let Ok(logfiles) = Logfile::logfiles(&self.context.domain, logfile::Mode::Read)
-            .map_err(track!(self.context.tracker))
-        {
-            logfiles
-                .filter(|f| f.datetime().timestamp() < expires)
-                .for_each(|file| file.delete().unwrap_or_else(track!(self.context.tracker)));
+        for file in logfiles.filter(|f| f.datetime().timestamp() < expires) {
+            let len = file.len().unwrap_or(0);
+            match file.delete() {
+                Ok(_) => {
+                    report.deleted_files += 1;
+                    report.freed_bytes += len;
+                }
+                Err(err) => report.errors.push(err),
+            }
         }
+
+        report
     }
 }
 
@@ -480,9 +2121,67 @@ where
     #[inline]
     fn handle(&mut self, event: Self::Event, context: &mut runloop::Context) {
         match event {
-            IoEvent::WriteChunk => self.write_chunk(),
-            IoEvent::Trim { lifetime } => self.trim(lifetime),
-            IoEvent::Shutdown => context.stop(),
+            IoEvent::WriteChunk => {
+                self.write_chunk();
+                self.context.written_chunks.fetch_add(1, Ordering::Release);
+            }
+            IoEvent::WriteIsolatedChunk(chunk) => {
+                // The writes are only retried with the buffer chunks, so that the
+                // chunk left in the buffer is never written twice.
+                let (context, breaker) = (&self.context, &mut self.breaker);
+                Self::write(context, &mut self.logfile, breaker, Some(&chunk), false);
+            }
+            IoEvent::Trim { lifetime, reporter } => {
+                let report = self.trim(lifetime);
+                match reporter {
+                    Some(reporter) => _ = reporter.send(report),
+                    None => {
+                        if report.deleted_files > 0 {
+                            let context = format!(
+                                "deleted {} log files, freed {} bytes",
+                                report.deleted_files, report.freed_bytes
+                            );
+                            track_event!(self.context.tracker, TrackCategory::Trim, context);
+                        }
+                        report.errors.into_iter().for_each(track_io!(self.context))
+                    }
+                }
+            }
+            IoEvent::Barrier(notifier) => drop(notifier),
+            IoEvent::Shutdown => {
+                self.retry();
+                context.stop()
+            }
+        }
+    }
+
+    /// The paused writes are retried once due, even if no chunk is written.
+    #[inline]
+    fn timeout(&self) -> Option<Duration> {
+        // At most once a second, if the retry interval is shorter.
+        let retry_in = self.breaker.as_ref().and_then(Breaker::retry_in);
+        retry_in.map(|remaining| remaining.max(Duration::from_secs(1)))
+    }
+
+    #[inline]
+    fn idle(&mut self, _: &mut runloop::Context) {
+        if self.breaker.as_ref().is_some_and(Breaker::is_due) {
+            self.retry();
+        }
+    }
+
+    fn recover(&mut self, payload: Box<dyn Any + Send>, context: &mut runloop::Context) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(Error::IoPanic(message)).unwrap_or_else(track_io!(self.context));
+
+        match self.context.io_panic_policy {
+            IoPanicPolicy::Restart => self.logfile = None,
+            IoPanicPolicy::Abort => process::abort(),
+            IoPanicPolicy::Report => context.stop(),
         }
     }
 }
@@ -497,7 +2196,7 @@ mod tests {
     #[test]
     fn test_processor() {
         type Processor = logger::Processor<Option<ZstdCompressor>, Option<AesEncryptor>>;
-        let mut processor = Processor::new(None, None);
+        let mut processor = Processor::new(None, 0, None, false);
 
         let mut memory = Vec::<u8>::with_capacity(256);
         unsafe {
@@ -510,7 +2209,7 @@ mod tests {
             contents: impl IntoIterator<Item = &'a str>,
         ) {
             let mut chunk = Chunk::bind(memory.as_mut_slice());
-            chunk.initialize(chrono::Utc::now(), [0; 33]);
+            chunk.initialize(chrono::Utc::now(), [0; 33], 0);
 
             let records = contents
                 .into_iter()
@@ -518,7 +2217,7 @@ mod tests {
                 .collect::<Vec<_>>();
 
             for record in &records {
-                processor.process(Operation::Input(&record), &mut chunk).unwrap();
+                processor.process(Operation::Input(&record, None), &mut chunk).unwrap();
             }
             processor.process(Operation::Rotate, &mut chunk).unwrap();
 
@@ -536,4 +2235,29 @@ mod tests {
         test_process(&mut processor, &mut memory, []);
         test_process(&mut processor, &mut memory, ["Hello", "World"]);
     }
+
+    #[test]
+    fn test_processor_deduplication() {
+        type Processor = logger::Processor<Option<ZstdCompressor>, Option<AesEncryptor>>;
+        let mut processor = Processor::new(None, 0, None, true);
+        let mut memory = vec![0; 256];
+
+        let mut chunk = Chunk::bind(memory.as_mut_slice());
+        chunk.initialize(chrono::Utc::now(), [0; 33], 0);
+
+        for content in ["Hello", "Hello", "Hello", "World", "Hello", "Hello"] {
+            let record = RecordBuilder::new().content(content).build();
+            processor.process(Operation::Input(&record, None), &mut chunk).unwrap();
+        }
+        processor.process(Operation::Rotate, &mut chunk).unwrap();
+
+        let payload_len = chunk.payload_len();
+        let mut payload = &memory[chunk::Header::LEN..chunk::Header::LEN + payload_len];
+
+        for (content, repeats) in [("Hello", 2), ("World", 0), ("Hello", 1)] {
+            let record = Record::decode(&mut payload).unwrap();
+            assert_eq!((record.content(), record.repeats()), (content, repeats));
+        }
+        assert_eq!(payload.len(), 0);
+    }
 }
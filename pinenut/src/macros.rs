@@ -0,0 +1,126 @@
+//! Logging macros that capture the metadata of the call site automatically.
+
+/// Logs a record with the level, through the logger.
+///
+/// The code location (see [`here!`]), the current thread id and name, and the
+/// datetime are captured automatically, and the content is formatted like
+/// [`format!`]. A tag can be specified optionally, before the format string.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use pinenut_log::{log, Domain, Level};
+///
+/// let logger = Domain::new("MyApp".into(), "/path/to/dir".into()).logger_with_default_config();
+/// log!(logger, Level::Info, "Hello {}", "World");
+/// log!(logger, Level::Warn, tag: "Network", "Request timeout: {}s", 30);
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($logger:expr, $level:expr, tag: $tag:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $level, ::std::option::Option::Some($tag), $($arg)+)
+    };
+    ($logger:expr, $level:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $level, ::std::option::Option::None, $($arg)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($logger:expr, $level:expr, $tag:expr, $($arg:tt)+) => {{
+        let thread = ::std::thread::current();
+        let meta = $crate::MetaBuilder::new()
+            .level($level)
+            .location($crate::here!())
+            .tag_opt($tag)
+            .thread_id($crate::__private::thread_id())
+            .thread_name_opt(thread.name())
+            .build();
+        $logger.log_fmt(meta, ::std::format_args!($($arg)+));
+    }};
+}
+
+/// Logs a record with the [`Level::Error`] level, see [`log!`].
+///
+/// [`Level::Error`]: crate::Level::Error
+#[macro_export]
+macro_rules! error {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::Level::Error, $($arg)+)
+    };
+}
+
+/// Logs a record with the [`Level::Warn`] level, see [`log!`].
+///
+/// [`Level::Warn`]: crate::Level::Warn
+#[macro_export]
+macro_rules! warn {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::Level::Warn, $($arg)+)
+    };
+}
+
+/// Logs a record with the [`Level::Info`] level, see [`log!`].
+///
+/// [`Level::Info`]: crate::Level::Info
+#[macro_export]
+macro_rules! info {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::Level::Info, $($arg)+)
+    };
+}
+
+/// Logs a record with the [`Level::Debug`] level, see [`log!`].
+///
+/// [`Level::Debug`]: crate::Level::Debug
+#[macro_export]
+macro_rules! debug {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::Level::Debug, $($arg)+)
+    };
+}
+
+/// Logs a record with the [`Level::Verbose`] level, see [`log!`].
+///
+/// [`Level::Verbose`]: crate::Level::Verbose
+#[macro_export]
+macro_rules! verbose {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::Level::Verbose, $($arg)+)
+    };
+}
+
+/// The identifier of the current thread assigned by the OS, which is consistent
+/// with the one seen by the platform code (e.g., Java, Objective-C).
+pub fn thread_id() -> u64 {
+    thread_local! {
+        static THREAD_ID: u64 = os_thread_id();
+    }
+    THREAD_ID.with(|id| *id)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn os_thread_id() -> u64 {
+    // SAFETY: `gettid` is always successful.
+    unsafe { libc::syscall(libc::SYS_gettid) as u64 }
+}
+
+#[cfg(target_vendor = "apple")]
+fn os_thread_id() -> u64 {
+    let mut id = 0;
+    // SAFETY: Getting the id of the current thread (`0`) is always successful.
+    unsafe {
+        libc::pthread_threadid_np(0, &mut id);
+    }
+    id
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+fn os_thread_id() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    // Falls back to a sequence number of the threads that have logged.
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
@@ -0,0 +1,263 @@
+//! The manifest of the domain.
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use chrono::SecondsFormat;
+use thiserror::Error;
+
+use crate::{DateTime, Domain, KeyAlgorithm, TimeDimension, FORMAT_VERSION, MANIFEST_EXTENSION};
+
+/// The cipher of the encrypted chunks, see [`Manifest::cipher`].
+const CIPHER: &str = "aes-128-ecb";
+
+/// Errors that can be occurred while reading the manifest of a domain, see
+/// [`Domain::manifest`].
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid manifest: {0}")]
+    Invalid(String),
+}
+
+/// Represents the manifest of a domain, which records the provenance of the log
+/// files (how and by which versions they are written), see [`Config::manifest`].
+///
+/// It is a small text file of `key = value` lines named
+/// `{identifier}.pinemeta` in the domain directory, so that the backend parsers can
+/// choose the decoders and audit which versions of the application produced the log
+/// files. The unknown keys are ignored when it is read, and the absent optional
+/// keys are left as `None`.
+///
+/// [`Config::manifest`]: crate::Config::manifest
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Manifest {
+    format_version: u16,
+    sdk_version: String,
+    rotation: TimeDimension,
+    cipher: Option<String>,
+    key_algorithm: Option<KeyAlgorithm>,
+    key_fingerprint: Option<String>,
+    app_version: Option<String>,
+    updated_at: DateTime,
+}
+
+impl Manifest {
+    /// The version of the Pinenut log structure, see [`FORMAT_VERSION`].
+    #[inline]
+    pub fn format_version(&self) -> u16 {
+        self.format_version
+    }
+
+    /// The version of the Pinenut SDK that wrote the manifest.
+    #[inline]
+    pub fn sdk_version(&self) -> &str {
+        &self.sdk_version
+    }
+
+    /// The dimension of the log rotation, see [`Config::rotation`].
+    ///
+    /// [`Config::rotation`]: crate::Config::rotation
+    #[inline]
+    pub fn rotation(&self) -> TimeDimension {
+        self.rotation
+    }
+
+    /// The cipher of the chunks (e.g., `aes-128-ecb`), it is `None` if the chunks
+    /// are not encrypted.
+    #[inline]
+    pub fn cipher(&self) -> Option<&str> {
+        self.cipher.as_deref()
+    }
+
+    /// The algorithm of the key exchange, it is `None` if the chunks are not
+    /// encrypted.
+    #[inline]
+    pub fn key_algorithm(&self) -> Option<KeyAlgorithm> {
+        self.key_algorithm
+    }
+
+    /// The fingerprint of the public key that the chunks are encrypted for, see
+    /// [`key_fingerprint`].
+    ///
+    /// [`key_fingerprint`]: crate::encrypt::key_fingerprint
+    #[inline]
+    pub fn key_fingerprint(&self) -> Option<&str> {
+        self.key_fingerprint.as_deref()
+    }
+
+    /// The version of the application, see [`Config::app_version`].
+    ///
+    /// [`Config::app_version`]: crate::Config::app_version
+    #[inline]
+    pub fn app_version(&self) -> Option<&str> {
+        self.app_version.as_deref()
+    }
+
+    /// The datetime at which the manifest was written, i.e., when the logger was
+    /// initialized.
+    #[inline]
+    pub fn updated_at(&self) -> DateTime {
+        self.updated_at
+    }
+
+    /// Constructs the manifest of the logger being initialized.
+    pub(crate) fn current(
+        rotation: TimeDimension,
+        key: Option<(KeyAlgorithm, String)>,
+        app_version: Option<String>,
+        updated_at: DateTime,
+    ) -> Self {
+        let (key_algorithm, key_fingerprint) = key.unzip();
+        Self {
+            format_version: FORMAT_VERSION,
+            sdk_version: env!("CARGO_PKG_VERSION").to_string(),
+            rotation,
+            cipher: key_algorithm.map(|_| CIPHER.to_string()),
+            key_algorithm,
+            key_fingerprint,
+            app_version,
+            updated_at,
+        }
+    }
+
+    /// Reads the manifest of the domain.
+    pub(crate) fn read(domain: &Domain) -> Result<Self, Error> {
+        fs::read_to_string(path(domain))?.parse()
+    }
+
+    /// Writes the manifest to the domain directory.
+    ///
+    /// It is written to a temporary file which then replaces the manifest, so that
+    /// the manifest is never seen half-written.
+    pub(crate) fn write(&self, domain: &Domain) -> io::Result<()> {
+        fs::create_dir_all(&domain.directory)?;
+        let path = path(domain);
+        let temp_path = path.with_extension(format!("{MANIFEST_EXTENSION}.tmp"));
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(self.to_string().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(temp_path, path)
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "format_version = {}", self.format_version)?;
+        writeln!(f, "sdk_version = {}", self.sdk_version)?;
+        writeln!(f, "rotation = {}", rotation_name(self.rotation))?;
+        if let Some(cipher) = &self.cipher {
+            writeln!(f, "cipher = {cipher}")?;
+        }
+        if let Some(algorithm) = self.key_algorithm {
+            writeln!(f, "key_algorithm = {}", key_algorithm_name(algorithm))?;
+        }
+        if let Some(fingerprint) = &self.key_fingerprint {
+            writeln!(f, "key_fingerprint = {fingerprint}")?;
+        }
+        if let Some(version) = &self.app_version {
+            writeln!(f, "app_version = {version}")?;
+        }
+        writeln!(f, "updated_at = {}", self.updated_at.to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |key: &str, value: &str| Error::Invalid(format!("{key} = {value}"));
+
+        let mut format_version = None;
+        let mut sdk_version = None;
+        let mut rotation = None;
+        let mut updated_at = None;
+        let (mut cipher, mut key_algorithm, mut key_fingerprint, mut app_version) =
+            (None, None, None, None);
+
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(Error::Invalid(line.to_string()));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "format_version" => {
+                    format_version = Some(value.parse().map_err(|_| invalid(key, value))?)
+                }
+                "sdk_version" => sdk_version = Some(value.to_string()),
+                "rotation" => {
+                    rotation = Some(parse_rotation(value).ok_or_else(|| invalid(key, value))?)
+                }
+                "cipher" => cipher = Some(value.to_string()),
+                "key_algorithm" => {
+                    key_algorithm =
+                        Some(parse_key_algorithm(value).ok_or_else(|| invalid(key, value))?)
+                }
+                "key_fingerprint" => key_fingerprint = Some(value.to_string()),
+                "app_version" => app_version = Some(value.to_string()),
+                "updated_at" => updated_at = Some(value.parse().map_err(|_| invalid(key, value))?),
+                // Written by the newer versions.
+                _ => {}
+            }
+        }
+
+        let missing = |key: &str| Error::Invalid(format!("missing {key}"));
+        Ok(Self {
+            format_version: format_version.ok_or_else(|| missing("format_version"))?,
+            sdk_version: sdk_version.ok_or_else(|| missing("sdk_version"))?,
+            rotation: rotation.ok_or_else(|| missing("rotation"))?,
+            cipher,
+            key_algorithm,
+            key_fingerprint,
+            app_version,
+            updated_at: updated_at.ok_or_else(|| missing("updated_at"))?,
+        })
+    }
+}
+
+/// The path of the manifest of the domain.
+#[inline]
+fn path(domain: &Domain) -> PathBuf {
+    domain.directory.join(&domain.identifier).with_extension(MANIFEST_EXTENSION)
+}
+
+#[inline]
+fn rotation_name(rotation: TimeDimension) -> &'static str {
+    match rotation {
+        TimeDimension::Day => "day",
+        TimeDimension::Hour => "hour",
+        TimeDimension::Minute => "minute",
+    }
+}
+
+#[inline]
+fn parse_rotation(name: &str) -> Option<TimeDimension> {
+    match name {
+        "day" => Some(TimeDimension::Day),
+        "hour" => Some(TimeDimension::Hour),
+        "minute" => Some(TimeDimension::Minute),
+        _ => None,
+    }
+}
+
+#[inline]
+fn key_algorithm_name(algorithm: KeyAlgorithm) -> &'static str {
+    match algorithm {
+        KeyAlgorithm::P256 => "p256",
+        KeyAlgorithm::X25519 => "x25519",
+    }
+}
+
+#[inline]
+fn parse_key_algorithm(name: &str) -> Option<KeyAlgorithm> {
+    match name {
+        "p256" => Some(KeyAlgorithm::P256),
+        "x25519" => Some(KeyAlgorithm::X25519),
+        _ => None,
+    }
+}
@@ -0,0 +1,101 @@
+//! The in-memory logger for viewing the recent records in the application.
+
+use std::{collections::VecDeque, fmt, sync::Mutex};
+
+use crate::{context, Meta, OwnedRecord, Query, Record, RecordSink};
+
+/// Keeps the last records in memory (a ring buffer), instead of writing them to
+/// the log files.
+///
+/// It is intended for an in-app debug console, which shows the recent records
+/// without extracting and parsing the log files. It has the same logging API as
+/// [`Logger`], and can also receive the records of a logger as its record sink
+/// (see [`Config::record_sink`]).
+///
+/// [`Logger`]: crate::Logger
+/// [`Config::record_sink`]: crate::Config::record_sink
+pub struct MemoryLogger {
+    records: Mutex<VecDeque<OwnedRecord>>,
+    capacity: usize,
+}
+
+impl MemoryLogger {
+    /// Constructs a new `MemoryLogger` keeping at most `capacity` records, the
+    /// oldest record is dropped once it is full.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self { records: Mutex::new(VecDeque::with_capacity(capacity)), capacity }
+    }
+
+    /// Logs the record.
+    pub fn log(&self, record: &Record) {
+        context::stamp(record, |record| self.push(record));
+    }
+
+    /// Keeps the record, which has been stamped with the context.
+    fn push(&self, record: &Record) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record.into());
+    }
+
+    /// Logs the record with the metadata and the formatted content, see
+    /// [`Logger::log_fmt`].
+    ///
+    /// [`Logger::log_fmt`]: crate::Logger::log_fmt
+    pub fn log_fmt(&self, meta: Meta, args: fmt::Arguments) {
+        match args.as_str() {
+            Some(content) => self.log(&Record::new(meta, content)),
+            None => self.log(&Record::new(meta, &args.to_string())),
+        }
+    }
+
+    /// The number of the records kept.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    /// Whether there are no records kept.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all of the records kept.
+    #[inline]
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+
+    /// A snapshot of the records kept, from the oldest to the latest.
+    #[inline]
+    pub fn snapshot(&self) -> Vec<OwnedRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Queries the records matching the conditions, from the oldest to the latest,
+    /// see [`Query`].
+    pub fn query(&self, query: &Query) -> Vec<OwnedRecord> {
+        let records = self.records.lock().unwrap();
+        records
+            .iter()
+            .filter(|record| query.matches(&record.as_record()))
+            .take(query.max_records())
+            .cloned()
+            .collect()
+    }
+}
+
+impl RecordSink for MemoryLogger {
+    #[inline]
+    fn receive(&self, record: &Record) {
+        // The records of the logger have been stamped with the context already.
+        self.push(record)
+    }
+}
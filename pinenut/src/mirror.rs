@@ -0,0 +1,126 @@
+//! Mirroring of records to the platform console.
+
+use std::io;
+
+use crate::{DefaultFormatter, Format, Record};
+
+/// The platform console to which the records are mirrored, see [`Config::mirror`].
+///
+/// On platforms where the specified console is not available, the records are
+/// mirrored to the standard error instead.
+///
+/// [`Config::mirror`]: crate::Config::mirror
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mirror {
+    /// The Android `logcat`.
+    Logcat,
+    /// The Apple unified logging system (`os_log`).
+    OsLog,
+    /// The standard error.
+    Stderr,
+}
+
+impl Mirror {
+    /// Writes the record to the console, errors are ignored.
+    pub(crate) fn write(self, record: &Record) {
+        match self {
+            #[cfg(target_os = "android")]
+            Self::Logcat => logcat::write(record),
+            #[cfg(target_vendor = "apple")]
+            Self::OsLog => oslog::write(record),
+            _ => _ = DefaultFormatter.format(record, &mut io::stderr().lock()),
+        }
+    }
+}
+
+/// Formatting for the system consoles, which record the datetime and level by
+/// themselves.
+#[cfg(any(target_os = "android", target_vendor = "apple"))]
+mod console {
+    use std::ffi::CString;
+
+    use crate::Record;
+
+    /// The tag used when the record doesn't have one.
+    const DEFAULT_TAG: &str = "Pinenut";
+
+    /// The tag of the record.
+    pub(super) fn tag(record: &Record) -> CString {
+        c_string(record.meta().tag().unwrap_or(DEFAULT_TAG).to_string())
+    }
+
+    /// The message of the record, including its code location.
+    pub(super) fn message(record: &Record) -> CString {
+        let location = record.meta().location();
+        c_string(format!(
+            "{}:{}|{}",
+            location.file().unwrap_or(""),
+            location.line().unwrap_or(0),
+            record.content()
+        ))
+    }
+
+    #[inline]
+    fn c_string(mut string: String) -> CString {
+        // Interior nul bytes would truncate the string, so they are removed.
+        string.retain(|c| c != '\0');
+        CString::new(string).unwrap()
+    }
+}
+
+#[cfg(target_os = "android")]
+mod logcat {
+    use std::ffi::{c_char, c_int};
+
+    use crate::{mirror::console, Level, Record};
+
+    #[link(name = "log")]
+    extern "C" {
+        fn __android_log_write(prio: c_int, tag: *const c_char, text: *const c_char) -> c_int;
+    }
+
+    pub(super) fn write(record: &Record) {
+        // See `android_LogPriority` in `android/log.h`.
+        let priority = match record.meta().level() {
+            Level::Error => 6,
+            Level::Warn => 5,
+            Level::Info => 4,
+            Level::Debug => 3,
+            Level::Verbose => 2,
+        };
+        let (tag, message) = (console::tag(record), console::message(record));
+        // SAFETY: Both strings are valid and nul-terminated.
+        unsafe {
+            __android_log_write(priority, tag.as_ptr(), message.as_ptr());
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+mod oslog {
+    use std::ffi::{c_char, c_int};
+
+    use crate::{mirror::console, Level, Record};
+
+    // `syslog` is backed by the unified logging system on Apple platforms, and unlike
+    // the `os_log` macros, it can be called without a C shim.
+    extern "C" {
+        fn syslog(priority: c_int, format: *const c_char, ...);
+    }
+
+    pub(super) fn write(record: &Record) {
+        // See `sys/syslog.h`.
+        let priority = match record.meta().level() {
+            Level::Error => 3,
+            Level::Warn => 4,
+            Level::Info => 6,
+            Level::Debug | Level::Verbose => 7,
+        };
+        let (tag, message) = (console::tag(record), console::message(record));
+        // SAFETY: The format and the arguments are valid and nul-terminated.
+        unsafe {
+            syslog(priority, c"[%s] %s".as_ptr(), tag.as_ptr(), message.as_ptr());
+        }
+    }
+}
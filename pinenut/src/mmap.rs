@@ -1,13 +1,17 @@
 //! Memory-mapped.
 
 use std::{
-    fs,
+    fs::{self, File},
     io::Error,
     ops::{Deref, DerefMut},
-    os::fd::{IntoRawFd, RawFd},
     path::Path,
-    ptr::{self, NonNull},
+    ptr::NonNull,
     slice,
+};
+#[cfg(unix)]
+use std::{
+    os::fd::AsRawFd,
+    ptr,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
@@ -15,6 +19,10 @@ use std::{
 ///
 /// It wraps around the unsafe `mmap` call, exposing the safe interfaces. When it is
 /// dropped, the `munmap` will be called automatically.
+///
+/// Memory mapping is only supported on Unix. Elsewhere (e.g., `wasm32`), mapping
+/// always fails with [`std::io::ErrorKind::Unsupported`], and the logger falls back
+/// to the buffer in memory.
 pub(crate) struct Mmap {
     ptr: NonNull<u8>,
     len: usize,
@@ -46,11 +54,43 @@ impl Mmap {
             file.set_len(len as u64)?;
         }
 
-        Self::map(file.into_raw_fd(), len).map(|ptr| Self { ptr, len })
+        // The mapping remains valid after the file is closed.
+        Self::map(&file, 0, len).map(|ptr| Self { ptr, len })
+    }
+
+    /// Maps the range of the underlying file to memory.
+    ///
+    /// The `offset` must be a multiple of the operating system's memory page size,
+    /// and the file must be long enough to cover the range, accessing the mapping
+    /// beyond the end of the file raises `SIGBUS`.
+    pub(crate) fn map_file(file: &File, offset: u64, len: usize) -> Result<Self, Error> {
+        debug_assert_eq!(offset % page_size() as u64, 0, "offset is not page-aligned");
+        Self::map(file, offset, len).map(|ptr| Self { ptr, len })
+    }
+
+    /// Schedules the modified pages to be written to the underlying file, without
+    /// waiting for the writes to complete.
+    #[cfg(unix)]
+    #[inline]
+    pub(crate) fn flush_async(&self) -> Result<(), Error> {
+        // SAFETY: The pointer and the length are associated with the mapping.
+        let ret =
+            unsafe { libc::msync(self.as_ptr() as *mut libc::c_void, self.len, libc::MS_ASYNC) };
+        if ret != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    #[inline]
+    pub(crate) fn flush_async(&self) -> Result<(), Error> {
+        Err(std::io::ErrorKind::Unsupported.into())
     }
 
     /// A thin wrapper around the `mmap` system call.
-    fn map(file: RawFd, len: usize) -> Result<NonNull<u8>, Error> {
+    #[cfg(unix)]
+    fn map(file: &File, offset: u64, len: usize) -> Result<NonNull<u8>, Error> {
         // SAFETY: Just a few FFI calls to libc.
         unsafe {
             let ptr = libc::mmap(
@@ -58,8 +98,8 @@ impl Mmap {
                 len,
                 libc::PROT_READ | libc::PROT_WRITE,
                 libc::MAP_SHARED,
-                file,
-                0,
+                file.as_raw_fd(),
+                offset as libc::off_t,
             );
 
             if ptr == libc::MAP_FAILED {
@@ -74,6 +114,11 @@ impl Mmap {
         }
     }
 
+    #[cfg(not(unix))]
+    fn map(_: &File, _: u64, _: usize) -> Result<NonNull<u8>, Error> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
     /// Returns the number of bytes in the mmap.
     #[inline]
     pub(crate) fn len(&self) -> usize {
@@ -119,6 +164,7 @@ impl DerefMut for Mmap {
     }
 }
 
+#[cfg(unix)]
 impl Drop for Mmap {
     #[inline]
     fn drop(&mut self) {
@@ -133,13 +179,14 @@ unsafe impl Sync for Mmap {}
 
 /// Rounds up to a multiple of the operating system's memory page size.
 #[inline]
-fn round_up_page_size(value: usize) -> usize {
+pub(crate) fn round_up_page_size(value: usize) -> usize {
     let page_size = page_size();
     ((value - 1) / page_size + 1) * page_size
 }
 
 /// Obtains the operating system's memory page size.
-fn page_size() -> usize {
+#[cfg(unix)]
+pub(crate) fn page_size() -> usize {
     static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
     // It is not guaranteed that `sysconf` will be called only once in multiple threads,
     // but it is possible to reduce the number of times it is called.
@@ -153,6 +200,12 @@ fn page_size() -> usize {
     }
 }
 
+/// There are no pages to be mapped, the common page size is used for rounding.
+#[cfg(not(unix))]
+pub(crate) fn page_size() -> usize {
+    4096
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs::File, io, io::Read};
@@ -0,0 +1,95 @@
+//! The logger forwarding the records to several loggers.
+
+use std::{fmt, sync::Mutex};
+
+use crate::{
+    codec::Encode, common::FnSink, context, EncodingError, Logger, Meta, Record, RotateReason,
+};
+
+/// Forwards each record to several underlying loggers, which have their own
+/// domains and configurations (e.g., an encrypted persistent domain, along with an
+/// unencrypted one for viewing in the application).
+///
+/// The record is encoded once and the encoded bytes are shared by the loggers,
+/// unless a logger changes the record (e.g., by the time source or the stages, see
+/// [`Config::pipeline_stage`]), which encodes it again. The records are stamped
+/// with the session id of the first logger, so that they are identical across the
+/// domains.
+///
+/// [`Config::pipeline_stage`]: crate::Config::pipeline_stage
+pub struct MultiLogger {
+    loggers: Vec<Logger>,
+    session_id: Option<u64>,
+    /// The buffer of the encoded bytes shared by the loggers.
+    encoded: Mutex<Vec<u8>>,
+}
+
+impl MultiLogger {
+    /// Constructs a new `MultiLogger` with the underlying loggers.
+    #[inline]
+    pub fn new(loggers: Vec<Logger>) -> Self {
+        let session_id = loggers.first().map(Logger::session_id);
+        Self { loggers, session_id, encoded: Mutex::new(Vec::new()) }
+    }
+
+    /// The underlying loggers, in the order they are constructed with.
+    #[inline]
+    pub fn loggers(&self) -> &[Logger] {
+        &self.loggers
+    }
+
+    /// Logs the record to all of the underlying loggers, see [`Logger::log`].
+    pub fn log(&self, record: &Record) {
+        context::stamp(record, |record| self.log_stamped(record));
+    }
+
+    /// Logs the record stamped with the context of the current thread.
+    fn log_stamped(&self, record: &Record) {
+        let mut record = record.clone();
+        if record.meta().session_id().is_none() {
+            record.meta_mut().set_session_id(self.session_id);
+        }
+
+        let mut encoded = self.encoded.lock().unwrap();
+        encoded.clear();
+        let result = record.encode(&mut FnSink::new(|bytes: &[u8]| {
+            encoded.extend_from_slice(bytes);
+            Ok::<_, EncodingError>(())
+        }));
+        // The loggers encode the record on their own if it fails.
+        let encoded = result.is_ok().then_some(encoded.as_slice());
+
+        for logger in &self.loggers {
+            logger.log_encoded(&record, encoded);
+        }
+    }
+
+    /// Logs the record with the metadata and the formatted content, see
+    /// [`Logger::log_fmt`].
+    pub fn log_fmt(&self, meta: Meta, args: fmt::Arguments) {
+        match args.as_str() {
+            Some(content) => self.log(&Record::new(meta, content)),
+            None => self.log(&Record::new(meta, &args.to_string())),
+        }
+    }
+
+    /// Flushes any buffered records of all of the underlying loggers, see
+    /// [`Logger::flush`].
+    #[inline]
+    pub fn flush(&self) {
+        self.loggers.iter().for_each(Logger::flush);
+    }
+
+    /// Rotates the chunks of all of the underlying loggers, see [`Logger::rotate`].
+    #[inline]
+    pub fn rotate(&self, reason: RotateReason) {
+        self.loggers.iter().for_each(|logger| logger.rotate(reason));
+    }
+
+    /// Flushes then shuts down all of the underlying loggers, see
+    /// [`Logger::shutdown`].
+    #[inline]
+    pub fn shutdown(self) {
+        self.loggers.into_iter().for_each(Logger::shutdown);
+    }
+}
@@ -1,27 +1,40 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
     fs::File,
     io,
-    io::{BufReader, BufWriter, Write},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    mem,
     ops::{Deref, RangeInclusive},
     path::Path,
+    sync::Arc,
 };
 
+use hmac::Mac;
 use thiserror::Error;
 
 use crate::{
-    chunk,
+    chunk::{self, Extensions},
     codec::Decode,
-    common::{BytesBuf, FnSink, LazyFileWriter},
-    compress::{Decompressor, ZstdDecompressor},
+    common::{BytesBuf, FnSink, LazyFileWriter, Sink},
+    compress::{Decompressor, ZstdDecompressReader, ZstdDecompressor},
     encrypt::{
-        ecdh::{ecdh_encryption_key, EMPTY_PUBLIC_KEY},
-        AesDecryptor, Decryptor,
+        ecdh::{fingerprint, fingerprint_hex, Fingerprint, EMPTY_PUBLIC_KEY},
+        AesDecryptor, Decryptor, KeyAlgorithm,
     },
     DateTime, DecodingError, DecompressionError, DecryptionError, EncryptionError, EncryptionKey,
-    PublicKey, Record, SecretKey, BUFFER_LEN, FORMAT_VERSION,
+    Level, Location, Meta, OwnedRecord, Producer, PublicKey, Query, Record, Redactor, RotateReason,
+    SecretKey, SigningKey, Timezone, BUFFER_LEN, FORMAT_VERSION,
 };
 
+/// The format versions whose records can be decoded.
+pub(crate) const SUPPORTED_VERSIONS: RangeInclusive<u16> = 1..=FORMAT_VERSION;
+
+/// The first format version whose records are prefixed with their schemas and
+/// lengths, the records of the initial version carry only the metadata and the
+/// content.
+const SCHEMA_VERSION: u16 = 2;
+
 /// Errors that can be occurred during the log parsing process ([`parse`]).
 #[derive(Error, Debug)]
 pub enum Error {
@@ -39,47 +52,682 @@ pub enum Error {
     Decompress(DecompressionError, RangeInclusive<DateTime>),
     #[error("decode error: {0}, in {1:?}")]
     Decode(DecodingError, RangeInclusive<DateTime>),
+    /// The records whose fields are undecodable are skipped, and the rest of the
+    /// chunk is still parsed. It carries the first error and the count of them.
+    #[error("decode error: {0}, {1} records skipped, in {2:?}")]
+    Skipped(DecodingError, usize, RangeInclusive<DateTime>),
+    #[error("{0}, {1} chunks skipped")]
+    Unsupported(chunk::Error, usize),
+    #[error("{0}, in {1:?}")]
+    Chunk(chunk::Error, RangeInclusive<DateTime>),
+    #[error("key mismatch, the chunk is encrypted for the key {chunk_pub_key_fingerprint}, in {time_range:?}")]
+    KeyMismatch { chunk_pub_key_fingerprint: String, time_range: RangeInclusive<DateTime> },
+    #[error("{0}, in {1:?}")]
+    Limit(ParseLimit, RangeInclusive<DateTime>),
 
     // The collection of chunk errors.
     #[error("chunk errors: {:#?}", .0.iter().map(|e|e.to_string()).collect::<Vec<_>>())]
     Chunks(Vec<Error>),
 }
 
+/// The resource limits of the parsing, which a chunk has exceeded (see
+/// [`Error::Limit`]).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseLimit {
+    /// See [`ParseOptions::max_record_len`].
+    RecordLen(usize),
+    /// See [`ParseOptions::max_chunk_payload`].
+    ChunkPayload(usize),
+    /// See [`ParseOptions::max_decompressed_bytes_per_chunk`].
+    DecompressedBytes(usize),
+}
+
+impl Display for ParseLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RecordLen(max) => write!(f, "the record is longer than {max} bytes"),
+            Self::ChunkPayload(max) => write!(f, "the chunk payload is longer than {max} bytes"),
+            Self::DecompressedBytes(max) => {
+                write!(f, "the chunk is decompressed into more than {max} bytes")
+            }
+        }
+    }
+}
+
+/// A transformation applied to the records during parsing, see
+/// [`ParseOptions::map`].
+///
+/// It returns the transformed record, or `None` to drop the record.
+pub type RecordMapper = Box<dyn FnMut(OwnedRecord) -> Option<OwnedRecord>>;
+
+/// A callback that receives the producer of the records during parsing, see
+/// [`ParseOptions::on_producer`].
+pub type ProducerCallback = Box<dyn FnMut(&Producer)>;
+
+/// A callback that receives the reasons of the chunk rotations during parsing, see
+/// [`ParseOptions::on_rotate`].
+pub type RotateCallback = Box<dyn FnMut(RotateReason, DateTime)>;
+
+/// A callback that receives the statistics of the chunks during parsing, see
+/// [`ParseOptions::on_chunk`].
+pub type ChunkCallback = Box<dyn FnMut(&ChunkStats)>;
+
+/// The statistics of a parsed chunk, see [`ParseOptions::on_chunk`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChunkStats {
+    /// The time range of the records in the chunk.
+    pub time_range: RangeInclusive<DateTime>,
+    /// The length (in bytes) of the payload in the log file, which is compressed and
+    /// maybe encrypted.
+    pub payload_len: usize,
+    /// The length (in bytes) of the encoded records after the payload has been
+    /// decrypted and decompressed.
+    pub decoded_len: usize,
+    /// The length (in bytes) of the encoded records that can't be recovered, i.e.,
+    /// the records skipped for their undecodable fields, the rest of the chunk after
+    /// the record whose frame (see [`Record::SCHEMA`]) is broken, and the incomplete
+    /// record at the end.
+    pub unrecoverable_len: usize,
+}
+
+/// The metadata of a chunk read from its header, see [`inspect`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChunkInfo {
+    /// The time range of the records in the chunk.
+    pub time_range: RangeInclusive<DateTime>,
+    /// The length (in bytes) of the payload in the log file, which is compressed and
+    /// maybe encrypted.
+    pub payload_len: usize,
+    /// The format version that the chunk is written in.
+    pub version: u16,
+    /// Whether the payload is encrypted.
+    pub encrypted: bool,
+    /// The sequence number of the chunk in the logger instance that wrote it, which
+    /// increases with the chunks written even if the clock jumps backwards. `None`
+    /// if the chunk isn't numbered.
+    pub sequence: Option<u64>,
+}
+
+/// Options of the log parsing, see [`parse_with_options`].
+#[derive(Default)]
+pub struct ParseOptions {
+    redactor: Option<Redactor>,
+    map: Option<RecordMapper>,
+    on_producer: Option<ProducerCallback>,
+    on_rotate: Option<RotateCallback>,
+    on_chunk: Option<ChunkCallback>,
+    signing_key: Option<SigningKey>,
+    secret_keys: Vec<SecretKey>,
+    /// The query that the parsed chunks may have records matching, see
+    /// [`Query::may_match_chunk`].
+    chunk_query: Option<Query>,
+    max_record_len: Option<usize>,
+    max_chunk_payload: Option<usize>,
+    max_decompressed_bytes_per_chunk: Option<usize>,
+    max_window_log: Option<u32>,
+}
+
+impl ParseOptions {
+    /// Constructs a new `ParseOptions`.
+    ///
+    /// None of the resource limits is set, which suits the log files written by
+    /// the trusted loggers. The ones of other origins (e.g., uploaded by clients)
+    /// should be parsed with [`ParseOptions::hardened`].
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Constructs a new `ParseOptions` with the resource limits set for the
+    /// untrusted log files:
+    ///
+    /// * [`BUFFER_LEN`] for [`ParseOptions::max_record_len`].
+    /// * `16 MB` for [`ParseOptions::max_chunk_payload`].
+    /// * `64 MB` for [`ParseOptions::max_decompressed_bytes_per_chunk`].
+    /// * `23` for [`ParseOptions::max_window_log`].
+    #[inline]
+    pub fn hardened() -> Self {
+        Self::new()
+            .max_record_len(Some(BUFFER_LEN))
+            .max_chunk_payload(Some(16 * 1024 * 1024))
+            .max_decompressed_bytes_per_chunk(Some(64 * 1024 * 1024))
+            .max_window_log(Some(23))
+    }
+
+    /// The redactor applied to the content of the records before they are called
+    /// back (e.g., formatted), see [`Redact`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn redactor(mut self, redactor: Option<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// The transformation applied to the records (after redaction) before they are
+    /// called back, which can normalize the records (e.g., tags and levels) or drop
+    /// them.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn map(mut self, map: Option<RecordMapper>) -> Self {
+        self.map = map;
+        self
+    }
+
+    /// The callback that receives the producer of the records (see [`Producer`]).
+    ///
+    /// It is called whenever the producer changes between chunks, before the
+    /// records of the chunk are called back, so the records can be attributed to
+    /// the process and the versions that produced them.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn on_producer(mut self, callback: Option<ProducerCallback>) -> Self {
+        self.on_producer = callback;
+        self
+    }
+
+    /// The callback that receives the reason and the datetime of the chunk
+    /// rotations initiated by [`Logger::rotate`].
+    ///
+    /// It is called before the records of the chunk started by the rotation are
+    /// called back, which helps to diagnose the gaps in the logs.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`Logger::rotate`]: crate::Logger::rotate
+    #[inline]
+    pub fn on_rotate(mut self, callback: Option<RotateCallback>) -> Self {
+        self.on_rotate = callback;
+        self
+    }
+
+    /// The callback that receives the statistics of each chunk (see
+    /// [`ChunkStats`]), after the records of the chunk have been called back.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn on_chunk(mut self, callback: Option<ChunkCallback>) -> Self {
+        self.on_chunk = callback;
+        self
+    }
+
+    /// The signing key that the chunks have been signed with (see
+    /// [`Config::signing_key`]).
+    ///
+    /// If it is set, the signature of each chunk is verified after its records
+    /// have been called back, the chunks whose signatures don't match (or are
+    /// missing) are reported as [`ChunkError::SignatureMismatch`] afterwards.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`Config::signing_key`]: crate::Config::signing_key
+    /// [`ChunkError::SignatureMismatch`]: crate::ChunkError::SignatureMismatch
+    #[inline]
+    pub fn signing_key(mut self, key: Option<SigningKey>) -> Self {
+        self.signing_key = key;
+        self
+    }
+
+    /// The candidate secret keys besides the one passed to the parsing function,
+    /// so that the files spanning a key rotation can be parsed in one pass.
+    ///
+    /// The key of each chunk is chosen by the fingerprint of the public key that
+    /// the chunk is encrypted for (see [`key_fingerprint`]). The chunks without the
+    /// fingerprint (i.e., written by older versions of Pinenut) are decrypted with
+    /// the first key.
+    ///
+    /// The default value is empty.
+    ///
+    /// [`key_fingerprint`]: crate::encrypt::key_fingerprint
+    #[inline]
+    pub fn secret_keys(mut self, keys: &[SecretKey]) -> Self {
+        self.secret_keys = keys.to_vec();
+        self
+    }
+
+    /// The maximum length (in bytes) of an encoded record, e.g., to reject the
+    /// absurd length prefixes of the corrupted or malicious files early.
+    ///
+    /// The rest of the chunk after the record exceeding it is not parsed, and the
+    /// chunk is reported as [`Error::Limit`] afterwards. The records are never
+    /// longer than [`BUFFER_LEN`] in any case.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn max_record_len(mut self, len: Option<usize>) -> Self {
+        self.max_record_len = len;
+        self
+    }
+
+    /// The maximum length (in bytes) of the payload of a chunk, which is
+    /// compressed and maybe encrypted.
+    ///
+    /// The chunk exceeding it is skipped without being decrypted, and reported as
+    /// [`Error::Limit`] afterwards.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn max_chunk_payload(mut self, len: Option<usize>) -> Self {
+        self.max_chunk_payload = len;
+        self
+    }
+
+    /// The maximum length (in bytes) that the payload of a chunk is decompressed
+    /// into, which guards against the decompression bombs.
+    ///
+    /// The decompression of the chunk exceeding it is stopped, and the chunk is
+    /// reported as [`Error::Limit`] afterwards.
+    ///
+    /// The log file wrapped in a `zstd` frame (see [`ExtractOptions::zstd_frame`])
+    /// is decompressed as well, so the chunks unwrapped from it are limited too.
+    /// The parsing stops at the chunk exceeding it, as the rest of the frame can't
+    /// be reached without decompressing the chunk.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`ExtractOptions::zstd_frame`]: crate::ExtractOptions::zstd_frame
+    #[inline]
+    pub fn max_decompressed_bytes_per_chunk(mut self, len: Option<usize>) -> Self {
+        self.max_decompressed_bytes_per_chunk = len;
+        self
+    }
+
+    /// The maximum window log of the `zstd` frames of the chunks (and of the log
+    /// file wrapped in a frame), which bounds the memory that the decompression
+    /// takes (`2^window_log` bytes).
+    ///
+    /// The chunks of larger windows (e.g., compressed at the levels above 19, or
+    /// with a larger [`ZstdParams::window_log`]) fail to be decompressed.
+    ///
+    /// The default value is `None`, which means the window logs up to 30 are
+    /// accepted.
+    ///
+    /// [`ZstdParams::window_log`]: crate::ZstdParams::window_log
+    #[inline]
+    pub fn max_window_log(mut self, window_log: Option<u32>) -> Self {
+        self.max_window_log = window_log;
+        self
+    }
+
+    /// Skips the chunks having no records matching the query for sure, see
+    /// [`query`].
+    ///
+    /// [`query`]: crate::query
+    #[inline]
+    pub(crate) fn chunk_query(mut self, query: Option<Query>) -> Self {
+        self.chunk_query = query;
+        self
+    }
+}
+
 /// Parses the compressed and encrypted binary log file into multiple log records and
 /// calls them back one by one.
+#[inline]
 pub fn parse(
     path: impl AsRef<Path>,
     secret_key: Option<SecretKey>,
     callback: impl FnMut(&Record) -> Result<(), io::Error>,
 ) -> Result<(), Error> {
-    let reader = BufReader::new(File::open(path.as_ref())?);
+    parse_with_options(path, secret_key, ParseOptions::default(), callback)
+}
+
+/// Parses the compressed and encrypted binary log file into multiple log records
+/// with options, and calls them back one by one.
+#[inline]
+pub fn parse_with_options(
+    path: impl AsRef<Path>,
+    secret_key: Option<SecretKey>,
+    options: ParseOptions,
+    callback: impl FnMut(&Record) -> Result<(), io::Error>,
+) -> Result<(), Error> {
+    let source = Source::open(path.as_ref(), options.max_window_log)?;
+    parse_chunks(source, None, secret_key, options, callback)
+}
+
+/// Parses the chunks of the log file from the offset with options, and calls their
+/// records back one by one, so that the log file being written can be followed.
+///
+/// The offset is advanced past the parsed chunks, the chunk that hasn't been
+/// written completely is left for the next call, and so is the footer of the
+/// sealed file (see [`crate::Config::minute_index`]), which is replaced by the
+/// chunks appended if the file is opened for writing again.
+///
+/// If no chunk starts at the offset (e.g., the file has been truncated), it is
+/// re-synced to the last chunk starting before it.
+pub fn parse_incremental(
+    path: impl AsRef<Path>,
+    offset: &mut u64,
+    secret_key: Option<SecretKey>,
+    options: ParseOptions,
+    callback: impl FnMut(&Record) -> Result<(), io::Error>,
+) -> Result<(), Error> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    *offset = sync_offset(&file, *offset, len)?;
+    file.seek(SeekFrom::Start(*offset))?;
+    let source = Source::File(BufReader::new(file));
+    parse_chunks(source, Some((offset, len)), secret_key, options, callback)
+}
+
+/// The offset of the log file being followed from which the chunks are parsed,
+/// see [`parse_incremental`].
+///
+/// It is kept if a chunk starts at it, or the file ends at it. Otherwise the file
+/// has changed under it, and the chunk headers are walked to find the start of
+/// the chunk containing it, or the end of the complete chunks if the file ends
+/// before it.
+fn sync_offset(file: &File, offset: u64, len: u64) -> Result<u64, Error> {
+    let mut reader = BufReader::new(file);
+    if offset <= len {
+        reader.seek(SeekFrom::Start(offset))?;
+        let header = chunk::Reader::new(&mut reader).read_header_or_reach_to_end().map(|_| ());
+        if !matches!(header, Err(chunk::ReadError::Invalid)) {
+            return Ok(offset);
+        }
+    }
+
+    reader.rewind()?;
     let mut reader = chunk::Reader::new(reader);
+    loop {
+        let position = reader.stream_position()?;
+        let payload_len = match reader.read_header_or_reach_to_end() {
+            Ok(Some(header)) => header.payload_len(),
+            // The chunks end before the offset.
+            Ok(None) | Err(_) => return Ok(position),
+        };
+        reader.skip(payload_len)?;
+        if reader.stream_position()? > offset.min(len) {
+            return Ok(position);
+        }
+    }
+}
+
+/// Parses the log file in memory, for the fuzz targets.
+#[cfg(feature = "fuzzing")]
+pub(crate) fn parse_bytes(
+    bytes: &[u8],
+    secret_key: Option<SecretKey>,
+    callback: impl FnMut(&Record) -> Result<(), io::Error>,
+) -> Result<(), Error> {
+    let source = Source::Bytes(io::Cursor::new(bytes.to_vec()));
+    parse_chunks(source, None, secret_key, ParseOptions::default(), callback)
+}
+
+/// Decodes the records from the decompressed bytes of a chunk in the format
+/// version, which arrive in the pieces of the length, for the fuzz targets.
+#[cfg(feature = "fuzzing")]
+pub(crate) fn decode_records(
+    bytes: &[u8],
+    version: u16,
+    piece_len: usize,
+    callback: impl FnMut(&Record) -> Result<(), io::Error>,
+) -> Result<(), DecodingError> {
+    let mut parser = RecordParser::new(callback);
+    parser.version = version;
+    for piece in bytes.chunks(piece_len.max(1)) {
+        parser.parse_all(piece).expect("the callback never fails");
+    }
+    parser.broken.map_or(Ok(()), Err)
+}
+
+/// Reads the metadata of the chunks in the log file from their headers, without
+/// decrypting or decompressing the payloads, e.g., to show the size and the time
+/// span of the logs cheaply.
+///
+/// The footer of the log file (see [`crate::Config::minute_index`]) carries no
+/// records and is not reported, nor is the chunk that hasn't been written
+/// completely at the end.
+pub fn inspect(path: impl AsRef<Path>) -> Result<Vec<ChunkInfo>, Error> {
+    let mut reader = chunk::Reader::new(Source::open(path.as_ref(), None)?);
+    let mut chunks = Vec::new();
+    loop {
+        let header = match reader.read_header_or_reach_to_end() {
+            Ok(Some(header)) => header.clone(),
+            Ok(None) | Err(chunk::ReadError::UnexpectedEnd) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let payload_len = header.payload_len();
+        let extensions = reader.extensions();
+        if !extensions.is_footer() {
+            let time_range = header.time_range();
+            chunks.push(ChunkInfo {
+                time_range: time_range.start()..=time_range.end(),
+                payload_len,
+                version: header.version(),
+                encrypted: header.pub_key() != EMPTY_PUBLIC_KEY,
+                sequence: extensions.sequence().map(|(_, sequence)| sequence),
+            });
+        }
+        reader.skip(payload_len)?;
+    }
+    Ok(chunks)
+}
 
-    let parser = RecordParser::new(callback);
-    let mut processor = Processor::new(secret_key, parser);
+/// Parses the chunks from the source, and calls their records back one by one.
+///
+/// If the progress (i.e., the offset and the length of the log file) is specified,
+/// the offset is advanced past the parsed chunks, and the parsing stops at the
+/// chunk exceeding the length instead of failing.
+fn parse_chunks(
+    source: Source,
+    mut progress: Option<(&mut u64, u64)>,
+    secret_key: Option<SecretKey>,
+    options: ParseOptions,
+    mut callback: impl FnMut(&Record) -> Result<(), io::Error>,
+) -> Result<(), Error> {
+    let ParseOptions {
+        redactor,
+        mut map,
+        mut on_producer,
+        mut on_rotate,
+        mut on_chunk,
+        signing_key,
+        secret_keys,
+        chunk_query,
+        max_record_len,
+        max_chunk_payload,
+        max_decompressed_bytes_per_chunk,
+        max_window_log,
+    } = options;
+    let mut callback = |record: &Record| match map.as_mut() {
+        Some(map) => map(record.into()).map_or(Ok(()), |record| callback(&record.as_record())),
+        None => callback(record),
+    };
+    let callback = |record: &Record| {
+        let redacted = redactor.as_ref().and_then(|r| r.redact(record.content()));
+        match redacted {
+            Some(content) => {
+                let mut record = record.clone();
+                record.set_content(&content);
+                callback(&record)
+            }
+            None => callback(record),
+        }
+    };
+
+    let mut parser = RecordParser::new(callback);
+    parser.max_record_len = max_record_len;
+    parser.max_decoded_len = max_decompressed_bytes_per_chunk;
+    let secret_keys = secret_key.into_iter().chain(secret_keys).collect();
+    let mut processor = Processor::new(secret_keys, parser, max_window_log);
+    let is_unwrapped = matches!(source, Source::Unwrapped(_));
+    let mut reader = chunk::Reader::new(source);
 
     let mut chunk_errors = Vec::new();
+    // The counts of the skipped chunks by version.
+    let mut unsupported = BTreeMap::new();
+    let mut last_producer = None;
 
-    while let Some(header) = reader.read_header_or_reach_to_end()? {
-        // Version is not supported, just skips this chunk.
-        if header.version() != FORMAT_VERSION {
+    loop {
+        if let Some((offset, _)) = progress.as_mut() {
+            **offset = reader.stream_position()?;
+        }
+        let header = match reader.read_header_or_reach_to_end() {
+            Ok(Some(header)) => header.clone(),
+            Ok(None) => break,
+            // The chunk being written is parsed next time.
+            Err(chunk::ReadError::UnexpectedEnd) if progress.is_some() => break,
+            Err(err) => return Err(err.into()),
+        };
+        let (version, payload_len) = (header.version(), header.payload_len());
+        if let Some((_, len)) = progress.as_ref()
+            && reader.stream_position()? + payload_len as u64 > *len
+        {
+            break;
+        }
+
+        // Version is not supported (e.g., written by a newer version of Pinenut), skips
+        // this chunk and reports it afterwards.
+        if !SUPPORTED_VERSIONS.contains(&version) {
+            *unsupported.entry(version).or_insert(0) += 1;
+            reader.skip(payload_len)?;
+            continue;
+        }
+
+        // The footer of the log file being followed is left to the next call, as it is
+        // replaced if the file is written again.
+        if progress.is_some() && reader.extensions().is_footer() {
+            break;
+        }
+
+        // The footer of the log file (see `Config::minute_index`) carries no records,
+        // and the chunk is skipped without being decrypted if it has no records
+        // matching the query for sure.
+        let is_skipped = reader.extensions().is_footer()
+            || chunk_query
+                .as_ref()
+                .is_some_and(|q| !q.may_match_chunk(&header, reader.extensions()));
+        if is_skipped {
+            reader.skip(payload_len)?;
             continue;
         }
 
-        let payload_len = header.payload_len();
         let time_range = header.time_range().start()..=header.time_range().end();
-        let mut sink =
-            processor.chunk_sink(payload_len, header.pub_key(), time_range, header.writeback());
+        let (pub_key, writeback) = (header.pub_key(), header.writeback());
+
+        // The chunk exceeding the limit is skipped without being decrypted.
+        if let Some(max) = max_chunk_payload
+            && payload_len > max
+        {
+            chunk_errors.push(Error::Limit(ParseLimit::ChunkPayload(max), time_range));
+            reader.skip(payload_len)?;
+            continue;
+        }
+        // The chunk unwrapped from the frame has been decompressed, the rest of the
+        // frame can only be reached by decompressing it.
+        if let Some(max) = max_decompressed_bytes_per_chunk
+            && is_unwrapped
+            && payload_len > max
+        {
+            chunk_errors.push(Error::Limit(ParseLimit::DecompressedBytes(max), time_range));
+            break;
+        }
+
+        if let Some(on_producer) = on_producer.as_mut() {
+            let producer = Producer::from_extensions(reader.extensions());
+            if last_producer.as_ref() != Some(&producer) {
+                on_producer(&producer);
+                last_producer = Some(producer);
+            }
+        }
+
+        let reason = on_rotate.as_mut().and_then(|on_rotate| {
+            let reason = reader.extensions().get(Extensions::ROTATE_REASON)?;
+            Some((on_rotate, RotateReason::try_from(*reason.first()?).ok()?))
+        });
+        if let Some((on_rotate, reason)) = reason {
+            on_rotate(reason, *time_range.start());
+        }
+
+        // The chunk encrypted for another key can't be decrypted, skips it and reports
+        // the fingerprint of that key afterwards.
+        let extensions = reader.extensions();
+        let chunk_fingerprint = extensions
+            .get(Extensions::KEY_FINGERPRINT)
+            .and_then(|fingerprint| Fingerprint::try_from(fingerprint).ok());
+        if let Some(fingerprint) = chunk_fingerprint
+            && pub_key != EMPTY_PUBLIC_KEY
+            && processor.lacks_key(fingerprint)
+        {
+            chunk_errors.push(Error::KeyMismatch {
+                chunk_pub_key_fingerprint: fingerprint_hex(&fingerprint),
+                time_range,
+            });
+            reader.skip(payload_len)?;
+            continue;
+        }
+
+        // The chunks without the algorithm are encrypted with the default one.
+        let algorithm = match extensions.get(Extensions::KEY_ALGORITHM) {
+            Some(value) => value.first().and_then(|v| KeyAlgorithm::try_from(*v).ok()),
+            None => Some(KeyAlgorithm::default()),
+        };
+        let key = ChunkKey { pub_key, algorithm, fingerprint: chunk_fingerprint };
+
+        // The signature and the checksum (of the sealed chunk) are verified against the
+        // payload as it is read, the records of the damaged chunk are still parsed as
+        // far as possible.
+        let mut signer = signing_key.map(|key| chunk::signer(&key, &header, extensions));
+        let signature = extensions.get(Extensions::SIGNATURE).map(<[u8]>::to_vec);
+        let mut checksum = extensions
+            .checksum()
+            .map(|value| (value.to_vec(), chunk::Checksum::new(&header, extensions)));
 
-        if let Err(err) = reader.read_payload(payload_len, &mut sink) {
-            if err.can_continue_to_read_chunk() {
+        let result = {
+            let mut chunk_sink =
+                processor.chunk_sink(version, payload_len, key, time_range.clone(), writeback);
+            let mut sink = FnSink::new(|bytes: &[u8]| {
+                if let Some(signer) = signer.as_mut() {
+                    signer.update(bytes);
+                }
+                if let Some((_, checksum)) = checksum.as_mut() {
+                    checksum.update(bytes);
+                }
+                Sink::<chunk::ReadError>::sink(&mut chunk_sink, bytes)
+            });
+            reader.read_payload(payload_len, &mut sink)
+        };
+        match result {
+            Ok(()) => {
+                if checksum.is_some_and(|(value, checksum)| !checksum.matches(&value)) {
+                    let err = chunk::Error::ChecksumMismatch;
+                    chunk_errors.push(Error::Chunk(err, time_range.clone()));
+                }
+                let verified = signer.map(|signer| {
+                    signature.is_some_and(|signature| signer.verify_slice(&signature).is_ok())
+                });
+                if verified == Some(false) {
+                    let err = chunk::Error::SignatureMismatch;
+                    chunk_errors.push(Error::Chunk(err, time_range.clone()));
+                }
+            }
+            Err(err) if err.can_continue_to_read_chunk() => {
+                processor.reset();
                 chunk_errors.push(err);
-            } else {
-                return Err(err);
             }
+            Err(err) => return Err(err),
+        }
+
+        let parser = &mut processor.parser;
+        if let Some((err, count)) = parser.skipped.take() {
+            chunk_errors.push(Error::Skipped(err, count, time_range.clone()));
+        }
+        if let Some(err) = parser.broken.take() {
+            chunk_errors.push(Error::Decode(err, time_range.clone()));
+        }
+        let decoded_len = mem::take(&mut parser.decoded_len);
+        let unrecoverable_len = mem::take(&mut parser.unrecoverable_len);
+        if let Some(on_chunk) = on_chunk.as_mut() {
+            on_chunk(&ChunkStats { time_range, payload_len, decoded_len, unrecoverable_len });
         }
     }
 
+    chunk_errors.extend(unsupported.into_iter().map(|(version, count)| {
+        Error::Unsupported(chunk::Error::UnsupportedVersion(version), count)
+    }));
+
     if chunk_errors.is_empty() {
         Ok(())
     } else {
@@ -103,37 +751,326 @@ pub fn parse_to_file(
     path: impl AsRef<Path>,
     dest_path: impl AsRef<Path>,
     secret_key: Option<SecretKey>,
+    formatter: impl Format,
+) -> Result<(), Error> {
+    parse_to_file_with_options(path, dest_path, secret_key, ParseOptions::default(), formatter)
+}
+
+/// Parses the compressed and encrypted binary log file into readable text file with
+/// options.
+///
+/// See [`parse_to_file`] for the details.
+pub fn parse_to_file_with_options(
+    path: impl AsRef<Path>,
+    dest_path: impl AsRef<Path>,
+    secret_key: Option<SecretKey>,
+    options: ParseOptions,
     mut formatter: impl Format,
 ) -> Result<(), Error> {
     let dest_path = dest_path.as_ref();
     let mut writer = BufWriter::new(LazyFileWriter::new(dest_path));
-    parse(path, secret_key, |record| formatter.format(record, &mut writer))
+    parse_with_options(path, secret_key, options, |record| formatter.format(record, &mut writer))
+}
+
+/// Parses multiple binary log files (e.g., from different domains or devices), and
+/// merges their records into a single readable text file in chronological order.
+///
+/// The records are held in memory to be sorted, and the records with the same
+/// datetime remain in the order of the paths. Chunk errors in a file don't stop the
+/// merging, they are reported together afterwards.
+///
+/// See [`parse_to_file`] for the details of the destination file.
+pub fn merge(
+    paths: &[impl AsRef<Path>],
+    secret_key: Option<SecretKey>,
+    mut formatter: impl Format,
+    dest_path: impl AsRef<Path>,
+) -> Result<(), Error> {
+    let mut records = Vec::new();
+    let mut chunk_errors = Vec::new();
+
+    for path in paths {
+        let res = parse(path, secret_key, |record| {
+            records.push(OwnedRecord::from(record));
+            Ok(())
+        });
+        match res {
+            Ok(()) => {}
+            Err(Error::Chunks(errors)) => chunk_errors.extend(errors),
+            Err(err) => return Err(err),
+        }
+    }
+
+    records.sort_by_key(|record| record.datetime());
+
+    let mut writer = BufWriter::new(LazyFileWriter::new(dest_path.as_ref()));
+    for record in &records {
+        formatter.format(&record.as_record(), &mut writer)?;
+    }
+    writer.flush()?;
+
+    if chunk_errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Chunks(chunk_errors))
+    }
+}
+
+/// The labels of the levels presented by the formatters, so that the parsed
+/// records match an existing vocabulary of levels (e.g., `FATAL` and `TRACE`), see
+/// [`CustomDefaultFormatter::level_labels`] and [`TemplateFormatter::level_labels`].
+///
+/// [`TemplateFormatter::level_labels`]: crate::TemplateFormatter::level_labels
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LevelLabels([String; 5]);
+
+impl LevelLabels {
+    /// Constructs a new `LevelLabels` from the labels of all the levels, which are
+    /// in the order of [`Level::iter`], i.e., from the most severe one.
+    #[inline]
+    pub fn new(labels: [impl Into<String>; 5]) -> Self {
+        Self(labels.map(Into::into))
+    }
+
+    /// Returns the label of the level.
+    #[inline]
+    pub fn label(&self, level: Level) -> &str {
+        &self.0[level as usize - 1]
+    }
 }
 
 /// The default formatter provides simple log formatting.
+///
+/// The datetimes are presented in the local timezone and the levels by their
+/// initials, see [`DefaultFormatter::timezone`] and
+/// [`DefaultFormatter::level_labels`] to customize them.
+#[derive(Clone, Copy, Default, Debug)]
 pub struct DefaultFormatter;
 
+impl DefaultFormatter {
+    /// Returns the formatter presenting the datetimes in the timezone, see
+    /// [`CustomDefaultFormatter::timezone`].
+    #[inline]
+    pub fn timezone(self, timezone: Timezone) -> CustomDefaultFormatter {
+        CustomDefaultFormatter::default().timezone(timezone)
+    }
+
+    /// Returns the formatter presenting the levels by the labels, see
+    /// [`CustomDefaultFormatter::level_labels`].
+    #[inline]
+    pub fn level_labels(self, labels: Option<LevelLabels>) -> CustomDefaultFormatter {
+        CustomDefaultFormatter::default().level_labels(labels)
+    }
+}
+
 impl Format for DefaultFormatter {
+    #[inline]
+    fn format(&mut self, record: &Record, writer: &mut impl Write) -> io::Result<()> {
+        CustomDefaultFormatter::default().format(record, writer)
+    }
+}
+
+/// The [`DefaultFormatter`] with the customized presentation, e.g., the timezone
+/// of the datetimes.
+#[derive(Clone, Default, Debug)]
+pub struct CustomDefaultFormatter {
+    timezone: Timezone,
+    /// It is shared by the clones of the formatter.
+    level_labels: Option<Arc<LevelLabels>>,
+}
+
+impl CustomDefaultFormatter {
+    /// The timezone in which the datetimes are presented, see [`Timezone`].
+    #[inline]
+    pub fn timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// The labels of the levels, see [`LevelLabels`].
+    ///
+    /// The default value is `None`, which means the levels are presented by their
+    /// initials, e.g., `W`.
+    #[inline]
+    pub fn level_labels(mut self, labels: Option<LevelLabels>) -> Self {
+        self.level_labels = labels.map(Arc::new);
+        self
+    }
+}
+
+impl Format for CustomDefaultFormatter {
     #[inline]
     fn format(&mut self, record: &Record, writer: &mut impl Write) -> io::Result<()> {
         const LEVELS: [&str; 5] = ["E", "W", "I", "D", "V"];
         let (meta, content) = (record.meta(), record.content());
-        let datetime: chrono::DateTime<chrono::Local> = meta.datetime().into();
+        let datetime = self.timezone.convert(meta.datetime());
+        let level = match &self.level_labels {
+            Some(labels) => labels.label(meta.level()),
+            None => LEVELS[meta.level() as usize - 1],
+        };
 
-        writeln!(
+        write!(
             writer,
-            "[{}] {}|{}|{}:{}|{}|{}",
-            LEVELS[meta.level() as usize - 1],
+            "[{}] {}|{}",
+            level,
             datetime.format("%F %T%.3f"),
-            meta.thread_id().unwrap_or(0),
+            meta.thread_id().unwrap_or(0)
+        )?;
+        if let Some(name) = meta.thread_name() {
+            write!(writer, "({name})")?;
+        }
+        write!(
+            writer,
+            "|{}:{}|{}|{}",
             meta.location().file().unwrap_or(""),
             meta.location().line().unwrap_or(0),
             meta.tag().unwrap_or(""),
             content
-        )
+        )?;
+        for (index, (key, value)) in record.context().iter().enumerate() {
+            let separator = if index == 0 { " {" } else { ", " };
+            write!(writer, "{separator}{key}={value}")?;
+        }
+        if !record.context().is_empty() {
+            write!(writer, "}}")?;
+        }
+        if record.truncated() {
+            write!(writer, " (truncated)")?;
+        }
+        match record.repeats() {
+            0 => writeln!(writer),
+            repeats => writeln!(writer, " (repeated {repeats} times)"),
+        }
+    }
+}
+
+/// The formatter that formats log records into CSV (RFC 4180) rows, which can be
+/// loaded into spreadsheets.
+///
+/// The header row is written before the first record. The datetimes are in RFC
+/// 3339 format in UTC, and the absent fields are empty.
+#[derive(Default)]
+pub struct CsvFormatter {
+    header_written: bool,
+}
+
+impl CsvFormatter {
+    /// The columns of the rows.
+    const HEADER: &'static str =
+        "datetime,level,tag,thread_id,thread_name,session_id,file,func,line,content,repeats";
+
+    /// Constructs a new `CsvFormatter`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the field, which is quoted if it contains the special characters.
+    fn write_field(field: &str, writer: &mut impl Write) -> io::Result<()> {
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))
+        } else {
+            writer.write_all(field.as_bytes())
+        }
+    }
+}
+
+impl Format for CsvFormatter {
+    fn format(&mut self, record: &Record, writer: &mut impl Write) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(writer, "{}", Self::HEADER)?;
+            self.header_written = true;
+        }
+
+        let (meta, location) = (record.meta(), record.meta().location());
+        write!(writer, "{},{},", rfc3339(meta.datetime()), meta.level())?;
+        Self::write_field(meta.tag().unwrap_or_default(), writer)?;
+        write!(writer, ",{},", optional(meta.thread_id()))?;
+        Self::write_field(meta.thread_name().unwrap_or_default(), writer)?;
+        write!(writer, ",{},", optional(meta.session_id()))?;
+        Self::write_field(location.file().unwrap_or_default(), writer)?;
+        writer.write_all(b",")?;
+        Self::write_field(location.func().unwrap_or_default(), writer)?;
+        write!(writer, ",{},", optional(location.line()))?;
+        Self::write_field(record.content(), writer)?;
+        writeln!(writer, ",{}", record.repeats())
+    }
+}
+
+/// The formatter that formats log records into [logfmt](https://brandur.org/logfmt)
+/// lines, which can be ingested by log pipelines (e.g., Loki, Vector).
+///
+/// The datetimes are in RFC 3339 format in UTC, and the absent fields are omitted.
+/// The pairs of the context (see [`Record::context`]) follow the fields, before
+/// the content (`msg`).
+pub struct LogfmtFormatter;
+
+impl LogfmtFormatter {
+    /// Writes the key-value pair, the value is quoted if it contains the special
+    /// characters.
+    fn write_pair(key: &str, value: &str, writer: &mut impl Write) -> io::Result<()> {
+        let needs_quotes = value.is_empty()
+            || value.contains(|c: char| c == ' ' || c == '=' || c == '"' || c.is_control());
+        if !needs_quotes {
+            return write!(writer, " {key}={value}");
+        }
+
+        write!(writer, " {key}=\"")?;
+        for char in value.chars() {
+            match char {
+                '"' => writer.write_all(b"\\\"")?,
+                '\\' => writer.write_all(b"\\\\")?,
+                '\n' => writer.write_all(b"\\n")?,
+                '\r' => writer.write_all(b"\\r")?,
+                '\t' => writer.write_all(b"\\t")?,
+                char => write!(writer, "{char}")?,
+            }
+        }
+        writer.write_all(b"\"")
+    }
+}
+
+impl Format for LogfmtFormatter {
+    fn format(&mut self, record: &Record, writer: &mut impl Write) -> io::Result<()> {
+        let (meta, location) = (record.meta(), record.meta().location());
+        write!(writer, "time={} level={}", rfc3339(meta.datetime()), meta.level())?;
+
+        let pairs = [
+            ("tag", meta.tag().map(str::to_string)),
+            ("thread_id", meta.thread_id().map(|id| id.to_string())),
+            ("thread_name", meta.thread_name().map(str::to_string)),
+            ("session_id", meta.session_id().map(|id| id.to_string())),
+            ("elapsed", meta.elapsed().map(|elapsed| elapsed.to_string())),
+            ("sequence", meta.sequence().map(|sequence| sequence.to_string())),
+            ("file", location.file().map(str::to_string)),
+            ("func", location.func().map(str::to_string)),
+            ("line", location.line().map(|line| line.to_string())),
+            ("repeats", (record.repeats() > 0).then(|| record.repeats().to_string())),
+            ("truncated", record.truncated().then(|| "true".to_string())),
+        ];
+        for (key, value) in pairs {
+            if let Some(value) = value {
+                Self::write_pair(key, &value, writer)?;
+            }
+        }
+        for (key, value) in record.context() {
+            Self::write_pair(key, value, writer)?;
+        }
+        Self::write_pair("msg", record.content(), writer)?;
+        writeln!(writer)
     }
 }
 
+#[inline]
+fn rfc3339(datetime: DateTime) -> String {
+    datetime.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+#[inline]
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
 // ============ Internal ============
 
 #[derive(Error, Debug)]
@@ -146,6 +1083,73 @@ enum ChunkError {
     Decompress(#[from] DecompressionError),
     #[error(transparent)]
     Decode(#[from] DecodingError),
+    #[error("{0}")]
+    Limit(ParseLimit),
+}
+
+/// The source of the log file being parsed.
+enum Source {
+    File(BufReader<File>),
+    /// The log file unwrapped from a `zstd` frame as it is read, see
+    /// [`crate::ExtractOptions::zstd_frame`].
+    Unwrapped(ZstdDecompressReader<BufReader<File>>),
+    /// The log file in memory, for the fuzz targets and the tests.
+    #[cfg(any(test, feature = "fuzzing"))]
+    Bytes(io::Cursor<Vec<u8>>),
+}
+
+impl Source {
+    /// The magic number at the start of a `zstd` frame.
+    const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+    /// Opens the log file, the one wrapped in a `zstd` frame is unwrapped as it is
+    /// read, with the maximum window log (see [`ParseOptions::max_window_log`]).
+    fn open(path: &Path, max_window_log: Option<u32>) -> Result<Self, Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        let is_wrapped = reader.read_exact(&mut magic).is_ok() && magic == Self::ZSTD_MAGIC;
+        reader.rewind()?;
+        Ok(if is_wrapped {
+            Self::Unwrapped(ZstdDecompressReader::new(reader, max_window_log))
+        } else {
+            Self::File(reader)
+        })
+    }
+}
+
+impl Read for Source {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::File(file) => file.read(buf),
+            Self::Unwrapped(reader) => reader.read(buf),
+            #[cfg(any(test, feature = "fuzzing"))]
+            Self::Bytes(bytes) => bytes.read(buf),
+        }
+    }
+}
+
+impl Seek for Source {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::File(file) => file.seek(pos),
+            Self::Unwrapped(reader) => reader.seek(pos),
+            #[cfg(any(test, feature = "fuzzing"))]
+            Self::Bytes(bytes) => bytes.seek(pos),
+        }
+    }
+}
+
+/// The key information of a chunk, which determines how it is decrypted.
+#[derive(Clone, Copy)]
+struct ChunkKey {
+    pub_key: PublicKey,
+    /// `None` if the algorithm is unknown (e.g., added by a newer version of
+    /// Pinenut).
+    algorithm: Option<KeyAlgorithm>,
+    /// The fingerprint of the public key that the chunk is encrypted for.
+    fingerprint: Option<Fingerprint>,
 }
 
 /// # Workflow
@@ -157,7 +1161,9 @@ enum ChunkError {
 /// ```
 struct Processor<F> {
     decompressor: ZstdDecompressor,
-    secret_key: Option<SecretKey>,
+    /// The secret keys with the fingerprints of their public keys (of each key
+    /// agreement algorithm).
+    keyring: Vec<([Option<Fingerprint>; 2], SecretKey)>,
     encryption_keys: HashMap<PublicKey, EncryptionKey>,
     parser: RecordParser<F>,
 }
@@ -167,18 +1173,38 @@ where
     F: FnMut(&Record) -> Result<(), io::Error>,
 {
     #[inline]
-    fn new(secret_key: Option<SecretKey>, parser: RecordParser<F>) -> Self {
+    fn new(
+        secret_keys: Vec<SecretKey>,
+        parser: RecordParser<F>,
+        max_window_log: Option<u32>,
+    ) -> Self {
+        let keyring = secret_keys
+            .into_iter()
+            .map(|key| {
+                let fingerprints = [KeyAlgorithm::P256, KeyAlgorithm::X25519]
+                    .map(|algorithm| algorithm.public_key(&key).ok().map(|k| fingerprint(&k)));
+                (fingerprints, key)
+            })
+            .collect();
         Self {
-            decompressor: ZstdDecompressor::new(),
-            secret_key,
+            decompressor: ZstdDecompressor::with_max_window_log(max_window_log),
+            keyring,
             encryption_keys: HashMap::new(),
             parser,
         }
     }
 
+    /// Checks whether none of the (valid) secret keys matches the fingerprint.
+    #[inline]
+    fn lacks_key(&self, fingerprint: Fingerprint) -> bool {
+        let mut fingerprints = self.keyring.iter().flat_map(|(f, _)| f.iter().flatten()).peekable();
+        fingerprints.peek().is_some() && fingerprints.all(|f| *f != fingerprint)
+    }
+
+    /// Obtains the decryptor of the chunk with the key information.
     fn obtain_decryptor(
         &mut self,
-        pub_key: PublicKey,
+        ChunkKey { pub_key, algorithm, fingerprint }: ChunkKey,
     ) -> Result<Option<AesDecryptor>, EncryptionError> {
         if pub_key == EMPTY_PUBLIC_KEY {
             // No encryption.
@@ -188,8 +1214,14 @@ where
             Ok(Some(AesDecryptor::new(key)))
         } else {
             // Negotiates the key.
-            if let Some(secret_key) = self.secret_key.as_ref() {
-                let key = ecdh_encryption_key(secret_key, &pub_key)?;
+            let secret_key = self
+                .keyring
+                .iter()
+                .find(|(f, _)| fingerprint.is_none() || f.contains(&fingerprint))
+                .map(|(_, key)| key);
+            if let Some(secret_key) = secret_key {
+                let algorithm = algorithm.ok_or(EncryptionError::Ecdh)?;
+                let key = algorithm.encryption_key(secret_key, &pub_key)?;
                 let key = self.encryption_keys.entry(pub_key).or_insert(key);
                 Ok(Some(AesDecryptor::new(key)))
             } else {
@@ -198,15 +1230,25 @@ where
         }
     }
 
+    /// Discards the state of the chunk that failed to be processed, so that the next
+    /// chunk is processed from scratch.
+    #[inline]
+    fn reset(&mut self) {
+        self.parser.clear_buffer();
+        self.decompressor = ZstdDecompressor::new();
+    }
+
     fn chunk_sink(
         &mut self,
+        version: u16,
         payload_len: usize,
-        pub_key: PublicKey,
+        key: ChunkKey,
         time_range: RangeInclusive<DateTime>,
         writeback: bool,
     ) -> FnSink<impl FnMut(&[u8]) -> Result<(), Error> + '_, Error> {
         let mut read_len = 0;
-        let mut decryptor = self.obtain_decryptor(pub_key);
+        let mut decryptor = self.obtain_decryptor(key);
+        self.parser.version = version;
 
         FnSink::new(move |bytes: &[u8]| {
             read_len += bytes.len();
@@ -230,6 +1272,11 @@ where
 
             if reached_to_end {
                 self.parser.clear_buffer();
+                // The compressed frame of the chunk written back is never ended, so the
+                // decompressor is reset for the next chunk.
+                if writeback {
+                    self.decompressor = ZstdDecompressor::new();
+                }
             }
 
             Ok(())
@@ -240,6 +1287,20 @@ where
 struct RecordParser<F> {
     callback: F,
     buffer: BytesBuf,
+    /// The length of the bytes parsed from the current chunk.
+    decoded_len: usize,
+    /// The format version of the chunk being parsed.
+    version: u16,
+    /// See [`ParseOptions::max_record_len`].
+    max_record_len: Option<usize>,
+    /// See [`ParseOptions::max_decompressed_bytes_per_chunk`].
+    max_decoded_len: Option<usize>,
+    /// The first error and the count of the records skipped in the current chunk.
+    skipped: Option<(DecodingError, usize)>,
+    /// The error of the record whose frame is broken in the current chunk.
+    broken: Option<DecodingError>,
+    /// See [`ChunkStats::unrecoverable_len`].
+    unrecoverable_len: usize,
 }
 
 impl<F> RecordParser<F>
@@ -248,11 +1309,31 @@ where
 {
     #[inline]
     fn new(callback: F) -> Self {
-        Self { callback, buffer: BytesBuf::with_capacity(BUFFER_LEN) }
+        Self {
+            callback,
+            buffer: BytesBuf::with_capacity(BUFFER_LEN),
+            decoded_len: 0,
+            version: FORMAT_VERSION,
+            max_record_len: None,
+            max_decoded_len: None,
+            skipped: None,
+            broken: None,
+            unrecoverable_len: 0,
+        }
     }
 
     #[inline]
     fn parse_all(&mut self, mut bytes: &[u8]) -> Result<(), ChunkError> {
+        self.decoded_len += bytes.len();
+        if let Some(max) = self.max_decoded_len
+            && self.decoded_len > max
+        {
+            return Err(ChunkError::Limit(ParseLimit::DecompressedBytes(max)));
+        }
+        if self.broken.is_some() {
+            self.unrecoverable_len += bytes.len();
+            return Ok(());
+        }
         while !bytes.is_empty() {
             let len = self.parse(bytes)?;
             bytes = &bytes[len..];
@@ -269,11 +1350,33 @@ where
             if source.is_empty() {
                 break Ok(());
             }
-            match Record::decode(&mut source) {
+            // The record whose fields are undecodable is skipped if its frame is intact,
+            // otherwise the rest of the chunk can't be decoded.
+            let result = if self.version >= SCHEMA_VERSION {
+                Record::decode_frame(&mut source)
+                    .map(|(schema, mut fields)| Record::decode_fields(&mut fields, schema))
+            } else {
+                decode_record(&mut source, self.version).map(Ok)
+            };
+            match result {
                 Ok(record) => {
+                    let record_len = self.buffer.len() - source.len() - read_len;
+                    if let Some(max) = self.max_record_len
+                        && record_len > max
+                    {
+                        break Err(ChunkError::Limit(ParseLimit::RecordLen(max)));
+                    }
                     read_len = self.buffer.len() - source.len();
-                    if let Err(e) = (self.callback)(&record) {
-                        break Err(e.into());
+                    match record {
+                        Ok(record) => {
+                            if let Err(e) = (self.callback)(&record) {
+                                break Err(e.into());
+                            }
+                        }
+                        Err(e) => {
+                            self.skipped.get_or_insert((e, 0)).1 += 1;
+                            self.unrecoverable_len += record_len;
+                        }
                     }
                 }
                 // Not necessarily an error, writer needs to continue reading bytes.
@@ -282,21 +1385,72 @@ where
             }
         };
 
-        self.buffer.drain(read_len);
-        res.map(|_| len)
+        if res.is_ok() && read_len == 0 {
+            // The record being buffered is known to exceed the limit before it is decoded.
+            if let Some(max) = self.max_record_len
+                && self.buffer.len() > max
+            {
+                return Err(ChunkError::Limit(ParseLimit::RecordLen(max)));
+            }
+        }
+        // The record doesn't fit into the full buffer (e.g., its length is corrupted), it
+        // would never be decoded however many bytes are read.
+        let res = match res {
+            Ok(()) if read_len == 0 && len < bytes.len() => {
+                Err(DecodingError::UnexpectedEnd { extra_len: bytes.len() - len }.into())
+            }
+            res => res,
+        };
+
+        match res {
+            // The frame of the record is broken, so the rest of the chunk can't be decoded,
+            // and it is only counted afterwards.
+            Err(ChunkError::Decode(err)) => {
+                self.unrecoverable_len += self.buffer.len() - read_len + bytes.len() - len;
+                self.buffer.clear();
+                self.broken = Some(err);
+                Ok(bytes.len())
+            }
+            res => {
+                self.buffer.drain(read_len);
+                res.map(|_| len)
+            }
+        }
     }
 
+    /// Clears the buffer at the end of the chunk, the incomplete record left in it
+    /// is unrecoverable.
     fn clear_buffer(&mut self) {
+        self.unrecoverable_len += self.buffer.len();
         self.buffer.clear()
     }
 }
 
+/// Decodes a record in the layout of the format version.
+///
+/// Records of the initial version are decoded field by field, and the fields they
+/// don't carry are left as default.
+fn decode_record<'a>(source: &mut &'a [u8], version: u16) -> Result<Record<'a>, DecodingError> {
+    if version >= SCHEMA_VERSION {
+        return Record::decode(source);
+    }
+
+    let level = Level::decode(source)?;
+    let datetime = DateTime::decode(source)?;
+    let location = Location::decode(source)?;
+    let tag = Option::decode(source)?;
+    let thread_id = Option::decode(source)?;
+    let meta = Meta::new(level, datetime, location, tag, thread_id);
+    Ok(Record::new(meta, <&str>::decode(source)?))
+}
+
 impl Error {
     #[inline]
     fn can_continue_to_read_chunk(&self) -> bool {
         matches!(self, Self::Decrypt(..))
             || matches!(self, Self::Decompress(..))
             || matches!(self, Self::Decode(..))
+            || matches!(self, Self::Limit(..))
     }
 
     #[inline]
@@ -307,6 +1461,7 @@ impl Error {
             Decrypt(err) => Self::Decrypt(err, time_range),
             Decompress(err) => Self::Decompress(err, time_range),
             Decode(err) => Self::Decode(err, time_range),
+            Limit(limit) => Self::Limit(limit, time_range),
         }
     }
 }
@@ -322,3 +1477,157 @@ impl From<chunk::ReadError> for Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, str::FromStr};
+
+    use crate::{
+        chunk::Chunk,
+        codec::{Decode, Encode},
+        compress::{CompressOp, Compressor, ZstdCompressor},
+        encrypt::ecdh::EMPTY_PUBLIC_KEY,
+        parse::{decode_record, parse_chunks, RecordParser, Source, SCHEMA_VERSION},
+        DateTime, DecodingError, Level, Location, MetaBuilder, ParseOptions, Record, RecordBuilder,
+        ZstdParams, BUFFER_LEN, FORMAT_VERSION,
+    };
+
+    /// Encodes a record with the content in the layout of the format version, those
+    /// of the initial version have no schema.
+    fn encode_record(version: u16, content: &str) -> Vec<u8> {
+        let mut sink = Vec::new();
+        if version >= SCHEMA_VERSION {
+            let meta = MetaBuilder::new().level(Level::Warn).build();
+            RecordBuilder::new().meta(meta).content(content).build().encode(&mut sink).unwrap();
+            return sink;
+        }
+
+        let datetime = DateTime::from_str("2013-11-18 13:35:12Z").unwrap();
+        Level::Warn.encode(&mut sink).unwrap();
+        datetime.encode(&mut sink).unwrap();
+        Location::new(Some("main.rs"), None, Some(7)).encode(&mut sink).unwrap();
+        Some("tag").encode(&mut sink).unwrap();
+        Some(1u64).encode(&mut sink).unwrap();
+        content.encode(&mut sink).unwrap();
+        sink
+    }
+
+    #[test]
+    fn test_decode_legacy_record() {
+        let bytes = encode_record(1, "content");
+        let mut source = bytes.as_slice();
+        let record = decode_record(&mut source, 1).unwrap();
+        assert!(source.is_empty());
+
+        let meta = record.meta();
+        let location = Location::new(Some("main.rs"), None, Some(7));
+        assert_eq!((meta.level(), meta.location()), (Level::Warn, &location));
+        assert_eq!(
+            (meta.tag(), meta.thread_id(), meta.thread_name()),
+            (Some("tag"), Some(1), None)
+        );
+        assert_eq!((meta.session_id(), meta.elapsed(), meta.sequence()), (None, None, None));
+        assert_eq!(record.content(), "content");
+        assert_eq!((record.repeats(), record.truncated(), record.extension()), (0, false, None));
+    }
+
+    #[test]
+    fn test_parse_legacy_chunks() {
+        // A log file of the chunks of every format version, each of which is written
+        // in the layouts of its version.
+        let mut bytes = Vec::new();
+        for version in 1..=FORMAT_VERSION {
+            let mut records = encode_record(version, &format!("log {version}"));
+            records.extend(encode_record(version, &format!("log {version} again")));
+            let mut compressor = ZstdCompressor::new(3, &ZstdParams::default()).unwrap();
+            let mut payload = Vec::new();
+            compressor.compress(CompressOp::Input(&records), &mut payload).unwrap();
+            compressor.compress(CompressOp::End, &mut payload).unwrap();
+
+            let mut chunk = Chunk::bind(vec![0; BUFFER_LEN]);
+            chunk.initialize(DateTime::default(), EMPTY_PUBLIC_KEY, 42);
+            chunk.set_version(version);
+            chunk.write(&payload).unwrap();
+            bytes.extend_from_slice(&chunk);
+        }
+
+        let mut contents = Vec::new();
+        let source = Source::Bytes(Cursor::new(bytes));
+        parse_chunks(source, None, None, ParseOptions::new(), |record| {
+            assert_eq!(record.meta().level(), Level::Warn);
+            contents.push(record.content().to_string());
+            Ok(())
+        })
+        .unwrap();
+        let expected: Vec<_> = (1..=FORMAT_VERSION)
+            .flat_map(|version| [format!("log {version}"), format!("log {version} again")])
+            .collect();
+        assert_eq!(contents, expected);
+    }
+
+    #[test]
+    fn test_decode_newer_schema() {
+        let record = RecordBuilder::new().content("content").extension(&[1, 2]).build();
+        let mut bytes = Vec::new();
+        record.encode(&mut bytes).unwrap();
+
+        // The record of a newer schema with a field appended, which is skipped.
+        let mut source = bytes.as_slice();
+        let (schema, len) =
+            (u32::decode(&mut source).unwrap(), usize::decode(&mut source).unwrap());
+        assert_eq!((schema, len), (Record::SCHEMA, source.len()));
+        let mut newer = Vec::new();
+        (Record::SCHEMA + 1).encode(&mut newer).unwrap();
+        (len + 2).encode(&mut newer).unwrap();
+        newer.extend_from_slice(source);
+        newer.extend_from_slice(&[0x01, 0x2A]);
+        newer.push(0xFF);
+
+        let mut source = newer.as_slice();
+        assert_eq!(decode_record(&mut source, FORMAT_VERSION).unwrap(), record);
+        // Only the bytes of the record are consumed.
+        assert_eq!(source, [0xFF]);
+    }
+
+    #[test]
+    fn test_skip_undecodable_record() {
+        let (mut bytes, mut corrupted_len) = (Vec::new(), 0);
+        for content in ["first", "second", "third"] {
+            let start = bytes.len();
+            RecordBuilder::new().content(content).build().encode(&mut bytes).unwrap();
+            if content == "second" {
+                // Corrupts the level of the record, which follows its schema and length.
+                bytes[start + 2] = 0xFF;
+                corrupted_len = bytes.len() - start;
+            }
+        }
+
+        let mut contents = Vec::new();
+        let mut parser = RecordParser::new(|record: &Record| {
+            contents.push(record.content().to_string());
+            Ok(())
+        });
+        parser.parse_all(&bytes).unwrap();
+        assert!(matches!(parser.skipped, Some((DecodingError::UnexpectedVariant { .. }, 1))));
+        assert_eq!(parser.unrecoverable_len, corrupted_len);
+        drop(parser);
+        assert_eq!(contents, ["first", "third"]);
+    }
+
+    #[test]
+    fn test_parse_oversized_record() {
+        // The length of the record is corrupted, which exceeds the buffer.
+        let mut bytes = Vec::new();
+        Record::SCHEMA.encode(&mut bytes).unwrap();
+        (BUFFER_LEN * 2).encode(&mut bytes).unwrap();
+        bytes.resize(BUFFER_LEN * 2, 0);
+
+        let mut parser = RecordParser::new(|_: &Record| Ok(()));
+        for piece in bytes.chunks(4096) {
+            parser.parse_all(piece).unwrap();
+        }
+        assert!(matches!(parser.broken, Some(DecodingError::UnexpectedEnd { .. })));
+        // None of the bytes can be recovered.
+        assert_eq!(parser.unrecoverable_len, bytes.len());
+    }
+}
@@ -0,0 +1,86 @@
+//! The custom stages of the processing pipeline of the records.
+
+use crate::{Record, Redactor};
+
+/// Represents what to do with a record after it has been processed by a
+/// [`PipelineStage`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    /// The record is passed to the next stage as it is.
+    Keep,
+    /// The record is dropped, it is neither passed to the next stages nor logged.
+    Drop,
+    /// The record is passed to the next stage with the content (or the encoded
+    /// bytes) rewritten by the stage.
+    Rewrite,
+}
+
+/// Represents a custom stage in the processing pipeline of the logger.
+///
+/// The records are passed through the stages in order twice: before they are
+/// encoded (i.e., after the session id and the datetime are stamped), so that the
+/// stages can sample, filter, or rewrite them (e.g., the redaction, see
+/// [`Config::redactor`]), and after they are encoded, so that the stages can see
+/// the exact bytes to be compressed and encrypted (e.g., to MAC them).
+///
+/// ```plain
+/// ┌──────────┐   ┌────────┐   ┌──────────────────┐   ┌──────────┐   ┌─────────┐   ┌───────┐
+/// │  Stages  │──▶│ Encode │──▶│  Stages (bytes)  │──▶│ Compress │──▶│ Encrypt │──▶│ Chunk │
+/// └──────────┘   └────────┘   └──────────────────┘   └──────────┘   └─────────┘   └───────┘
+/// ```
+///
+/// [`Config::redactor`]: crate::Config::redactor
+pub trait PipelineStage: Send {
+    /// The name of the stage, a stage replaces the stage of the same name that
+    /// has been added before it (see [`Config::pipeline_stage`]).
+    ///
+    /// [`Config::pipeline_stage`]: crate::Config::pipeline_stage
+    fn name(&self) -> &str;
+
+    /// Processes the record before it is encoded.
+    ///
+    /// `content` is the content of the record as rewritten by the previous stages,
+    /// which may differ from [`Record::content`]. To rewrite it, the stage modifies
+    /// `content` and returns [`Verdict::Rewrite`].
+    ///
+    /// The default implementation keeps the record.
+    #[inline]
+    fn process(&mut self, record: &Record, content: &mut String) -> Verdict {
+        _ = (record, content);
+        Verdict::Keep
+    }
+
+    /// Processes the encoded bytes of the record before they are compressed.
+    ///
+    /// `encoded` is the encoding of the record as rewritten by the previous
+    /// stages. To rewrite it, the stage modifies `encoded` and returns
+    /// [`Verdict::Rewrite`]. The bytes are decoded as a record on parsing, so the
+    /// rewritten ones must remain a valid encoding of a record.
+    ///
+    /// The default implementation keeps the record.
+    #[inline]
+    fn process_encoded(&mut self, record: &Record, encoded: &mut Vec<u8>) -> Verdict {
+        _ = (record, encoded);
+        Verdict::Keep
+    }
+}
+
+/// The built-in stage of the redactor, it is always the first stage.
+pub(crate) struct RedactStage(pub(crate) Redactor);
+
+impl PipelineStage for RedactStage {
+    #[inline]
+    fn name(&self) -> &str {
+        "redact"
+    }
+
+    fn process(&mut self, _: &Record, content: &mut String) -> Verdict {
+        match self.0.redact(content) {
+            Some(redacted) => {
+                *content = redacted;
+                Verdict::Rewrite
+            }
+            None => Verdict::Keep,
+        }
+    }
+}
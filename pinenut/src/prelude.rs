@@ -0,0 +1,24 @@
+//! The Pinenut prelude.
+//!
+//! It re-exports the items commonly used together, so that they can be imported
+//! with a single `use`:
+//!
+//! ```rust,no_run
+//! use pinenut_log::prelude::*;
+//!
+//! let domain = Domain::new("MyApp".into(), "/path/to/dir".into());
+//! let logger = Logger::new(domain, Config::new().mirror(Some(Mirror::Stderr)));
+//!
+//! let meta = MetaBuilder::new().level(Level::Info).location(here!()).build();
+//! logger.log(&RecordBuilder::new().meta(meta).content("Hello World").build());
+//!
+//! info!(logger, tag: "Network", "Request sent: {}", 1);
+//! ```
+
+pub use crate::{
+    debug, error, here, info, log, verbose, warn, CompressionHint, Config, CustomDefaultFormatter,
+    DateTime, DefaultFormatter, Domain, Format, IoPanicPolicy, Level, Location, LocationBuilder,
+    Logger, MemoryLogger, Meta, MetaBuilder, Mirror, MultiLogger, OwnedRecord, ParseOptions,
+    Producer, Query, RateLimit, Record, RecordBuilder, RecordSink, Redact, Redactor, TimeDimension,
+    TimestampSource, Timezone,
+};
@@ -0,0 +1,97 @@
+//! The producer metadata of the chunks.
+
+use std::{convert::Infallible, ops::DerefMut};
+
+use crate::chunk::{Chunk, Error as ChunkError, Extensions};
+
+/// Represents the producer (i.e., the process of the application) of the records,
+/// which is written once per chunk in its header.
+///
+/// It attributes the records correctly when the logs of multiple processes or
+/// multiple versions of the application are archived together, see
+/// [`ParseOptions::on_producer`].
+///
+/// [`ParseOptions::on_producer`]: crate::ParseOptions::on_producer
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct Producer {
+    pid: Option<u32>,
+    app_version: Option<String>,
+    os_version: Option<String>,
+}
+
+impl Producer {
+    /// The identifier of the process that produced the records.
+    #[inline]
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// The version of the application that produced the records.
+    #[inline]
+    pub fn app_version(&self) -> Option<&str> {
+        self.app_version.as_deref()
+    }
+
+    /// The version of the operating system on which the records were produced.
+    #[inline]
+    pub fn os_version(&self) -> Option<&str> {
+        self.os_version.as_deref()
+    }
+
+    /// Constructs the producer of the current process.
+    #[inline]
+    pub(crate) fn current(app_version: Option<String>, os_version: Option<String>) -> Self {
+        Self { pid: Some(std::process::id()), app_version, os_version }
+    }
+
+    /// Reads the producer from the extension fields of a chunk header, the fields
+    /// that are absent or malformed are left as `None`.
+    pub(crate) fn from_extensions(extensions: Extensions) -> Self {
+        let string =
+            |tag| extensions.get(tag).and_then(|v| std::str::from_utf8(v).ok()).map(str::to_string);
+        Self {
+            pid: extensions
+                .get(Extensions::PID)
+                .and_then(|v| v.try_into().ok())
+                .map(u32::from_le_bytes),
+            app_version: string(Extensions::APP_VERSION),
+            os_version: string(Extensions::OS_VERSION),
+        }
+    }
+
+    /// Length of the extension fields of the producer in bytes.
+    pub(crate) fn extensions_len(&self) -> usize {
+        let mut len = 0;
+        let Ok(()) = self.try_for_each_field(|_, value| {
+            len += Extensions::field_len(value);
+            Ok::<_, Infallible>(())
+        });
+        len
+    }
+
+    /// Writes the producer to the extension fields of the chunk header.
+    pub(crate) fn write_to<T>(&self, chunk: &mut Chunk<T>) -> Result<(), ChunkError>
+    where
+        T: DerefMut<Target = [u8]>,
+    {
+        self.try_for_each_field(|tag, value| chunk.push_extension(tag, value))
+    }
+
+    /// Visits the fields in place, so that the chunk initialization doesn't
+    /// allocate.
+    fn try_for_each_field<E>(
+        &self,
+        mut f: impl FnMut(u8, &[u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        if let Some(pid) = self.pid {
+            f(Extensions::PID, &pid.to_le_bytes())?;
+        }
+        if let Some(version) = &self.app_version {
+            f(Extensions::APP_VERSION, version.as_bytes())?;
+        }
+        if let Some(version) = &self.os_version {
+            f(Extensions::OS_VERSION, version.as_bytes())?;
+        }
+        Ok(())
+    }
+}
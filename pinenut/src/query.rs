@@ -0,0 +1,172 @@
+use std::{ops::RangeInclusive, path::Path, sync::Arc};
+
+use crate::{
+    chunk::{Extensions, Header, Index},
+    logfile::{self, Logfile},
+    parse_with_options, DateTime, Domain, FileSystem, Level, OwnedRecord, ParseOptions,
+    ParsingError, Record, SecretKey,
+};
+
+/// Conditions of the log query, see [`query`].
+///
+/// A record matches the query only if it satisfies all the conditions that are
+/// set.
+#[derive(Default, Clone, Debug)]
+pub struct Query {
+    level: Option<Level>,
+    tag: Option<String>,
+    text: Option<String>,
+    range: Option<RangeInclusive<DateTime>>,
+    limit: Option<usize>,
+}
+
+impl Query {
+    /// Constructs a new `Query`, which matches all the records.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The lowest severity of the records, i.e., records at this level or more
+    /// severe levels match.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn level(mut self, level: Option<Level>) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The tag of the records.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn tag(mut self, tag: Option<impl Into<String>>) -> Self {
+        self.tag = tag.map(Into::into);
+        self
+    }
+
+    /// The text that the content of the records contains (case-sensitive).
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn text(mut self, text: Option<impl Into<String>>) -> Self {
+        self.text = text.map(Into::into);
+        self
+    }
+
+    /// The datetime range in which the records were generated.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn range(mut self, range: Option<RangeInclusive<DateTime>>) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// The maximum number of the records to return. The first matching records
+    /// in the log file are returned.
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// The maximum number of the records to return, see [`Query::limit`].
+    #[inline]
+    pub(crate) fn max_records(&self) -> usize {
+        self.limit.unwrap_or(usize::MAX)
+    }
+
+    /// Checks whether the record matches the query.
+    pub(crate) fn matches(&self, record: &Record) -> bool {
+        let meta = record.meta();
+        self.level.is_none_or(|level| meta.level() <= level)
+            && self.tag.as_deref().is_none_or(|tag| meta.tag() == Some(tag))
+            && self.text.as_deref().is_none_or(|text| record.content().contains(text))
+            && self.range.as_ref().is_none_or(|range| range.contains(&meta.datetime()))
+    }
+
+    /// Checks whether the chunk may have records matching the query, by its time
+    /// range and its index (see [`Config::chunk_index`]). The text is not checked.
+    ///
+    /// [`Config::chunk_index`]: crate::Config::chunk_index
+    pub(crate) fn may_match_chunk(&self, header: &Header, extensions: Extensions) -> bool {
+        let time_range = header.time_range();
+        if let Some(range) = &self.range
+            && (time_range.end() < *range.start() || time_range.start() > *range.end())
+        {
+            return false;
+        }
+        let Some(index) = extensions.get(Extensions::INDEX).and_then(Index::from_bytes) else {
+            return true;
+        };
+        self.level.is_none_or(|level| index.may_contain_level(level))
+            && self.tag.as_deref().is_none_or(|tag| index.may_contain_tag(tag))
+    }
+}
+
+/// Queries the records matching the conditions from the compressed and encrypted
+/// binary log file (e.g., the file extracted by [`extract`]).
+///
+/// It is suitable for in-app log viewers and support tools, which search logs
+/// without formatting all of them. The chunks having no matching records for sure
+/// are skipped without being decrypted (see [`Config::chunk_index`]).
+///
+/// [`extract`]: crate::extract
+/// [`Config::chunk_index`]: crate::Config::chunk_index
+pub fn query(
+    path: impl AsRef<Path>,
+    secret_key: Option<SecretKey>,
+    query: Query,
+) -> Result<Vec<OwnedRecord>, ParsingError> {
+    let mut records = Vec::new();
+    query_into(path.as_ref(), secret_key, &query, &mut records)?;
+    Ok(records)
+}
+
+/// Queries the records matching the conditions from the log files of the domain,
+/// in chronological order.
+///
+/// Like [`query`], the chunks are skipped by their time ranges and indexes without
+/// being decrypted, so the queries like "the errors of the last week" only decrypt
+/// the chunks having errors. The log files must be stored in the file system.
+pub fn query_domain(
+    domain: Domain,
+    secret_key: Option<SecretKey>,
+    query: Query,
+) -> Result<Vec<OwnedRecord>, ParsingError> {
+    let mut logfiles =
+        Logfile::logfiles(&Arc::new(domain), logfile::Mode::Read, FileSystem::shared())?
+            .collect::<Vec<_>>();
+    logfiles.sort_by_key(|f| f.datetime());
+
+    let mut records = Vec::new();
+    for logfile in logfiles {
+        if records.len() >= query.max_records() {
+            break;
+        }
+        query_into(&logfile.path(), secret_key, &query, &mut records)?;
+    }
+    Ok(records)
+}
+
+/// Queries the records from the log file, and appends them to the records until the
+/// limit is reached.
+fn query_into(
+    path: &Path,
+    secret_key: Option<SecretKey>,
+    query: &Query,
+    records: &mut Vec<OwnedRecord>,
+) -> Result<(), ParsingError> {
+    let limit = query.max_records();
+    let options = ParseOptions::new().chunk_query(Some(query.clone()));
+    parse_with_options(path, secret_key, options, |record| {
+        if records.len() < limit && query.matches(record) {
+            records.push(record.into());
+        }
+        Ok(())
+    })
+}
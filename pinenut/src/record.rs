@@ -1,6 +1,14 @@
 //!  The `Pinenut` log record.
 
+use std::{cell::RefCell, fmt, str::FromStr};
+
 use pinenut_derive::{Builder, Decode, Encode};
+use thiserror::Error;
+
+use crate::{
+    codec::{Decode, DecodingError, Encode, EncodingError, Sink, Source},
+    common::{self, FnSink},
+};
 
 /// Represents logging levels of a `Pinenut` log.
 ///
@@ -8,6 +16,8 @@ use pinenut_derive::{Builder, Decode, Encode};
 #[repr(u8)]
 #[non_exhaustive]
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Level {
     /// The `error` log level.
     ///
@@ -36,6 +46,28 @@ pub enum Level {
 }
 
 impl Level {
+    /// Returns an iterator over all the levels, from the most severe
+    /// ([`Level::Error`]) to the least severe ([`Level::Verbose`]).
+    #[inline]
+    pub fn iter() -> impl DoubleEndedIterator<Item = Self> + ExactSizeIterator {
+        [Self::Error, Self::Warn, Self::Info, Self::Debug, Self::Verbose].into_iter()
+    }
+
+    /// Returns the lowercase name of the level, e.g., `warn` for [`Level::Warn`].
+    ///
+    /// It is the same as the one of its [`Display`](fmt::Display) and the one that
+    /// its [`FromStr`] parses.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Verbose => "verbose",
+        }
+    }
+
     /// Returns the underlying primitive representation.
     #[inline]
     pub(crate) fn primitive(&self) -> u8 {
@@ -55,6 +87,37 @@ impl Level {
     }
 }
 
+impl fmt::Display for Level {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Level {
+    type Err = ParseLevelError;
+
+    /// Parses the level from its name (e.g., `warn`), its initial (e.g., `W`, as
+    /// presented by the [`crate::DefaultFormatter`]) or `warning`,
+    /// case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let level = match s.to_ascii_lowercase().as_str() {
+            "e" => Self::Error,
+            "w" | "warning" => Self::Warn,
+            "i" => Self::Info,
+            "d" => Self::Debug,
+            "v" => Self::Verbose,
+            name => Self::iter().find(|level| level.as_str() == name).ok_or(ParseLevelError)?,
+        };
+        Ok(level)
+    }
+}
+
+/// The error returned when parsing a [`Level`] from an unknown name.
+#[derive(Error, Clone, Copy, PartialEq, Eq, Debug)]
+#[error("invalid level, expected one of `error`, `warn`, `info`, `debug` and `verbose`")]
+pub struct ParseLevelError;
+
 /// Represents a location in the code where a `Pinenut` log was generated.
 ///
 /// The default options are:
@@ -66,8 +129,11 @@ impl Level {
 /// `Location` supports `Builder Pattern`, it can be constructed by
 /// `LocationBuilder`.
 #[derive(Encode, Decode, Builder, Default, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     file: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     func: Option<&'a str>,
     line: Option<u32>,
 }
@@ -97,6 +163,42 @@ impl<'a> Location<'a> {
     pub fn line(&self) -> Option<u32> {
         self.line
     }
+
+    /// Strips the prefix from the code file, if the file starts with it at a path
+    /// component boundary.
+    #[inline]
+    pub(crate) fn strip_file_prefix(&mut self, prefix: &str) {
+        if let Some(file) = self.file.and_then(|f| common::strip_path_prefix(f, prefix)) {
+            self.file = Some(file);
+        }
+    }
+}
+
+/// Constructs a [`Location`] of the code where it is invoked.
+///
+/// The file is relative to the crate's manifest directory (see
+/// [`Location::file`]), so that the absolute paths of the build machine are not
+/// leaked, and the function is the path of the enclosing function (e.g.,
+/// `my_crate::network::request`).
+///
+/// # Examples
+///
+/// ```
+/// use pinenut_log::{here, MetaBuilder};
+///
+/// let meta = MetaBuilder::new().location(here!()).build();
+/// assert!(meta.location().func().is_some_and(|f| f.ends_with("main")));
+/// ```
+#[macro_export]
+macro_rules! here {
+    () => {{
+        fn f() {}
+        $crate::Location::new(
+            Some($crate::__private::prune_path(file!(), env!("CARGO_MANIFEST_DIR"))),
+            Some($crate::__private::function_name(::std::any::type_name_of_val(&f))),
+            Some(line!()),
+        )
+    }};
 }
 
 /// Represents a date and time in the UTC time zone.
@@ -111,15 +213,29 @@ pub type DateTime = chrono::DateTime<chrono::Utc>;
 /// - [`Meta::location`] : [`Location::default()`]
 /// - [`Meta::tag`] : [`None`]
 /// - [`Meta::thread_id`] : [`None`]
+/// - [`Meta::thread_name`] : [`None`]
+/// - [`Meta::session_id`] : [`None`]
+/// - [`Meta::elapsed`] : [`None`]
+/// - [`Meta::sequence`] : [`None`]
 ///
 /// `Meta` supports `Builder Pattern`, it can be constructed by `MetaBuilder`.
 #[derive(Encode, Decode, Builder, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Meta<'a> {
     level: Level,
     datetime: DateTime,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     location: Location<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     tag: Option<&'a str>,
     thread_id: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    thread_name: Option<&'a str>,
+    session_id: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    elapsed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    sequence: Option<u64>,
 }
 
 impl<'a> Meta<'a> {
@@ -132,7 +248,17 @@ impl<'a> Meta<'a> {
         tag: Option<&'a str>,
         thread_id: Option<u64>,
     ) -> Self {
-        Self { level, datetime, location, tag, thread_id }
+        Self {
+            level,
+            datetime,
+            location,
+            tag,
+            thread_id,
+            thread_name: None,
+            session_id: None,
+            elapsed: None,
+            sequence: None,
+        }
     }
 
     /// The level of the log.
@@ -153,6 +279,12 @@ impl<'a> Meta<'a> {
         &self.location
     }
 
+    /// The mutable location in the code where the log was generated.
+    #[inline]
+    pub(crate) fn location_mut(&mut self) -> &mut Location<'a> {
+        &mut self.location
+    }
+
     /// An optional tag associated with the log.
     #[inline]
     pub fn tag(&self) -> Option<&'a str> {
@@ -164,6 +296,68 @@ impl<'a> Meta<'a> {
     pub fn thread_id(&self) -> Option<u64> {
         self.thread_id
     }
+
+    /// The name of the thread (or queue) where the log was generated.
+    #[inline]
+    pub fn thread_name(&self) -> Option<&'a str> {
+        self.thread_name
+    }
+
+    /// The identifier of the logger session (e.g., an app launch) in which the log
+    /// was generated.
+    ///
+    /// If it is `None` when logging, the logger fills in its own session id.
+    #[inline]
+    pub fn session_id(&self) -> Option<u64> {
+        self.session_id
+    }
+
+    /// Sets the session id.
+    #[inline]
+    pub(crate) fn set_session_id(&mut self, session_id: Option<u64>) {
+        self.session_id = session_id;
+    }
+
+    /// The milliseconds elapsed since the logger was initialized when the log was
+    /// generated, which is monotonic regardless of the changes of the wall clock and
+    /// the timezone.
+    ///
+    /// If it is `None` when logging, the logger fills it in if it is enabled, see
+    /// [`crate::Config::elapsed_time`].
+    #[inline]
+    pub fn elapsed(&self) -> Option<u64> {
+        self.elapsed
+    }
+
+    /// Sets the elapsed milliseconds.
+    #[inline]
+    pub(crate) fn set_elapsed(&mut self, elapsed: Option<u64>) {
+        self.elapsed = elapsed;
+    }
+
+    /// The sequence number of the log in the logger, which increases by one for
+    /// each log, so that the lost logs (e.g., dropped by the rate limiting or the
+    /// chunk overflow) can be detected by the gaps in the sequence. The repetitions
+    /// of a log (see [`Record::repeats`]) take the numbers after it.
+    ///
+    /// If it is `None` when logging, the logger fills it in if it is enabled, see
+    /// [`crate::Config::record_sequence`].
+    #[inline]
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    /// Sets the sequence number.
+    #[inline]
+    pub(crate) fn set_sequence(&mut self, sequence: Option<u64>) {
+        self.sequence = sequence;
+    }
+
+    /// Sets the datetime.
+    #[inline]
+    pub(crate) fn set_datetime(&mut self, datetime: DateTime) {
+        self.datetime = datetime;
+    }
 }
 
 impl<'a> Default for Meta<'a> {
@@ -175,18 +369,68 @@ impl<'a> Default for Meta<'a> {
 
 /// Represents a `Pinenut` log record.
 ///
+/// The default options are:
+///
+/// - [`Record::meta`] : [`Meta::default()`]
+/// - [`Record::content`] : `""`
+/// - [`Record::repeats`] : `0`
+/// - [`Record::truncated`] : `false`
+/// - [`Record::extension`] : [`None`]
+/// - [`Record::context`] : `[]`
+///
 /// `Record` supports `Builder Pattern`, it can be constructed by `RecordBuilder`.
-#[derive(Encode, Decode, Builder, Default, Clone, PartialEq, Eq, Debug)]
+///
+/// The encoded record is prefixed with its schema ([`Record::SCHEMA`]) and the
+/// length of its fields, so that the fields appended by the newer schemas are
+/// skipped by the older decoders, and the fields missing from the older schemas
+/// are left as default by the newer decoders.
+#[derive(Builder, Default, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Record<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     meta: Meta<'a>,
     content: &'a str,
+    repeats: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    truncated: bool,
+    #[cfg_attr(feature = "serde", serde(default, borrow))]
+    extension: Option<&'a [u8]>,
+    #[builder(into)]
+    #[cfg_attr(feature = "serde", serde(default, borrow))]
+    context: Vec<(&'a str, &'a str)>,
 }
 
 impl<'a> Record<'a> {
+    /// The schema of the encoded records, which is bumped when the fields are
+    /// appended to them.
+    ///
+    /// Only the fields appended to the end of the record are skipped or defaulted
+    /// across the schemas. The fields of [`Meta`] and [`Location`] are encoded in
+    /// the middle of the record, so a field added to them would shift the following
+    /// fields. The new fields must therefore be appended to `Record` (e.g., as the
+    /// context is), even if they belong to the metadata.
+    ///
+    /// - `1`: The initial schema.
+    pub const SCHEMA: u32 = 1;
+
+    /// The maximum length of the tag in bytes accepted by
+    /// [`RecordBuilder::build_checked`], as the tags are meant to be short
+    /// categories.
+    pub const MAX_TAG_LEN: usize = 256;
+
+    /// The maximum length of the content in bytes accepted by
+    /// [`RecordBuilder::build_checked`], as a sanity cap against runaway contents
+    /// (e.g., a whole file logged by mistake).
+    ///
+    /// It is unrelated to what the logger writes: the contents longer than its
+    /// buffer are written to chunks of their own, and they are only truncated to
+    /// [`crate::Config::max_record_len`] if it is set.
+    pub const MAX_CONTENT_LEN: usize = 16 * 1024 * 1024;
+
     /// Constructs a new `Record`.
     #[inline]
     pub fn new(meta: Meta<'a>, content: &'a str) -> Self {
-        Self { meta, content }
+        Self { meta, content, repeats: 0, truncated: false, extension: None, context: Vec::new() }
     }
 
     /// The metadata associated with the log.
@@ -195,9 +439,362 @@ impl<'a> Record<'a> {
         &self.meta
     }
 
+    /// The mutable metadata associated with the log.
+    #[inline]
+    pub(crate) fn meta_mut(&mut self) -> &mut Meta<'a> {
+        &mut self.meta
+    }
+
     /// The content of the log.
     #[inline]
     pub fn content(&self) -> &'a str {
         self.content
     }
+
+    /// Sets the content of the log.
+    #[inline]
+    pub(crate) fn set_content(&mut self, content: &'a str) {
+        self.content = content;
+    }
+
+    /// How many times the log was repeated consecutively after it was generated.
+    ///
+    /// It is always `0` unless deduplication is enabled in the logger.
+    #[inline]
+    pub fn repeats(&self) -> u32 {
+        self.repeats
+    }
+
+    /// Sets the count of consecutive repetitions.
+    #[inline]
+    pub(crate) fn set_repeats(&mut self, repeats: u32) {
+        self.repeats = repeats;
+    }
+
+    /// Whether the content of the log was truncated, as it exceeded the maximum
+    /// length (see [`crate::Config::max_record_len`]).
+    #[inline]
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Sets whether the content of the log was truncated.
+    #[inline]
+    pub(crate) fn set_truncated(&mut self, truncated: bool) {
+        self.truncated = truncated;
+    }
+
+    /// The opaque extension of the log, which carries the structured data of the
+    /// user alongside the content and is kept as is through the chunks.
+    ///
+    /// With the `codec` feature, it can be encoded from and decoded into a custom
+    /// type by [`crate::codec::encode`] and [`crate::codec::decode`].
+    #[inline]
+    pub fn extension(&self) -> Option<&'a [u8]> {
+        self.extension
+    }
+
+    /// The key-value pairs of the context in which the log was generated, e.g., the
+    /// id of the request being handled.
+    ///
+    /// The context of the logging thread (see [`crate::context::push`]) is stamped
+    /// onto the records by the logger, before the pairs of the record itself.
+    #[inline]
+    pub fn context(&self) -> &[(&'a str, &'a str)] {
+        &self.context
+    }
+
+    /// Sets the key-value pairs of the context.
+    #[inline]
+    pub(crate) fn set_context(&mut self, context: Vec<(&'a str, &'a str)>) {
+        self.context = context;
+    }
+
+    /// An upper bound of the length of the encoded record in bytes, which is much
+    /// cheaper than encoding it.
+    pub(crate) fn encoded_len_bound(&self) -> usize {
+        // Covers the schema, the length, the fields other than the strings, and the
+        // lengths of the strings. A variable-length integer takes at most 10 bytes.
+        const FIXED_LEN: usize = 160;
+
+        let (meta, location) = (&self.meta, &self.meta.location);
+        let strings = [meta.tag, meta.thread_name, location.file, location.func];
+        let strings_len: usize = strings.iter().flatten().map(|s| s.len()).sum();
+        let extension_len = self.extension.map_or(0, <[u8]>::len);
+        // The lengths of the key and the value of each pair take at most 20 bytes.
+        let context_len: usize = self.context.iter().map(|(k, v)| 20 + k.len() + v.len()).sum();
+        FIXED_LEN + strings_len + self.content.len() + extension_len + context_len
+    }
+
+    /// Encodes the fields of the record, without the schema and the length.
+    fn encode_fields<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        self.meta.encode(sink)?;
+        self.content.encode(sink)?;
+        self.repeats.encode(sink)?;
+        self.truncated.encode(sink)?;
+        self.extension.encode(sink)?;
+        self.context.encode(sink)
+    }
+
+    /// Decodes the schema and the encoded fields of the record, without decoding
+    /// the fields, so that the record whose fields are undecodable can be skipped.
+    pub(crate) fn decode_frame<'de: 'a, S>(source: &mut S) -> Result<(u32, &'a [u8]), S::Error>
+    where
+        S: Source<'de>,
+    {
+        Ok((u32::decode(source)?, Decode::decode(source)?))
+    }
+
+    /// Decodes the fields of the record in the schema, the fields that the schema
+    /// doesn't carry are left as default.
+    pub(crate) fn decode_fields(
+        source: &mut &'a [u8],
+        _schema: u32,
+    ) -> Result<Self, DecodingError> {
+        Ok(Self {
+            meta: Decode::decode(source)?,
+            content: Decode::decode(source)?,
+            repeats: Decode::decode(source)?,
+            truncated: Decode::decode(source)?,
+            extension: Decode::decode(source)?,
+            context: Decode::decode(source)?,
+        })
+    }
+}
+
+impl Encode for Record<'_> {
+    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        // The fields are encoded once into the scratch space of the thread, whose
+        // length prefixes them. It's about 3 times faster than encoding them twice
+        // (firstly to count their length), and doesn't allocate once warmed up.
+        SCRATCH.with_borrow_mut(|scratch| {
+            scratch.clear();
+            self.encode_fields(&mut FnSink::new(|bytes: &[u8]| {
+                scratch.extend_from_slice(bytes);
+                Ok::<_, EncodingError>(())
+            }))?;
+
+            Self::SCHEMA.encode(sink)?;
+            scratch.len().encode(sink)?;
+            let res = sink.sink(scratch);
+            // Doesn't hold the space of the occasional large records.
+            if scratch.capacity() > SCRATCH_CAPACITY_MAX {
+                *scratch = Vec::new();
+            }
+            res
+        })
+    }
+}
+
+thread_local! {
+    /// The scratch space encoding the fields of the records, see `Encode for Record`.
+    static SCRATCH: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The maximum capacity of the scratch space kept after encoding a record.
+const SCRATCH_CAPACITY_MAX: usize = 64 * 1024;
+
+impl<'de: 'a, 'a> Decode<'de> for Record<'a> {
+    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+    where
+        S: Source<'de>,
+    {
+        let (schema, mut fields) = Self::decode_frame(source)?;
+        // The rest of the fields are appended by the newer schemas, skips them.
+        Ok(Self::decode_fields(&mut fields, schema)?)
+    }
+}
+
+impl<'a> RecordBuilder<'a> {
+    /// Invokes the builder and returns the record like [`RecordBuilder::build`],
+    /// but validates it first, so that the misuse (e.g., of the SDK wrappers)
+    /// surfaces early instead of the record being logged as is.
+    ///
+    /// See [`BuildError`] for the validations.
+    pub fn build_checked(&self) -> Result<Record<'a>, BuildError> {
+        let (record, meta) = (&self.0, &self.0.meta);
+        if meta.datetime.timestamp() < 0 {
+            return Err(BuildError::DatetimeBeforeEpoch(meta.datetime));
+        }
+        match meta.tag.map(str::len) {
+            Some(0) => return Err(BuildError::EmptyTag),
+            Some(len) if len > Record::MAX_TAG_LEN => {
+                return Err(BuildError::TagTooLong { len, max: Record::MAX_TAG_LEN })
+            }
+            _ => {}
+        }
+        if meta.location.line == Some(0) {
+            return Err(BuildError::ZeroLine);
+        }
+        if record.content.len() > Record::MAX_CONTENT_LEN {
+            let (len, max) = (record.content.len(), Record::MAX_CONTENT_LEN);
+            return Err(BuildError::ContentTooLong { len, max });
+        }
+        if record.context.iter().any(|(key, _)| key.is_empty()) {
+            return Err(BuildError::EmptyContextKey);
+        }
+        Ok(self.build())
+    }
+}
+
+/// Errors that can be occurred while validating the record, see
+/// [`RecordBuilder::build_checked`].
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum BuildError {
+    /// The datetime can't be encoded, it would be logged as the Unix epoch.
+    #[error("datetime {0} is earlier than the Unix epoch")]
+    DatetimeBeforeEpoch(DateTime),
+    #[error("tag is empty, it should be `None` instead")]
+    EmptyTag,
+    /// See [`Record::MAX_TAG_LEN`].
+    #[error("tag of {len} bytes exceeds {max} bytes")]
+    TagTooLong { len: usize, max: usize },
+    /// The lines start at `1`.
+    #[error("line is 0")]
+    ZeroLine,
+    /// See [`Record::MAX_CONTENT_LEN`].
+    #[error("content of {len} bytes exceeds {max} bytes")]
+    ContentTooLong { len: usize, max: usize },
+    #[error("key of the context is empty")]
+    EmptyContextKey,
+}
+
+/// Represents an owned `Pinenut` log record, which doesn't borrow its strings.
+///
+/// It can be constructed from a [`Record`], and viewed as a [`Record`] by
+/// [`OwnedRecord::as_record`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedRecord {
+    level: Level,
+    datetime: DateTime,
+    file: Option<String>,
+    func: Option<String>,
+    line: Option<u32>,
+    tag: Option<String>,
+    thread_id: Option<u64>,
+    thread_name: Option<String>,
+    session_id: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    elapsed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    sequence: Option<u64>,
+    content: String,
+    repeats: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    truncated: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    extension: Option<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    context: Vec<(String, String)>,
+}
+
+impl OwnedRecord {
+    /// The level of the log.
+    #[inline]
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The datetime when the log was generated.
+    #[inline]
+    pub fn datetime(&self) -> DateTime {
+        self.datetime
+    }
+
+    /// An optional tag associated with the log.
+    #[inline]
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// The content of the log.
+    #[inline]
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// The opaque extension of the log, see [`Record::extension`].
+    #[inline]
+    pub fn extension(&self) -> Option<&[u8]> {
+        self.extension.as_deref()
+    }
+
+    /// The key-value pairs of the context of the log, see [`Record::context`].
+    #[inline]
+    pub fn context(&self) -> &[(String, String)] {
+        &self.context
+    }
+
+    /// Sets the level of the log.
+    #[inline]
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
+
+    /// Sets the tag of the log.
+    #[inline]
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+    }
+
+    /// Sets the content of the log.
+    #[inline]
+    pub fn set_content(&mut self, content: String) {
+        self.content = content;
+    }
+
+    /// Views the owned record as a [`Record`].
+    pub fn as_record(&self) -> Record<'_> {
+        let location = Location::new(self.file.as_deref(), self.func.as_deref(), self.line);
+        let meta = Meta {
+            level: self.level,
+            datetime: self.datetime,
+            location,
+            tag: self.tag.as_deref(),
+            thread_id: self.thread_id,
+            thread_name: self.thread_name.as_deref(),
+            session_id: self.session_id,
+            elapsed: self.elapsed,
+            sequence: self.sequence,
+        };
+        Record {
+            meta,
+            content: &self.content,
+            repeats: self.repeats,
+            truncated: self.truncated,
+            extension: self.extension.as_deref(),
+            context: self.context.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect(),
+        }
+    }
+}
+
+impl From<&Record<'_>> for OwnedRecord {
+    fn from(record: &Record<'_>) -> Self {
+        let (meta, location) = (record.meta(), record.meta().location());
+        Self {
+            level: meta.level(),
+            datetime: meta.datetime(),
+            file: location.file().map(Into::into),
+            func: location.func().map(Into::into),
+            line: location.line(),
+            tag: meta.tag().map(Into::into),
+            thread_id: meta.thread_id(),
+            thread_name: meta.thread_name().map(Into::into),
+            session_id: meta.session_id(),
+            elapsed: meta.elapsed(),
+            sequence: meta.sequence(),
+            content: record.content().into(),
+            repeats: record.repeats(),
+            truncated: record.truncated(),
+            extension: record.extension().map(Into::into),
+            context: record.context().iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
 }
@@ -0,0 +1,115 @@
+//! Redaction of sensitive information (e.g., PII) in the records.
+
+/// Represents a redactor that masks sensitive information in the content of records.
+///
+/// It can be applied at write time (see [`Config::redactor`]), before the records
+/// are compressed, or at parse time (see [`ParseOptions::redactor`]), before the
+/// records are formatted.
+///
+/// [`Config::redactor`]: crate::Config::redactor
+/// [`ParseOptions::redactor`]: crate::ParseOptions::redactor
+pub trait Redact {
+    /// Redacts the content, returns `None` if there is nothing to redact.
+    fn redact(&self, content: &str) -> Option<String>;
+}
+
+impl<F> Redact for F
+where
+    F: Fn(&str) -> Option<String>,
+{
+    #[inline]
+    fn redact(&self, content: &str) -> Option<String> {
+        self(content)
+    }
+}
+
+/// Trait object type for [`Redact`].
+pub type Redactor = Box<dyn Redact + Send + Sync>;
+
+#[cfg(feature = "regex")]
+pub use regex_redactor::RegexRedactor;
+
+#[cfg(feature = "regex")]
+mod regex_redactor {
+    use std::borrow::Cow;
+
+    use regex::Regex;
+
+    use crate::Redact;
+
+    /// A regex based redactor, which replaces all matches of its rules.
+    ///
+    /// Rules are applied in the order they are added.
+    #[derive(Clone, Default, Debug)]
+    pub struct RegexRedactor {
+        rules: Vec<(Regex, String)>,
+    }
+
+    impl RegexRedactor {
+        /// The pattern of email addresses.
+        pub const EMAIL: &'static str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+
+        /// The pattern of phone numbers, with optional country code and separators.
+        pub const PHONE: &'static str = r"\+?\d[\d -]{7,}\d";
+
+        /// The pattern of bearer tokens.
+        pub const BEARER_TOKEN: &'static str = r"(?i)bearer\s+[A-Za-z0-9._~+/-]+=*";
+
+        /// Constructs a new `RegexRedactor` without rules.
+        #[inline]
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        /// Adds a rule that replaces the matches of the pattern with the
+        /// replacement.
+        ///
+        /// The replacement supports the capture group syntax of
+        /// [`Regex::replace_all`], e.g., `$1`.
+        pub fn rule(
+            mut self,
+            pattern: &str,
+            replacement: impl Into<String>,
+        ) -> Result<Self, regex::Error> {
+            self.rules.push((Regex::new(pattern)?, replacement.into()));
+            Ok(self)
+        }
+    }
+
+    impl Redact for RegexRedactor {
+        fn redact(&self, content: &str) -> Option<String> {
+            let mut redacted: Option<String> = None;
+            for (regex, replacement) in &self.rules {
+                let current = redacted.as_deref().unwrap_or(content);
+                if let Cow::Owned(replaced) = regex.replace_all(current, replacement.as_str()) {
+                    redacted = Some(replaced);
+                }
+            }
+            redacted
+        }
+    }
+}
+
+#[cfg(all(test, feature = "regex"))]
+mod tests {
+    use crate::{Redact, RegexRedactor};
+
+    #[test]
+    fn test_regex_redactor() {
+        let redactor = RegexRedactor::new()
+            .rule(RegexRedactor::EMAIL, "<email>")
+            .and_then(|r| r.rule(RegexRedactor::PHONE, "<phone>"))
+            .and_then(|r| r.rule(RegexRedactor::BEARER_TOKEN, "Bearer <token>"))
+            .unwrap();
+
+        assert_eq!(
+            redactor.redact("mail tangent@example.com, call +86 138-0013-8000").as_deref(),
+            Some("mail <email>, call <phone>")
+        );
+        assert_eq!(
+            redactor.redact("Authorization: Bearer eyJhbGciOi.J9.abc").as_deref(),
+            Some("Authorization: Bearer <token>")
+        );
+        assert_eq!(redactor.redact("nothing sensitive"), None);
+    }
+}
@@ -1,6 +1,16 @@
 //! An event loop implementation.
 
-use std::{any::Any, sync::mpsc, thread, thread::JoinHandle};
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    thread::JoinHandle,
+    time::Duration,
+};
 
 use thiserror::Error;
 
@@ -19,6 +29,29 @@ pub(crate) trait Handle {
     /// Handles the received event.
     fn handle(&mut self, event: Self::Event, context: &mut Context);
 
+    /// Recovers from a panic occurred while handling an event.
+    ///
+    /// The runloop keeps running after the recovery unless it is stopped via the
+    /// context. By default, the panic is propagated and the runloop terminates.
+    #[inline]
+    fn recover(&mut self, payload: Box<dyn Any + Send>, _: &mut Context) {
+        panic::resume_unwind(payload)
+    }
+
+    /// How long to wait for the next event before [`Handle::idle`] is called,
+    /// `None` to wait indefinitely.
+    ///
+    /// It is ignored where the threads are unsupported, as there is no thread
+    /// waiting for the events.
+    #[inline]
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Called when no event has been received within the timeout.
+    #[inline]
+    fn idle(&mut self, _: &mut Context) {}
+
     /// Starts a new associated runloop.
     #[inline]
     fn run(self) -> Runloop<Self::Event>
@@ -55,11 +88,22 @@ impl Context {
     }
 }
 
-pub(crate) struct Runloop<Event> {
-    sender: mpsc::Sender<Event>,
-    thread_handle: JoinHandle<()>,
+/// Whether the threads can be spawned on the target, they can't on `wasm32`
+/// without the `atomics` feature.
+pub(crate) const THREADS_SUPPORTED: bool =
+    !cfg!(all(target_family = "wasm", not(target_feature = "atomics")));
+
+pub(crate) enum Runloop<Event> {
+    /// The events are handled on the associated thread.
+    Thread { sender: mpsc::Sender<Event>, thread_handle: JoinHandle<()> },
+    /// The events are handled on the sending thread, where the threads are not
+    /// supported. The handler is dropped once the runloop has stopped.
+    Inline(Mutex<Option<InlineHandler<Event>>>),
 }
 
+/// Handles the event, and returns whether the runloop is still running.
+type InlineHandler<Event> = Box<dyn FnMut(Event) -> bool + Send>;
+
 impl<Event> Runloop<Event>
 where
     Event: Send + 'static,
@@ -69,16 +113,42 @@ where
     where
         H: Handle<Event = Event> + Send + 'static,
     {
+        if !THREADS_SUPPORTED {
+            return Self::inline(handler);
+        }
+
         let (sender, receiver) = mpsc::channel();
 
         let thread_handle = thread::spawn(move || {
             let mut context = Context::new();
-            while !context.is_stopped() && let Ok(event) = receiver.recv() {
-                handler.handle(event, &mut context);
+            while !context.is_stopped() {
+                let event = match handler.timeout() {
+                    Some(timeout) => receiver.recv_timeout(timeout),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match event {
+                    Ok(event) => handle(&mut handler, Some(event), &mut context),
+                    Err(RecvTimeoutError::Timeout) => handle(&mut handler, None, &mut context),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
             }
         });
 
-        Self { sender, thread_handle }
+        Self::Thread { sender, thread_handle }
+    }
+
+    /// Starts a new runloop with handler, whose events are handled on the sending
+    /// thread.
+    fn inline<H>(mut handler: H) -> Self
+    where
+        H: Handle<Event = Event> + Send + 'static,
+    {
+        let mut context = Context::new();
+        let handler = move |event| {
+            handle(&mut handler, Some(event), &mut context);
+            !context.is_stopped()
+        };
+        Self::Inline(Mutex::new(Some(Box::new(handler))))
     }
 
     /// Sends an event to the runloop.
@@ -86,7 +156,26 @@ where
     /// When the runloop has stopped, it returns [`Err`].
     #[inline]
     pub(crate) fn on(&self, event: Event) -> Result<(), Error> {
-        self.sender.send(event).map_err(|_| Error)
+        match self {
+            Self::Thread { sender, .. } => sender.send(event).map_err(|_| Error),
+            Self::Inline(handler) => {
+                let mut handler = handler.lock().map_err(|_| Error)?;
+                let is_running = handler.as_mut().ok_or(Error)?(event);
+                if !is_running {
+                    *handler = None;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the associated thread is still running.
+    #[inline]
+    pub(crate) fn is_alive(&self) -> bool {
+        match self {
+            Self::Thread { thread_handle, .. } => !thread_handle.is_finished(),
+            Self::Inline(handler) => handler.lock().is_ok_and(|h| h.is_some()),
+        }
     }
 
     /// Waits for the runloop to finish.
@@ -95,6 +184,112 @@ where
     /// parameter given to [`panic!`].
     #[inline]
     pub(crate) fn join(self) -> Result<(), Box<dyn Any + Send + 'static>> {
-        self.thread_handle.join()
+        match self {
+            Self::Thread { thread_handle, .. } => thread_handle.join(),
+            Self::Inline(_) => Ok(()),
+        }
+    }
+}
+
+/// Handles the event, or the idleness if there is none. The panic occurred is
+/// recovered by the handler.
+#[inline]
+fn handle<H>(handler: &mut H, event: Option<H::Event>, context: &mut Context)
+where
+    H: Handle,
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match event {
+        Some(event) => handler.handle(event, context),
+        None => handler.idle(context),
+    }));
+    if let Err(payload) = result {
+        handler.recover(payload, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{any::Any, sync::mpsc, time::Duration};
+
+    use crate::runloop::{Context, Handle, Runloop};
+
+    struct Handler {
+        handled: mpsc::Sender<u32>,
+        recovered: usize,
+    }
+
+    impl Handle for Handler {
+        type Event = u32;
+
+        fn handle(&mut self, event: Self::Event, _: &mut Context) {
+            assert_ne!(event, 0, "the event is zero");
+            self.handled.send(event).unwrap();
+        }
+
+        fn recover(&mut self, _: Box<dyn Any + Send>, context: &mut Context) {
+            self.recovered += 1;
+            if self.recovered == 2 {
+                context.stop();
+            }
+        }
+    }
+
+    #[test]
+    fn test_recover() {
+        let (sender, receiver) = mpsc::channel();
+        let runloop = Handler { handled: sender, recovered: 0 }.run();
+        for event in [1, 0, 2, 0, 3] {
+            _ = runloop.on(event);
+        }
+        runloop.join().unwrap();
+
+        // The runloop keeps running after the first panic, and stops after the second.
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn test_inline() {
+        let (sender, receiver) = mpsc::channel();
+        let runloop = Runloop::inline(Handler { handled: sender, recovered: 0 });
+        // The events are handled before they are sent.
+        assert!(runloop.on(1).is_ok());
+        assert_eq!(receiver.try_recv().ok(), Some(1));
+
+        for event in [0, 2, 0] {
+            _ = runloop.on(event);
+        }
+        assert!(!runloop.is_alive());
+        assert!(runloop.on(3).is_err());
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [2]);
+    }
+
+    struct Idler {
+        idled: mpsc::Sender<()>,
+    }
+
+    impl Handle for Idler {
+        type Event = ();
+
+        fn handle(&mut self, _: Self::Event, context: &mut Context) {
+            context.stop();
+        }
+
+        fn timeout(&self) -> Option<Duration> {
+            Some(Duration::from_millis(1))
+        }
+
+        fn idle(&mut self, _: &mut Context) {
+            _ = self.idled.send(());
+        }
+    }
+
+    #[test]
+    fn test_idle() {
+        let (sender, receiver) = mpsc::channel();
+        let runloop = Idler { idled: sender }.run();
+        // The handler is idle until an event is received.
+        assert!(receiver.recv_timeout(Duration::from_secs(10)).is_ok());
+        assert!(runloop.on(()).is_ok());
+        runloop.join().unwrap();
     }
 }
@@ -0,0 +1,91 @@
+//! The salvage of damaged log files.
+
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use thiserror::Error;
+
+use crate::{chunk, chunk::Chunk, common::LazyFileWriter};
+
+/// Errors that can be occurred while salvaging a log file ([`salvage`]).
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("no valid chunks were found")]
+    NotFound,
+}
+
+/// The report of the salvage, see [`salvage`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SalvageReport {
+    /// The number of chunks that have been written out intact.
+    pub chunks: usize,
+    /// The number of chunks that have been cut off (e.g., by the end of the file or
+    /// by the following chunk), whose remaining payloads have been written out.
+    pub truncated_chunks: usize,
+    /// The total length (in bytes) of the unreadable regions that have been
+    /// skipped, excluding the zero-filled space.
+    pub skipped_bytes: u64,
+}
+
+/// Salvages a damaged log file (e.g., truncated or partially overwritten on a
+/// crashed device), and writes out only the valid chunks to the destination file.
+///
+/// The file is scanned for the chunk boundaries, and the unreadable regions between
+/// them are skipped. A chunk that is cut off is written out as a chunk whose payload
+/// ends without being finalized, so that the records in its remaining payload can
+/// still be parsed.
+///
+/// The destination file is not created if no valid chunks were found.
+pub fn salvage(
+    path: impl AsRef<Path>,
+    dest_path: impl AsRef<Path>,
+) -> Result<SalvageReport, Error> {
+    let bytes = fs::read(path)?;
+    let mut writer = BufWriter::new(LazyFileWriter::new(dest_path.as_ref()));
+    let mut report = SalvageReport::default();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let Some(start) = chunk::find(&bytes, pos..bytes.len()) else {
+            report.skipped_bytes += non_zero_len(&bytes[pos..]);
+            break;
+        };
+        report.skipped_bytes += non_zero_len(&bytes[pos..start]);
+
+        let (header_len, payload_len) = chunk::locate(&bytes[start..]).unwrap_or_default();
+        let (payload_start, payload_end) = (start + header_len, start + header_len + payload_len);
+        // If the writing of the chunk was interrupted (e.g., by a crash), it is cut
+        // off by the end of the file or the chunk written after it.
+        let end = payload_end.min(bytes.len());
+        let end = chunk::find(&bytes, payload_start..end).unwrap_or(end);
+
+        if end == payload_end {
+            writer.write_all(&bytes[start..end])?;
+            report.chunks += 1;
+        } else if end > payload_start
+            && let Some(chunk) = Chunk::recover(&bytes[start..end])
+        {
+            writer.write_all(&chunk)?;
+            report.truncated_chunks += 1;
+        } else {
+            report.skipped_bytes += header_len as u64;
+        }
+        pos = end;
+    }
+
+    if writer.into_inner().map_err(|err| err.into_error())?.is_empty() {
+        Err(Error::NotFound)
+    } else {
+        Ok(report)
+    }
+}
+
+#[inline]
+fn non_zero_len(bytes: &[u8]) -> u64 {
+    bytes.iter().filter(|b| **b != 0).count() as u64
+}
@@ -0,0 +1,200 @@
+//! The per-thread staging of the records.
+
+use std::{
+    cell::RefCell,
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{
+    codec::{Decode, DecodingError, Encode, EncodingError},
+    common::FnSink,
+    DateTime, Meta, Record,
+};
+
+/// The staging area of a logger, which holds the stages of the threads that have
+/// logged to it.
+///
+/// The records are encoded into the stage of the logging thread without locking
+/// the logger, and are drained into the logger when the stage is full or the
+/// logger is flushed.
+pub(crate) struct Staging {
+    id: usize,
+    stage_len: usize,
+    stages: Mutex<Vec<Arc<Stage>>>,
+    /// The scratch space that the stages are drained into.
+    scratch: Mutex<Scratch>,
+}
+
+/// The scratch space that the stages are drained into, which is reused so that
+/// draining doesn't allocate once warmed up.
+#[derive(Default)]
+struct Scratch {
+    bytes: Vec<u8>,
+    /// The datetimes and the ranges in `bytes` of the encoded records.
+    frames: Vec<(DateTime, Range<usize>)>,
+}
+
+/// The encoded records staged by a thread.
+///
+/// Only the owner thread and the draining thread access it, so the lock is almost
+/// never contended.
+struct Stage {
+    bytes: Mutex<Vec<u8>>,
+}
+
+thread_local! {
+    /// The stages of the current thread, keyed by the staging ids.
+    ///
+    /// Both the thread and the staging hold the stage, so the stage held by only
+    /// one of them belongs to the exited thread or the dropped staging.
+    static STAGES: RefCell<Vec<(usize, Arc<Stage>)>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Staging {
+    /// Constructs a new `Staging` whose stages are full once they exceed the length.
+    pub(crate) fn new(stage_len: usize) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            stage_len,
+            stages: Mutex::new(Vec::new()),
+            scratch: Mutex::new(Scratch::default()),
+        }
+    }
+
+    /// Stages the record in the stage of the current thread.
+    ///
+    /// Returns whether the stage is full, that is, the staging needs to be drained.
+    pub(crate) fn stage(&self, record: &Record) -> bool {
+        let stage = self.current();
+        let mut bytes = stage.bytes.lock().unwrap();
+        let mut sink = FnSink::new(|encoded: &[u8]| {
+            bytes.extend_from_slice(encoded);
+            Ok::<_, EncodingError>(())
+        });
+        // Encoding into memory is infallible.
+        _ = record.encode(&mut sink);
+        bytes.len() >= self.stage_len
+    }
+
+    /// Drains the records of all stages, and passes them to the callback in the
+    /// order of their datetimes.
+    ///
+    /// The records are sorted so that the records staged by different threads don't
+    /// cause the chunks to be rotated back and forth. The stages of the exited
+    /// threads are dropped once drained.
+    ///
+    /// Returns the error if any of the records can't be decoded, they are dropped.
+    pub(crate) fn drain(&self, mut callback: impl FnMut(&Record)) -> Result<(), DecodingError> {
+        let mut scratch = self.scratch.lock().unwrap();
+        let Scratch { bytes, frames } = &mut *scratch;
+        self.stages.lock().unwrap().retain(|stage| {
+            let mut staged = stage.bytes.lock().unwrap();
+            bytes.extend_from_slice(&staged);
+            staged.clear();
+            Arc::strong_count(stage) > 1
+        });
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        // Only the datetimes are decoded for sorting, so that the records aren't
+        // buffered. The rest of the bytes can't be framed after an error.
+        let mut result = Ok(());
+        let mut source = bytes.as_slice();
+        while !source.is_empty() {
+            let start = bytes.len() - source.len();
+            match Self::decode_datetime(&mut source) {
+                Ok(datetime) => frames.push((datetime, start..bytes.len() - source.len())),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+        // Sorting by the starts as well keeps the order of the records of the same
+        // datetime, without the allocation of the stable sorting.
+        frames.sort_unstable_by_key(|(datetime, range)| (*datetime, range.start));
+        for (_, range) in frames.drain(..) {
+            match Record::decode(&mut &bytes[range]) {
+                Ok(record) => callback(&record),
+                Err(err) => result = result.and(Err(err)),
+            }
+        }
+
+        bytes.clear();
+        result
+    }
+
+    /// Releases the memory of the stages, which are expected to have been drained.
+    pub(crate) fn shrink(&self) {
+        for stage in self.stages.lock().unwrap().iter() {
+            stage.bytes.lock().unwrap().shrink_to_fit();
+        }
+        let mut scratch = self.scratch.lock().unwrap();
+        scratch.bytes.shrink_to_fit();
+        scratch.frames.shrink_to_fit();
+    }
+
+    /// Decodes the datetime of the encoded record, and skips the record.
+    fn decode_datetime(source: &mut &[u8]) -> Result<DateTime, DecodingError> {
+        let (_, mut fields) = Record::decode_frame(source)?;
+        Ok(Meta::decode(&mut fields)?.datetime())
+    }
+
+    /// The stage of the current thread, it is registered on the first use.
+    fn current(&self) -> Arc<Stage> {
+        STAGES.with_borrow_mut(|stages| {
+            if let Some((_, stage)) = stages.iter().find(|(id, _)| *id == self.id) {
+                return Arc::clone(stage);
+            }
+
+            // Forgets the stages of the dropped loggers.
+            stages.retain(|(_, stage)| Arc::strong_count(stage) > 1);
+            let stage = Arc::new(Stage { bytes: Mutex::new(Vec::with_capacity(self.stage_len)) });
+            self.stages.lock().unwrap().push(Arc::clone(&stage));
+            stages.push((self.id, Arc::clone(&stage)));
+            stage
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::RecordBuilder;
+
+    #[test]
+    fn test_drain() {
+        let staging = Staging::new(1024);
+        let log = |content| _ = staging.stage(&RecordBuilder::new().content(content).build());
+        // The threads are joined, so that their stages have been released.
+        thread::scope(|scope| {
+            let a = scope.spawn(|| log("a"));
+            let b = scope.spawn(|| log("b"));
+            _ = (a.join(), b.join());
+        });
+        log("c");
+        assert_eq!(staging.stages.lock().unwrap().len(), 3);
+
+        let mut contents = Vec::new();
+        staging.drain(|record| contents.push(record.content().to_string())).unwrap();
+        contents.sort();
+        assert_eq!(contents, ["a", "b", "c"]);
+        // The stages of the exited threads are dropped.
+        assert_eq!(staging.stages.lock().unwrap().len(), 1);
+
+        // The undecodable records are reported.
+        log("d");
+        staging.current().bytes.lock().unwrap().push(0xFF);
+        let mut contents = Vec::new();
+        assert!(staging.drain(|record| contents.push(record.content().to_string())).is_err());
+        assert_eq!(contents, ["d"]);
+    }
+}
@@ -0,0 +1,159 @@
+//! The pluggable storage of the log files.
+
+use std::{
+    fs,
+    fs::File,
+    io,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Represents where the log files are stored, see [`Config::storage`].
+///
+/// By default, the log files are stored in the file system (see [`FileSystem`]). A
+/// custom storage enables the platforms without a usable file system (e.g., the
+/// browser with OPFS or IndexedDB, the scoped storage of Android), and the test
+/// doubles that don't touch the disk.
+///
+/// The paths passed to the storage are the ones the log files would have in the
+/// file system (see [`Domain`]), the storage is free to use them as keys only. The
+/// mmap buffer file is not stored in the storage, see [`Config::use_mmap`].
+///
+/// [`Config::storage`]: crate::Config::storage
+/// [`Config::use_mmap`]: crate::Config::use_mmap
+/// [`Domain`]: crate::Domain
+pub trait Storage: Send + Sync {
+    /// Appends the bytes to the end of the file, the file is created if it doesn't
+    /// exist.
+    fn append(&self, path: &Path, bytes: &[u8]) -> io::Result<()>;
+
+    /// Reads the whole file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Lists the paths of the files in the directory, including the ones in its
+    /// subdirectories (see [`FileLayout::Dated`]).
+    ///
+    /// [`FileLayout::Dated`]: crate::FileLayout::Dated
+    fn list(&self, directory: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Deletes the file.
+    fn delete(&self, path: &Path) -> io::Result<()>;
+
+    /// The length of the file in bytes, which is used to report the extraction
+    /// progress without reading the files.
+    fn len(&self, path: &Path) -> io::Result<u64>;
+
+    /// Removes the directory if it is empty, e.g., the dated subdirectory (see
+    /// [`FileLayout::Dated`]) whose last file has been deleted. It fails if the
+    /// directory is not empty.
+    ///
+    /// The default implementation does nothing, for the storages without
+    /// directories.
+    ///
+    /// [`FileLayout::Dated`]: crate::FileLayout::Dated
+    fn remove_dir(&self, directory: &Path) -> io::Result<()> {
+        _ = directory;
+        Ok(())
+    }
+
+    /// Opens the file in the file system for the random access, which the
+    /// preallocation (see [`Config::preallocation`]), the minute index (see
+    /// [`Config::minute_index`]) and the direct mode (see [`Config::direct_mmap`])
+    /// need. The file is created if it is opened for writing.
+    ///
+    /// The default implementation returns `None`, which means the files are not in
+    /// the file system, and these features are disabled.
+    ///
+    /// [`Config::preallocation`]: crate::Config::preallocation
+    /// [`Config::minute_index`]: crate::Config::minute_index
+    /// [`Config::direct_mmap`]: crate::Config::direct_mmap
+    fn open(&self, path: &Path, write: bool) -> Option<io::Result<File>> {
+        _ = (path, write);
+        None
+    }
+}
+
+/// Shared trait object type for [`Storage`].
+pub type SharedStorage = Arc<dyn Storage>;
+
+/// The storage of the log files in the file system, which is the default one.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileSystem;
+
+impl FileSystem {
+    /// The depth of the dated subdirectories (year, month and day).
+    const DATED_DEPTH: usize = 3;
+
+    /// The shared file system storage.
+    #[inline]
+    pub fn shared() -> SharedStorage {
+        Arc::new(Self)
+    }
+
+    /// Collects the paths in the directory, and in the dated subdirectories (see
+    /// [`crate::FileLayout::Dated`]) down to the depth.
+    fn collect_paths(directory: &Path, depth: usize, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(directory)?.filter_map(|entry| entry.ok()) {
+            let is_dated = depth > 0
+                && entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                && entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dated {
+                Self::collect_paths(&entry.path(), depth - 1, paths)?;
+            } else {
+                paths.push(entry.path());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Storage for FileSystem {
+    fn append(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        if let Some(parent_path) = path.parent() {
+            fs::create_dir_all(parent_path)?;
+        }
+        fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(bytes)
+    }
+
+    #[inline]
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    #[inline]
+    fn list(&self, directory: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        Self::collect_paths(directory, Self::DATED_DEPTH, &mut paths)?;
+        Ok(paths)
+    }
+
+    #[inline]
+    fn delete(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    #[inline]
+    fn len(&self, path: &Path) -> io::Result<u64> {
+        fs::metadata(path).map(|m| m.len())
+    }
+
+    #[inline]
+    fn remove_dir(&self, directory: &Path) -> io::Result<()> {
+        fs::remove_dir(directory)
+    }
+
+    fn open(&self, path: &Path, write: bool) -> Option<io::Result<File>> {
+        // Creates all intermediate directories if they are missing.
+        if write
+            && let Some(parent_path) = path.parent()
+            && let Err(err) = fs::create_dir_all(parent_path)
+        {
+            return Some(Err(err));
+        }
+        Some(fs::OpenOptions::new().read(true).write(write).create(write).open(path))
+    }
+}
@@ -0,0 +1,236 @@
+//! The template-based formatter.
+
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+use chrono::format::{Item, StrftimeItems};
+use thiserror::Error;
+
+use crate::{Format, Level, LevelLabels, Record, Timezone};
+
+/// Errors that can be occurred while parsing the template, see
+/// [`TemplateFormatter::new`].
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("unknown placeholder `{{{0}}}`")]
+    UnknownPlaceholder(String),
+    #[error("invalid datetime format `{0}`")]
+    InvalidDatetimeFormat(String),
+    #[error("unclosed `{{` at byte {0}")]
+    Unclosed(usize),
+    #[error("unmatched `}}` at byte {0}")]
+    Unmatched(usize),
+}
+
+/// The formatter that formats log records according to a template, such as
+/// `"{datetime} [{level}] {tag} {file}:{line} - {content}"`.
+///
+/// The template is parsed once on construction. The placeholders are:
+///
+/// - `{datetime}`: The datetime in the same format as [`DefaultFormatter`]. The
+///   format can be specified with `strftime` specifiers, e.g.,
+///   `{datetime:%H:%M:%S}`. It is presented in the local timezone by default, see
+///   [`TemplateFormatter::timezone`].
+/// - `{timestamp}`: The milliseconds since the Unix epoch.
+/// - `{level}`: The level in lowercase, e.g., `warn`, or its label, see
+///   [`TemplateFormatter::level_labels`].
+/// - `{level_short}`: The initial of the level (or of its label), e.g., `W`.
+/// - `{tag}`, `{thread_id}`, `{thread_name}`, `{session_id}`, `{elapsed}`,
+///   `{sequence}`, `{file}`, `{func}`, `{line}`, `{content}`, `{repeats}` and
+///   `{truncated}`.
+/// - `{context}`: The key-value pairs of the context, e.g., `request_id=42`,
+///   separated by spaces. The value of a key can be specified, e.g.,
+///   `{context:request_id}`, which is the innermost one if the key is repeated.
+///
+/// The absent fields are empty, `{{` and `}}` are the literal braces. A newline is
+/// written after each record.
+///
+/// [`DefaultFormatter`]: crate::DefaultFormatter
+#[derive(Clone, Debug)]
+pub struct TemplateFormatter {
+    segments: Vec<Segment>,
+    timezone: Timezone,
+    level_labels: Option<Arc<LevelLabels>>,
+}
+
+impl TemplateFormatter {
+    /// Constructs a new `TemplateFormatter` by parsing the template.
+    pub fn new(template: &str) -> Result<Self, Error> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((index, char)) = chars.next() {
+            match char {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => literal.push('}'),
+                '}' => return Err(Error::Unmatched(index)),
+                '{' => {
+                    let start = index + 1;
+                    let end = template[start..]
+                        .find('}')
+                        .map(|len| start + len)
+                        .ok_or(Error::Unclosed(index))?;
+                    while chars.next_if(|&(i, _)| i <= end).is_some() {}
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::parse(&template[start..end])?);
+                }
+                char => literal.push(char),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self { segments, timezone: Timezone::Local, level_labels: None })
+    }
+
+    /// The timezone in which the datetimes are presented, see [`Timezone`].
+    #[inline]
+    pub fn timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// The labels of the levels presented by `{level}`, and by their initials by
+    /// `{level_short}`, see [`LevelLabels`].
+    ///
+    /// The default value is `None`.
+    #[inline]
+    pub fn level_labels(mut self, labels: Option<LevelLabels>) -> Self {
+        self.level_labels = labels.map(Arc::new);
+        self
+    }
+
+    /// Returns the label of the level.
+    #[inline]
+    fn level_label(&self, level: Level) -> &str {
+        match &self.level_labels {
+            Some(labels) => labels.label(level),
+            None => level.as_str(),
+        }
+    }
+}
+
+impl Format for TemplateFormatter {
+    fn format(&mut self, record: &Record, writer: &mut impl Write) -> io::Result<()> {
+        let (meta, location) = (record.meta(), record.meta().location());
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => writer.write_all(literal.as_bytes())?,
+                Segment::Datetime(format) => {
+                    let datetime = self.timezone.convert(meta.datetime());
+                    let format = format.as_deref().unwrap_or("%F %T%.3f");
+                    write!(writer, "{}", datetime.format(format))?;
+                }
+                Segment::Timestamp => write!(writer, "{}", meta.datetime().timestamp_millis())?,
+                Segment::Level => writer.write_all(self.level_label(meta.level()).as_bytes())?,
+                Segment::LevelShort => {
+                    let initial = self.level_label(meta.level()).chars().next().unwrap_or_default();
+                    write!(writer, "{}", initial.to_ascii_uppercase())?;
+                }
+                Segment::Tag => write_optional(writer, meta.tag())?,
+                Segment::ThreadId => write_optional(writer, meta.thread_id())?,
+                Segment::ThreadName => write_optional(writer, meta.thread_name())?,
+                Segment::SessionId => write_optional(writer, meta.session_id())?,
+                Segment::Elapsed => write_optional(writer, meta.elapsed())?,
+                Segment::Sequence => write_optional(writer, meta.sequence())?,
+                Segment::File => write_optional(writer, location.file())?,
+                Segment::Func => write_optional(writer, location.func())?,
+                Segment::Line => write_optional(writer, location.line())?,
+                Segment::Content => writer.write_all(record.content().as_bytes())?,
+                Segment::Repeats => write!(writer, "{}", record.repeats())?,
+                Segment::Truncated => write!(writer, "{}", record.truncated())?,
+                Segment::Context(None) => {
+                    for (index, (key, value)) in record.context().iter().enumerate() {
+                        let separator = if index == 0 { "" } else { " " };
+                        write!(writer, "{separator}{key}={value}")?;
+                    }
+                }
+                Segment::Context(Some(key)) => {
+                    let mut pairs = record.context().iter().rev();
+                    let value = pairs.find(|(k, _)| k == key).map(|(_, value)| *value);
+                    write_optional(writer, value)?;
+                }
+            }
+        }
+        writeln!(writer)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    /// The datetime with the optional `strftime` format.
+    Datetime(Option<String>),
+    Timestamp,
+    Level,
+    LevelShort,
+    Tag,
+    ThreadId,
+    ThreadName,
+    SessionId,
+    Elapsed,
+    Sequence,
+    File,
+    Func,
+    Line,
+    Content,
+    Repeats,
+    Truncated,
+    /// The context, or the value of the key in it.
+    Context(Option<String>),
+}
+
+impl Segment {
+    /// Parses the placeholder without braces.
+    fn parse(placeholder: &str) -> Result<Self, Error> {
+        if let Some(format) = placeholder.strip_prefix("datetime:") {
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(Error::InvalidDatetimeFormat(format.to_string()));
+            }
+            return Ok(Self::Datetime(Some(format.to_string())));
+        }
+        if let Some(key) = placeholder.strip_prefix("context:") {
+            return Ok(Self::Context(Some(key.to_string())));
+        }
+
+        Ok(match placeholder {
+            "datetime" => Self::Datetime(None),
+            "timestamp" => Self::Timestamp,
+            "level" => Self::Level,
+            "level_short" => Self::LevelShort,
+            "tag" => Self::Tag,
+            "thread_id" => Self::ThreadId,
+            "thread_name" => Self::ThreadName,
+            "session_id" => Self::SessionId,
+            "elapsed" => Self::Elapsed,
+            "sequence" => Self::Sequence,
+            "file" => Self::File,
+            "func" => Self::Func,
+            "line" => Self::Line,
+            "content" => Self::Content,
+            "repeats" => Self::Repeats,
+            "truncated" => Self::Truncated,
+            "context" => Self::Context(None),
+            _ => return Err(Error::UnknownPlaceholder(placeholder.to_string())),
+        })
+    }
+}
+
+#[inline]
+fn write_optional(
+    writer: &mut impl Write,
+    value: Option<impl std::fmt::Display>,
+) -> io::Result<()> {
+    match value {
+        Some(value) => write!(writer, "{value}"),
+        None => Ok(()),
+    }
+}
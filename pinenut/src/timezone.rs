@@ -0,0 +1,62 @@
+//! The timezone of the formatted datetimes.
+
+use std::{fmt, str::FromStr};
+
+use chrono::{FixedOffset, Local, Offset};
+
+use crate::DateTime;
+
+/// The timezone in which the formatters (e.g., [`DefaultFormatter`]) present the
+/// datetimes of the records.
+///
+/// The logs are often parsed on a machine (e.g., a build server) in a timezone
+/// different from the device, `Utc` or a fixed offset makes the datetimes
+/// unambiguous.
+///
+/// It can be parsed from `local`, `utc` or an offset such as `+08:00`.
+///
+/// [`DefaultFormatter`]: crate::DefaultFormatter
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Timezone {
+    /// The local timezone of the machine formatting the records.
+    #[default]
+    Local,
+    /// The UTC.
+    Utc,
+    /// The fixed offset from UTC.
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// Converts the datetime into the timezone.
+    pub(crate) fn convert(&self, datetime: DateTime) -> chrono::DateTime<FixedOffset> {
+        let offset = match self {
+            Self::Local => datetime.with_timezone(&Local).offset().fix(),
+            Self::Utc => datetime.offset().fix(),
+            Self::Fixed(offset) => *offset,
+        };
+        datetime.with_timezone(&offset)
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = chrono::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "utc" | "z" => Ok(Self::Utc),
+            _ => s.parse().map(Self::Fixed),
+        }
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => f.write_str("local"),
+            Self::Utc => f.write_str("utc"),
+            Self::Fixed(offset) => write!(f, "{offset}"),
+        }
+    }
+}
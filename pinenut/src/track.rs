@@ -0,0 +1,204 @@
+//! The tracking of the errors and the notable events occurred in the logger.
+
+use std::sync::Arc;
+
+use crate::{ChunkError, LoggerError};
+
+/// Represents a tracker used to track errors and notable events occurred from the
+/// logger operations.
+///
+/// The tracker receives [`TrackEvent`]s via [`Track::track_event`]. The trackers
+/// implementing only [`Track::track`] (including the closures with its signature)
+/// keep receiving the errors, but not the events without errors.
+pub trait Track {
+    /// Handles the event.
+    ///
+    /// By default, the error of the event (if any) is passed to [`Track::track`].
+    #[inline]
+    fn track_event(&self, event: TrackEvent) {
+        if let Some(error) = event.error {
+            #[allow(deprecated)]
+            self.track(error, event.file, event.line);
+        }
+    }
+
+    /// Handles the error on the code location.
+    #[deprecated(note = "implement `Track::track_event` instead")]
+    #[inline]
+    fn track(&self, error: LoggerError, file: &'static str, line: u32) {
+        _ = (error, file, line);
+    }
+}
+
+impl<F> Track for F
+where
+    F: Fn(LoggerError, &'static str, u32),
+{
+    #[inline]
+    fn track(&self, error: LoggerError, file: &'static str, line: u32) {
+        self(error, file, line)
+    }
+}
+
+/// Trait object type for [`Track`].
+pub type Tracker = Box<dyn Track + Send + Sync>;
+
+/// The tracker shared by the loggers of a domain and its split stream, see
+/// [`Config::split_by_level`].
+///
+/// [`Config::split_by_level`]: crate::Config::split_by_level
+struct SharedTracker(Arc<dyn Track + Send + Sync>);
+
+impl Track for SharedTracker {
+    #[inline]
+    fn track_event(&self, event: TrackEvent) {
+        self.0.track_event(event)
+    }
+}
+
+/// Shares the tracker, returns another tracker forwarding to the same one.
+pub(crate) fn share(tracker: &mut Option<Tracker>) -> Option<Tracker> {
+    let shared: Arc<dyn Track + Send + Sync> = Arc::from(tracker.take()?);
+    *tracker = Some(Box::new(SharedTracker(Arc::clone(&shared))));
+    Some(Box::new(SharedTracker(shared)))
+}
+
+/// Represents an event occurred in the logger, see [`Track`].
+#[derive(Debug)]
+pub struct TrackEvent {
+    /// The machine-readable category of the event.
+    pub category: TrackCategory,
+    pub severity: Severity,
+    /// The error of the event, there is none for the informational events (e.g.,
+    /// [`TrackCategory::Trim`]).
+    pub error: Option<LoggerError>,
+    /// The human-readable details of the event, if any.
+    pub context: Option<String>,
+    /// The code location where the event occurred.
+    pub file: &'static str,
+    pub line: u32,
+}
+
+impl TrackEvent {
+    /// Constructs a new `TrackEvent` of the category, whose severity is the one of
+    /// the category.
+    #[inline]
+    pub(crate) fn new(category: TrackCategory, file: &'static str, line: u32) -> Self {
+        let severity = category.severity();
+        Self { category, severity, error: None, context: None, file, line }
+    }
+
+    /// Constructs a new `TrackEvent` of the error, whose category is derived from
+    /// the error.
+    #[inline]
+    pub(crate) fn from_error(error: LoggerError, file: &'static str, line: u32) -> Self {
+        Self::new(TrackCategory::of(&error), file, line).error(error)
+    }
+
+    #[inline]
+    pub(crate) fn error(mut self, error: LoggerError) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    #[inline]
+    pub(crate) fn context(mut self, context: String) -> Self {
+        self.context = Some(context);
+        self
+    }
+}
+
+/// The category of the [`TrackEvent`].
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TrackCategory {
+    /// The records failed to be encoded, compressed or encrypted.
+    Processing,
+    /// The records didn't fit in the chunk and were dropped.
+    ChunkOverflow,
+    /// The IO operations on the log files failed.
+    Io,
+    /// The IO thread panicked.
+    IoPanic,
+    /// The IO thread has stopped, the records are no longer written to the log
+    /// files.
+    Runloop,
+    /// The mmap buffer was corrupted and has been quarantined.
+    BufferCorrupted,
+    /// The chunks in the mmap buffer failed the validation (e.g., their checksums
+    /// mismatch after the file system corruption), they have been salvaged as far as
+    /// possible and re-initialized.
+    BufferReinitialized,
+    /// The preferred storage (i.e., the mmap buffer or the direct log file) couldn't
+    /// be used, the logger fell back to the buffer in memory or the mmap buffer.
+    BufferFallback,
+    /// There is not enough free space, see [`Config::min_free_space`].
+    ///
+    /// [`Config::min_free_space`]: crate::Config::min_free_space
+    InsufficientSpace,
+    /// The expired log files have been deleted, see [`Logger::trim`].
+    ///
+    /// [`Logger::trim`]: crate::Logger::trim
+    Trim,
+    /// The chunk left in the mmap buffer (e.g., by a crash) has been written back
+    /// to the log file.
+    WritebackRecovered,
+    /// The log file writes have been paused after consecutive failures, see
+    /// [`CircuitBreaker`].
+    ///
+    /// [`CircuitBreaker`]: crate::CircuitBreaker
+    IoPaused,
+    /// The paused log file writes have been resumed.
+    IoResumed,
+    /// The clock has jumped backwards (e.g., corrected by NTP), the records after
+    /// it are written to a new chunk.
+    ClockRegressed,
+}
+
+impl TrackCategory {
+    /// The severity of the events of the category.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Trim | Self::WritebackRecovered | Self::IoResumed => Severity::Info,
+            Self::BufferCorrupted
+            | Self::BufferReinitialized
+            | Self::BufferFallback
+            | Self::InsufficientSpace
+            | Self::IoPaused
+            | Self::ClockRegressed => Severity::Warning,
+            Self::Processing | Self::ChunkOverflow | Self::Io => Severity::Error,
+            Self::IoPanic | Self::Runloop => Severity::Critical,
+        }
+    }
+
+    /// The category of the error.
+    fn of(error: &LoggerError) -> Self {
+        match error {
+            LoggerError::Encode(_)
+            | LoggerError::Compress(_)
+            | LoggerError::Encrypt(_)
+            | LoggerError::Chunk(ChunkError::UnsupportedVersion(_))
+            | LoggerError::Chunk(ChunkError::SignatureMismatch)
+            | LoggerError::Chunk(ChunkError::ChecksumMismatch) => Self::Processing,
+            LoggerError::Chunk(ChunkError::Overflow) => Self::ChunkOverflow,
+            LoggerError::IoRunloop(_) => Self::Runloop,
+            LoggerError::Io(_) => Self::Io,
+            LoggerError::IoPanic(_) => Self::IoPanic,
+            LoggerError::BufferCorrupted(_) => Self::BufferCorrupted,
+            LoggerError::InsufficientSpace { .. } => Self::InsufficientSpace,
+        }
+    }
+}
+
+/// The severity of the [`TrackEvent`], in ascending order.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Severity {
+    /// The notable events that are not errors.
+    Info,
+    /// The logger is degraded, but the records are still written.
+    Warning,
+    /// Some records are lost.
+    Error,
+    /// The logger no longer writes the records.
+    Critical,
+}
@@ -0,0 +1,51 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, AdaptiveCompression, Config, DateTime, Domain, MetaBuilder, RecordBuilder,
+    RotateReason,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_adaptive_compression() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    // No records can be logged within the zero target, so that the level steps down
+    // on every rotation.
+    let auto = AdaptiveCompression::new(1..=3, Duration::ZERO);
+    let config =
+        Config::new().use_mmap(false).compression_level(10).compression_level_auto(Some(auto));
+    let logger = domain.clone().logger(config);
+    // The level is clamped to the range.
+    assert_eq!(logger.health().compression_level, 3);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let mut levels = Vec::new();
+    for round in 0..3 {
+        for index in 0..10 {
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            let content = format!("test log {round} {index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.rotate(RotateReason::UserInitiated);
+        levels.push(logger.health().compression_level);
+        // Waits for the rotated chunk to be written to the file.
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+    assert_eq!(levels, [2, 1, 1]);
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    let mut count = 0;
+    parse(&path, None, |_| {
+        count += 1;
+        Ok(())
+    })?;
+    assert_eq!(count, 30);
+
+    Ok(())
+}
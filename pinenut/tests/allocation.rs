@@ -0,0 +1,79 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    error::Error,
+    str::FromStr,
+};
+
+use pinenut_log::{
+    Config, DateTime, Domain, Level, LocationBuilder, MetaBuilder, RateLimit, RecordBuilder,
+};
+use tempfile::tempdir;
+
+/// Counts the allocations of the current thread, the allocations of the IO thread
+/// are not counted.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_no_allocation() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let location = LocationBuilder::new().file("main.rs").line(42).build();
+    let meta = MetaBuilder::new()
+        .level(Level::Info)
+        .datetime(DateTime::from_str("2013-11-18 13:35:12Z")?)
+        .location(location)
+        .tag("net")
+        .thread_id(1)
+        .build();
+    let contents: Vec<_> = (0..100).map(|index| format!("test log {index}")).collect();
+
+    let configs = [
+        Config::new(),
+        Config::new().use_mmap(false),
+        Config::new().deduplicate(true),
+        Config::new().rate_limit(Some(RateLimit::new(1000, true))),
+    ];
+    for config in configs {
+        let logger = domain.clone().logger(config.tracker(None));
+        let log = || {
+            for content in &contents {
+                logger.log(&RecordBuilder::new().meta(meta.clone()).content(content).build());
+            }
+        };
+
+        // Warms up the buffers and the scratch spaces.
+        log();
+        let before = ALLOCATIONS.with(Cell::get);
+        log();
+        assert_eq!(ALLOCATIONS.with(Cell::get) - before, 0);
+
+        logger.shutdown();
+    }
+
+    Ok(())
+}
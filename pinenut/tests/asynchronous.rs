@@ -0,0 +1,60 @@
+use std::{
+    error::Error,
+    fs,
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread::{self, Thread},
+};
+
+use pinenut_log::{Config, Domain, RecordBuilder, FILE_EXTENSION};
+use tempfile::tempdir;
+
+/// A minimal executor, which parks the current thread until the future is woken.
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_flush_async_and_shutdown_async() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.logger(Config::new().use_mmap(false));
+
+    // Returns the total length of the log files.
+    let logfiles_len = || -> Result<u64, Box<dyn Error>> {
+        Ok(fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension() == Some(FILE_EXTENSION.as_ref()))
+            .map(|e| e.metadata().unwrap().len())
+            .sum())
+    };
+
+    logger.log(&RecordBuilder::new().content("test log").build());
+    block_on(logger.flush_async())?;
+    let len = logfiles_len()?;
+    assert!(len > 0);
+
+    logger.log(&RecordBuilder::new().content("test log").build());
+    block_on(logger.shutdown_async());
+    assert!(logfiles_len()? > len);
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{
+    error::Error,
+    fs, io, mem,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use pinenut_log::{
+    extract, parse, ChunkError, CircuitBreaker, Config, DateTime, Domain, MetaBuilder,
+    ParsingError, RecordBuilder, Storage, Track, TrackCategory, TrackEvent, MMAP_BUFFER_EXTENSION,
+};
+use tempfile::tempdir;
+
+/// The buffer header consists of the magic, the version, the alpha side (4 bytes
+/// each) and the MAC (16 bytes), followed by the two sides of the same length.
+const BUFFER_HEADER_LEN: usize = 28;
+const ALPHA_SIDE_OFFSET: usize = 8;
+/// The side of the buffer that the IO thread writes to the log file from, i.e.,
+/// the output one.
+const OUTPUT_SIDE: [u8; 4] = 0xDEFu32.to_le_bytes();
+
+/// The chunk header starts with the magic (4 bytes), the version (2), the payload
+/// length (4), the writeback flag (1), the time range (16) and the public key (33),
+/// followed by the session id.
+const SESSION_ID_OFFSET: usize = 4 + 2 + 4 + 1 + 16 + 33;
+
+/// The storage failing to append, so the finalized chunks are deferred (and left
+/// in the buffer) by the circuit breaker.
+struct FailingStorage;
+
+impl Storage for FailingStorage {
+    fn append(&self, _: &Path, _: &[u8]) -> io::Result<()> {
+        Err(io::ErrorKind::PermissionDenied.into())
+    }
+
+    fn read(&self, _: &Path) -> io::Result<Vec<u8>> {
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    fn list(&self, _: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+
+    fn delete(&self, _: &Path) -> io::Result<()> {
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    fn len(&self, _: &Path) -> io::Result<u64> {
+        Err(io::ErrorKind::NotFound.into())
+    }
+}
+
+/// Collects the categories of the events.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<TrackCategory>>>);
+
+impl Track for Collector {
+    fn track_event(&self, event: TrackEvent) {
+        self.0.lock().unwrap().push(event.category);
+    }
+}
+
+#[test]
+fn test_buffer_checksum() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let config = Config::new()
+        .storage(Some(Arc::new(FailingStorage)))
+        .circuit_breaker(Some(CircuitBreaker::new(1, Duration::from_secs(3600), 1 << 20)));
+    let logger = domain.clone().logger(config);
+    for index in 0..3 {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+    }
+    // The chunk is finalized and sealed, but it fails to be written and is kept in
+    // the buffer. Then simulates a crash.
+    logger.flush();
+    mem::forget(logger);
+
+    // Damages the session id of the sealed chunk, which is covered by the checksum
+    // only.
+    let buffer_path = dir.join("test").with_extension(MMAP_BUFFER_EXTENSION);
+    let mut bytes = fs::read(&buffer_path)?;
+    let side_len = (bytes.len() - BUFFER_HEADER_LEN) / 2;
+    let alpha_side = &bytes[ALPHA_SIDE_OFFSET..ALPHA_SIDE_OFFSET + 4];
+    let output_offset = BUFFER_HEADER_LEN + if alpha_side == OUTPUT_SIDE { 0 } else { side_len };
+    bytes[output_offset + SESSION_ID_OFFSET] ^= 0xFF;
+    fs::write(&buffer_path, bytes)?;
+
+    // The damage is reported, and the records are salvaged.
+    let collector = Collector::default();
+    let logger = domain.clone().logger(Config::new().tracker(Some(Box::new(collector.clone()))));
+    logger.shutdown();
+    let categories = collector.0.lock().unwrap().clone();
+    assert!(categories.contains(&TrackCategory::BufferReinitialized));
+
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let path = dir.join("result.pine");
+    extract(domain, datetime..=end, &path)?;
+    let mut contents = Vec::new();
+    let result = parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    });
+    assert_eq!(contents, ["test log 0", "test log 1", "test log 2"]);
+
+    // The salvaged chunk keeps its damaged header, which is reported by the parsing.
+    let Err(ParsingError::Chunks(errors)) = result else {
+        panic!("the checksum mismatch is not reported");
+    };
+    assert!(matches!(errors[..], [ParsingError::Chunk(ChunkError::ChecksumMismatch, _)]));
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::{error::Error, panic, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder, BUFFER_LEN,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_buffer_resize() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let records: Vec<_> = ["2013-11-18 13:36:57Z", "2013-11-18 13:37:01Z", "2013-11-18 13:38:02Z"]
+        .into_iter()
+        .map(|datetime| {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime).unwrap()).build();
+            RecordBuilder::new().meta(meta).content("test log").build()
+        })
+        .collect();
+
+    _ = panic::catch_unwind(|| {
+        let logger = domain.clone().logger(Config::new());
+        for record in &records {
+            logger.log(record);
+            // Waits for the rotated chunk to be written to the file.
+            thread::sleep(Duration::from_millis(10));
+        }
+        // Yes, just let it panic.
+        panic!();
+    });
+
+    // The chunk in progress is in the second side of the buffer, which is cut off by
+    // the shrinking.
+    let logger = domain.clone().logger(Config::new().buffer_len(BUFFER_LEN / 4));
+    thread::sleep(Duration::from_micros(100));
+    logger.shutdown();
+
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:36:00Z")?..=DateTime::from_str("2013-11-18 13:39:00Z")?;
+    let extracted_path = dir.join("result.pine");
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut index = 0;
+    parse(&extracted_path, None, |record| {
+        assert_eq!(record.meta().datetime(), records[index].meta().datetime());
+        index += 1;
+        Ok(())
+    })?;
+    assert_eq!(index, records.len());
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use std::error::Error;
+
+use chrono::{Duration, TimeZone, Utc};
+use pinenut_log::{
+    encrypt::gen_echd_key_pair, parse, BulkConfig, BulkWriter, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_bulk_writer() -> Result<(), Box<dyn Error>> {
+    const COUNT: usize = 1000;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("bulk.pine");
+    let (secret_key, public_key) = gen_echd_key_pair();
+
+    // Small chunks, so that the records are spread over many chunks and workers.
+    let config =
+        BulkConfig::new().chunk_len(1024).workers(3).key(Some(public_key)).session_id(Some(42));
+    let mut writer = BulkWriter::new(&path, config)?;
+
+    let start = Utc.with_ymd_and_hms(2013, 11, 18, 13, 35, 12).unwrap();
+    for index in 0..COUNT {
+        let datetime = start + Duration::seconds(index as i64);
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content = format!("record {index}");
+        writer.write(&RecordBuilder::new().meta(meta).content(&content).build())?;
+    }
+    writer.finish()?;
+
+    let mut index = 0;
+    parse(&path, Some(secret_key), |record| {
+        assert_eq!(record.meta().datetime(), start + Duration::seconds(index as i64));
+        assert_eq!(record.meta().session_id(), Some(42));
+        assert_eq!(record.content(), format!("record {index}"));
+        index += 1;
+        Ok(())
+    })?;
+    assert_eq!(index, COUNT);
+
+    Ok(())
+}
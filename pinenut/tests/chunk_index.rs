@@ -0,0 +1,60 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract_with_options, parse, query_domain, Config, DateTime, Domain, ExtractOptions, Level,
+    MetaBuilder, Query, RecordBuilder, RotateReason,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_chunk_index() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false).chunk_index(true));
+
+    // The first chunk has only infos, and the second one has errors tagged `net`.
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for round in 0..2 {
+        for index in 0..10 {
+            let (level, tag) = if round == 1 && index % 3 == 0 {
+                (Level::Error, "net")
+            } else {
+                (Level::Info, "ui")
+            };
+            let meta = MetaBuilder::new().datetime(datetime).level(level).tag(tag).build();
+            let content = format!("test log {round} {index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.rotate(RotateReason::UserInitiated);
+        // Waits for the rotated chunk to be written to the file.
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    let contents = |query: Query| -> Result<Vec<String>, Box<dyn Error>> {
+        let records = query_domain(domain.clone(), None, query)?;
+        Ok(records.iter().map(|record| record.content().to_string()).collect())
+    };
+    let errors = ["test log 1 0", "test log 1 3", "test log 1 6", "test log 1 9"];
+    assert_eq!(contents(Query::new().level(Some(Level::Error)))?, errors);
+    assert_eq!(contents(Query::new().tag(Some("net")))?, errors);
+    assert!(contents(Query::new().tag(Some("db")))?.is_empty());
+    assert_eq!(contents(Query::new().level(Some(Level::Info)))?.len(), 20);
+
+    // Only the chunk which may have errors is extracted.
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let options = ExtractOptions::new().query(Some(Query::new().level(Some(Level::Error))));
+    extract_with_options(domain, datetime..=end, &path, options)?;
+
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> = (0..10).map(|index| format!("test log 1 {index}")).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
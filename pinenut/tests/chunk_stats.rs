@@ -0,0 +1,55 @@
+use std::{cell::RefCell, error::Error, rc::Rc, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, inspect, parse_with_options, ChunkCallback, Config, DateTime, Domain, MetaBuilder,
+    ParseOptions, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_chunk_stats() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetimes =
+        [DateTime::from_str("2013-11-18 13:35:12Z")?, DateTime::from_str("2013-11-18 13:37:00Z")?];
+    let content = "test log ".repeat(100);
+    for datetime in datetimes {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:40:00Z")?;
+    extract(domain, datetimes[0]..=end, &path)?;
+
+    let chunks = Rc::new(RefCell::new(Vec::new()));
+    let on_chunk: ChunkCallback = Box::new({
+        let chunks = chunks.clone();
+        move |stats| chunks.borrow_mut().push(stats.clone())
+    });
+    let options = ParseOptions::new().on_chunk(Some(on_chunk));
+    parse_with_options(&path, None, options, |_| Ok(()))?;
+
+    let chunks = chunks.borrow();
+    assert_eq!(chunks.len(), 2);
+    for (stats, datetime) in chunks.iter().zip(datetimes) {
+        assert!(stats.time_range.contains(&datetime));
+        // The repetitive content is compressed.
+        assert!(stats.decoded_len > stats.payload_len, "{stats:?}");
+        assert_eq!(stats.unrecoverable_len, 0);
+    }
+
+    // The headers report the same chunks without parsing them.
+    let infos = inspect(&path)?;
+    assert_eq!(infos.len(), chunks.len());
+    for (info, stats) in infos.iter().zip(chunks.iter()) {
+        assert_eq!((&info.time_range, info.payload_len), (&stats.time_range, stats.payload_len));
+        assert!(!info.encrypted);
+    }
+
+    Ok(())
+}
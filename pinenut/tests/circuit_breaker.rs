@@ -0,0 +1,164 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use pinenut_log::{
+    parse, CircuitBreaker, Config, DateTime, Domain, FileNaming, Logger, MetaBuilder,
+    RecordBuilder, Storage, Track, TrackCategory, TrackEvent,
+};
+use tempfile::tempdir;
+
+/// Stores the log files in memory, the appending fails while it is failing.
+#[derive(Default)]
+struct FlakyStorage {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    failing: AtomicBool,
+}
+
+impl Storage for FlakyStorage {
+    fn append(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+        self.files.lock().unwrap().entry(path.to_path_buf()).or_default().extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files.lock().unwrap().get(path).cloned().ok_or(io::ErrorKind::NotFound.into())
+    }
+
+    fn list(&self, directory: &Path) -> io::Result<Vec<PathBuf>> {
+        let files = self.files.lock().unwrap();
+        Ok(files.keys().filter(|p| p.starts_with(directory)).cloned().collect())
+    }
+
+    fn delete(&self, path: &Path) -> io::Result<()> {
+        self.files.lock().unwrap().remove(path).map(drop).ok_or(io::ErrorKind::NotFound.into())
+    }
+
+    fn len(&self, path: &Path) -> io::Result<u64> {
+        self.read(path).map(|bytes| bytes.len() as u64)
+    }
+}
+
+impl FlakyStorage {
+    /// Counts the records written to the storage.
+    fn count_records(&self) -> Result<usize, Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("parsed.pine");
+        let mut count = 0;
+        for bytes in self.files.lock().unwrap().values() {
+            fs::write(&path, bytes)?;
+            parse(&path, None, |_| {
+                count += 1;
+                Ok(())
+            })?;
+        }
+        Ok(count)
+    }
+}
+
+/// Collects the categories of the events.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<TrackCategory>>>);
+
+impl Track for Collector {
+    fn track_event(&self, event: TrackEvent) {
+        self.0.lock().unwrap().push(event.category);
+    }
+}
+
+impl Collector {
+    fn count(&self, category: TrackCategory) -> usize {
+        self.0.lock().unwrap().iter().filter(|&&c| c == category).count()
+    }
+}
+
+fn log_chunks(logger: &Logger, count: usize) -> Result<(), Box<dyn Error>> {
+    for _ in 0..count {
+        let meta = MetaBuilder::new().datetime(DateTime::from_str("2013-11-18 13:35:12Z")?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+        logger.flush();
+    }
+    // Waits for the IO operations above to complete.
+    logger.trim_with_report(u64::MAX)?;
+    Ok(())
+}
+
+#[test]
+fn test_circuit_breaker() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir).file_naming(FileNaming::Hourly);
+    let storage = Arc::new(FlakyStorage::default());
+    let collector = Collector::default();
+    let config = |breaker| {
+        Config::new()
+            .use_mmap(false)
+            .storage(Some(storage.clone()))
+            .circuit_breaker(Some(breaker))
+            .tracker(Some(Box::new(collector.clone())))
+    };
+
+    // The writes are paused after the failure, and only the transition is reported
+    // while the retries keep failing.
+    storage.failing.store(true, Ordering::SeqCst);
+    let logger = domain.clone().logger(config(CircuitBreaker::new(1, Duration::ZERO, 1 << 20)));
+    log_chunks(&logger, 4)?;
+    assert_eq!(collector.count(TrackCategory::Io), 0);
+    assert_eq!(collector.count(TrackCategory::IoPaused), 1);
+    assert_eq!(storage.count_records()?, 0);
+
+    // The chunks deferred during the pause are written once the retry succeeds.
+    storage.failing.store(false, Ordering::SeqCst);
+    log_chunks(&logger, 1)?;
+    assert_eq!(collector.count(TrackCategory::IoResumed), 1);
+    assert_eq!(storage.count_records()?, 5);
+    logger.shutdown();
+
+    // The writes are not retried before the interval elapses, but they are retried
+    // before shutting down.
+    storage.files.lock().unwrap().clear();
+    storage.failing.store(true, Ordering::SeqCst);
+    let breaker = CircuitBreaker::new(1, Duration::from_secs(3600), 1 << 20);
+    let logger = domain.clone().logger(config(breaker));
+    log_chunks(&logger, 1)?;
+    assert_eq!(collector.count(TrackCategory::IoPaused), 2);
+
+    storage.failing.store(false, Ordering::SeqCst);
+    log_chunks(&logger, 1)?;
+    assert_eq!(storage.count_records()?, 0);
+    logger.shutdown();
+    assert_eq!(collector.count(TrackCategory::IoResumed), 2);
+    assert_eq!(storage.count_records()?, 2);
+
+    // The writes are retried once due, even if no more chunks are written.
+    storage.files.lock().unwrap().clear();
+    storage.failing.store(true, Ordering::SeqCst);
+    let logger = domain.logger(config(CircuitBreaker::new(1, Duration::ZERO, 1 << 20)));
+    log_chunks(&logger, 1)?;
+    assert_eq!(collector.count(TrackCategory::IoPaused), 3);
+
+    storage.failing.store(false, Ordering::SeqCst);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while collector.count(TrackCategory::IoResumed) < 3 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(collector.count(TrackCategory::IoResumed), 3);
+    assert_eq!(storage.count_records()?, 1);
+    logger.shutdown();
+    assert_eq!(storage.count_records()?, 1);
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::{
+    error::Error,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use pinenut_log::{
+    extract, inspect, parse, Config, DateTime, Domain, FileNaming, MetaBuilder, RecordBuilder,
+    Track, TrackCategory, TrackEvent,
+};
+use tempfile::tempdir;
+
+/// Collects the categories of the events.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<TrackCategory>>>);
+
+impl Track for Collector {
+    fn track_event(&self, event: TrackEvent) {
+        self.0.lock().unwrap().push(event.category);
+    }
+}
+
+fn parse_contents(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut contents = Vec::new();
+    parse(path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    Ok(contents)
+}
+
+#[test]
+fn test_clock_regression() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let collector = Collector::default();
+    let config = Config::new().use_mmap(false).tracker(Some(Box::new(collector.clone())));
+    let logger = domain.clone().logger(config);
+
+    // The clock jumps backwards within the minute, then to an earlier minute of the
+    // same hour, and the chunks after the jumps are written to the log files of their
+    // own. The records logged concurrently may be slightly out of order, which is
+    // tolerated.
+    let datetimes = ["13:35:12", "13:35:50", "13:35:49", "13:35:30", "13:20:05"];
+    for (index, datetime) in datetimes.iter().enumerate() {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {datetime}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(&format!("test log {index}")).build());
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    let regressions = collector.0.lock().unwrap().clone();
+    assert_eq!(regressions, [TrackCategory::ClockRegressed, TrackCategory::ClockRegressed]);
+
+    // The chunks written after the later ones are still extracted.
+    let path = dir.join("earlier.pine");
+    let datetime = DateTime::from_str("2013-11-18 13:20:05Z")?;
+    extract(domain.clone(), datetime..=datetime, &path)?;
+    assert_eq!(parse_contents(&path)?, ["test log 4"]);
+
+    let path = dir.join("later.pine");
+    let start = DateTime::from_str("2013-11-18 13:35:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:35:59Z")?;
+    extract(domain, start..=end, &path)?;
+    assert_eq!(parse_contents(&path)?, ["test log 0", "test log 1", "test log 2", "test log 3"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_clock_regression_sequence() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // Each record is flushed in a chunk of its own.
+    let datetimes = ["13:35:12", "13:35:50", "13:35:30", "13:20:05"];
+    for (index, datetime) in datetimes.iter().enumerate() {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {datetime}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(&format!("test log {index}")).build());
+        logger.flush();
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    // The chunks are spread over several log files, their sequence numbers follow the
+    // write order rather than the time.
+    let hour =
+        DateTime::from_str("2013-11-18 13:00:00Z")?..=DateTime::from_str("2013-11-18 13:59:59Z")?;
+    let mut chunks = Vec::new();
+    for logfile in domain.logfiles()? {
+        let logged =
+            inspect(logfile)?.into_iter().filter(|chunk| hour.contains(chunk.time_range.start()));
+        chunks.extend(logged);
+    }
+    assert!(chunks.iter().all(|chunk| chunk.sequence.is_some()));
+    chunks.sort_by_key(|chunk| chunk.sequence);
+    assert!(chunks.windows(2).all(|pair| pair[0].sequence < pair[1].sequence));
+    let starts: Vec<_> =
+        chunks.iter().map(|chunk| chunk.time_range.start().format("%T").to_string()).collect();
+    assert_eq!(starts, datetimes);
+
+    // The extraction keeps the write order.
+    let path = dir.join("extracted.pine");
+    extract(domain, hour, &path)?;
+    assert_eq!(parse_contents(&path)?, ["test log 0", "test log 1", "test log 2", "test log 3"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_sequence_of_shared_session() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+
+    // Two logger instances (e.g., two launches) share the session id, and both of
+    // them number their chunks from zero.
+    let datetimes = [["13:35:10", "13:35:20"], ["13:35:30", "13:35:40"]];
+    for (launch, datetimes) in datetimes.iter().enumerate() {
+        let logger = domain.clone().logger(Config::new().use_mmap(false).session_id(Some(42)));
+        for (index, datetime) in datetimes.iter().enumerate() {
+            let datetime = DateTime::from_str(&format!("2013-11-18 {datetime}Z"))?;
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            let content = format!("test log {launch}-{index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            logger.flush();
+            thread::sleep(Duration::from_millis(10));
+        }
+        logger.shutdown();
+    }
+
+    // The chunks of the instances are not interleaved.
+    let path = dir.join("extracted.pine");
+    let start = DateTime::from_str("2013-11-18 13:35:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:35:59Z")?;
+    extract(domain, start..=end, &path)?;
+    let expected = ["test log 0-0", "test log 0-1", "test log 1-0", "test log 1-1"];
+    assert_eq!(parse_contents(&path)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_clock_regression_across_restart() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone()).file_naming(FileNaming::Hourly);
+    let log_at = |datetime: &str| -> Result<(), Box<dyn Error>> {
+        let logger = domain.clone().logger(Config::new().use_mmap(false));
+        let datetime = DateTime::from_str(&format!("2013-11-18 {datetime}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(&datetime.to_string()).build());
+        logger.shutdown();
+        Ok(())
+    };
+
+    // The clock jumps backwards while the logger is not running, the chunk would be
+    // out of order in the log file of the hour, it is written to a log file named by
+    // its timestamp instead.
+    log_at("13:50:12")?;
+    log_at("13:20:05")?;
+    let logfiles = domain.logfiles()?;
+    let names: Vec<_> =
+        logfiles.iter().filter_map(|p| Some(p.file_name()?.to_str()?.to_string())).collect();
+    let earlier = DateTime::from_str("2013-11-18 13:20:05Z")?;
+    assert!(names.contains(&"test-2013-11-18-13.pine".to_string()));
+    assert!(names.contains(&format!("test-{}.pine", earlier.timestamp())));
+
+    let path = dir.join("earlier.pine");
+    extract(domain, earlier..=DateTime::from_str("2013-11-18 13:30:00Z")?, &path)?;
+    assert_eq!(parse_contents(&path)?, [earlier.to_string()]);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "codec")]
+
+use std::error::Error;
+
+use pinenut_log::{
+    codec::{self, Decode, Encode, Sink, Source},
+    query_domain, Config, Domain, Query, RecordBuilder,
+};
+use tempfile::tempdir;
+
+/// The extension of the records logged by the network module.
+#[derive(PartialEq, Debug)]
+struct Request<'a> {
+    url: &'a str,
+    status: u32,
+    elapsed: Option<u64>,
+}
+
+impl Encode for Request<'_> {
+    fn encode<S>(&self, sink: &mut S) -> Result<(), S::Error>
+    where
+        S: Sink,
+    {
+        self.url.encode(sink)?;
+        self.status.encode(sink)?;
+        self.elapsed.encode(sink)
+    }
+}
+
+impl<'de> Decode<'de> for Request<'de> {
+    fn decode<S>(source: &mut S) -> Result<Self, S::Error>
+    where
+        S: Source<'de>,
+    {
+        Ok(Self {
+            url: Decode::decode(source)?,
+            status: Decode::decode(source)?,
+            elapsed: Decode::decode(source)?,
+        })
+    }
+}
+
+#[test]
+fn test_record_extension() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let request = Request { url: "https://example.com", status: 200, elapsed: Some(42) };
+    let extension = codec::encode(&request)?;
+    logger.log(&RecordBuilder::new().content("request").extension(&extension).build());
+    logger.log(&RecordBuilder::new().content("plain").build());
+    logger.shutdown();
+
+    let records = query_domain(domain, None, Query::new())?;
+    assert_eq!(records.len(), 2);
+    let decoded: Request = codec::decode(records[0].extension().unwrap())?;
+    assert_eq!(decoded, request);
+    assert_eq!(records[1].extension(), None);
+
+    // The bytes must be fully consumed by the value.
+    let mut bytes = extension.clone();
+    bytes.push(0);
+    assert!(codec::decode::<Request>(&bytes).is_err());
+
+    Ok(())
+}
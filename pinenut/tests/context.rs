@@ -0,0 +1,113 @@
+use std::{error::Error, str::FromStr, sync::Arc, thread};
+
+use pinenut_log::{
+    context, extract, parse, Config, DateTime, Domain, Format, LogfmtFormatter, MemoryLogger,
+    MetaBuilder, Record, RecordBuilder, TemplateFormatter,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_context() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let log = |content| {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+    };
+
+    log("no context");
+    let session = context::push("session", "a");
+    {
+        let _request = context::push("request_id", "42");
+        log("request");
+        // The context of the other threads is separate.
+        thread::scope(|scope| _ = scope.spawn(|| log("other thread")).join());
+    }
+    log("session");
+    drop(session);
+    log("no context again");
+    assert!(context::current().is_empty());
+    logger.shutdown();
+
+    let path = dir.join("extracted.pine");
+    extract(domain, datetime..=datetime, &path)?;
+    let mut records = Vec::new();
+    parse(&path, None, |record| {
+        let context: Vec<_> = record.context().iter().map(|(k, v)| format!("{k}={v}")).collect();
+        records.push((record.content().to_string(), context.join(" ")));
+        Ok(())
+    })?;
+    let expected = [
+        ("no context", ""),
+        ("request", "session=a request_id=42"),
+        ("other thread", ""),
+        ("session", "session=a"),
+        ("no context again", ""),
+    ];
+    let expected: Vec<_> = expected.map(|(c, p)| (c.to_string(), p.to_string())).into();
+    assert_eq!(records, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_context_format() -> Result<(), Box<dyn Error>> {
+    let logger = MemoryLogger::new(1);
+    let _request = context::push("request_id", "42");
+    let _user = context::push("user", "tom");
+    let meta = MetaBuilder::new().datetime(DateTime::from_str("2013-11-18 13:35:12Z")?).build();
+    // The context of the thread is stamped before the one of the record.
+    let record =
+        RecordBuilder::new().meta(meta).content("test log").context([("try", "2")]).build();
+    logger.log(&record);
+    context::clear();
+
+    let records = logger.snapshot();
+    let record = records[0].as_record();
+    assert_eq!(record.context(), [("request_id", "42"), ("user", "tom"), ("try", "2")]);
+
+    let mut output = Vec::new();
+    LogfmtFormatter.format(&record, &mut output)?;
+    assert_eq!(
+        String::from_utf8(output)?,
+        "time=2013-11-18T13:35:12.000Z level=info request_id=42 user=tom try=2 msg=\"test log\"\n"
+    );
+
+    let mut output = Vec::new();
+    TemplateFormatter::new("{content} [{context}] {context:user}{context:none}")?
+        .format(&record, &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "test log [request_id=42 user=tom try=2] tom\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_context_in_record_sink() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let memory = Arc::new(MemoryLogger::new(2));
+    let sink = {
+        let memory = memory.clone();
+        move |record: &Record| {
+            // The sink may push the context while the record is being logged.
+            let _guard = context::push("sink", "1");
+            // The record forwarded to another logger is not stamped again.
+            memory.log(record);
+        }
+    };
+    let config = Config::new().use_mmap(false).record_sink(Some(Box::new(sink)));
+    let logger = Domain::new("test".to_string(), dir).logger(config);
+
+    let _request = context::push("request_id", "42");
+    logger.log(&RecordBuilder::new().content("forwarded").build());
+    logger.shutdown();
+    assert_eq!(context::current(), [("request_id".to_string(), "42".to_string())]);
+
+    let records = memory.snapshot();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].as_record().context(), [("request_id", "42")]);
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::{
+    error::Error, fs, mem, ops::RangeInclusive, path::Path, str::FromStr, thread, time::Duration,
+};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, FileNaming, Logger, MetaBuilder, RecordBuilder,
+    MMAP_BUFFER_EXTENSION,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_direct_mmap() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf()).file_naming(FileNaming::Hourly);
+    let logfile = dir.join("test-2013-11-18-14.pine");
+    let logger = || domain.clone().logger(Config::new().direct_mmap(true));
+
+    let logger1 = logger();
+    log(&logger1, &["2013-11-18 13:35:12Z", "2013-11-18 13:36:00Z"])?;
+    logger1.flush();
+
+    // The chunks are written in place, the records can be extracted while the log file
+    // is still mapped.
+    assert_eq!(extract_count(&domain, hour(13)?, &dir.join("open.pine"))?, 2);
+
+    // The log file is rotated by the logger itself.
+    log(&logger1, &["2013-11-18 14:00:00Z", "2013-11-18 14:00:01Z"])?;
+    // Simulates a crash, the chunk being written remains in the log file.
+    mem::forget(logger1);
+    assert_eq!(parse_count(&logfile)?, 2);
+
+    let logger2 = logger();
+    log(&logger2, &["2013-11-18 14:05:00Z"])?;
+    logger2.shutdown();
+
+    // There is no buffer file, and the mapped space that is not written is released.
+    assert!(!dir.join("test").with_extension(MMAP_BUFFER_EXTENSION).exists());
+    assert_eq!(extract_count(&domain, hour(14)?, &dir.join("closed.pine"))?, 3);
+    assert_eq!(fs::metadata(&logfile)?.len(), fs::metadata(dir.join("closed.pine"))?.len());
+
+    Ok(())
+}
+
+fn log(logger: &Logger, datetimes: &[&str]) -> Result<(), Box<dyn Error>> {
+    for datetime in datetimes {
+        let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    Ok(())
+}
+
+fn hour(hour: u32) -> Result<RangeInclusive<DateTime>, Box<dyn Error>> {
+    let start = DateTime::from_str(&format!("2013-11-18 {hour:02}:00:00Z"))?;
+    Ok(start..=start + chrono::Duration::minutes(59))
+}
+
+/// Extracts the records in the time range, and returns the count of them.
+fn extract_count(
+    domain: &Domain,
+    time_range: RangeInclusive<DateTime>,
+    path: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    _ = fs::remove_file(path);
+    extract(domain.clone(), time_range, path)?;
+    parse_count(path)
+}
+
+fn parse_count(path: &Path) -> Result<usize, Box<dyn Error>> {
+    let mut count = 0;
+    parse(path, None, |_| {
+        count += 1;
+        Ok(())
+    })?;
+    Ok(count)
+}
@@ -0,0 +1,42 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_elapsed_time() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false).elapsed_time(true));
+
+    // The wall clock jumps backwards, and the third record has its own elapsed time.
+    let datetimes = ["13:35:12", "13:35:10", "13:35:11"];
+    for (index, datetime) in datetimes.iter().enumerate() {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {datetime}Z"))?;
+        let elapsed = (index == 2).then_some(7);
+        let meta = MetaBuilder::new().datetime(datetime).elapsed_opt(elapsed).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(&index.to_string()).build());
+        thread::sleep(Duration::from_millis(20));
+    }
+    logger.shutdown();
+
+    let path = dir.join("extracted.pine");
+    let start = DateTime::from_str("2013-11-18 13:35:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:35:59Z")?;
+    extract(domain, start..=end, &path)?;
+    // The records after the jump are written to another log file, which is earlier.
+    let mut elapsed = vec![None; datetimes.len()];
+    parse(&path, None, |record| {
+        elapsed[record.content().parse::<usize>().unwrap()] = Some(record.meta().elapsed());
+        Ok(())
+    })?;
+    let elapsed: Vec<_> = elapsed.into_iter().flatten().collect();
+
+    let [Some(first), Some(second), third] = elapsed[..] else {
+        panic!("unexpected elapsed times: {elapsed:?}");
+    };
+    assert!(second >= first + 20);
+    assert_eq!(third, Some(7));
+
+    Ok(())
+}
@@ -6,6 +6,8 @@ use pinenut_log::{
 };
 use tempfile::tempdir;
 
+const SESSION_ID: u64 = 0xFEED;
+
 /// Entrie Process: `Log` -> `Extract` -> `Parse`.
 #[test]
 fn test_entire_process() -> Result<(), Box<dyn Error>> {
@@ -17,7 +19,7 @@ fn test_entire_process() -> Result<(), Box<dyn Error>> {
     let logger = domain.clone().logger(config);
 
     let log = |datetime: DateTime| {
-        let meta = MetaBuilder::new().datetime(datetime).build();
+        let meta = MetaBuilder::new().datetime(datetime).session_id(SESSION_ID).build();
         let record = RecordBuilder::new().meta(meta).content("test log").build();
         logger.log(&record);
         thread::sleep(Duration::from_micros(100));
@@ -37,8 +39,9 @@ fn test_entire_process() -> Result<(), Box<dyn Error>> {
 
     logger.shutdown();
 
-    // 1 buffer file + 2013-11-18.13 + 2013-11-18.14 + 2013-11-18.15 + current hour.
-    assert_eq!(fs::read_dir(&dir)?.count(), 5);
+    // 1 buffer file + 1 buffer key file + 2013-11-18.13 + 2013-11-18.14 +
+    // 2013-11-18.15 + current hour.
+    assert_eq!(fs::read_dir(&dir)?.count(), 6);
 
     // Extracts records[2..6].
     let datetime_range =
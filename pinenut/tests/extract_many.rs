@@ -0,0 +1,49 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract_many, parse, Config, DateTime, Domain, ExtractOptions, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_extract_many() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let app = Domain::new("app".to_string(), dir.to_path_buf());
+    let extension = Domain::new("extension".to_string(), dir.to_path_buf());
+
+    for (domain, datetimes) in [
+        (&app, ["2013-11-18 13:35:12Z", "2013-11-18 13:37:00Z", "2013-11-18 14:01:00Z"]),
+        (&extension, ["2013-11-18 13:36:00Z", "2013-11-18 13:38:00Z", "2013-11-18 15:00:00Z"]),
+    ] {
+        let logger = domain.clone().logger(Config::new().use_mmap(false));
+        for datetime in datetimes {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+            let content = domain.identifier.clone();
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.shutdown();
+    }
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:36:00Z")?..=DateTime::from_str("2013-11-18 14:30:00Z")?;
+    extract_many(&[app, extension], datetime_range, &extracted_path, ExtractOptions::new())?;
+
+    let mut records = Vec::new();
+    parse(&extracted_path, None, |record| {
+        records.push((record.meta().datetime(), record.content().to_string()));
+        Ok(())
+    })?;
+    assert_eq!(
+        records,
+        [
+            (DateTime::from_str("2013-11-18 13:36:00Z")?, "extension".to_string()),
+            (DateTime::from_str("2013-11-18 13:37:00Z")?, "app".to_string()),
+            (DateTime::from_str("2013-11-18 13:38:00Z")?, "extension".to_string()),
+            (DateTime::from_str("2013-11-18 14:01:00Z")?, "app".to_string()),
+        ]
+    );
+
+    Ok(())
+}
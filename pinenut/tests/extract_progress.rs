@@ -0,0 +1,74 @@
+use std::{
+    error::Error,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use pinenut_log::{
+    extract_with_options, CancelToken, Config, DateTime, Domain, ExtractOptions, ExtractionError,
+    MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_extract_progress() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // The records are written to the chunks of different minutes, and are too long
+    // to be buffered before being written to the destination file.
+    let content: String =
+        (0..8192u32).map(|i| format!("{:08x}", i.wrapping_mul(0x9E37_79B9))).collect();
+    for minute in 30..35 {
+        let datetime = DateTime::from_str(&format!("2013-11-18 13:{minute}:12Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    let start = DateTime::from_str("2013-11-18 13:30:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:34:59Z")?;
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let options = ExtractOptions::new().on_progress(Some(Arc::new({
+        let progress = progress.clone();
+        move |p| progress.lock().unwrap().push(p)
+    })));
+    extract_with_options(domain.clone(), start..=end, dir.join("all.pine"), options)?;
+
+    let progress = progress.lock().unwrap();
+    assert!(progress.len() > 2);
+    assert!(progress.windows(2).all(|p| p[0].scanned_bytes <= p[1].scanned_bytes));
+    let last = progress.last().unwrap();
+    assert!(last.total_bytes > 0);
+    assert_eq!(last.scanned_bytes, last.total_bytes);
+
+    // The extraction is cancelled after the first chunk is extracted, and the
+    // destination file written partially is deleted.
+    let path = dir.join("cancelled.pine");
+    let token = CancelToken::new();
+    let is_created = Arc::new(AtomicBool::new(false));
+    let options =
+        ExtractOptions::new().cancel_token(Some(token.clone())).on_progress(Some(Arc::new({
+            let (path, is_created) = (path.clone(), is_created.clone());
+            move |p| {
+                if p.scanned_bytes > 0 {
+                    is_created.store(path.exists(), Ordering::Relaxed);
+                    token.cancel()
+                }
+            }
+        })));
+    let result = extract_with_options(domain, start..=end, &path, options);
+    assert!(matches!(result, Err(ExtractionError::Cancelled)));
+    assert!(is_created.load(Ordering::Relaxed));
+    assert!(!path.exists());
+
+    Ok(())
+}
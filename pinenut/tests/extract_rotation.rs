@@ -0,0 +1,69 @@
+use std::{error::Error, path::Path, str::FromStr};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder, RotateReason,
+    TimeDimension,
+};
+use tempfile::tempdir;
+
+fn parse_contents(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut contents = Vec::new();
+    parse(path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    Ok(contents)
+}
+
+#[test]
+fn test_extract_rotation() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let config = Config::new().use_mmap(false).rotation(TimeDimension::Hour);
+    let logger = domain.clone().logger(config);
+
+    // The chunks are split within the hour.
+    let times = ["10:05:12", "10:30:12", "10:50:12"];
+    for time in times {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {time}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(time).build());
+        logger.rotate(RotateReason::UserInitiated);
+    }
+    logger.shutdown();
+
+    // Only the chunks overlapping the time range are extracted.
+    let start = DateTime::from_str("2013-11-18 10:20:00Z")?;
+    let end = DateTime::from_str("2013-11-18 10:40:00Z")?;
+    let path = dir.join("extracted.pine");
+    extract(domain, start..=end, &path)?;
+    assert_eq!(parse_contents(&path)?, ["10:30:12"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_extract_rotation_of_logfiles() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let config = Config::new().use_mmap(false).rotation(TimeDimension::Hour);
+    let logger = domain.clone().logger(config);
+
+    // The clock jumps backwards, the chunks after the jump are written to the log
+    // file of `10:05:12`, which is rotated daily and lasts beyond `10:50:12`.
+    for time in ["10:50:12", "10:05:12", "11:55:12"] {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {time}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(time).build());
+    }
+    logger.shutdown();
+
+    // The rotation is read from the log files.
+    let start = DateTime::from_str("2013-11-18 11:50:00Z")?;
+    let end = DateTime::from_str("2013-11-18 11:59:00Z")?;
+    let path = dir.join("extracted.pine");
+    extract(domain, start..=end, &path)?;
+    assert_eq!(parse_contents(&path)?, ["11:55:12"]);
+
+    Ok(())
+}
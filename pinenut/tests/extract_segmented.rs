@@ -0,0 +1,79 @@
+use std::{error::Error, fs, str::FromStr, sync::Arc, thread, time::Duration};
+
+use pinenut_log::{
+    extract_segmented, parse, CancelToken, Config, DateTime, Domain, ExtractOptions,
+    ExtractionError, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_extract_segmented() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.join("logs"));
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // The records of the four minutes are in four chunks.
+    let times = ["13:35:12", "13:36:12", "13:37:12", "13:38:12"];
+    for time in times {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {time}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(time).build());
+        // Waits for the rotated chunk to be written to the file.
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    let start = DateTime::from_str("2013-11-18 13:30:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let range = start..=end;
+
+    // All of the chunks fit in a segment, which is not taken as a log file even in
+    // the directory of the domain.
+    let whole = extract_segmented(
+        domain.clone(),
+        range.clone(),
+        &domain.directory,
+        u64::MAX,
+        ExtractOptions::new(),
+    )?;
+    assert_eq!(whole, [domain.directory.join("test-segment1.pine")]);
+    assert!(!domain.logfiles()?.contains(&whole[0]));
+
+    // Each chunk is in a segment of its own.
+    let chunk_len = fs::metadata(&whole[0])?.len() / times.len() as u64;
+    let split = dir.join("split");
+    let segments =
+        extract_segmented(domain.clone(), range.clone(), &split, 1, ExtractOptions::new())?;
+    assert_eq!(segments.len(), times.len());
+
+    // The segments are deleted once the extraction is cancelled.
+    let cancelled = dir.join("cancelled");
+    let token = CancelToken::new();
+    let options = ExtractOptions::new().cancel_token(Some(token.clone())).on_progress(Some(
+        Arc::new(move |p| {
+            if p.scanned_bytes > 0 {
+                token.cancel()
+            }
+        }),
+    ));
+    let result = extract_segmented(domain.clone(), range.clone(), &cancelled, 1, options);
+    assert!(matches!(result, Err(ExtractionError::Cancelled)));
+    assert_eq!(fs::read_dir(&cancelled)?.count(), 0);
+
+    // Two chunks per segment.
+    let len = chunk_len * 2 + 1;
+    let segments = extract_segmented(domain, range, dir.join("pairs"), len, ExtractOptions::new())?;
+    assert_eq!(segments.len(), 2);
+
+    let mut contents = Vec::new();
+    for segment in segments {
+        assert!(fs::metadata(&segment)?.len() <= chunk_len * 2 + 1);
+        parse(&segment, None, |record| {
+            contents.push(record.content().to_string());
+            Ok(())
+        })?;
+    }
+    assert_eq!(contents, times);
+
+    Ok(())
+}
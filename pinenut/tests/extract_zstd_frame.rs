@@ -0,0 +1,48 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract_report, parse, Config, DateTime, Domain, ExtractOptions, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_extract_zstd_frame() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..10 {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine.zst");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let options = ExtractOptions::new().zstd_frame(Some(3));
+    let report = extract_report(domain.clone(), datetime..=end, &path, options)?;
+    assert_eq!(report.chunks, 1);
+    // The destination file is a `zstd` frame.
+    assert_eq!(fs::read(&path)?[..4], [0x28, 0xB5, 0x2F, 0xFD]);
+
+    // The frame is unwrapped transparently.
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> = (0..10).map(|index| format!("test log {index}")).collect();
+    assert_eq!(contents, expected);
+
+    // The destination file is not created if no logs were found.
+    let path = dir.join("empty.pine.zst");
+    let start = DateTime::from_str("2013-11-18 13:40:00Z")?;
+    let options = ExtractOptions::new().zstd_frame(Some(3));
+    assert_eq!(extract_report(domain, start..=end, &path, options)?.chunks, 0);
+    assert!(!path.exists());
+
+    Ok(())
+}
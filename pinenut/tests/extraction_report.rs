@@ -0,0 +1,44 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract_report, Config, DateTime, Domain, ExtractOptions, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_extraction_report() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // The records of the two minutes are in two chunks.
+    for time in ["13:35:12", "13:35:40", "13:36:05", "13:36:30"] {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {time}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(time).build());
+        // Waits for the rotated chunk to be written to the file.
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let start = DateTime::from_str("2013-11-18 13:30:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let report = extract_report(domain.clone(), start..=end, &path, ExtractOptions::new())?;
+    assert_eq!(report.files, 1);
+    assert_eq!(report.chunks, 2);
+    assert_eq!(report.bytes, fs::metadata(&path)?.len());
+    let covered =
+        DateTime::from_str("2013-11-18 13:35:12Z")?..=DateTime::from_str("2013-11-18 13:36:30Z")?;
+    assert_eq!(report.time_range_covered, Some(covered));
+
+    // No logs in the time range is not an error.
+    let path = dir.join("empty.pine");
+    let start = DateTime::from_str("2013-11-18 13:40:00Z")?;
+    let report = extract_report(domain, start..=end, &path, ExtractOptions::new())?;
+    assert_eq!(report.chunks, 0);
+    assert_eq!(report.time_range_covered, None);
+    assert!(!path.exists());
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, FileLayout, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_dated_file_layout() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf()).file_layout(FileLayout::Dated);
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    for datetime in ["2013-11-18 13:35:12Z", "2013-11-19 08:00:00Z"] {
+        let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.flush();
+    // Waits for the IO operations above to complete.
+    logger.trim_with_report(u64::MAX)?;
+
+    assert_eq!(dir.join("2013/11/18").read_dir()?.count(), 1);
+    assert_eq!(dir.join("2013/11/19").read_dir()?.count(), 1);
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range =
+        DateTime::from_str("2013-11-18 00:00:00Z")?..=DateTime::from_str("2013-11-20 00:00:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut count = 0;
+    parse(&extracted_path, None, |_| {
+        count += 1;
+        Ok(())
+    })?;
+    assert_eq!(count, 2);
+
+    // The dated subdirectories that become empty are removed.
+    let report = logger.trim_with_report(60)?;
+    assert_eq!(report.deleted_files, 2);
+    assert!(!dir.join("2013").exists());
+
+    logger.shutdown();
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, FileNaming, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_hourly_file_naming() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // Logs with the default naming first, then switches to the hourly naming.
+    for (file_naming, datetimes) in [
+        (FileNaming::Timestamp, ["2013-11-18 12:10:00Z", "2013-11-18 12:20:00Z"]),
+        (FileNaming::Hourly, ["2013-11-18 13:35:12Z", "2013-11-18 13:50:00Z"]),
+    ] {
+        let domain = domain.clone().file_naming(file_naming);
+        let logger = domain.logger(Config::new().use_mmap(false));
+        for datetime in datetimes {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+            logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.shutdown();
+    }
+
+    assert!(dir.join("test-2013-11-18-13.pine").exists());
+    assert!(dir.join("test-1384776600.pine").exists());
+
+    // Both naming schemes are recognized.
+    let extracted_path = dir.join("result.pine");
+    let datetime_range =
+        DateTime::from_str("2013-11-18 12:00:00Z")?..=DateTime::from_str("2013-11-18 14:00:00Z")?;
+    extract(domain.file_naming(FileNaming::Hourly), datetime_range, &extracted_path)?;
+
+    let mut count = 0;
+    parse(&extracted_path, None, |_| {
+        count += 1;
+        Ok(())
+    })?;
+    assert_eq!(count, 4);
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, str::FromStr};
+
+use pinenut_log::{
+    CsvFormatter, DateTime, DefaultFormatter, Format, Level, LevelLabels, LocationBuilder,
+    LogfmtFormatter, MetaBuilder, RecordBuilder, TemplateError, TemplateFormatter, Timezone,
+};
+
+#[test]
+fn test_machine_readable_formats() -> Result<(), Box<dyn Error>> {
+    let location = LocationBuilder::new().file("main.rs").line(42).build();
+    let meta = MetaBuilder::new()
+        .level(Level::Warn)
+        .datetime(DateTime::from_str("2013-11-18 13:35:12Z")?)
+        .location(location)
+        .tag("net")
+        .thread_id(7)
+        .build();
+    let records = [
+        RecordBuilder::new().meta(meta.clone()).content("plain").build(),
+        RecordBuilder::new().meta(meta).content("say \"hi\", bye\nnow").build(),
+    ];
+
+    let (mut csv, mut formatter) = (Vec::new(), CsvFormatter::new());
+    for record in &records {
+        formatter.format(record, &mut csv)?;
+    }
+    assert_eq!(
+        String::from_utf8(csv)?,
+        "datetime,level,tag,thread_id,thread_name,session_id,file,func,line,content,repeats\n\
+         2013-11-18T13:35:12.000Z,warn,net,7,,,main.rs,,42,plain,0\n\
+         2013-11-18T13:35:12.000Z,warn,net,7,,,main.rs,,42,\"say \"\"hi\"\", bye\nnow\",0\n"
+    );
+
+    let mut logfmt = Vec::new();
+    for record in &records {
+        LogfmtFormatter.format(record, &mut logfmt)?;
+    }
+    assert_eq!(
+        String::from_utf8(logfmt)?,
+        "time=2013-11-18T13:35:12.000Z level=warn tag=net thread_id=7 file=main.rs line=42 \
+         msg=plain\n\
+         time=2013-11-18T13:35:12.000Z level=warn tag=net thread_id=7 file=main.rs line=42 \
+         msg=\"say \\\"hi\\\", bye\\nnow\"\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_template_formatter() -> Result<(), Box<dyn Error>> {
+    let location = LocationBuilder::new().file("main.rs").line(42).build();
+    let meta = MetaBuilder::new()
+        .level(Level::Warn)
+        .datetime(DateTime::from_str("2013-11-18 13:35:12Z")?)
+        .location(location)
+        .tag("net")
+        .build();
+    let record = RecordBuilder::new().meta(meta).content("test log").build();
+
+    let mut formatter = TemplateFormatter::new(
+        "{timestamp} [{level}|{level_short}] {tag} {file}:{line} {thread_id}- {content} {{x}}",
+    )?;
+    let mut output = Vec::new();
+    formatter.format(&record, &mut output)?;
+    assert_eq!(
+        String::from_utf8(output)?,
+        "1384781712000 [warn|W] net main.rs:42 - test log {x}\n"
+    );
+
+    assert_eq!(
+        TemplateFormatter::new("{level} {unknown}").unwrap_err(),
+        TemplateError::UnknownPlaceholder("unknown".to_string())
+    );
+    assert_eq!(TemplateFormatter::new("{level").unwrap_err(), TemplateError::Unclosed(0));
+    assert_eq!(TemplateFormatter::new("level}").unwrap_err(), TemplateError::Unmatched(5));
+    assert!(TemplateFormatter::new("{datetime:%H:%M}").is_ok());
+    assert!(matches!(
+        TemplateFormatter::new("{datetime:%Q}"),
+        Err(TemplateError::InvalidDatetimeFormat(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_timezone() -> Result<(), Box<dyn Error>> {
+    let meta = MetaBuilder::new().datetime(DateTime::from_str("2013-11-18 13:35:12Z")?).build();
+    let record = RecordBuilder::new().meta(meta).content("test log").build();
+
+    let mut output = Vec::new();
+    DefaultFormatter.timezone(Timezone::Utc).format(&record, &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "[I] 2013-11-18 13:35:12.000|0|:0||test log\n");
+
+    let mut output = Vec::new();
+    let mut formatter = TemplateFormatter::new("{datetime:%F %T %:z}")?.timezone("+08:00".parse()?);
+    formatter.format(&record, &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "2013-11-18 21:35:12 +08:00\n");
+
+    assert_eq!(Timezone::from_str("UTC")?, Timezone::Utc);
+    assert_eq!(Timezone::from_str("local")?, Timezone::Local);
+    assert!(Timezone::from_str("mars").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_level_labels() -> Result<(), Box<dyn Error>> {
+    let labels = LevelLabels::new(["FATAL", "WARN", "INFO", "DEBUG", "TRACE"]);
+    let meta = MetaBuilder::new()
+        .level(Level::Error)
+        .datetime(DateTime::from_str("2013-11-18 13:35:12Z")?)
+        .build();
+    let record = RecordBuilder::new().meta(meta).content("test log").build();
+
+    let mut output = Vec::new();
+    let mut formatter = DefaultFormatter.timezone(Timezone::Utc).level_labels(Some(labels.clone()));
+    formatter.format(&record, &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "[FATAL] 2013-11-18 13:35:12.000|0|:0||test log\n");
+
+    let mut output = Vec::new();
+    let mut formatter = TemplateFormatter::new("{level}/{level_short}")?.level_labels(Some(labels));
+    formatter.format(&record, &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "FATAL/F\n");
+
+    Ok(())
+}
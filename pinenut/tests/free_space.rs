@@ -0,0 +1,57 @@
+use std::{error::Error, fs, path::Path, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    Config, DateTime, Domain, FileNaming, Logger, MetaBuilder, RecordBuilder, FILE_EXTENSION,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_min_free_space() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf()).file_naming(FileNaming::Hourly);
+    let config = || Config::new().use_mmap(false).tracker(None);
+
+    let logger = domain.clone().logger(config());
+    log(&logger, &["2013-11-18 13:35:12Z", "2013-11-18 14:00:00Z", "2013-11-18 15:20:12Z"])?;
+    logger.shutdown();
+    assert_eq!(logfiles(&dir)?, 3);
+
+    // No new log files are created while the free space is below the minimum.
+    let logger = domain.clone().logger(config().min_free_space(Some(u64::MAX)));
+    log(&logger, &["2013-11-18 16:00:00Z"])?;
+    let error = logger.health().last_io_error.unwrap_or_default();
+    assert!(error.contains("insufficient disk space"), "{error}");
+    logger.shutdown();
+    assert_eq!(logfiles(&dir)?, 3);
+
+    // The oldest log files are deleted to free up the space, but it is never enough.
+    let config = config().min_free_space(Some(u64::MAX)).trim_on_low_space(true);
+    let logger = domain.logger(config);
+    log(&logger, &["2013-11-18 17:00:00Z"])?;
+    logger.shutdown();
+    assert_eq!(logfiles(&dir)?, 0);
+
+    Ok(())
+}
+
+fn log(logger: &Logger, datetimes: &[&str]) -> Result<(), Box<dyn Error>> {
+    for datetime in datetimes {
+        let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.flush();
+    // Waits for the IO operations above to complete.
+    logger.trim_with_report(u64::MAX)?;
+    Ok(())
+}
+
+/// The count of the log files of 2013 in the directory, the ones of the current hour
+/// (e.g., written on shutdown) are excluded.
+fn logfiles(dir: &Path) -> Result<usize, Box<dyn Error>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension() == Some(FILE_EXTENSION.as_ref()))
+        .filter(|e| e.file_name().to_string_lossy().starts_with("test-2013"))
+        .count())
+}
@@ -0,0 +1,57 @@
+use std::{error::Error, fs};
+
+use pinenut_log::{Config, Domain, RecordBuilder, StorageKind};
+use tempfile::tempdir;
+
+#[test]
+fn test_health() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let domain = |name: &str| Domain::new("test".to_string(), dir.path().join(name));
+
+    let logger = domain("mmap").logger(Config::new());
+    let health = logger.health();
+    assert_eq!(health.storage, StorageKind::Mmap);
+    assert!(health.io_alive);
+    assert!(health.last_io_error.is_none());
+    assert!(health.available_space.is_some_and(|space| space > 0));
+    assert_eq!(health.buffer_utilization, 0.0);
+
+    logger.log(&RecordBuilder::new().content("test log").build());
+    assert!(logger.health().buffer_utilization > 0.0);
+    logger.shutdown();
+
+    let logger = domain("memory").logger(Config::new().use_mmap(false));
+    assert_eq!(logger.health().storage, StorageKind::Memory);
+    logger.shutdown();
+
+    let logger = domain("direct").logger(Config::new().direct_mmap(true));
+    assert_eq!(logger.health().storage, StorageKind::Direct);
+    logger.shutdown();
+
+    Ok(())
+}
+
+#[test]
+fn test_health_degraded() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    // The domain directory is occupied by a file, neither the buffer file nor the log
+    // files can be created.
+    let path = dir.path().join("test");
+    fs::write(&path, [])?;
+    let domain = Domain::new("test".to_string(), path);
+
+    let logger = domain.logger(Config::new().tracker(None));
+    assert_eq!(logger.health().storage, StorageKind::Memory);
+
+    logger.log(&RecordBuilder::new().content("test log").build());
+    logger.flush();
+    // Waits for the IO operations above to complete.
+    _ = logger.trim_with_report(u64::MAX)?;
+
+    let health = logger.health();
+    assert!(health.io_alive);
+    assert!(health.last_io_error.is_some());
+    logger.shutdown();
+
+    Ok(())
+}
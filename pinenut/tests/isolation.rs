@@ -0,0 +1,79 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    encrypt::gen_echd_key_pair, extract, parse, CompressionHint, Config, DateTime, Domain,
+    MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_isolated_record() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let (secret_key, public_key) = gen_echd_key_pair();
+
+    // The big payload is larger than the buffer.
+    let config = Config::new().use_mmap(false).buffer_len(4096).key(Some(public_key));
+    let logger = domain.clone().logger(config);
+
+    let payload = format!("[{}]", vec!["{\"key\": \"value\"}"; 1024].join(", "));
+    let contents = ["before", &payload, "after"];
+    let hints = [CompressionHint::Shared, CompressionHint::Isolated, CompressionHint::Shared];
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for (content, hint) in contents.iter().zip(hints) {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log_with_hint(&RecordBuilder::new().meta(meta).content(content).build(), hint);
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = datetime..=DateTime::from_str("2013-11-18 13:36:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut parsed = Vec::new();
+    parse(&extracted_path, Some(secret_key), |record| {
+        parsed.push(record.content().to_string());
+        Ok(())
+    })?;
+    assert_eq!(parsed, contents);
+
+    Ok(())
+}
+
+#[test]
+fn test_oversized_record() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // The big payload is larger than the buffer, but logged without the hint.
+    let config = Config::new().use_mmap(false).buffer_len(4096);
+    let logger = domain.clone().logger(config);
+
+    let payload = format!("[{}]", vec!["{\"key\": \"value\"}"; 1024].join(", "));
+    // The filler may not fit in the rest of the chunk, which is rotated first.
+    let filler = "x".repeat(1500);
+    let contents = ["before", &filler, &payload, "after"];
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for content in contents {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = datetime..=DateTime::from_str("2013-11-18 13:36:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut parsed = Vec::new();
+    parse(&extracted_path, None, |record| {
+        parsed.push(record.content().to_string());
+        Ok(())
+    })?;
+    assert_eq!(parsed, contents);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    encrypt::{gen_echd_key_pair, key_fingerprint},
+    extract, parse, parse_with_options, Config, DateTime, Domain, MetaBuilder, ParseOptions,
+    ParsingError, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_key_mismatch() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let (secret_key, public_key) = gen_echd_key_pair();
+    let logger = domain.clone().logger(Config::new().use_mmap(false).key(Some(public_key)));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..10 {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    let mut count = 0;
+    parse(&path, Some(secret_key), |_| {
+        count += 1;
+        Ok(())
+    })?;
+    assert_eq!(count, 10);
+
+    // Parses with the secret key of another key pair.
+    let (other_secret_key, _) = gen_echd_key_pair();
+    let res = parse(&path, Some(other_secret_key), |_| panic!("no records are expected"));
+    let Err(ParsingError::Chunks(errors)) = res else {
+        panic!("chunk errors are expected");
+    };
+    assert!(!errors.is_empty());
+    let expected = key_fingerprint(&public_key);
+    for error in errors {
+        let ParsingError::KeyMismatch { chunk_pub_key_fingerprint, .. } = error else {
+            panic!("unexpected error: {error}");
+        };
+        assert_eq!(chunk_pub_key_fingerprint, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_secret_keys() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let key_pairs = [gen_echd_key_pair(), gen_echd_key_pair()];
+
+    // Rotates the key between the sessions.
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for (session, (_, public_key)) in key_pairs.iter().enumerate() {
+        let config = Config::new().use_mmap(false).key(Some(*public_key));
+        let logger = domain.clone().logger(config);
+        for index in 0..10 {
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            let content = format!("test log {session} {index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.shutdown();
+    }
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    let mut contents = Vec::new();
+    let secret_keys = key_pairs.map(|(secret_key, _)| secret_key);
+    let options = ParseOptions::new().secret_keys(&secret_keys);
+    parse_with_options(&path, None, options, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> =
+        (0..2).flat_map(|s| (0..10).map(move |i| format!("test log {s} {i}"))).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
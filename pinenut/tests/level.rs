@@ -0,0 +1,21 @@
+use pinenut_log::{Level, ParseLevelError};
+
+#[test]
+fn test_level_names() {
+    let levels: Vec<_> = Level::iter().collect();
+    assert_eq!(levels, [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Verbose]);
+    // The levels are iterated from the most severe one.
+    assert!(levels.windows(2).all(|l| l[0] < l[1]));
+
+    for level in Level::iter() {
+        assert_eq!(level.to_string(), level.as_str());
+        assert_eq!(level.as_str().parse(), Ok(level));
+        assert_eq!(level.as_str().to_uppercase().parse(), Ok(level));
+        assert_eq!(level.as_str()[..1].to_uppercase().parse(), Ok(level));
+    }
+    assert_eq!("Warning".parse(), Ok(Level::Warn));
+    assert_eq!(format!("[{:<7}]", Level::Warn), "[warn   ]");
+
+    assert_eq!("".parse::<Level>(), Err(ParseLevelError));
+    assert_eq!("trace".parse::<Level>(), Err(ParseLevelError));
+}
@@ -0,0 +1,53 @@
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use pinenut_log::{here, Config, Domain, LocationBuilder, MetaBuilder, Record, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_here() {
+    let (location, line) = (here!(), line!());
+    assert!(location.file().is_some_and(|f| f.ends_with("tests/location.rs")));
+    assert!(location.file().is_some_and(|f| !f.starts_with('/')));
+    assert_eq!(location.func(), Some("location::test_here"));
+    assert_eq!(location.line(), Some(line));
+
+    let closure = || here!();
+    let location = closure();
+    assert_eq!(location.func(), Some("location::test_here"));
+}
+
+#[test]
+fn test_strip_path_prefix() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let files = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let files = Arc::clone(&files);
+        move |record: &Record| {
+            files.lock().unwrap().push(record.meta().location().file().map(str::to_string))
+        }
+    };
+    let config = Config::new()
+        .use_mmap(false)
+        .strip_path_prefix(Some("/build/machine"))
+        .record_sink(Some(Box::new(sink)));
+    let logger = domain.logger(config);
+
+    let files_logged =
+        ["/build/machine/src/main.rs", "/build/machines/main.rs", "/elsewhere/main.rs"];
+    for file in files_logged {
+        let location = LocationBuilder::new().file(file).build();
+        let meta = MetaBuilder::new().location(location).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+    }
+    logger.shutdown();
+
+    // The prefix is only stripped at a path component boundary.
+    let expected = ["src/main.rs", "/build/machines/main.rs", "/elsewhere/main.rs"];
+    assert_eq!(*files.lock().unwrap(), expected.map(|file| Some(file.to_string())));
+    Ok(())
+}
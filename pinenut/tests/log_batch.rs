@@ -0,0 +1,23 @@
+use std::error::Error;
+
+use pinenut_log::{query_domain, Config, Domain, Query, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_log_batch() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let contents: Vec<_> = (0..100).map(|index| format!("test log {index}")).collect();
+    let records: Vec<_> =
+        contents.iter().map(|content| RecordBuilder::new().content(content).build()).collect();
+    logger.log_batch(&records);
+    logger.shutdown();
+
+    let records = query_domain(domain, None, Query::new())?;
+    let logged: Vec<_> = records.iter().map(|record| record.content().to_string()).collect();
+    assert_eq!(logged, contents);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::{error::Error, thread, time::Duration};
+
+use pinenut_log::{error, extract, info, log, parse, Config, Domain, Level, OwnedRecord};
+use tempfile::tempdir;
+
+#[test]
+fn test_macros() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let start = chrono::Utc::now();
+    info!(logger, "Hello World");
+    thread::sleep(Duration::from_micros(100));
+    error!(logger, tag: "Network", "Request failed: {}", 404);
+    thread::sleep(Duration::from_micros(100));
+    log!(logger, Level::Debug, "{} + {} = {}", 1, 1, 1 + 1);
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let end = chrono::Utc::now() + chrono::Duration::minutes(1);
+    extract(domain, start - chrono::Duration::minutes(1)..=end, &extracted_path)?;
+
+    let mut records = Vec::new();
+    parse(&extracted_path, None, |record| {
+        records.push(OwnedRecord::from(record));
+        Ok(())
+    })?;
+
+    let expected = [
+        (Level::Info, None, "Hello World"),
+        (Level::Error, Some("Network"), "Request failed: 404"),
+        (Level::Debug, None, "1 + 1 = 2"),
+    ];
+    assert_eq!(records.len(), expected.len());
+    for (record, (level, tag, content)) in records.iter().zip(expected) {
+        assert_eq!((record.level(), record.tag(), record.content()), (level, tag, content));
+        assert!(record.datetime() >= start);
+
+        let record = record.as_record();
+        let (meta, location) = (record.meta(), record.meta().location());
+        assert!(meta.thread_id().is_some());
+        assert_eq!(meta.thread_name(), thread::current().name());
+        assert!(location.file().is_some_and(|f| f.ends_with("tests/macros.rs")));
+        assert!(location.func().is_some_and(|f| f.ends_with("test_macros")));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::{error::Error, fs, str::FromStr};
+
+use pinenut_log::{
+    encrypt::{gen_x25519_key_pair, key_fingerprint},
+    Config, DateTime, Domain, KeyAlgorithm, Manifest, ManifestError, TimeDimension, FORMAT_VERSION,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_manifest() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // It is not written by default.
+    domain.clone().logger(Config::new().use_mmap(false)).shutdown();
+    assert!(matches!(domain.manifest(), Err(ManifestError::Io(_))));
+
+    let (_, public_key) = gen_x25519_key_pair();
+    let config = Config::new()
+        .use_mmap(false)
+        .manifest(true)
+        .rotation(TimeDimension::Hour)
+        .x25519_key(Some(public_key))
+        .app_version(Some("1.2.3"));
+    domain.clone().logger(config).shutdown();
+
+    let manifest = domain.manifest()?;
+    assert_eq!(manifest.format_version(), FORMAT_VERSION);
+    assert_eq!(manifest.sdk_version(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest.rotation(), TimeDimension::Hour);
+    assert_eq!(manifest.cipher(), Some("aes-128-ecb"));
+    assert_eq!(manifest.key_algorithm(), Some(KeyAlgorithm::X25519));
+    assert_eq!(manifest.key_fingerprint(), Some(key_fingerprint(&public_key).as_str()));
+    assert_eq!(manifest.app_version(), Some("1.2.3"));
+
+    // It is updated on the initialization of the logger.
+    domain.clone().logger(Config::new().use_mmap(false).manifest(true)).shutdown();
+    let manifest = domain.manifest()?;
+    assert_eq!(manifest.rotation(), TimeDimension::Minute);
+    assert_eq!(manifest.cipher(), None);
+    assert_eq!(manifest.key_fingerprint(), None);
+    assert_eq!(manifest.app_version(), None);
+
+    // It round-trips through the text, and the unknown keys are ignored.
+    let text = fs::read_to_string(dir.join("test.pinemeta"))?;
+    assert_eq!(Manifest::from_str(&text)?, manifest);
+    assert_eq!(Manifest::from_str(&format!("{text}future_key = 1\n"))?, manifest);
+    assert!(Manifest::from_str("format_version = 6").is_err());
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let text = format!(
+        "format_version = 6\nsdk_version = 0.0.1\nrotation = day\nupdated_at = {}",
+        datetime.to_rfc3339()
+    );
+    assert_eq!(Manifest::from_str(&text)?.updated_at(), datetime);
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_max_record_len() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false).max_record_len(Some(6)));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    // `é` takes 2 bytes, the content is truncated before it.
+    for content in ["short", "test héllo"] {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    extract(domain, datetime..=DateTime::from_str("2013-11-18 13:36:00Z")?, &path)?;
+
+    let mut parsed = Vec::new();
+    parse(&path, None, |record| {
+        parsed.push((record.content().to_string(), record.truncated()));
+        Ok(())
+    })?;
+    assert_eq!(parsed, [("short".to_string(), false), ("test h".to_string(), true)]);
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use pinenut_log::{
+    error, info, Level, MemoryLogger, MetaBuilder, OwnedRecord, Query, RecordBuilder,
+};
+
+fn contents(records: Vec<OwnedRecord>) -> Vec<String> {
+    records.iter().map(|record| record.content().to_string()).collect()
+}
+
+#[test]
+fn test_memory_logger() {
+    let logger = MemoryLogger::new(5);
+    assert!(logger.is_empty());
+
+    let levels = [Level::Info, Level::Warn, Level::Error];
+    for index in 0..10 {
+        let tag = if index % 2 == 0 { "net" } else { "ui" };
+        let meta = MetaBuilder::new().level(levels[index % levels.len()]).tag(tag).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+    }
+
+    // Only the last records are kept.
+    assert_eq!(logger.len(), 5);
+    let expected: Vec<_> = (5..10).map(|index| format!("test log {index}")).collect();
+    assert_eq!(contents(logger.snapshot()), expected);
+
+    let query = Query::new().level(Some(Level::Warn));
+    assert_eq!(contents(logger.query(&query)), ["test log 5", "test log 7", "test log 8"]);
+    let query = Query::new().level(Some(Level::Warn)).tag(Some("net"));
+    assert_eq!(contents(logger.query(&query)), ["test log 8"]);
+    let query = Query::new().limit(Some(2));
+    assert_eq!(contents(logger.query(&query)), ["test log 5", "test log 6"]);
+
+    // The logging macros.
+    logger.clear();
+    info!(logger, "Hello {}", "World");
+    error!(logger, tag: "net", "Request failed");
+    let records = logger.snapshot();
+    assert_eq!(records[0].content(), "Hello World");
+    assert_eq!(records[1].level(), Level::Error);
+    assert_eq!(records[1].tag(), Some("net"));
+}
@@ -0,0 +1,84 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_memory_pressure() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+    let capacity = logger.buffer_usage().capacity;
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let log = |range: std::ops::Range<usize>| {
+        for index in range {
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            let content = format!("test log {index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+    };
+
+    log(0..100);
+    logger.set_memory_pressure(true)?;
+    let usage = logger.buffer_usage();
+    assert_eq!(usage.used, 0);
+    assert!(usage.capacity < capacity / 10);
+
+    log(100..200);
+    logger.set_memory_pressure(false)?;
+    assert_eq!(logger.buffer_usage().capacity, capacity);
+
+    log(200..300);
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> = (0..300).map(|index| format!("test log {index}")).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_memory_pressure_while_logging() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    // The records logged while the buffer is being replaced are kept.
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for index in 0..1000 {
+                let meta = MetaBuilder::new().datetime(datetime).build();
+                let content = format!("test log {index}");
+                logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            }
+        });
+        for flag in [true, false, true, false] {
+            logger.set_memory_pressure(flag).unwrap();
+        }
+    });
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    extract(domain, datetime..=datetime, &path)?;
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> = (0..1000).map(|index| format!("test log {index}")).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
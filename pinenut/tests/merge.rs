@@ -0,0 +1,47 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, merge, Config, DateTime, Domain, Format, MetaBuilder, Record, RecordBuilder,
+};
+use tempfile::tempdir;
+
+/// Formats the content of the record only.
+struct ContentFormatter;
+
+impl Format for ContentFormatter {
+    fn format(&mut self, record: &Record, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        writeln!(writer, "{}", record.content())
+    }
+}
+
+#[test]
+fn test_merge() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:00:00Z")?..=DateTime::from_str("2013-11-18 14:00:00Z")?;
+
+    let mut paths = Vec::new();
+    for (identifier, records) in [
+        ("device-a", [("2013-11-18 13:35:12Z", "a1"), ("2013-11-18 13:37:00Z", "a2")]),
+        ("device-b", [("2013-11-18 13:36:00Z", "b1"), ("2013-11-18 13:38:00Z", "b2")]),
+    ] {
+        let domain = Domain::new(identifier.to_string(), dir.to_path_buf());
+        let logger = domain.clone().logger(Config::new().use_mmap(false));
+        for (datetime, content) in records {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+            logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.shutdown();
+
+        let path = dir.join(format!("{identifier}.pine"));
+        extract(domain, datetime_range.clone(), &path)?;
+        paths.push(path);
+    }
+
+    let merged_path = dir.join("merged.log");
+    merge(&paths, None, ContentFormatter, &merged_path)?;
+    assert_eq!(fs::read_to_string(merged_path)?, "a1\nb1\na2\nb2\n");
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, FileNaming, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+fn log_at(domain: &Domain, times: &[&str]) -> Result<(), Box<dyn Error>> {
+    let logger = domain.clone().logger(Config::new().use_mmap(false).minute_index(true));
+    for time in times {
+        let datetime = DateTime::from_str(&format!("2013-11-18 {time}Z"))?;
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(time).build());
+        // Waits for the rotated chunk to be written to the file.
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+    Ok(())
+}
+
+#[test]
+fn test_minute_index() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf()).file_naming(FileNaming::Hourly);
+
+    // The log file of 13:00 is sealed once it is rotated.
+    log_at(&domain, &["13:35:12", "13:40:12", "13:50:12", "14:05:12"])?;
+    // It is appended to and sealed again.
+    log_at(&domain, &["13:55:12", "14:10:12"])?;
+
+    // The first chunk is broken, but it is skipped by the index without being read.
+    let logfile = dir.join("test-2013-11-18-13.pine");
+    let mut bytes = fs::read(&logfile)?;
+    bytes[0] = !bytes[0];
+    fs::write(&logfile, bytes)?;
+
+    let path = dir.join("result.pine");
+    let start = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:59:59Z")?;
+    extract(domain, start..=end, &path)?;
+
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    assert_eq!(contents, ["13:50:12", "13:55:12"]);
+
+    Ok(())
+}
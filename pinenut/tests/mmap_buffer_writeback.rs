@@ -1,11 +1,32 @@
-use std::{error::Error, panic, path::Path, str::FromStr, thread, time::Duration};
+use std::{
+    error::Error,
+    fs, panic,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
 use pinenut_log::{
-    encrypt::gen_echd_key_pair, extract, parse, Config, DateTime, Domain, MetaBuilder, Record,
-    RecordBuilder, SecretKey,
+    encrypt::gen_echd_key_pair, extract, parse, Config, DateTime, Domain, LoggerError, MetaBuilder,
+    Record, RecordBuilder, SecretKey, MMAP_BUFFER_EXTENSION, MMAP_BUFFER_KEY_EXTENSION,
+    QUARANTINED_BUFFER_EXTENSION,
 };
 use tempfile::tempdir;
 
+const SESSION_ID: u64 = 0xFEED;
+
+/// The buffer header consists of the magic, the version, the alpha side (4 bytes
+/// each) and the MAC (16 bytes).
+const HEADER_LEN: usize = 28;
+const ALPHA_SIDE_OFFSET: usize = 8;
+/// The legacy buffer header consists of the magic and the alpha side.
+const LEGACY_HEADER_LEN: usize = 8;
+
 #[test]
 fn test_mmap_buffer_writeback() -> Result<(), Box<dyn Error>> {
     let dir = tempdir()?.path().join("test");
@@ -13,7 +34,7 @@ fn test_mmap_buffer_writeback() -> Result<(), Box<dyn Error>> {
     let (secret_key, public_key) = gen_echd_key_pair();
 
     fn record(datetime: DateTime) -> Record<'static> {
-        let meta = MetaBuilder::new().datetime(datetime).build();
+        let meta = MetaBuilder::new().datetime(datetime).session_id(SESSION_ID).build();
         RecordBuilder::new().meta(meta).content("test log").build()
     }
     let records = [
@@ -85,7 +106,7 @@ fn test_mmap_buffer_no_need_to_writeback() -> Result<(), Box<dyn Error>> {
     let (secret_key, public_key) = gen_echd_key_pair();
 
     fn record(datetime: DateTime) -> Record<'static> {
-        let meta = MetaBuilder::new().datetime(datetime).build();
+        let meta = MetaBuilder::new().datetime(datetime).session_id(SESSION_ID).build();
         RecordBuilder::new().meta(meta).content("test log").build()
     }
     let records = [
@@ -150,3 +171,155 @@ fn test_mmap_buffer_no_need_to_writeback() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_mmap_buffer_quarantine() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let logger = domain.clone().logger(Config::new());
+    logger.log(&RecordBuilder::new().content("test log").build());
+    logger.shutdown();
+
+    // Swaps the buffers by corrupting the alpha side in the buffer header.
+    let buffer_path = dir.join("test").with_extension(MMAP_BUFFER_EXTENSION);
+    let mut bytes = fs::read(&buffer_path)?;
+    bytes[ALPHA_SIDE_OFFSET] ^= 0xFF;
+    fs::write(&buffer_path, bytes)?;
+
+    let corrupted = Arc::new(AtomicBool::new(false));
+    let tracker = {
+        let corrupted = Arc::clone(&corrupted);
+        move |err: LoggerError, _: &str, _: u32| {
+            if matches!(err, LoggerError::BufferCorrupted(_)) {
+                corrupted.store(true, Ordering::Relaxed);
+            }
+        }
+    };
+    let logger = domain.logger(Config::new().tracker(Some(Box::new(tracker))));
+    logger.shutdown();
+
+    assert!(corrupted.load(Ordering::Relaxed));
+    assert!(dir.join("test").with_extension(QUARANTINED_BUFFER_EXTENSION).exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_mmap_buffer_recovery() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let records: Vec<_> = ["2013-11-18 13:36:57Z", "2013-11-18 13:36:58Z", "2013-11-18 13:36:59Z"]
+        .into_iter()
+        .map(|datetime| {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime).unwrap()).build();
+            RecordBuilder::new().meta(meta).content("test log").build()
+        })
+        .collect();
+
+    _ = panic::catch_unwind(|| {
+        let logger = domain.clone().logger(Config::new());
+        for record in &records {
+            logger.log(record);
+            thread::sleep(Duration::from_micros(100));
+        }
+        // Yes, just let it panic.
+        panic!();
+    });
+
+    // Damages the magic of the empty chunk in the buffer, so that both chunks are
+    // re-initialized.
+    const MAGIC: [u8; 4] = 0xFEEDCA7Cu32.to_le_bytes();
+    let buffer_path = dir.join("test").with_extension(MMAP_BUFFER_EXTENSION);
+    let mut bytes = fs::read(&buffer_path)?;
+    let offsets: Vec<_> = bytes
+        .windows(MAGIC.len())
+        .enumerate()
+        .filter(|(_, w)| *w == MAGIC)
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(offsets.len(), 2);
+    let empty = offsets.iter().find(|&&i| bytes[i + 6..i + 10] == [0; 4]).unwrap();
+    bytes[*empty] ^= 0xFF;
+    fs::write(&buffer_path, bytes)?;
+
+    // The chunk in progress is recovered and written back.
+    let logger = domain.clone().logger(Config::new());
+    thread::sleep(Duration::from_micros(100));
+    logger.shutdown();
+
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:36:00Z")?..=DateTime::from_str("2013-11-18 13:37:00Z")?;
+    let extracted_path = dir.join("result.pine");
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut index = 0;
+    parse(&extracted_path, None, |record| {
+        assert_eq!(record.meta().datetime(), records[index].meta().datetime());
+        assert_eq!(record.content(), records[index].content());
+        index += 1;
+        Ok(())
+    })?;
+    assert_eq!(index, records.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_mmap_buffer_legacy_header() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let records: Vec<_> = ["2013-11-18 13:36:57Z", "2013-11-18 13:36:58Z"]
+        .into_iter()
+        .map(|datetime| {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime).unwrap()).build();
+            RecordBuilder::new().meta(meta).content("test log").build()
+        })
+        .collect();
+
+    _ = panic::catch_unwind(|| {
+        let logger = domain.clone().logger(Config::new());
+        records.iter().for_each(|record| logger.log(record));
+        panic!();
+    });
+
+    // Moves the sides of the buffer after the legacy header, as the buffer files
+    // written by older versions, which have no key file either.
+    let buffer_path = dir.join("test").with_extension(MMAP_BUFFER_EXTENSION);
+    let bytes = fs::read(&buffer_path)?;
+    let (side_len, legacy_side_len) =
+        ((bytes.len() - HEADER_LEN) / 2, (bytes.len() - LEGACY_HEADER_LEN) / 2);
+    let mut legacy = vec![0; bytes.len()];
+    legacy[..4].copy_from_slice(&bytes[..4]);
+    legacy[4..8].copy_from_slice(&bytes[ALPHA_SIDE_OFFSET..ALPHA_SIDE_OFFSET + 4]);
+    for side in 0..2 {
+        let from = HEADER_LEN + side * side_len;
+        let to = LEGACY_HEADER_LEN + side * legacy_side_len;
+        legacy[to..to + side_len].copy_from_slice(&bytes[from..from + side_len]);
+    }
+    fs::write(&buffer_path, legacy)?;
+    fs::remove_file(buffer_path.with_extension(MMAP_BUFFER_KEY_EXTENSION))?;
+
+    // The chunk in progress is recovered with the legacy layout, and the buffer is
+    // not quarantined.
+    domain.clone().logger(Config::new()).shutdown();
+    assert!(!dir.join("test").with_extension(QUARANTINED_BUFFER_EXTENSION).exists());
+
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:36:00Z")?..=DateTime::from_str("2013-11-18 13:37:00Z")?;
+    let extracted_path = dir.join("result.pine");
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut contents = Vec::new();
+    parse(&extracted_path, None, |record| {
+        contents.push((record.meta().datetime(), record.content().to_string()));
+        Ok(())
+    })?;
+    let expected: Vec<_> =
+        records.iter().map(|r| (r.meta().datetime(), r.content().to_string())).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::{error::Error, path::Path, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    encrypt::gen_echd_key_pair, extract, parse, Config, DateTime, Domain, MetaBuilder, MultiLogger,
+    RecordBuilder, SecretKey,
+};
+use tempfile::tempdir;
+
+/// The session ids and the contents of the records.
+type Records = Vec<(Option<u64>, String)>;
+
+fn parse_records(path: &Path, secret_key: Option<SecretKey>) -> Result<Records, Box<dyn Error>> {
+    let mut records = Vec::new();
+    parse(path, secret_key, |record| {
+        records.push((record.meta().session_id(), record.content().to_string()));
+        Ok(())
+    })?;
+    Ok(records)
+}
+
+#[test]
+fn test_multi_logger() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let persistent = Domain::new("persistent".to_string(), dir.join("persistent"));
+    let viewing = Domain::new("viewing".to_string(), dir.join("viewing"));
+
+    let (secret_key, public_key) = gen_echd_key_pair();
+    let redactor =
+        |content: &str| content.contains("secret").then(|| content.replace("secret", "***"));
+    let logger = MultiLogger::new(vec![
+        persistent.clone().logger(Config::new().use_mmap(false).key(Some(public_key))),
+        // The records are rewritten, so they are encoded again.
+        viewing.clone().logger(Config::new().use_mmap(false).redactor(Some(Box::new(redactor)))),
+    ]);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..10 {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content =
+            if index % 2 == 0 { format!("test log {index}") } else { format!("secret {index}") };
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let persistent_path = dir.join("persistent.pine");
+    extract(persistent, datetime..=end, &persistent_path)?;
+    let viewing_path = dir.join("viewing.pine");
+    extract(viewing, datetime..=end, &viewing_path)?;
+
+    let persistent_records = parse_records(&persistent_path, Some(secret_key))?;
+    let viewing_records = parse_records(&viewing_path, None)?;
+    assert_eq!(persistent_records.len(), 10);
+    assert_eq!(viewing_records.len(), 10);
+
+    for (index, (persistent, viewing)) in
+        persistent_records.iter().zip(&viewing_records).enumerate()
+    {
+        // The records share the session id of the first logger.
+        assert!(persistent.0.is_some());
+        assert_eq!(persistent.0, viewing.0);
+        if index % 2 == 0 {
+            assert_eq!(persistent.1, format!("test log {index}"));
+            assert_eq!(viewing.1, persistent.1);
+        } else {
+            assert_eq!(persistent.1, format!("secret {index}"));
+            assert_eq!(viewing.1, format!("*** {index}"));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::{error::Error, fs, path::Path, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    parse_incremental, Config, DateTime, Domain, FileNaming, MetaBuilder, ParseOptions,
+    RecordBuilder,
+};
+use tempfile::tempdir;
+
+fn parse_contents(path: &Path, offset: &mut u64) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut contents = Vec::new();
+    parse_incremental(path, offset, None, ParseOptions::new(), |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    Ok(contents)
+}
+
+#[test]
+fn test_parse_incremental() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // Every record is written in a chunk of its own.
+    for index in 0..3 {
+        logger.log(&RecordBuilder::new().content(&format!("test log {index}")).build());
+        thread::sleep(Duration::from_millis(10));
+        logger.flush();
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.shutdown();
+
+    let logfiles = domain.logfiles()?;
+    assert_eq!(logfiles.len(), 1);
+
+    // The log file is cut anywhere as it is being written, the records of the
+    // complete chunks are parsed and the rest are parsed after they land.
+    let bytes = fs::read(&logfiles[0])?;
+    let path = dir.join("partial.pine");
+    for len in 0..bytes.len() {
+        fs::write(&path, &bytes[..len])?;
+        let mut offset = 0;
+        let mut contents = parse_contents(&path, &mut offset)?;
+        assert!(offset <= len as u64);
+
+        fs::write(&path, &bytes)?;
+        contents.extend(parse_contents(&path, &mut offset)?);
+        assert_eq!(contents, ["test log 0", "test log 1", "test log 2"]);
+        assert_eq!(offset, bytes.len() as u64);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_incremental_reopened() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone()).file_naming(FileNaming::Hourly);
+    let log_at = |times: &[&str]| -> Result<(), Box<dyn Error>> {
+        let logger = domain.clone().logger(Config::new().use_mmap(false).minute_index(true));
+        for time in times {
+            let datetime = DateTime::from_str(&format!("2013-11-18 {time}Z"))?;
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            logger.log(&RecordBuilder::new().meta(meta).content(time).build());
+        }
+        logger.shutdown();
+        Ok(())
+    };
+
+    // The log file of 13:00 is sealed once it is rotated, its footer is left to the
+    // next call.
+    log_at(&["13:35:12", "14:05:12"])?;
+    let path = dir.join("test-2013-11-18-13.pine");
+    let mut offset = 0;
+    assert_eq!(parse_contents(&path, &mut offset)?, ["13:35:12"]);
+    assert!(offset < fs::metadata(&path)?.len());
+    assert_eq!(parse_contents(&path, &mut offset)?, Vec::<String>::new());
+
+    // The footer is overwritten as the file is appended to again.
+    log_at(&["13:55:12", "14:10:12"])?;
+    assert_eq!(parse_contents(&path, &mut offset)?, ["13:55:12"]);
+
+    // The offset beyond the truncated file is re-synced to the last chunk.
+    let bytes = fs::read(&path)?;
+    fs::write(&path, &bytes[..offset as usize - 1])?;
+    let mut truncated = offset;
+    assert_eq!(parse_contents(&path, &mut truncated)?, Vec::<String>::new());
+    assert!(truncated < offset);
+    fs::write(&path, &bytes)?;
+    let mut within = truncated + 1;
+    assert_eq!(parse_contents(&path, &mut truncated)?, ["13:55:12"]);
+    assert_eq!(truncated, offset);
+
+    // The offset within a chunk is re-synced to the start of the chunk.
+    assert_eq!(parse_contents(&path, &mut within)?, ["13:55:12"]);
+    assert_eq!(within, offset);
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::{error::Error, path::Path, thread, time::Duration};
+
+use pinenut_log::{
+    extract_report, parse_with_options, Config, DateTime, Domain, ExtractOptions, ParseLimit,
+    ParseOptions, ParsingError, RecordBuilder, ZstdParams,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_limits() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // The first chunk has a long record, the second one has the short ones.
+    let long_content = "long log ".repeat(200);
+    logger.log(&RecordBuilder::new().content(&long_content).build());
+    thread::sleep(Duration::from_millis(10));
+    logger.flush();
+    thread::sleep(Duration::from_millis(10));
+    for index in 0..10 {
+        logger.log(&RecordBuilder::new().content(&format!("test log {index}")).build());
+    }
+    logger.shutdown();
+
+    let logfiles = domain.logfiles()?;
+    let parse = |path: &Path, options: ParseOptions| {
+        let mut contents = Vec::new();
+        let result = parse_with_options(path, None, options, |record| {
+            contents.push(record.content().to_string());
+            Ok(())
+        });
+        let limits: Vec<_> = match result {
+            Ok(()) => Vec::new(),
+            Err(ParsingError::Chunks(errors)) => errors
+                .into_iter()
+                .map(|err| match err {
+                    ParsingError::Limit(limit, _) => limit,
+                    err => panic!("unexpected error: {err}"),
+                })
+                .collect(),
+            Err(err) => panic!("unexpected error: {err}"),
+        };
+        (contents.len(), limits)
+    };
+
+    assert_eq!(parse(&logfiles[0], ParseOptions::new()), (11, vec![]));
+    assert_eq!(
+        parse(&logfiles[0], ParseOptions::new().max_record_len(Some(1000))),
+        (10, vec![ParseLimit::RecordLen(1000)])
+    );
+    assert_eq!(
+        parse(&logfiles[0], ParseOptions::new().max_decompressed_bytes_per_chunk(Some(1000))),
+        (10, vec![ParseLimit::DecompressedBytes(1000)])
+    );
+    // Both chunks are skipped without being decrypted.
+    assert_eq!(
+        parse(&logfiles[0], ParseOptions::new().max_chunk_payload(Some(1))),
+        (0, vec![ParseLimit::ChunkPayload(1), ParseLimit::ChunkPayload(1)])
+    );
+    assert_eq!(parse(&logfiles[0], ParseOptions::hardened()), (11, vec![]));
+
+    // The parsing stops at the first chunk unwrapped from the `zstd` frame, which
+    // exceeds the limit.
+    let path = dir.join("result.pine.zst");
+    let range = DateTime::MIN_UTC..=DateTime::MAX_UTC;
+    extract_report(domain, range, &path, ExtractOptions::new().zstd_frame(Some(3)))?;
+    assert_eq!(parse(&path, ParseOptions::new()), (11, vec![]));
+    assert_eq!(
+        parse(&path, ParseOptions::new().max_decompressed_bytes_per_chunk(Some(1))),
+        (0, vec![ParseLimit::DecompressedBytes(1)])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_max_window_log() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir);
+    let params = ZstdParams { window_log: Some(27), ..Default::default() };
+    let logger = domain.clone().logger(Config::new().use_mmap(false).zstd_params(params));
+    logger.log(&RecordBuilder::new().content("test log").build());
+    logger.shutdown();
+
+    let path = &domain.logfiles()?[0];
+    let mut count = 0;
+    parse_with_options(path, None, ParseOptions::new(), |_| {
+        count += 1;
+        Ok(())
+    })?;
+    assert_eq!(count, 1);
+
+    // The chunk of the larger window is rejected.
+    let result = parse_with_options(path, None, ParseOptions::hardened(), |_| Ok(()));
+    let Err(ParsingError::Chunks(errors)) = result else {
+        panic!("unexpected result: {result:?}");
+    };
+    assert!(matches!(errors[..], [ParsingError::Decompress(..)]));
+
+    Ok(())
+}
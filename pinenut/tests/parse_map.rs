@@ -0,0 +1,54 @@
+use std::{error::Error, str::FromStr};
+
+use pinenut_log::{
+    extract, parse_with_options, Config, DateTime, Domain, Level, MetaBuilder, OwnedRecord,
+    ParseOptions, RecordBuilder, RecordMapper,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_map() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for (level, tag, content) in [
+        (Level::Info, "net", "request sent"),
+        (Level::Verbose, "net", "noise"),
+        (Level::Warn, "NET", "request failed"),
+    ] {
+        let meta = MetaBuilder::new().level(level).tag(tag).datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+    }
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = datetime..=DateTime::from_str("2013-11-18 13:36:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    // Normalizes the tags, and drops the verbose records.
+    let map: RecordMapper = Box::new(|mut record: OwnedRecord| {
+        if record.level() == Level::Verbose {
+            return None;
+        }
+        record.set_tag(record.tag().map(str::to_uppercase));
+        Some(record)
+    });
+    let options = ParseOptions::new().map(Some(map));
+
+    let mut records = Vec::new();
+    parse_with_options(&extracted_path, None, options, |record| {
+        records.push((record.meta().tag().map(str::to_string), record.content().to_string()));
+        Ok(())
+    })?;
+    assert_eq!(
+        records,
+        [
+            (Some("NET".to_string()), "request sent".to_string()),
+            (Some("NET".to_string()), "request failed".to_string())
+        ]
+    );
+
+    Ok(())
+}
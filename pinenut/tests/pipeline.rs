@@ -0,0 +1,145 @@
+use std::{
+    error::Error,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, MetaBuilder, PipelineStage, Record, RecordBuilder,
+    Verdict,
+};
+use tempfile::tempdir;
+
+/// Keeps one of every `n` records.
+struct Sample {
+    n: usize,
+    count: usize,
+}
+
+impl PipelineStage for Sample {
+    fn name(&self) -> &str {
+        "sample"
+    }
+
+    fn process(&mut self, _: &Record, _: &mut String) -> Verdict {
+        self.count += 1;
+        if (self.count - 1).is_multiple_of(self.n) {
+            Verdict::Keep
+        } else {
+            Verdict::Drop
+        }
+    }
+}
+
+/// Prefixes the content with its tag.
+struct Prefix;
+
+impl PipelineStage for Prefix {
+    fn name(&self) -> &str {
+        "prefix"
+    }
+
+    fn process(&mut self, record: &Record, content: &mut String) -> Verdict {
+        let Some(tag) = record.meta().tag() else {
+            return Verdict::Keep;
+        };
+        content.insert_str(0, &format!("{tag}: "));
+        Verdict::Rewrite
+    }
+}
+
+/// Captures the encoded bytes of the records, and drops the ones longer than the
+/// maximum length.
+struct Capture {
+    max_len: usize,
+    captured: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl PipelineStage for Capture {
+    fn name(&self) -> &str {
+        "capture"
+    }
+
+    fn process_encoded(&mut self, _: &Record, encoded: &mut Vec<u8>) -> Verdict {
+        if encoded.len() > self.max_len {
+            return Verdict::Drop;
+        }
+        self.captured.lock().unwrap().push(encoded.clone());
+        Verdict::Keep
+    }
+}
+
+#[test]
+fn test_stages() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let redactor =
+        |content: &str| content.contains("secret").then(|| content.replace("secret", "***"));
+    let config = Config::new()
+        .use_mmap(false)
+        .redactor(Some(Box::new(redactor)))
+        .pipeline_stage(Box::new(Sample { n: 3, count: 0 }))
+        .pipeline_stage(Box::new(Prefix))
+        // Replaces the sampling stage above.
+        .pipeline_stage(Box::new(Sample { n: 2, count: 0 }));
+    let logger = domain.clone().logger(config);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..10 {
+        let meta = MetaBuilder::new().datetime(datetime).tag("net").build();
+        let content = format!("secret {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> = (0..10).step_by(2).map(|index| format!("net: *** {index}")).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_encoded_stages() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let capture = Capture { max_len: 64, captured: captured.clone() };
+    let config = Config::new().use_mmap(false).pipeline_stage(Box::new(capture));
+    let logger = domain.clone().logger(config);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for content in ["short", &"long".repeat(32), "short again"] {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    extract(domain, datetime..=datetime, &path)?;
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    // The record whose encoded bytes are too long is dropped.
+    assert_eq!(contents, ["short", "short again"]);
+
+    // The stage sees the exact bytes that are written, with the stamped metadata.
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured.len(), 2);
+    assert!(captured[1].windows(11).any(|w| w == b"short again"));
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::{error::Error, fs, path::PathBuf, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, FileNaming, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+const STEP: u64 = 64 * 1024;
+
+#[test]
+fn test_preallocation() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf()).file_naming(FileNaming::Hourly);
+    let logfile = dir.join("test-2013-11-18-13.pine");
+
+    let log =
+        |datetimes: &[&str], allocated_len: u64, count: usize| -> Result<(), Box<dyn Error>> {
+            let config = Config::new().use_mmap(false).preallocation(Some(STEP));
+            let logger = domain.clone().logger(config);
+            for datetime in datetimes {
+                let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+                logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+                thread::sleep(Duration::from_micros(100));
+            }
+            logger.flush();
+            // Waits for the IO operations above to complete.
+            logger.trim_with_report(u64::MAX)?;
+
+            // The log file is preallocated while it is open, and the records can be extracted
+            // meanwhile.
+            assert_eq!(fs::metadata(&logfile)?.len(), allocated_len);
+            assert_eq!(extract_count(&domain, &dir.join("open.pine"))?, count);
+
+            logger.shutdown();
+            Ok(())
+        };
+
+    log(&["2013-11-18 13:35:12Z", "2013-11-18 13:36:00Z"], STEP, 2)?;
+    // The preallocated space is released once the log file is closed.
+    let len = fs::metadata(&logfile)?.len();
+    assert!(len < STEP);
+
+    // Simulates the zero-filled space left by a crash, the records are appended after
+    // the chunks, within the space.
+    let mut bytes = fs::read(&logfile)?;
+    bytes.resize(bytes.len() + 1024, 0);
+    fs::write(&logfile, bytes)?;
+
+    log(&["2013-11-18 13:37:00Z", "2013-11-18 13:38:00Z"], len + 1024, 4)?;
+    assert_eq!(extract_count(&domain, &dir.join("closed.pine"))?, 4);
+
+    Ok(())
+}
+
+/// Extracts the records of the hour, and returns the count of them.
+fn extract_count(domain: &Domain, path: &PathBuf) -> Result<usize, Box<dyn Error>> {
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:00:00Z")?..=DateTime::from_str("2013-11-18 14:00:00Z")?;
+    _ = fs::remove_file(path);
+    extract(domain.clone(), datetime_range, path)?;
+
+    let mut count = 0;
+    parse(path, None, |_| {
+        count += 1;
+        Ok(())
+    })?;
+    Ok(count)
+}
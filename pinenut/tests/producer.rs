@@ -0,0 +1,54 @@
+use std::{cell::RefCell, error::Error, process, rc::Rc, str::FromStr};
+
+use pinenut_log::{
+    extract, parse_with_options, Config, DateTime, Domain, MetaBuilder, ParseOptions,
+    ProducerCallback, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_producer() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // Two launches of different app versions.
+    for (app_version, datetime) in
+        [("1.0.0", "2013-11-18 13:35:12Z"), ("1.1.0", "2013-11-18 13:40:00Z")]
+    {
+        let config = Config::new()
+            .use_mmap(false)
+            .app_version(Some(app_version))
+            .os_version(Some("iOS 17.0"));
+        let logger = domain.clone().logger(config);
+        let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(app_version).build());
+        logger.shutdown();
+    }
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range =
+        DateTime::from_str("2013-11-18 13:30:00Z")?..=DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let on_producer: ProducerCallback = Box::new({
+        let events = events.clone();
+        move |producer| {
+            assert_eq!(producer.pid(), Some(process::id()));
+            assert_eq!(producer.os_version(), Some("iOS 17.0"));
+            events.borrow_mut().push(format!("producer {}", producer.app_version().unwrap()));
+        }
+    });
+    let options = ParseOptions::new().on_producer(Some(on_producer));
+
+    parse_with_options(&extracted_path, None, options, |record| {
+        events.borrow_mut().push(format!("record {}", record.content()));
+        Ok(())
+    })?;
+    assert_eq!(
+        *events.borrow(),
+        ["producer 1.0.0", "record 1.0.0", "producer 1.1.0", "record 1.1.0"]
+    );
+
+    Ok(())
+}
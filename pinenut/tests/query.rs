@@ -0,0 +1,56 @@
+use std::{error::Error, str::FromStr, thread};
+
+use chrono::Duration;
+use pinenut_log::{
+    extract, query, Config, DateTime, Domain, Level, MetaBuilder, Query, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_query() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let records = [
+        (Level::Info, "app", "launched", 0),
+        (Level::Error, "network", "request failed: timeout", 1),
+        (Level::Debug, "network", "request sent", 2),
+        (Level::Warn, "network", "request failed: retrying", 3),
+        (Level::Error, "app", "crashed", 4),
+    ];
+    for (level, tag, content, seconds) in records {
+        let datetime = datetime + Duration::seconds(seconds);
+        let meta = MetaBuilder::new().level(level).tag(tag).datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        thread::sleep(std::time::Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = datetime..=DateTime::from_str("2013-11-18 13:36:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let contents = |query_: Query| -> Result<Vec<String>, Box<dyn Error>> {
+        let records = query(&extracted_path, None, query_)?;
+        Ok(records.iter().map(|r| r.content().to_string()).collect())
+    };
+
+    assert_eq!(contents(Query::new())?.len(), records.len());
+    assert_eq!(
+        contents(Query::new().level(Some(Level::Warn)))?,
+        ["request failed: timeout", "request failed: retrying", "crashed"]
+    );
+    assert_eq!(
+        contents(Query::new().tag(Some("network")).text(Some("failed")).limit(Some(1)))?,
+        ["request failed: timeout"]
+    );
+    let range = datetime + Duration::seconds(2)..=datetime + Duration::seconds(3);
+    assert_eq!(
+        contents(Query::new().range(Some(range)))?,
+        ["request sent", "request failed: retrying"]
+    );
+
+    Ok(())
+}
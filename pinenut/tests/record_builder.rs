@@ -0,0 +1,43 @@
+use std::{error::Error, str::FromStr};
+
+use pinenut_log::{
+    BuildError, DateTime, LocationBuilder, MetaBuilder, Record, RecordBuilder, BUFFER_LEN,
+};
+
+#[test]
+fn test_build_checked() -> Result<(), Box<dyn Error>> {
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let meta = MetaBuilder::new().datetime(datetime).tag("net").build();
+    let record = RecordBuilder::new().meta(meta.clone()).content("test log").build_checked()?;
+    assert_eq!(record, RecordBuilder::new().meta(meta.clone()).content("test log").build());
+
+    let before_epoch = DateTime::from_str("1969-12-31 23:59:59Z")?;
+    let meta = MetaBuilder::new().datetime(before_epoch).build();
+    let result = RecordBuilder::new().meta(meta).build_checked();
+    assert_eq!(result, Err(BuildError::DatetimeBeforeEpoch(before_epoch)));
+
+    let meta = MetaBuilder::new().datetime(datetime).tag("").build();
+    assert_eq!(RecordBuilder::new().meta(meta).build_checked(), Err(BuildError::EmptyTag));
+
+    let tag = "t".repeat(Record::MAX_TAG_LEN + 1);
+    let meta = MetaBuilder::new().datetime(datetime).tag(&tag).build();
+    let result = RecordBuilder::new().meta(meta).build_checked();
+    assert_eq!(result, Err(BuildError::TagTooLong { len: tag.len(), max: Record::MAX_TAG_LEN }));
+
+    let location = LocationBuilder::new().line(0).build();
+    let meta = MetaBuilder::new().datetime(datetime).location(location).build();
+    assert_eq!(RecordBuilder::new().meta(meta).build_checked(), Err(BuildError::ZeroLine));
+
+    // The contents longer than the buffer are written to chunks of their own.
+    let content = "c".repeat(BUFFER_LEN + 1);
+    let meta = MetaBuilder::new().datetime(datetime).build();
+    assert!(RecordBuilder::new().meta(meta).content(&content).build_checked().is_ok());
+
+    let content = "c".repeat(Record::MAX_CONTENT_LEN + 1);
+    let meta = MetaBuilder::new().datetime(datetime).build();
+    let result = RecordBuilder::new().meta(meta).content(&content).build_checked();
+    let max = Record::MAX_CONTENT_LEN;
+    assert_eq!(result, Err(BuildError::ContentTooLong { len: content.len(), max }));
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, MetaBuilder, PipelineStage, Record, RecordBuilder,
+    Verdict,
+};
+use tempfile::tempdir;
+
+/// Drops the records whose contents contain `drop`.
+struct DropStage;
+
+impl PipelineStage for DropStage {
+    fn name(&self) -> &str {
+        "drop"
+    }
+
+    fn process(&mut self, record: &Record, _: &mut String) -> Verdict {
+        if record.content().contains("drop") {
+            Verdict::Drop
+        } else {
+            Verdict::Keep
+        }
+    }
+}
+
+#[test]
+fn test_record_sequence() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.clone());
+    let config =
+        Config::new().use_mmap(false).record_sequence(true).pipeline_stage(Box::new(DropStage));
+    let logger = domain.clone().logger(config);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let contents = ["test log", "drop log", "drop log", "test log", "numbered log"];
+    for content in contents {
+        let sequence = (content == "numbered log").then_some(42);
+        let meta = MetaBuilder::new().datetime(datetime).sequence_opt(sequence).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("extracted.pine");
+    extract(domain, datetime..=datetime, &path)?;
+    let mut sequences = Vec::new();
+    parse(&path, None, |record| {
+        sequences.push(record.meta().sequence());
+        Ok(())
+    })?;
+
+    // The dropped records leave a gap, and the record numbered already keeps it.
+    assert_eq!(sequences, [Some(0), Some(3), Some(42)]);
+
+    Ok(())
+}
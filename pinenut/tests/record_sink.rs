@@ -0,0 +1,36 @@
+use std::{
+    error::Error,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use pinenut_log::{Config, DateTime, Domain, MetaBuilder, RateLimit, Record, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_record_sink() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let received = Arc::clone(&received);
+        move |record: &Record| received.lock().unwrap().push(record.content().to_string())
+    };
+    let config = Config::new()
+        .use_mmap(false)
+        .rate_limit(Some(RateLimit::new(2, false)))
+        .record_sink(Some(Box::new(sink)));
+    let logger = domain.logger(config);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for content in ["a", "b", "c", "d"] {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+    }
+    logger.shutdown();
+
+    // The records dropped by the rate limiting are not received, but the summary is.
+    assert_eq!(*received.lock().unwrap(), ["a", "b", "suppressed 2 similar records"]);
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{error::Error, str::FromStr};
+
+use pinenut_log::{
+    extract, parse_with_options, Config, DateTime, Domain, MetaBuilder, ParseOptions,
+    RecordBuilder, Redactor,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_redaction() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // Masks the digits at write time.
+    let redactor: Redactor = Box::new(|content: &str| {
+        content.contains(char::is_numeric).then(|| content.replace(char::is_numeric, "*"))
+    });
+    let logger = domain.clone().logger(Config::new().use_mmap(false).redactor(Some(redactor)));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for content in ["phone: 13800138000", "token: secret", "nothing sensitive"] {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+    }
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = datetime..=DateTime::from_str("2013-11-18 13:36:00Z")?;
+    extract(domain, datetime_range, &extracted_path)?;
+
+    // Masks the tokens at parse time.
+    let redactor: Redactor = Box::new(|content: &str| {
+        content.contains("secret").then(|| content.replace("secret", "*"))
+    });
+    let options = ParseOptions::new().redactor(Some(redactor));
+
+    let mut contents = Vec::new();
+    parse_with_options(&extracted_path, None, options, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    assert_eq!(contents, ["phone: ***********", "token: *", "nothing sensitive"]);
+
+    Ok(())
+}
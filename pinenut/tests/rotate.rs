@@ -0,0 +1,97 @@
+use std::{cell::RefCell, error::Error, fs, rc::Rc, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse_with_options, Config, Domain, Hint, MetaBuilder, ParseOptions, RecordBuilder,
+    RotateCallback, RotateReason, FILE_EXTENSION,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_rotate_with_reason() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    // The chunk started by the rotation begins at the current time.
+    let start = chrono::Utc::now();
+    let log = |content| {
+        let meta = MetaBuilder::new().build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        thread::sleep(Duration::from_micros(100));
+    };
+
+    log("before");
+    logger.rotate(RotateReason::PreUpload);
+    log("after");
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = start - chrono::Duration::minutes(1)..=chrono::Utc::now();
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let on_rotate: RotateCallback = Box::new({
+        let events = events.clone();
+        move |reason, _| events.borrow_mut().push(format!("{reason:?}"))
+    });
+    let options = ParseOptions::new().on_rotate(Some(on_rotate));
+
+    parse_with_options(&extracted_path, None, options, |record| {
+        events.borrow_mut().push(record.content().to_string());
+        Ok(())
+    })?;
+    assert_eq!(*events.borrow(), ["before", "PreUpload", "after"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_rotate_now() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.logger(Config::new().use_mmap(false));
+
+    let usage = logger.buffer_usage();
+    assert_eq!(usage.used, 0);
+    assert!(usage.capacity > 0);
+
+    logger.log(&RecordBuilder::new().content("test log").build());
+    let usage = logger.buffer_usage();
+    assert!(usage.used > 0 && usage.used < usage.capacity);
+
+    // The records have been written to the log file once it returns.
+    logger.rotate_now()?;
+    assert_eq!(logger.buffer_usage().used, 0);
+    let logfiles_len: u64 = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension() == Some(FILE_EXTENSION.as_ref()))
+        .map(|e| e.metadata().unwrap().len())
+        .sum();
+    assert!(logfiles_len > 0);
+
+    logger.shutdown();
+    Ok(())
+}
+
+#[test]
+fn test_hint() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let config = Config::new().use_mmap(false).thread_staging_len(Some(1024));
+    let logger = domain.logger(config);
+
+    // The staged records are drained and flushed.
+    logger.log(&RecordBuilder::new().content("test log").build());
+    logger.hint(Hint::LowMemory)?;
+    assert_eq!(logger.buffer_usage().used, 0);
+
+    logger.log(&RecordBuilder::new().content("test log").build());
+    logger.hint(Hint::Terminating)?;
+    assert_eq!(logger.buffer_usage().used, 0);
+    assert!(fs::read_dir(&dir)?.filter_map(|e| e.ok()).any(|e| {
+        e.path().extension() == Some(FILE_EXTENSION.as_ref()) && e.metadata().unwrap().len() > 0
+    }));
+
+    logger.shutdown();
+    Ok(())
+}
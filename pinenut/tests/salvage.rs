@@ -0,0 +1,73 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, salvage, Config, DateTime, Domain, MetaBuilder, RecordBuilder, SalvageError,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_salvage() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let datetimes = [
+        DateTime::from_str("2013-11-18 13:35:12Z")?,
+        DateTime::from_str("2013-11-18 13:37:00Z")?,
+        DateTime::from_str("2013-11-18 13:39:00Z")?,
+    ];
+    for datetime in datetimes {
+        for index in 0..100 {
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            let content = format!("test log {index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetimes[0]..=end, &path)?;
+
+    // The chunk magic in little endian.
+    const MAGIC: [u8; 4] = 0xFEEDCA7Cu32.to_le_bytes();
+    let bytes = fs::read(&path)?;
+    let offsets: Vec<_> = bytes
+        .windows(MAGIC.len())
+        .enumerate()
+        .filter(|(_, w)| *w == MAGIC)
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(offsets.len(), 3);
+
+    // Garbage at the start, the header of the second chunk is overwritten, and the
+    // last chunk is truncated.
+    let mut damaged = b"garbage".to_vec();
+    damaged.extend_from_slice(&bytes[..(offsets[2] + bytes.len()) / 2]);
+    damaged[7 + offsets[1]] = 0;
+    let damaged_path = dir.join("damaged.pine");
+    fs::write(&damaged_path, &damaged)?;
+    assert!(parse(&damaged_path, None, |_| Ok(())).is_err());
+
+    let salvaged_path = dir.join("salvaged.pine");
+    let report = salvage(&damaged_path, &salvaged_path)?;
+    assert_eq!(report.chunks, 1);
+    assert_eq!(report.truncated_chunks, 1);
+    assert!(report.skipped_bytes > 7);
+
+    let mut counts = [0; 3];
+    parse(&salvaged_path, None, |record| {
+        let index = datetimes.iter().position(|d| *d == record.meta().datetime()).unwrap();
+        counts[index] += 1;
+        Ok(())
+    })?;
+    assert_eq!(counts[0], 100);
+    assert_eq!(counts[1], 0);
+    assert!(counts[2] > 0 && counts[2] < 100, "{counts:?}");
+
+    fs::write(&damaged_path, b"garbage")?;
+    assert!(matches!(salvage(&damaged_path, &salvaged_path), Err(SalvageError::NotFound)));
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "serde")]
+
+use std::{error::Error, str::FromStr};
+
+use pinenut_log::{
+    DateTime, Level, LocationBuilder, MetaBuilder, OwnedRecord, Record, RecordBuilder,
+};
+
+#[test]
+fn test_serde() -> Result<(), Box<dyn Error>> {
+    let location = LocationBuilder::new().file("main.rs").line(42).build();
+    let meta = MetaBuilder::new()
+        .level(Level::Warn)
+        .datetime(DateTime::from_str("2013-11-18 13:35:12Z")?)
+        .location(location)
+        .tag("net")
+        .thread_id(7)
+        .build();
+    let record = RecordBuilder::new().meta(meta.clone()).content("test log").build();
+
+    let json = serde_json::to_value(&record)?;
+    assert_eq!(json["meta"]["level"], "warn");
+    assert_eq!(json["meta"]["datetime"], "2013-11-18T13:35:12Z");
+    assert_eq!(json["meta"]["location"]["line"], 42);
+    assert_eq!(json["content"], "test log");
+
+    let string = serde_json::to_string(&record)?;
+    assert_eq!(serde_json::from_str::<Record>(&string)?, record);
+
+    // The escaped strings can't be borrowed, the owned record is deserialized instead.
+    let record = RecordBuilder::new().meta(meta).content("\"quoted\"\n").build();
+    let string = serde_json::to_string(&OwnedRecord::from(&record))?;
+    let owned: OwnedRecord = serde_json::from_str(&string)?;
+    assert_eq!(owned.as_record(), record);
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract_session, parse, Config, DateTime, Domain, ExtractOptions, MetaBuilder, RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_extract_session() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // Two sessions (launches) in the same hour.
+    for (session_id, datetimes) in [
+        (1, ["2013-11-18 13:35:12Z", "2013-11-18 13:36:00Z"]),
+        (2, ["2013-11-18 13:40:00Z", "2013-11-18 13:41:00Z"]),
+    ] {
+        let config = Config::new().use_mmap(false).session_id(Some(session_id));
+        let logger = domain.clone().logger(config);
+        for datetime in datetimes {
+            let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+            logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        logger.shutdown();
+    }
+
+    let extracted_path = dir.join("result.pine");
+    extract_session(domain, 2, &extracted_path, ExtractOptions::new())?;
+
+    let mut datetimes = Vec::new();
+    parse(&extracted_path, None, |record| {
+        assert_eq!(record.meta().session_id(), Some(2));
+        datetimes.push(record.meta().datetime());
+        Ok(())
+    })?;
+    assert_eq!(
+        datetimes,
+        [DateTime::from_str("2013-11-18 13:40:00Z")?, DateTime::from_str("2013-11-18 13:41:00Z")?]
+    );
+
+    Ok(())
+}
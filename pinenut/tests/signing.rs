@@ -0,0 +1,68 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse_with_options, ChunkError, Config, DateTime, Domain, MetaBuilder, ParseOptions,
+    ParsingError, RecordBuilder, SigningKey,
+};
+use tempfile::tempdir;
+
+/// Offset of the session field in the chunk header.
+const SESSION_OFFSET: usize = 60;
+
+fn parse_signed(path: &std::path::Path, key: SigningKey) -> (Vec<String>, usize) {
+    let mut contents = Vec::new();
+    let options = ParseOptions::new().signing_key(Some(key));
+    let res = parse_with_options(path, None, options, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    });
+    let mismatches = match res {
+        Ok(()) => 0,
+        Err(ParsingError::Chunks(errors)) => errors
+            .iter()
+            .filter(|e| matches!(e, ParsingError::Chunk(ChunkError::SignatureMismatch, _)))
+            .count(),
+        Err(err) => panic!("unexpected error: {err}"),
+    };
+    (contents, mismatches)
+}
+
+#[test]
+fn test_signing() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let key = [7; 32];
+    let logger = domain.clone().logger(Config::new().use_mmap(false).signing_key(Some(key)));
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..100 {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    let expected: Vec<_> = (0..100).map(|index| format!("test log {index}")).collect();
+    let (contents, mismatches) = parse_signed(&path, key);
+    assert_eq!(contents, expected);
+    assert_eq!(mismatches, 0);
+
+    // The records are still called back, but the chunks are not verified.
+    let (contents, mismatches) = parse_signed(&path, [8; 32]);
+    assert_eq!(contents, expected);
+    assert!(mismatches > 0);
+
+    // Tampers with the session of the first chunk.
+    let mut bytes = fs::read(&path)?;
+    bytes[SESSION_OFFSET] ^= 0xFF;
+    fs::write(&path, bytes)?;
+    let (_, mismatches) = parse_signed(&path, key);
+    assert_eq!(mismatches, 1);
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{extract, parse, Config, DateTime, Domain, Level, MetaBuilder, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_split_by_level() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let config = Config::new().use_mmap(false).split_by_level(&[Level::Error, Level::Warn]);
+    let logger = domain.clone().logger(config);
+
+    let levels = [Level::Info, Level::Warn, Level::Debug, Level::Error];
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..20 {
+        let level = levels[index % levels.len()];
+        let meta = MetaBuilder::new().datetime(datetime).level(level).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let parse_contents = |domain: Domain, name: &str| -> Result<Vec<String>, Box<dyn Error>> {
+        let path = dir.join(name);
+        extract(domain, datetime..=end, &path)?;
+        let mut contents = Vec::new();
+        parse(&path, None, |record| {
+            contents.push(record.content().to_string());
+            Ok(())
+        })?;
+        Ok(contents)
+    };
+
+    // All of the records are written to the log files of the domain.
+    let expected: Vec<_> = (0..20).map(|index| format!("test log {index}")).collect();
+    assert_eq!(parse_contents(domain.clone(), "result.pine")?, expected);
+
+    // The errors and warnings are also written to the split stream.
+    let expected: Vec<_> =
+        (0..20).filter(|i| i % 2 == 1).map(|index| format!("test log {index}")).collect();
+    assert_eq!(parse_contents(domain.split_domain(), "errors.pine")?, expected);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::{error::Error, str::FromStr, sync::Arc, thread, time::Duration};
+
+use pinenut_log::{extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder};
+use tempfile::tempdir;
+
+#[test]
+fn test_thread_staging() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let config = Config::new().use_mmap(false).thread_staging_len(Some(1024));
+    let logger = Arc::new(domain.clone().logger(config));
+
+    let start = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let threads: Vec<_> = (0..4)
+        .map(|thread| {
+            let logger = Arc::clone(&logger);
+            thread::spawn(move || {
+                for index in 0..200 {
+                    let meta = MetaBuilder::new().datetime(start).build();
+                    let content = format!("test log {thread} {index}");
+                    logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+                    thread::sleep(Duration::from_micros(100));
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    // The remaining staged records are drained on shutdown.
+    Arc::into_inner(logger).unwrap().shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, start..=end, &path)?;
+
+    let mut contents = Vec::new();
+    parse(&path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    contents.sort();
+    let mut expected: Vec<_> =
+        (0..4).flat_map(|t| (0..200).map(move |i| format!("test log {t} {i}"))).collect();
+    expected.sort();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}
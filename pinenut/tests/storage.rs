@@ -0,0 +1,108 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use pinenut_log::{
+    extract_many, extract_session, extract_with_options, parse, Config, DateTime, Domain,
+    ExtractOptions, FileNaming, MetaBuilder, RecordBuilder, SharedStorage, Storage,
+};
+use tempfile::tempdir;
+
+/// Stores the log files in memory.
+#[derive(Default)]
+struct MemoryStorage(Mutex<BTreeMap<PathBuf, Vec<u8>>>);
+
+impl Storage for MemoryStorage {
+    fn append(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        self.0.lock().unwrap().entry(path.to_path_buf()).or_default().extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.0.lock().unwrap().get(path).cloned().ok_or(io::ErrorKind::NotFound.into())
+    }
+
+    fn list(&self, directory: &Path) -> io::Result<Vec<PathBuf>> {
+        let files = self.0.lock().unwrap();
+        Ok(files.keys().filter(|p| p.starts_with(directory)).cloned().collect())
+    }
+
+    fn delete(&self, path: &Path) -> io::Result<()> {
+        self.0.lock().unwrap().remove(path).map(drop).ok_or(io::ErrorKind::NotFound.into())
+    }
+
+    fn len(&self, path: &Path) -> io::Result<u64> {
+        let files = self.0.lock().unwrap();
+        files.get(path).map(|b| b.len() as u64).ok_or(io::ErrorKind::NotFound.into())
+    }
+}
+
+impl MemoryStorage {
+    fn names(&self) -> Vec<String> {
+        let files = self.0.lock().unwrap();
+        files.keys().filter_map(|p| Some(p.file_name()?.to_str()?.to_string())).collect()
+    }
+}
+
+#[test]
+fn test_storage() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf()).file_naming(FileNaming::Hourly);
+    let storage = Arc::new(MemoryStorage::default());
+    let shared: SharedStorage = storage.clone();
+
+    let config = Config::new().use_mmap(false).session_id(Some(1)).storage(Some(shared.clone()));
+    let logger = domain.clone().logger(config);
+    for datetime in ["2013-11-18 13:35:12Z", "2013-11-18 13:36:00Z", "2013-11-18 14:00:00Z"] {
+        let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.flush();
+    // Waits for the IO operations above to complete.
+    logger.trim_with_report(u64::MAX)?;
+
+    // Nothing is written to the file system.
+    assert!(!dir.exists());
+    let names = storage.names();
+    assert!(names.contains(&"test-2013-11-18-13.pine".to_string()), "{names:?}");
+    assert!(names.contains(&"test-2013-11-18-14.pine".to_string()), "{names:?}");
+
+    // The records are extracted from the storage.
+    let start = DateTime::from_str("2013-11-18 13:00:00Z")?;
+    let end = DateTime::from_str("2013-11-18 13:59:00Z")?;
+    let count = |path: &Path| -> Result<usize, Box<dyn Error>> {
+        let mut count = 0;
+        parse(path, None, |_| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    };
+    let extracted = tempdir()?;
+    let options = ExtractOptions::new().storage(Some(shared));
+    let path = extracted.path().join("extracted.pine");
+    extract_with_options(domain.clone(), start..=end, &path, options.clone())?;
+    assert_eq!(count(&path)?, 2);
+    let path = extracted.path().join("many.pine");
+    extract_many(std::slice::from_ref(&domain), start..=end, &path, options.clone())?;
+    assert_eq!(count(&path)?, 2);
+    let path = extracted.path().join("session.pine");
+    extract_session(domain, 1, &path, options)?;
+    assert_eq!(count(&path)?, 3);
+
+    // The expired log files are deleted from the storage.
+    let report = logger.trim_with_report(60)?;
+    assert!(report.deleted_files >= 2);
+    assert!(!storage.names().iter().any(|n| n.starts_with("test-2013")));
+    logger.shutdown();
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, extract_with_options, parse, Config, DateTime, Domain, ExtractOptions,
+    ExtractionError, MetaBuilder, RecordBuilder, FILE_EXTENSION,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_strict_extraction() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.clone().logger(Config::new().use_mmap(false));
+
+    let past = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    // A record of the minute that is still open, in the future to avoid crossing
+    // the minute boundary during the test.
+    let open = chrono::Utc::now() + chrono::Duration::minutes(2);
+    for datetime in [past, open] {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    // Simulates a chunk being written to the end of the past log file.
+    let mut logfiles = fs::read_dir(&dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .filter(|path| {
+            path.as_ref().map_or(true, |p| p.extension() == Some(FILE_EXTENSION.as_ref()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    logfiles.sort();
+    let mut bytes = fs::read(&logfiles[0])?;
+    bytes.extend_from_within(..bytes.len() - 1);
+    fs::write(&logfiles[0], bytes)?;
+
+    let datetime_range = past..=open;
+    let extracted_path = dir.join("result.pine");
+
+    assert!(matches!(
+        extract(domain.clone(), datetime_range.clone(), &extracted_path),
+        Err(ExtractionError::FileIncomplete(_))
+    ));
+    fs::remove_file(&extracted_path)?;
+
+    // Only the complete chunk of the past minute is extracted.
+    let options = ExtractOptions::new().strict(true);
+    extract_with_options(domain, datetime_range, &extracted_path, options)?;
+
+    let mut datetimes = Vec::new();
+    parse(&extracted_path, None, |record| {
+        datetimes.push(record.meta().datetime());
+        Ok(())
+    })?;
+    assert_eq!(datetimes, [past]);
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use pinenut_log::{extract, parse, Config, Domain, MetaBuilder, RecordBuilder, TimestampSource};
+use tempfile::tempdir;
+
+#[test]
+fn test_timestamp_source() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+    // The device clock is one day behind, then the correction is lost.
+    let offset = Arc::new(AtomicI64::new(chrono::Duration::days(1).num_seconds()));
+    let source = TimestampSource::Callback(Box::new({
+        let offset = offset.clone();
+        move || chrono::Utc::now() + chrono::Duration::seconds(offset.load(Ordering::Relaxed))
+    }));
+    let logger = domain.clone().logger(Config::new().use_mmap(false).timestamp_source(source));
+
+    let start = chrono::Utc::now();
+    for content in ["corrected", "monotonic"] {
+        let meta = MetaBuilder::new().build();
+        logger.log(&RecordBuilder::new().meta(meta).content(content).build());
+        thread::sleep(Duration::from_micros(100));
+        offset.store(0, Ordering::Relaxed);
+    }
+    logger.shutdown();
+
+    let extracted_path = dir.join("result.pine");
+    let datetime_range = start..=start + chrono::Duration::days(2);
+    extract(domain, datetime_range, &extracted_path)?;
+
+    let mut datetimes = Vec::new();
+    parse(&extracted_path, None, |record| {
+        datetimes.push(record.meta().datetime());
+        Ok(())
+    })?;
+    assert_eq!(datetimes.len(), 2);
+    assert!(datetimes[0] - start >= chrono::Duration::days(1));
+    assert!(datetimes[0] - start < chrono::Duration::days(1) + chrono::Duration::minutes(1));
+    // The datetime never moves backwards.
+    assert_eq!(datetimes[1], datetimes[0]);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::{
+    error::Error,
+    fs, mem,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use pinenut_log::{
+    Config, DateTime, Domain, MetaBuilder, RecordBuilder, Severity, Track, TrackCategory,
+    TrackEvent,
+};
+use tempfile::tempdir;
+
+/// Collects the categories and the severities of the events.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<(TrackCategory, Severity)>>>);
+
+impl Track for Collector {
+    fn track_event(&self, event: TrackEvent) {
+        self.0.lock().unwrap().push((event.category, event.severity));
+    }
+}
+
+impl Collector {
+    fn contains(&self, category: TrackCategory, severity: Severity) -> bool {
+        self.0.lock().unwrap().contains(&(category, severity))
+    }
+}
+
+#[test]
+fn test_track_event() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let collector = Collector::default();
+    let config = || Config::new().tracker(Some(Box::new(collector.clone())));
+
+    // The domain directory is occupied by a file, the logger falls back to the buffer in
+    // memory and the log files can't be written.
+    let path = dir.path().join("occupied");
+    fs::write(&path, [])?;
+    let logger = Domain::new("test".to_string(), path).logger(config());
+    logger.log(&RecordBuilder::new().content("test log").build());
+    logger.shutdown();
+    assert!(collector.contains(TrackCategory::BufferFallback, Severity::Warning));
+    assert!(collector.contains(TrackCategory::Io, Severity::Error));
+
+    // The records left in the mmap buffer are written back by the next logger.
+    let domain = Domain::new("test".to_string(), dir.path().join("test"));
+    let logger = domain.clone().logger(config());
+    logger.log(&RecordBuilder::new().content("test log").build());
+    // Simulates a crash.
+    mem::forget(logger);
+    assert!(!collector.contains(TrackCategory::WritebackRecovered, Severity::Info));
+
+    let logger = domain.logger(config());
+    assert!(collector.contains(TrackCategory::WritebackRecovered, Severity::Info));
+
+    // The results of the trimming are reported.
+    let meta = MetaBuilder::new().datetime(DateTime::from_str("2013-11-18 13:35:12Z")?).build();
+    logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+    thread::sleep(Duration::from_micros(100));
+    logger.flush();
+    logger.trim(60);
+    // Waits for the IO operations above to complete.
+    logger.trim_with_report(u64::MAX)?;
+    assert!(collector.contains(TrackCategory::Trim, Severity::Info));
+    logger.shutdown();
+
+    Ok(())
+}
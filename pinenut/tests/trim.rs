@@ -0,0 +1,43 @@
+use std::{error::Error, fs, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{Config, DateTime, Domain, MetaBuilder, RecordBuilder, FILE_EXTENSION};
+use tempfile::tempdir;
+
+#[test]
+fn test_trim_with_report() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let logger = domain.logger(Config::new().use_mmap(false));
+
+    for datetime in ["2013-11-18 13:35:12Z", "2013-11-18 14:00:00Z", "2013-11-18 15:20:12Z"] {
+        let meta = MetaBuilder::new().datetime(DateTime::from_str(datetime)?).build();
+        logger.log(&RecordBuilder::new().meta(meta).content("test log").build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.flush();
+
+    // Nothing expires, it just waits for the IO operations above to complete.
+    assert_eq!(logger.trim_with_report(u64::MAX)?.deleted_files, 0);
+
+    // Returns the count and the total length of the log files.
+    let logfiles = || -> Result<(usize, u64), Box<dyn Error>> {
+        let files = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension() == Some(FILE_EXTENSION.as_ref()))
+            .collect::<Vec<_>>();
+        Ok((files.len(), files.iter().map(|e| e.metadata().unwrap().len()).sum()))
+    };
+    let (_, total_len) = logfiles()?;
+
+    let report = logger.trim_with_report(60)?;
+    // 2013-11-18.13 + 2013-11-18.14 + 2013-11-18.15, the current hour is excluded.
+    assert_eq!(report.deleted_files, 3);
+    assert!(report.errors.is_empty());
+
+    let (count, remaining_len) = logfiles()?;
+    assert_eq!(count, 1);
+    assert_eq!(report.freed_bytes, total_len - remaining_len);
+
+    logger.shutdown();
+    Ok(())
+}
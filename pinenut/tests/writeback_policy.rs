@@ -0,0 +1,65 @@
+use std::{error::Error, mem, path::Path, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    extract, parse, Config, DateTime, Domain, MetaBuilder, RecordBuilder, Writeback,
+    RECOVERED_FILE_EXTENSION,
+};
+use tempfile::tempdir;
+
+fn parse_contents(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut contents = Vec::new();
+    parse(path, None, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    Ok(contents)
+}
+
+#[test]
+fn test_writeback_policy() -> Result<(), Box<dyn Error>> {
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    let expected = ["test log 0", "test log 1", "test log 2"];
+
+    for writeback in [Writeback::Always, Writeback::Skip, Writeback::Quarantine] {
+        let dir = tempdir()?.path().join("test");
+        let domain = Domain::new("test".to_string(), dir.to_path_buf());
+
+        let logger = domain.clone().logger(Config::new());
+        for index in 0..3 {
+            let meta = MetaBuilder::new().datetime(datetime).build();
+            let content = format!("test log {index}");
+            logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+            thread::sleep(Duration::from_micros(100));
+        }
+        // Simulates a crash.
+        mem::forget(logger);
+
+        let logger = domain.clone().logger(Config::new().writeback(writeback));
+        // Waits for the chunk written back to be handled.
+        thread::sleep(Duration::from_millis(10));
+        logger.shutdown();
+
+        let path = dir.join("result.pine");
+        let extracted = extract(domain, datetime..=end, &path);
+        let recovered_path = dir.join("test").with_extension(RECOVERED_FILE_EXTENSION);
+        match writeback {
+            Writeback::Always => {
+                extracted?;
+                assert_eq!(parse_contents(&path)?, expected);
+                assert!(!recovered_path.exists());
+            }
+            Writeback::Skip => {
+                assert!(extracted.is_err());
+                assert!(!recovered_path.exists());
+            }
+            _ => {
+                // The log files are kept clean.
+                assert!(extracted.is_err());
+                assert_eq!(parse_contents(&recovered_path)?, expected);
+            }
+        }
+    }
+
+    Ok(())
+}
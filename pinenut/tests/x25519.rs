@@ -0,0 +1,45 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use pinenut_log::{
+    encrypt::{gen_echd_key_pair, gen_x25519_key_pair},
+    extract, parse_with_options, Config, DateTime, Domain, KeyAlgorithm, MetaBuilder, ParseOptions,
+    RecordBuilder,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_x25519() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?.path().join("test");
+    let domain = Domain::new("test".to_string(), dir.to_path_buf());
+    let (secret_key, public_key) = gen_x25519_key_pair();
+    // The raw key, which is told from the P-256 ones by its length.
+    assert_eq!(KeyAlgorithm::of_public_key(&public_key), Some(KeyAlgorithm::X25519));
+    assert_eq!(KeyAlgorithm::X25519.public_key(&secret_key)?, public_key);
+    let config = Config::new().use_mmap(false).x25519_key(Some(public_key));
+    let logger = domain.clone().logger(config);
+
+    let datetime = DateTime::from_str("2013-11-18 13:35:12Z")?;
+    for index in 0..10 {
+        let meta = MetaBuilder::new().datetime(datetime).build();
+        let content = format!("test log {index}");
+        logger.log(&RecordBuilder::new().meta(meta).content(&content).build());
+        thread::sleep(Duration::from_micros(100));
+    }
+    logger.shutdown();
+
+    let path = dir.join("result.pine");
+    let end = DateTime::from_str("2013-11-18 13:45:00Z")?;
+    extract(domain, datetime..=end, &path)?;
+
+    // The key is chosen among the keys of both algorithms.
+    let mut contents = Vec::new();
+    let options = ParseOptions::new().secret_keys(&[gen_echd_key_pair().0, secret_key]);
+    parse_with_options(&path, None, options, |record| {
+        contents.push(record.content().to_string());
+        Ok(())
+    })?;
+    let expected: Vec<_> = (0..10).map(|index| format!("test log {index}")).collect();
+    assert_eq!(contents, expected);
+
+    Ok(())
+}